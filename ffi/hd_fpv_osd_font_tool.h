@@ -0,0 +1,50 @@
+/*
+ * C interface of the hd_fpv_osd_font_tool library, built with the `ffi` feature.
+ *
+ * Tile sets are opaque handles created by the load functions and released with hd_fpv_tile_set_free.
+ * The functions returning an int return 0 on success and -1 on failure, the functions returning a pointer
+ * return NULL on failure. hd_fpv_last_error returns the message of the last failure of the calling thread.
+ * The tile kinds are 0 for SD and 1 for HD.
+ */
+
+#ifndef HD_FPV_OSD_FONT_TOOL_H
+#define HD_FPV_OSD_FONT_TOOL_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define HD_FPV_TILE_KIND_SD 0
+#define HD_FPV_TILE_KIND_HD 1
+
+typedef struct HdFpvTileSet HdFpvTileSet;
+
+/* valid until the next failing call of the thread, NULL if none */
+const char *hd_fpv_last_error(void);
+
+/* normalized bin files of a directory e.g. font_001.bin, font_hd_001.bin, ident may be NULL */
+HdFpvTileSet *hd_fpv_tile_set_load_bin_files_norm(const char *dir, const char *ident);
+/* tile set directory with the SD and HD sub-directories */
+HdFpvTileSet *hd_fpv_tile_set_load_dir(const char *dir);
+void hd_fpv_tile_set_free(HdFpvTileSet *tile_set);
+
+size_t hd_fpv_tile_set_tile_count(const HdFpvTileSet *tile_set, uint32_t kind);
+uint32_t hd_fpv_tile_width(uint32_t kind);
+uint32_t hd_fpv_tile_height(uint32_t kind);
+/* RGBA pixels row by row, size receives the number of bytes, the buffer belongs to the tile set */
+const uint8_t *hd_fpv_tile_set_tile_rgba(const HdFpvTileSet *tile_set, uint32_t kind, size_t index, size_t *size);
+
+int hd_fpv_tile_set_save_bin_files_norm(const HdFpvTileSet *tile_set, const char *dir, const char *ident);
+/* normalized PNG grid images e.g. font.png, font_hd.png */
+int hd_fpv_tile_set_save_grids_norm(const HdFpvTileSet *tile_set, const char *dir, const char *ident);
+/* PNG tile set directory with the SD and HD sub-directories */
+int hd_fpv_tile_set_save_dir(const HdFpvTileSet *tile_set, const char *dir);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,98 @@
+
+use std::io::Error as IOError;
+use std::path::{Path, PathBuf};
+
+// The output files are written to a temporary file next to their final path and renamed over it once complete so that
+// an interrupted conversion never leaves a truncated file behind e.g. a half written bin file the goggles fail to
+// display. Both files being in the same directory the rename is atomic.
+
+// .<file name>.<pid>.tmp, hidden so that it is not picked up when loading the directory
+fn temp_path<P: AsRef<Path>>(path: P) -> PathBuf {
+    let path = path.as_ref();
+    let file_name = path.file_name().map(|file_name| file_name.to_string_lossy().to_string()).unwrap_or_default();
+    path.with_file_name(format!(".{file_name}.{}.tmp", std::process::id()))
+}
+
+// the temporary file is removed when dropped without being persisted
+#[derive(Debug)]
+pub struct AtomicFile {
+    path: PathBuf,
+    temp_path: PathBuf,
+    persisted: bool,
+}
+
+impl AtomicFile {
+
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self { path: path.as_ref().to_path_buf(), temp_path: temp_path(&path), persisted: false }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    // path to write the content to
+    pub fn temp_path(&self) -> &Path {
+        &self.temp_path
+    }
+
+    // replaces the file at the final path with the written content
+    pub fn persist(mut self) -> Result<(), IOError> {
+        fs_err::rename(&self.temp_path, &self.path)?;
+        self.persisted = true;
+        Ok(())
+    }
+
+}
+
+impl Drop for AtomicFile {
+    fn drop(&mut self) {
+        if !self.persisted {
+            let _ = std::fs::remove_file(&self.temp_path);
+        }
+    }
+}
+
+// writes the file with `write` called with the temporary path
+pub fn write_with<P, E, F>(path: P, write: F) -> Result<(), E>
+where
+    P: AsRef<Path>,
+    E: From<IOError>,
+    F: FnOnce(&Path) -> Result<(), E>,
+{
+    let file = AtomicFile::new(path);
+    write(file.temp_path())?;
+    file.persist()?;
+    Ok(())
+}
+
+// atomic equivalent of `fs_err::write`
+pub fn write<P: AsRef<Path>, C: AsRef<[u8]>>(path: P, content: C) -> Result<(), IOError> {
+    write_with(path, |temp_path| fs_err::write(temp_path, content))
+}
+
+#[cfg(test)]
+mod tests {
+    use temp_dir::TempDir;
+
+    use super::{write, write_with, AtomicFile};
+
+    #[test]
+    fn atomic_write() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.child("font.bin");
+        std::fs::write(&path, b"old").unwrap();
+        let result: Result<(), std::io::Error> = write_with(&path, |temp_path| {
+            std::fs::write(temp_path, b"partial")?;
+            Err(std::io::Error::new(std::io::ErrorKind::Interrupted, "interrupted"))
+        });
+        assert!(result.is_err());
+        // the original file is untouched and the temporary file removed
+        assert_eq!(std::fs::read(&path).unwrap(), b"old");
+        assert!(!AtomicFile::new(&path).temp_path().exists());
+        write(&path, b"new").unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), b"new");
+        assert_eq!(std::fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+    }
+
+}
@@ -0,0 +1,108 @@
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use hd_fpv_osd_font_tool::{atomic_file, report::{CollectionReport, CommandReport}};
+use thiserror::Error;
+
+use crate::install::{font_files, goggles_media, is_font_file_name};
+use crate::overwrite::check_outputs;
+
+// Snapshots of the font files of the goggles SD card as .tar.gz archives holding the bin files at their root.
+
+#[derive(Debug, Error)]
+pub enum BackupError {
+    #[error("no font files found in {0}")]
+    NoFontFiles(PathBuf),
+    #[error("archive {0} does not contain any font file")]
+    EmptyArchive(PathBuf),
+}
+
+pub fn default_backup_file_name() -> anyhow::Result<String> {
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    Ok(format!("font_backup_{timestamp}.tar.gz"))
+}
+
+// returns the number of archived files
+pub fn backup_font_files(media: &Path, archive_path: &Path) -> anyhow::Result<usize> {
+    let files = font_files(media)?;
+    if files.is_empty() {
+        return Err(BackupError::NoFontFiles(media.to_path_buf()).into())
+    }
+    // an interrupted backup must not leave a truncated archive which would then be restored
+    atomic_file::write_with(archive_path, |temp_path| -> anyhow::Result<()> {
+        let mut builder = tar::Builder::new(GzEncoder::new(fs_err::File::create(temp_path)?, Compression::default()));
+        for file in &files {
+            builder.append_path_with_name(file, file.file_name().unwrap())?;
+        }
+        builder.into_inner()?.finish()?;
+        Ok(())
+    })?;
+    Ok(files.len())
+}
+
+// only the font files at the root of the archive are restored, they replace the files with the same names
+pub fn restore_font_files(archive_path: &Path, media: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let mut archive = tar::Archive::new(GzDecoder::new(fs_err::File::open(archive_path)?));
+    let mut restored = vec![];
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let file_name = match entry.path()?.to_str() {
+            Some(file_name) if is_font_file_name(file_name) && !file_name.contains('/') => file_name.to_owned(),
+            _ => continue,
+        };
+        let path = media.join(&file_name);
+        entry.unpack(&path)?;
+        restored.push(path);
+    }
+    match restored.is_empty() {
+        true => Err(BackupError::EmptyArchive(archive_path.to_path_buf()).into()),
+        false => Ok(restored),
+    }
+}
+
+pub fn backup_command(target: Option<&Path>, out: Option<&Path>, report: &mut CommandReport) -> anyhow::Result<()> {
+    let media = goggles_media(target)?;
+    let archive_path = match out {
+        Some(out) => out.to_path_buf(),
+        None => PathBuf::from(default_backup_file_name()?),
+    };
+    report.inputs.push(CollectionReport::new("media", &[&media]));
+    check_outputs(&[&archive_path])?;
+    let file_count = backup_font_files(&media, &archive_path)?;
+    log::info!("backed up {file_count} font files of {} -> {}", media.to_string_lossy(), archive_path.to_string_lossy());
+    report.outputs.push(CollectionReport::new("archive", &[archive_path]));
+    Ok(())
+}
+
+pub fn restore_command(archive: &Path, target: Option<&Path>, report: &mut CommandReport) -> anyhow::Result<()> {
+    let media = goggles_media(target)?;
+    report.inputs.push(CollectionReport::new("archive", &[archive]));
+    let restored = restore_font_files(archive, &media)?;
+    log::info!("restored {} font files from {} -> {}", restored.len(), archive.to_string_lossy(), media.to_string_lossy());
+    report.outputs.push(CollectionReport::new("media", &restored));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use temp_dir::TempDir;
+
+    use super::{backup_font_files, restore_font_files};
+
+    #[test]
+    fn backup_and_restore() {
+        let media = TempDir::new().unwrap();
+        std::fs::write(media.child("font_btfl_hd.bin"), b"hd").unwrap();
+        std::fs::write(media.child("font.bin"), b"sd").unwrap();
+        std::fs::write(media.child("other.bin"), b"other").unwrap();
+        let backup = TempDir::new().unwrap();
+        let archive = backup.child("backup.tar.gz");
+        assert_eq!(backup_font_files(media.path(), &archive).unwrap(), 2);
+
+        std::fs::write(media.child("font.bin"), b"modified").unwrap();
+        let restored = restore_font_files(&archive, media.path()).unwrap();
+        assert_eq!(restored.len(), 2);
+        assert_eq!(std::fs::read(media.child("font.bin")).unwrap(), b"sd");
+    }
+}
@@ -0,0 +1,161 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::anyhow;
+use clap::ValueEnum;
+use hd_fpv_osd_font_tool::{
+    prelude::*,
+    report::CommandReport,
+};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    convert::convert_command,
+    convert_set::{convert_set_command_targets, is_tile_set_arg},
+};
+
+
+#[derive(Debug, Error)]
+pub enum BatchError {
+    #[error("failed to parse batch manifest {file_path}: {error}")]
+    FileStructure { file_path: PathBuf, error: String },
+    #[error("{failed_count} of {conversion_count} conversions failed")]
+    ConversionsFailed { failed_count: usize, conversion_count: usize },
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Targets {
+    One(String),
+    Many(Vec<String>),
+}
+
+// options of an entry override the ones given on the command line
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ManifestEntry {
+    from: String,
+    to: Targets,
+    #[serde(default)]
+    ident: Option<String>,
+    #[serde(default)]
+    symbol_specs_files: Option<Vec<PathBuf>>,
+    #[serde(default)]
+    symbol_names: Option<String>,
+    #[serde(default)]
+    image_format: Option<String>,
+    #[serde(default)]
+    grid_layout: Option<String>,
+    #[serde(default)]
+    scale: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Manifest {
+    conversions: Vec<ManifestEntry>,
+}
+
+impl ManifestEntry {
+
+    fn targets(&self) -> Vec<String> {
+        let targets = match &self.to {
+            Targets::One(to) => vec![to.clone()],
+            Targets::Many(targets) => targets.clone(),
+        };
+        match &self.ident {
+            Some(ident) => targets.into_iter().map(|to| with_ident(to, ident)).collect(),
+            None => targets,
+        }
+    }
+
+    fn options(&self, base_options: &ConvertOptions) -> anyhow::Result<ConvertOptions> {
+        let mut options = base_options.clone();
+        if let Some(symbol_specs_files) = &self.symbol_specs_files {
+            options = options.symbol_specs_files(symbol_specs_files);
+        }
+        if let Some(symbol_names) = &self.symbol_names {
+            let symbol_names = SymbolNames::from_str(symbol_names, true).map_err(|error| anyhow!("invalid symbol names: {error}"))?;
+            options = options.symbol_names(Some(symbol_names));
+        }
+        if let Some(image_format) = &self.image_format {
+            options = options.image_format(image_format.parse()?);
+        }
+        if let Some(grid_layout) = &self.grid_layout {
+            options = options.grid_layout(grid_layout.parse()?);
+        }
+        if let Some(scale) = self.scale {
+            options = options.scale(scale);
+        }
+        options.validate()?;
+        Ok(options)
+    }
+
+}
+
+// the ident is only added to the normalized set destinations which do not already name one
+fn with_ident(to: String, ident: &str) -> String {
+    let is_norm_set = ["djibinsetnorm:", "tilesetgridsnorm:"].iter()
+        .any(|prefix| matches!(to.strip_prefix(prefix), Some(dir) if !dir.contains(':')));
+    if is_norm_set { format!("{to}:{ident}") } else { to }
+}
+
+fn load_manifest(path: &Path) -> anyhow::Result<Manifest> {
+    let content = fs_err::read_to_string(path)?;
+    let is_toml = matches!(path.extension().and_then(|extension| extension.to_str()), Some(extension) if extension.eq_ignore_ascii_case("toml"));
+    let manifest = if is_toml {
+        toml::from_str(&content).map_err(|error| error.to_string())
+    } else {
+        serde_yaml::from_str(&content).map_err(|error| error.to_string())
+    };
+    Ok(manifest.map_err(|error| BatchError::FileStructure { file_path: path.to_path_buf(), error })?)
+}
+
+fn run_entry(entry: &ManifestEntry, base_options: &ConvertOptions, report: &mut CommandReport) -> anyhow::Result<()> {
+    let options = entry.options(base_options)?;
+    let targets = entry.targets();
+    if is_tile_set_arg(&entry.from) {
+        convert_set_command_targets(&entry.from, &targets, options, false, report)
+    } else {
+        for to in &targets {
+            convert_command(&entry.from, to, options.clone(), false, report)?;
+        }
+        Ok(())
+    }
+}
+
+// every conversion is attempted, the failures are reported together at the end
+pub fn batch_command(manifest_path: &Path, base_options: ConvertOptions, report: &mut CommandReport) -> anyhow::Result<()> {
+    let manifest = load_manifest(manifest_path)?;
+    let conversion_count = manifest.conversions.len();
+    let mut failed_count = 0;
+    for entry in &manifest.conversions {
+        let mut entry_report = CommandReport::default();
+        let result = run_entry(entry, &base_options, &mut entry_report);
+        report.inputs.extend(entry_report.inputs);
+        report.outputs.extend(entry_report.outputs);
+        if let Err(error) = result {
+            log::error!("{}: {error}", entry.from);
+            failed_count += 1;
+        }
+    }
+    log::info!("{} of {conversion_count} conversions done", conversion_count - failed_count);
+    if failed_count > 0 {
+        return Err(BatchError::ConversionsFailed { failed_count, conversion_count }.into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::with_ident;
+
+    #[test]
+    fn ident() {
+        assert_eq!(with_ident("djibinsetnorm:fonts".to_owned(), "btfl"), "djibinsetnorm:fonts:btfl");
+        assert_eq!(with_ident("djibinsetnorm:fonts:inav".to_owned(), "btfl"), "djibinsetnorm:fonts:inav");
+        assert_eq!(with_ident("tilesetdir:tiles".to_owned(), "btfl"), "tilesetdir:tiles");
+    }
+
+}
@@ -0,0 +1,155 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::anyhow;
+use hd_fpv_osd_font_tool::{
+    osd::{
+        bin_file::{self, BinFileReader},
+        ident_scan::scan_idents,
+        tile::{self, container::{
+            dir_check::{check_dir, DirContent},
+            stats::palette_violation_count,
+            symbol::spec::{LoadSpecsFileError, Specs as SymbolSpecs},
+        }},
+    },
+    convert_options::DEFAULT_MAX_TILE_COUNT,
+    image_format::ImageDecoding,
+    prelude::{SymbolSet, TileSet},
+    report::{CollectionReport, CommandReport},
+};
+use strum::IntoEnumIterator;
+use thiserror::Error;
+
+use crate::{
+    convert::{identify_convert_arg, ConvertArg},
+    convert_set::argument_norm_args,
+    output::OutputFormat,
+};
+
+
+#[derive(Debug, Error)]
+#[error("{problem_count} problem(s) found")]
+pub struct CheckError {
+    pub problem_count: usize,
+}
+
+fn check_bin_file(path: &Path, expected_tile_kind: Option<tile::Kind>, problems: &mut Vec<String>) {
+    match BinFileReader::open(path) {
+        Ok(reader) => match expected_tile_kind {
+            Some(expected_tile_kind) if *reader.tile_kind() != expected_tile_kind =>
+                problems.push(format!("{}: holds {} tiles instead of {expected_tile_kind} tiles", path.to_string_lossy(), reader.tile_kind())),
+            _ => {},
+        },
+        Err(error) => problems.push(error.to_string()),
+    }
+}
+
+// files of an SD card install: the bin file pages must all be there for SD and HD with valid sizes and kinds
+fn check_bin_set_norm(dir: &Path, ident: Option<&str>, problems: &mut Vec<String>) -> anyhow::Result<()> {
+    let scan = scan_idents(dir)?;
+    for file_name in scan.unrecognized() {
+        problems.push(format!("{}: not a normalized bin file name", dir.join(file_name).to_string_lossy()));
+    }
+    let ident_files = match scan.idents().iter().find(|ident_files| ident_files.ident().as_deref() == ident) {
+        Some(ident_files) if !ident_files.sd_bin_pages().is_empty() || !ident_files.hd_bin_pages().is_empty() => ident_files,
+        _ => {
+            let base_file_name = bin_file::normalized_file_name(tile::Kind::SD, &ident, bin_file::FontPart::Base);
+            problems.push(format!("{}: no bin file found", dir.join(base_file_name).to_string_lossy()));
+            return Ok(())
+        },
+    };
+    for missing in ident_files.missing().iter().filter(|missing| missing.extension().is_some_and(|extension| extension == "bin")) {
+        problems.push(format!("{}: missing bin file page", dir.join(missing).to_string_lossy()));
+    }
+    for (tile_kind, pages) in [(tile::Kind::SD, ident_files.sd_bin_pages()), (tile::Kind::HD, ident_files.hd_bin_pages())] {
+        for file_name in pages.values() {
+            check_bin_file(&dir.join(file_name), Some(tile_kind), problems);
+        }
+    }
+    Ok(())
+}
+
+fn check_set_dir(dir: &str, content: DirContent, problems: &mut Vec<String>) -> anyhow::Result<()> {
+    for tile_kind in tile::Kind::iter() {
+        problems.extend(check_dir(tile_kind.set_dir_path(dir), content, tile_kind)?.iter().map(ToString::to_string));
+    }
+    Ok(())
+}
+
+// only checked once the directories are loadable, their other problems are reported first
+fn check_set_dir_palette(dir: &str, content: DirContent, problems: &mut Vec<String>) -> anyhow::Result<()> {
+    let tile_set = match content {
+        DirContent::Tiles => TileSet::load_from_dir(dir, DEFAULT_MAX_TILE_COUNT, None, ImageDecoding::default())?,
+        DirContent::Symbols => SymbolSet::load_from_dir(dir, DEFAULT_MAX_TILE_COUNT, None, ImageDecoding::default())?.into(),
+    };
+    for (tile_kind, index, tile) in tile_set.iter() {
+        let violation_count = palette_violation_count(tile);
+        if violation_count > 0 {
+            problems.push(format!("{}: tile {index}: {violation_count} pixel(s) out of the OSD palette (fully transparent or opaque gray)",
+                tile_kind.set_dir_path(dir).to_string_lossy()));
+        }
+    }
+    Ok(())
+}
+
+pub fn check_command(target: &str, palette: bool, output_format: OutputFormat, report: &mut CommandReport) -> anyhow::Result<()> {
+    let mut problems = vec![];
+    if let Some(args) = target.strip_prefix("djibinsetnorm:") {
+        let (dir, ident) = argument_norm_args(args)?;
+        report.inputs.push(CollectionReport::new("djibinsetnorm", &[dir]));
+        check_bin_set_norm(Path::new(dir), ident, &mut problems)?;
+    } else if let Some(dir) = target.strip_prefix("tilesetdir:") {
+        report.inputs.push(CollectionReport::new("tilesetdir", &[dir]));
+        check_set_dir(dir, DirContent::Tiles, &mut problems)?;
+        if palette && problems.is_empty() {
+            check_set_dir_palette(dir, DirContent::Tiles, &mut problems)?;
+        }
+    } else if let Some(dir) = target.strip_prefix("symsetdir:") {
+        report.inputs.push(CollectionReport::new("symsetdir", &[dir]));
+        check_set_dir(dir, DirContent::Symbols, &mut problems)?;
+        if palette && problems.is_empty() {
+            check_set_dir_palette(dir, DirContent::Symbols, &mut problems)?;
+        }
+    } else {
+        match identify_convert_arg(target)? {
+            ConvertArg::BinFile { path, tile_kind } => {
+                report.inputs.push(CollectionReport::new("djibin", &[path]));
+                check_bin_file(Path::new(path), tile_kind, &mut problems);
+            },
+            _ => return Err(anyhow!("invalid check argument `{target}`: expected bin:[sd:|hd:]path, djibin:path, djibinsetnorm:dir[:ident], tilesetdir:path or symsetdir:path")),
+        }
+    }
+
+    if output_format == OutputFormat::Human {
+        for problem in &problems {
+            println!("{problem}");
+        }
+    }
+    if problems.is_empty() {
+        log::info!("no problem found");
+        return Ok(())
+    }
+    let problem_count = problems.len();
+    report.problems = problems;
+    Err(CheckError { problem_count }.into())
+}
+
+// the specs files are merged in order as when converting, every conflict is reported before failing
+pub fn check_specs_command(files: &[PathBuf], tile_count: Option<usize>, output_format: OutputFormat, report: &mut CommandReport) -> anyhow::Result<()> {
+    report.inputs.push(CollectionReport::new("symbol_specs", files));
+    let problems = match SymbolSpecs::load_files_tile_count(files, tile_count) {
+        Ok(specs) => {
+            log::info!("{} symbol specs without conflict", specs.len());
+            return Ok(())
+        },
+        Err(LoadSpecsFileError::Conflicts(error)) => error.conflicts().iter().map(ToString::to_string).collect::<Vec<_>>(),
+        Err(error) => return Err(error.into()),
+    };
+    if output_format == OutputFormat::Human {
+        for problem in &problems {
+            println!("{problem}");
+        }
+    }
+    let problem_count = problems.len();
+    report.problems = problems;
+    Err(CheckError { problem_count }.into())
+}
@@ -1,20 +1,112 @@
 
 use std::path::PathBuf;
 
-use clap::{Parser, Subcommand};
+use clap::{ArgAction, Args, Parser, Subcommand};
+use clap_complete::Shell;
 use getset::{CopyGetters, Getters};
-use hd_fpv_osd_font_tool::log_level::LogLevel;
+use hd_fpv_osd_font_tool::{
+    color::parse_color,
+    convert_options::{DEFAULT_SYMBOL_SPECS_FILE, MAX_PAGE_COUNT},
+    image_format::{AlphaMode, ColorKey},
+    log_level::LogLevel,
+    terminal_graphics::Protocol as TerminalGraphicsProtocol,
+    osd::name_template::NameTemplate,
+    osd::tile::{font_render, container::save_tiles_to_dir::DedupMode},
+    prelude::{BinFilePadding, ConvertOptions, ImageFormat, IndexList, MissingTiles, RescaleFilter, SymbolNames, TileFit, TileGridAnnotation, TileGridLayout, TileGridValidation, TileTransform},
+};
+use image::Rgba;
 
+use crate::output::{error_code, OutputFormat};
 
-#[derive(Parser, CopyGetters)]
-#[clap(author, version, about, long_about = None)]
+const EXIT_CODES_HELP: &str = "Exit codes:
+    0  success
+    1  other failures
+    2  invalid arguments or options, including existing outputs without --force
+    3  input files which cannot be decoded e.g. invalid bin file size or grid image dimensions
+    4  files which cannot be read or written
+    5  validation failures e.g. `check` problems, `verify` differences, `compare` thresholds exceeded, invalid symbol specs or tiles exceeding the capacity of the target";
+
+// Process exit codes by failure class so that scripts can branch on the kind of failure, do not change existing values.
+// clap exits with 2 as well for invalid command lines.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ExitCode {
+    Failure = 1,
+    Argument = 2,
+    InputFormat = 3,
+    Io = 4,
+    Validation = 5,
+}
+
+impl ExitCode {
+
+    // the class follows the error code of the JSON report, the errors caused by an I/O error are I/O failures whatever
+    // the format being read or written
+    pub fn for_error(error: &anyhow::Error) -> Self {
+        match error_code(error) {
+            "invalid_argument" | "invalid_options" | "existing_output" | "tile_edit" => Self::Argument,
+            "check_failed" | "verify_failed" | "compare_failed" | "batch_failed" | "invalid_symbol_specs" | "ambiguous_fix" | "transformed_dimensions" | "capacity" => Self::Validation,
+            _ if error.chain().any(|cause| cause.is::<std::io::Error>()) => Self::Io,
+            code if code.ends_with("_save") => Self::Io,
+            "backup_output" | "dir_scan" | "fix_apply" => Self::Io,
+            code if code.ends_with("_load") => Self::InputFormat,
+            "tile_kind" => Self::InputFormat,
+            _ => Self::Failure,
+        }
+    }
+
+    pub fn code(self) -> i32 {
+        self as i32
+    }
+
+}
+
+#[derive(Parser, CopyGetters, Getters)]
+#[clap(author, version, about, long_about = None, after_long_help = EXIT_CODES_HELP)]
 pub struct Cli {
 
+    /// Level of the log messages, -v and -q take precedence
     #[clap(short, long, value_parser, default_value_t = LogLevel::Info)]
     #[arg(value_enum)]
-    #[getset(get_copy = "pub")]
     log_level: LogLevel,
 
+    /// More log messages: -v for the debug messages and -vv for the trace messages, both showing the module logging{n}
+    /// them. Per module levels can be set with the HD_FPV_FONT_TOOL_LOG environment variable e.g.{n}
+    /// HD_FPV_FONT_TOOL_LOG=hd_fpv_osd_font_tool::osd::bin_file=trace
+    #[clap(short, long, action = ArgAction::Count, global = true, conflicts_with = "quiet")]
+    verbose: u8,
+
+    /// Fewer log messages: -q for the warnings and errors only and -qq for the errors only
+    #[clap(short, long, action = ArgAction::Count, global = true)]
+    quiet: u8,
+
+    /// Also write the log messages to this file, including the debug messages whatever the level
+    #[clap(long, value_name = "FILE", global = true)]
+    #[getset(get = "pub")]
+    log_file: Option<PathBuf>,
+
+    /// Configuration file providing defaults for the options, $XDG_CONFIG_HOME/hd_fpv_osd_font_tool/config.toml{n}
+    /// (~/.config/hd_fpv_osd_font_tool/config.toml) is read when it exists otherwise
+    #[clap(long, value_name = "FILE", global = true)]
+    #[getset(get = "pub")]
+    config: Option<PathBuf>,
+
+    /// Format of the command result: `human` for log messages only, `json` to also print a report on stdout
+    #[clap(long, alias = "output-format", value_enum, default_value_t = OutputFormat::Human, global = true)]
+    #[getset(get_copy = "pub")]
+    output: OutputFormat,
+
+    /// Replace the existing output files and non-empty output directories, by default the commands refuse to write over them{n}
+    /// and list the files which would be replaced. The output files are always written to a temporary file renamed over the{n}
+    /// destination once complete. With `fix` allows modifying the directory in place
+    #[clap(long, alias = "overwrite", global = true)]
+    #[getset(get_copy = "pub")]
+    force: bool,
+
+    /// Rename the existing outputs aside to <name>.bak (<name>.bak.1, ... when taken) before writing the new ones
+    #[clap(long, global = true)]
+    #[getset(get_copy = "pub")]
+    backup: bool,
+
     #[command(subcommand)]
     pub command: Commands,
 
@@ -26,10 +118,26 @@ pub enum Commands {
     ///
     /// Valid collection specifications are:{n}
     ///     * djibin:path       raw RGBA file{n}
+    ///     * bin:[sd:|hd:]path single page raw RGBA file, the tile kind is inferred from the file size unless given{n}
     ///     * avatar:path       Avatar tile collection image file{n}
     ///     * tilegrid:path     grid of tiles image{n}
     ///     * tiledir:path      directory with each tile in a separate file{n}
     ///     * symdir:path       directory with each symbol in a separate file{n}
+    ///     * analoggrid:path[:path_2]  16x16 sheet of SD tiles without separators, the second sheet holds the tiles 256 to 511{n}
+    ///     * mcm:[sd:|hd:]path Betaflight/INAV analog MCM font file, the 12x18 characters are loaded as 3x upscaled SD tiles unless HD is given{n}
+    ///     * hdzero:path       HDZero goggles font bitmap, 16x32 sheet of HD tiles without separators{n}
+    ///     * cheader:[rgba:|max7456:]path  C header with a `const uint8_t` array of the tiles (target only){n}
+    ///     * rustsrc:[rgba:|max7456:]path  Rust source file with a `pub const` array of the tiles (target only){n}
+    ///     * atlas:path        packed sprite atlas image with a TexturePacker style JSON index next to it (target only){n}
+    ///
+    /// Sprite atlas (atlas){n}
+    ///     The distinct tiles packed without separators in a roughly square image, identical tiles sharing the same image.{n}
+    ///     The index is written to the path with a .json extension in the TexturePacker hash format, each frame being{n}
+    ///     named from the index of its tile 0 padded to 3 digits e.g. 011{n}
+    ///
+    /// Source arrays (cheader, rustsrc){n}
+    ///     One sub-array per tile, raw RGBA bytes by default or 64 bytes MAX7456 characters as in MCM files with max7456:,{n}
+    ///     with constants for the tile count, width, height and byte count named from the file name e.g. FONT_HD_TILE_COUNT{n}
     ///
     /// Bin files normalized names{n}
     ///     Generic bin files (no ident):{n}
@@ -41,21 +149,35 @@ pub enum Commands {
     ///
     /// Tile directory (tiledir){n}
     ///     A tile directory is a directory representing a collection of tiles with each tile in a separate file. Each file{n}
-    ///     is named from the index of the tile 0 padded to 3 digits and with the png extensions e.g. 011.png
+    ///     is named from the index of the tile 0 padded to 3 digits and with the image format extension e.g. 011.png{n}
+    ///     png, bmp, tga and webp files are accepted when reading
     ///
     /// Symbol directory (symdir){n}
     ///     A symbol is a small sub-collection of tiles representing a full symbol (symbol spanning across several tiles).{n}
     ///     When saving to a symdir the symbol specifications file can be specified with the -s/--symbols-specs-file argument.{n}
     ///     A symbol directory contains every symbol of the collection with specific name formats:{n}
     ///     - symbols spanning a single tile: index of the symbol 0 padded to 3 digits and with png extension e.g. 011.png{n}
-    ///     - other symbols: index of the first tile and index of the last tile 0 padded to 3 digits and separated by `-` e.g. 030-032.png
+    ///     - other symbols: index of the first tile and index of the last tile 0 padded to 3 digits and separated by `-` e.g. 030-032.png{n}
+    ///     - with --symbol-names the name of the symbol is appended when known e.g. 001_SYM_RSSI.png{n}
+    ///     The extension follows the --image-format argument when saving
     ///
     /// Example: extracting the tiles from a bin file to individual files in the `tiles` directory:{n}
     ///     `convert bin:font.bin tiledir:tiles`
     Convert {
 
-        #[clap(short, long, value_parser, default_value = "sym_specs.yaml")]
-        symbol_specs_file: PathBuf,
+        #[command(flatten)]
+        options: ConvertOptionsArgs,
+
+        /// skip the conversion when the source content and the options did not change since the last conversion{n}
+        /// and the destination still has the content it was written with, the state is kept in a{n}
+        /// .hd_fpv_font_tool_state.json file next to the destination
+        #[clap(long)]
+        incremental: bool,
+
+        /// keep running and convert again each time the source or the symbol specs files change, the outputs are{n}
+        /// then replaced without --force
+        #[clap(long)]
+        watch: bool,
 
         /// source collection in the form of a tile collection specification, see above
         from: String,
@@ -70,11 +192,17 @@ pub enum Commands {
     ///
     /// Valid collection specifications are:{n}
     ///     * djibinset:sd_path:sd_2_path:hd_path:hd_2_path{n}
+    ///       any number of pages is supported as long as there are as many SD pages as HD pages e.g. djibinset:sd_path:hd_path{n}
     ///     * djibinsetnorm:path:ident      set of bin files with normalized names{n}
     ///     * tilesetgrids:sd_path:hd_path  grids of tiles image forming a SD/HD set{n}
     ///     * tilesetgridsnorm:path:ident   grid of tiles image set with normalized names{n}
     ///     * tilesetdir:path               directory with SD and HD tiles in the corresponding directory{n}
-    ///     * symsetdir:path                directory with SD and HD symbols in the corresponding directory
+    ///     * symsetdir:path                directory with SD and HD symbols in the corresponding directory{n}
+    ///     * tilesetzip:path               zip or tar.gz archive with the structure of a tilesetdir{n}
+    ///     * symsetzip:path                zip or tar.gz archive with the structure of a symsetdir{n}
+    ///     * combinedgrid:path             single image with every SD and HD page{n}
+    ///     * mcm:path                      Betaflight/INAV analog MCM font file, loaded as both SD and HD tiles, only the SD tiles are written{n}
+    ///     * hdzero:path                   HDZero goggles font bitmap, only the HD tiles are written, the SD tiles are resampled when reading
     ///
     /// Bin files normalized names (binsetnorm){n}
     ///     Generic bin files (no ident):{n}
@@ -83,6 +211,7 @@ pub enum Commands {
     ///     With ident:{n}
     ///         SD: font_<ident>.bin + font_<ident>_2.bin{n}
     ///         HD: font_<ident>_hd.bin + font_<ident>_hd_2.bin{n}
+    ///     Additional pages are numbered from 3 e.g. font_3.bin, the pages are read until one is missing{n}
     ///     If `path/indent` is not provided will read the files from the current directory without ident
     ///
     /// Grid files normalized names{n}
@@ -91,26 +220,717 @@ pub enum Commands {
     ///         HD: grid_hd.bin{n}
     ///     With ident:{n}
     ///         SD: grid_<ident>.png{n}
-    ///         HD: grid_<ident>_hd.png
+    ///         HD: grid_<ident>_hd.png{n}
+    ///     The extension follows the --image-format argument when saving, any supported image extension is read
     ///
     /// Tile/symbol sets directory (tilesetdir / symsetdir){n}
     ///     A directory with the SD tiles in the SD subdirectory and HD tiles in the HD subdirectory{n}
+    ///     The tilesetzip / symsetzip archives (.zip, .tar.gz or .tgz) hold the same structure, possibly inside a single top directory{n}
     ///     When saving to a symsetdir the symbol specifications file can be specified with the -s/--symbols-specs-file argument.{n}
     ///     If `path/indent` is not provided will read the files from the current directory without ident
     ///
+    /// Combined grid (combinedgrid){n}
+    ///     Bands stacked from top to bottom: every SD page in order then every HD page in order, as many HD pages as SD pages{n}
+    ///     Each band is a 14 pixels label strip (e.g. "SD 1") followed by the 16x16 grid of the page laid out like a grid image{n}
+    ///     The image is as wide as a SD grid (606 pixels), the HD grids are aligned on the left
+    ///
     /// Example: extracting the tiles from a bin file set with normalized name and no ident from the `font_files` directory{n}
     ///          to individual files. SD tiles in the `tiles/SD` directory and HD tiles in the `tiles/HD` directory:{n}
     ///     `convert-set binsetnorm:font_files tiledir:tiles`
     ConvertSet {
 
-        #[clap(short, long, value_parser, default_value = "sym_specs.yaml")]
-        symbol_specs_file: PathBuf,
+        #[command(flatten)]
+        options: ConvertOptionsArgs,
+
+        /// skip the conversion when the source content and the options did not change since the last conversion{n}
+        /// and the destination still has the content it was written with, the state is kept in a{n}
+        /// .hd_fpv_font_tool_state.json file next to the destination
+        #[clap(long)]
+        incremental: bool,
+
+        /// keep running and convert again each time the source or the symbol specs files change, the outputs are{n}
+        /// then replaced without --force
+        #[clap(long)]
+        watch: bool,
+
+        /// additional destination collection, can be repeated, the source is loaded once and written to each destination
+        #[clap(long = "to", value_name = "TO")]
+        extra_to: Vec<String>,
 
         /// source collection in the form of a tile collection specification, see above
         from: String,
 
         /// destination collection in the form of a tile collection specification, see above
-        to: String
+        #[clap(required_unless_present = "extra_to")]
+        to: Option<String>,
+    },
+
+    /// Prints statistics about a tile collection
+    ///
+    /// Reports the number of tiles and non-blank tiles per page, the groups of duplicate tiles, the tiles touching the{n}
+    /// tile edge (likely clipped glyphs), the tiles with visible pixels in the safe margin along the tile border and{n}
+    /// the pixels which are neither fully transparent nor an opaque gray shade.{n}
+    /// The safe margin intrusions are grouped by how deep they go into the margin, separately for the left/right{n}
+    /// edges and the top/bottom edges.{n}
+    /// The source collection is specified the same way as for the `convert` command.
+    Stats {
+
+        /// output the statistics as JSON
+        #[clap(long)]
+        json: bool,
+
+        /// only check the safe margin, with a width of N pixels instead of the default 2 pixels
+        #[clap(long, value_name = "N")]
+        check_margins: Option<u32>,
+
+        /// source collection in the form of a tile collection specification, see the `convert` command
+        from: String,
+    },
+
+    /// Validates bin files and tile/symbol set directories
+    ///
+    /// Every problem found is reported before failing:{n}
+    ///     bin:[sd:|hd:]path, djibin:path     bin file size and tile kind{n}
+    ///     djibinsetnorm:dir[:ident]         SD card install: bin file names, missing SD/HD pages, size and tile kind of each page{n}
+    ///     tilesetdir:path, symsetdir:path   file names, image dimensions for the tile kind and PNG pixel format{n}
+    /// With --palette the tiles of tile/symbol set directories are also checked for pixels out of the OSD palette:{n}
+    /// neither fully transparent nor an opaque gray shade.{n}
+    /// The command fails with the check_failed error code when problems are found.
+    Check {
+
+        /// report the tiles with pixels out of the OSD palette (tilesetdir/symsetdir targets)
+        #[clap(long)]
+        palette: bool,
+
+        /// bin file, SD card install directory or tile/symbol set directory to check
+        target: String,
+    },
+
+    /// Checks symbol specs files for conflicts
+    ///
+    /// The files are merged in order as with the --symbol-specs-file option of the conversions, then every symbol{n}
+    /// overlapping another one, symbol name used more than once and, with --tile-count, symbol going beyond the{n}
+    /// tiles is reported. The command fails with the check_failed error code when conflicts are found.
+    CheckSpecs {
+
+        /// number of tiles of the fonts the specs are for e.g. 512 for two bin file pages
+        #[clap(long, value_name = "COUNT")]
+        tile_count: Option<usize>,
+
+        /// symbol specs files
+        #[clap(required = true)]
+        files: Vec<PathBuf>,
+    },
+
+    /// Repairs common problems of tile/symbol set directories
+    ///
+    /// The directory is specified as tilesetdir:path or symsetdir:path, only the selected fixes are applied:{n}
+    ///     --renumber        rename the files whose index is not zero padded to 3 digits e.g. 7.png -> 007.png{n}
+    ///     --strip-foreign   remove the files which are not tile/symbol images e.g. .DS_Store or Thumbs.db{n}
+    ///     --normalize-png   rewrite the PNG images which are not stored as 8 bit RGBA e.g. indexed color{n}
+    ///     --fit <FIT>       adjust the images which do not have the expected dimensions
+    ///
+    /// Every file touched is printed. Nothing is modified if any of the fixes would be ambiguous e.g. two files{n}
+    /// renamed to the same name. The directory is only modified in place with --force, use --out to write the fixed{n}
+    /// directory somewhere else.
+    Fix {
+
+        /// rename the files whose index is not zero padded to 3 digits
+        #[clap(long)]
+        renumber: bool,
+
+        /// remove the files which are not tile/symbol images
+        #[clap(long)]
+        strip_foreign: bool,
+
+        /// rewrite the PNG images which are not stored as 8 bit RGBA
+        #[clap(long)]
+        normalize_png: bool,
+
+        /// adjust the images which do not have the expected dimensions
+        #[clap(long, value_enum)]
+        fit: Option<TileFit>,
+
+        /// write the fixed directory to this directory instead of modifying it in place
+        #[clap(long, value_name = "DIR")]
+        out: Option<PathBuf>,
+
+        /// directory to fix: tilesetdir:path or symsetdir:path
+        target: String,
+    },
+
+    /// Prints content hashes of a tile collection or collection set
+    ///
+    /// The hashes are computed over the raw RGBA data of the tiles in index order, ignoring the trailing blank tiles,{n}
+    /// so they are the same whatever the format the tiles are loaded from. The source is specified the same way as{n}
+    /// for the `convert` command or the `convert-set` command.{n}
+    /// With --manifest the content hash, the hash of each tile and the SHA-256 of each file the tiles are loaded{n}
+    /// from are also written to a JSON manifest file checked with the `verify` command.
+    Hash {
+
+        /// also print the hash of each tile
+        #[clap(long)]
+        per_tile: bool,
+
+        /// write the manifest of the collection to this file
+        #[clap(long, value_name = "FILE")]
+        manifest: Option<PathBuf>,
+
+        /// source collection or collection set specification
+        from: String,
+    },
+
+    /// Checks a tile collection or collection set against a manifest written by `hash --manifest`
+    ///
+    /// The content hash and the hash of each tile are compared whatever the format the collection is loaded from,{n}
+    /// e.g. the bin files published from the reviewed tile directories are checked against the manifest of the{n}
+    /// directories. The checksums of the files are compared for the files listed in the manifest. Each difference{n}
+    /// is printed and the command fails with the verify_failed error code when there are any.
+    Verify {
+
+        /// manifest file written by `hash --manifest`
+        #[clap(long, value_name = "FILE")]
+        manifest: PathBuf,
+
+        /// collection or collection set specification
+        from: String,
+    },
+
+    /// Renders the characters of a TrueType/OpenType font into a tile collection set
+    ///
+    /// The glyphs are rendered white, anti-aliased through the alpha channel, scaled so that the line height of the{n}
+    /// font fills the tile height minus the margins and centered horizontally. Use --add-outline to outline them.{n}
+    /// The mapping file is a YAML map of characters (or U+XXXX codepoints) to tile indices e.g.{n}
+    ///     "A": 0x41{n}
+    ///     "U+00B0": 8{n}
+    /// Without a mapping file the printable ASCII characters are rendered at the index of their code. The{n}
+    /// destination is specified the same way as for the `convert-set` command.
+    Generate {
+
+        /// YAML file mapping the characters to the tile indices
+        #[clap(long, value_name = "FILE")]
+        mapping: Option<PathBuf>,
+
+        /// margin in pixels between the text line and the top/bottom edges, the margin is for SD tiles and is scaled for HD tiles
+        #[clap(long, default_value_t = font_render::DEFAULT_MARGIN)]
+        margin: u32,
+
+        #[command(flatten)]
+        options: ConvertOptionsArgs,
+
+        /// TrueType/OpenType font file
+        font: PathBuf,
+
+        /// destination collection set in the form of a tile collection set specification, see the `convert-set` command
+        to: String,
+    },
+
+    /// Layers tile collection sets on top of each other
+    ///
+    /// The first source is the base, the tiles of each following source which are not fully transparent replace{n}
+    /// the tiles at the same index in the result, extending it when needed. The sources and the destination are{n}
+    /// specified the same way as for the `convert-set` command, the transformations are applied to the result.
+    Merge {
+
+        #[command(flatten)]
+        options: ConvertOptionsArgs,
+
+        /// destination collection set in the form of a tile collection set specification, see the `convert-set` command
+        #[clap(short, long)]
+        out: String,
+
+        /// source collection sets in the form of tile collection set specifications, from bottom to top
+        #[clap(required = true, num_args = 2..)]
+        sources: Vec<String>,
+    },
+
+    /// Writes a patch holding only some tiles of a tile collection set
+    ///
+    /// The patch holds the tiles of both kinds at the given indexes or, with `--base`, the tiles which differ from{n}
+    /// the base set, e.g. to distribute a logo as a tiny patch rather than a full font. It is written like a tile{n}
+    /// set directory with only the files of the patched tiles, as a .zip, .tar.gz or .tgz archive or as a directory{n}
+    /// for other paths. The source is specified the same way as for the `convert-set` command.
+    MakePatch {
+
+        /// indexes of the tiles to include as a comma separated list of indexes and ranges e.g. 1,5-9,32
+        #[clap(short, long, value_name = "INDEXES", required_unless_present = "base", conflicts_with = "base")]
+        indexes: Option<IndexList>,
+
+        /// include the tiles which differ from this collection set, in the form of a tile collection set specification
+        #[clap(short, long)]
+        base: Option<String>,
+
+        #[command(flatten)]
+        options: ConvertOptionsArgs,
+
+        /// source collection set in the form of a tile collection set specification, see the `convert-set` command
+        from: String,
+
+        /// patch archive or directory to write
+        patch: PathBuf,
+    },
+
+    /// Overlays a patch on a tile collection set
+    ///
+    /// The tiles of the patch replace the tiles at the same index of the base, fully transparent ones included,{n}
+    /// extending it when needed. The base and the destination are specified the same way as for the `convert-set`{n}
+    /// command, the transformations are applied to the result.
+    ApplyPatch {
+
+        #[command(flatten)]
+        options: ConvertOptionsArgs,
+
+        /// base collection set in the form of a tile collection set specification
+        base: String,
+
+        /// patch archive or directory written by the `make-patch` command
+        patch: PathBuf,
+
+        /// destination collection set in the form of a tile collection set specification
+        to: String,
+    },
+
+    /// Runs the conversions listed in a manifest file
+    ///
+    /// The manifest is a YAML file (TOML with a .toml extension) with a `conversions` list, each entry has a `from`{n}
+    /// collection and a `to` collection or list of collections specified the same way as for the `convert` or{n}
+    /// `convert-set` command depending on the source, the paths being relative to the current directory.{n}
+    /// An entry can override the options given on the command line with the `symbol_specs_files`, `symbol_names`,{n}
+    /// `image_format`, `grid_layout` and `scale` keys. The `ident` key is appended to its djibinsetnorm and{n}
+    /// tilesetgridsnorm destinations which do not name one.{n}
+    /// Every conversion is attempted, the command fails at the end if any of them failed.
+    Batch {
+
+        #[command(flatten)]
+        options: ConvertOptionsArgs,
+
+        /// manifest file listing the conversions
+        manifest: PathBuf,
+    },
+
+    /// Moves the tiles of a collection set to the indices used by another firmware
+    ///
+    /// The char map file maps the source tile indices to the destination tile indices, both can be hexadecimal{n}
+    /// e.g. `0x08: 0x1E`. The tiles which are not the destination of an entry keep the tile at the same index and{n}
+    /// the result is extended with transparent tiles when a destination is past its end. The source and the{n}
+    /// destination are specified the same way as for the `convert-set` command, the transformations are applied{n}
+    /// to the result.
+    Remap {
+
+        #[command(flatten)]
+        options: ConvertOptionsArgs,
+
+        /// char map YAML file
+        char_map: PathBuf,
+
+        /// source collection set in the form of a tile collection set specification, see the `convert-set` command
+        from: String,
+
+        /// destination collection set in the form of a tile collection set specification, see the `convert-set` command
+        to: String,
+    },
+
+    /// Writes a single tile of a collection to an image file
+    ///
+    /// The collection is specified the same way as for the `convert` command. Only the requested tile is read from{n}
+    /// bin files, the other formats are loaded whole.
+    ExtractTile {
+
+        /// integer factor the tile is upscaled by, upscaled tiles are for presentation only and cannot be set back
+        #[clap(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+        scale: u32,
+
+        /// arrangement of the tiles of a tile grid source as <columns> or <columns>x<rows>, see the `convert` command
+        #[clap(long, value_name = "LAYOUT", default_value_t = TileGridLayout::default())]
+        grid_layout: TileGridLayout,
+
+        /// source collection in the form of a tile collection specification, see the `convert` command
+        from: String,
+
+        /// index of the tile in the collection
+        index: usize,
+
+        /// image file to write the tile to
+        to: PathBuf,
+    },
+
+    /// Replaces a single tile of an existing bin file or tile grid
+    ///
+    /// The target is specified as bin:[sd:|hd:]path, djibin:path or tilegrid:path. Bin files are modified in place{n}
+    /// without rewriting the other tiles, gzip compressed bin files are not supported. The tile image must have the{n}
+    /// dimensions of the tiles of the target unless --fit is used.
+    SetTile {
+
+        /// adjust the tile image if it does not have the expected dimensions
+        #[clap(long, value_enum)]
+        fit: Option<TileFit>,
+
+        /// arrangement of the tiles of a tile grid target as <columns> or <columns>x<rows>, the grid is written back{n}
+        /// with the same layout, see the `convert` command
+        #[clap(long, value_name = "LAYOUT", default_value_t = TileGridLayout::default())]
+        grid_layout: TileGridLayout,
+
+        /// bin file or tile grid to modify
+        target: String,
+
+        /// index of the tile to replace
+        index: usize,
+
+        /// image file of the new tile
+        tile: PathBuf,
+    },
+
+    /// Renders a text with the tiles of a font to an image file
+    ///
+    /// The font is specified the same way as for the `convert` command. Each character is replaced by the tile it{n}
+    /// is mapped to, the lines are separated by \n. The mapping file is the same as for the `generate` command,{n}
+    /// without one the printable ASCII characters are looked up at the index of their code. Useful for previews{n}
+    /// and to verify that the characters are at the expected indices.
+    RenderText {
+
+        /// YAML file mapping the characters to the tile indices
+        #[clap(long, value_name = "FILE")]
+        mapping: Option<PathBuf>,
+
+        /// color behind the tiles (black, white, transparent, #RRGGBB or #RRGGBBAA)
+        #[clap(long, value_parser = parse_color, default_value = "transparent")]
+        background: Rgba<u8>,
+
+        /// integer factor the image is upscaled by
+        #[clap(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+        scale: u32,
+
+        /// font in the form of a tile collection specification, see the `convert` command
+        from: String,
+
+        /// text to render
+        text: String,
+
+        /// image file to write
+        to: PathBuf,
+    },
+
+    /// Writes a contact sheet of all the tiles of a collection for review
+    ///
+    /// The collection is specified the same way as for the `convert` command. The tiles are laid out 16 per row{n}
+    /// with their index under each of them and the bin file pages (256 tiles) separated by a wider gap. The sheet{n}
+    /// is a standalone HTML page when the destination has the .html or .htm extension, an image otherwise.
+    Preview {
+
+        /// integer factor the tiles are upscaled by
+        #[clap(long, default_value_t = 2, value_parser = clap::value_parser!(u32).range(1..))]
+        scale: u32,
+
+        /// source collection in the form of a tile collection specification, see the `convert` command
+        from: String,
+
+        /// image or HTML file to write
+        to: PathBuf,
+    },
+
+    /// Renders a mock of a Betaflight OSD screen with a tile set to judge its readability
+    ///
+    /// The tile set is specified the same way as for the `convert-set` command. RSSI, battery, current, consumption,{n}
+    /// speed, flight time, altitude, crosshair and a warning are laid out on the character grid of the screen:{n}
+    /// 30x16 for SD (PAL analog) and 53x20 for HD (Betaflight HD canvas). The symbols are taken at their Betaflight{n}
+    /// indices and the text at the index of the ASCII code of its characters. The mocks are written to{n}
+    /// <dir>/osd_sd.png and <dir>/osd_hd.png.
+    PreviewOsd {
+
+        /// solid background color instead of sky and ground (black, white, transparent, #RRGGBB or #RRGGBBAA)
+        #[clap(long, value_parser = parse_color)]
+        background: Option<Rgba<u8>>,
+
+        /// source tile set in the form of a tile collection set specification, see the `convert-set` command
+        from: String,
+
+        /// directory to write the mocks to
+        dir: PathBuf,
+    },
+
+    /// Displays a tile or a whole collection in the terminal
+    ///
+    /// The collection is specified the same way as for the `convert` command, without a tile index all the tiles{n}
+    /// are shown as a grid. The image is drawn with the kitty graphics protocol or sixel when the terminal is{n}
+    /// detected to support them, with colored half block characters otherwise which works over any SSH session{n}
+    /// with a 24-bit color terminal.
+    Show {
+
+        /// graphics protocol instead of the one detected from the environment (TERM, TERM_PROGRAM, KITTY_WINDOW_ID)
+        #[clap(long, value_enum)]
+        protocol: Option<TerminalGraphicsProtocol>,
+
+        /// integer factor the image is upscaled by
+        #[clap(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+        scale: u32,
+
+        /// source collection in the form of a tile collection specification, see the `convert` command
+        from: String,
+
+        /// index of the tile to show
+        index: Option<usize>,
+    },
+
+    /// Browses the tiles of a collection interactively
+    ///
+    /// The collection is specified the same way as for the `convert` command. The tiles are listed with a preview{n}
+    /// of the selected one, with --compare the differences with a second collection are highlighted and both{n}
+    /// tiles are previewed side by side. The marked tiles are extracted as images named from their index.{n}
+    /// Only available when built with the `tui` feature.
+    #[cfg(feature = "tui")]
+    Tui {
+
+        /// collection to compare with, in the form of a tile collection specification
+        #[clap(long, value_name = "COLLECTION")]
+        compare: Option<String>,
+
+        /// directory the marked tiles are extracted to
+        #[clap(long, value_name = "DIR", default_value = "extracted_tiles")]
+        extract_dir: PathBuf,
+
+        /// source collection in the form of a tile collection specification, see the `convert` command
+        from: String,
+    },
+
+    /// Converts a tile set and writes it to the SD card of the goggles
+    ///
+    /// The tile set is specified the same way as for the `convert-set` command. The bin files are written to the{n}
+    /// root directory of the SD card with the names expected by the goggles e.g. font_btfl_hd.bin, same as the{n}
+    /// djibinsetnorm destination. Without --target the SD card is looked for among the mounted volumes (/media,{n}
+    /// /run/media, /Volumes): the single one with a DCIM directory. The fonts already on the SD card are only{n}
+    /// replaced with --force or --backup like any other output.
+    Install {
+
+        /// directory the SD card is mounted on
+        #[clap(long, value_name = "DIR")]
+        target: Option<PathBuf>,
+
+        /// font identifier of the firmware the font is for e.g. btfl, inav, ardu, ultra
+        #[clap(long)]
+        ident: Option<String>,
+
+        /// archive the font files already on the SD card first, see the `backup` command
+        #[clap(long)]
+        archive_existing: bool,
+
+        #[command(flatten)]
+        options: ConvertOptionsArgs,
+
+        /// source tile set in the form of a tile collection set specification, see the `convert-set` command
+        from: String,
+    },
+
+    /// Archives the font files of the goggles SD card
+    ///
+    /// The font bin files at the root of the SD card (font*.bin) are stored in a .tar.gz archive which can be put{n}
+    /// back with the `restore` command. The SD card is found the same way as for the `install` command.
+    Backup {
+
+        /// directory the SD card is mounted on
+        #[clap(long, value_name = "DIR")]
+        target: Option<PathBuf>,
+
+        /// archive to write, font_backup_<unix time>.tar.gz in the current directory by default
+        #[clap(short, long, value_name = "FILE")]
+        out: Option<PathBuf>,
+    },
+
+    /// Puts back the font files archived with the `backup` command on the goggles SD card
+    ///
+    /// The archived files replace the files with the same names, the other font files of the SD card are kept.
+    Restore {
+
+        /// directory the SD card is mounted on
+        #[clap(long, value_name = "DIR")]
+        target: Option<PathBuf>,
+
+        /// archive written by the `backup` command
+        archive: PathBuf,
+    },
+
+    /// Downloads a font into the local cache and optionally converts it
+    ///
+    /// The font is given by http(s) URL or by name, the names are looked up in the registry file: a YAML map of{n}
+    /// names to URLs e.g.{n}
+    ///     btfl_hd: https://example.com/fonts/btfl_hd.zip{n}
+    /// The downloaded file is kept in the cache and reused unless --refresh is given. Without a destination the{n}
+    /// path of the cached file is printed, with one the file is converted the same way as with the `convert-set`{n}
+    /// command: zip and tar.gz archives are read as tilesetzip and .mcm files as mcm unless --from-format is given.{n}
+    /// Only available when built with the `network` feature.
+    #[cfg(feature = "network")]
+    Fetch {
+
+        /// collection set specification prefix of the downloaded file e.g. symsetzip
+        #[clap(long, value_name = "PREFIX")]
+        from_format: Option<String>,
+
+        /// cache directory, $XDG_CACHE_HOME/hd_fpv_osd_font_tool or ~/.cache/hd_fpv_osd_font_tool by default
+        #[clap(long, value_name = "DIR")]
+        cache_dir: Option<PathBuf>,
+
+        /// registry file mapping the font names to URLs, registry.yaml in the cache directory by default
+        #[clap(long, value_name = "FILE")]
+        registry: Option<PathBuf>,
+
+        /// download the font again even when it is in the cache
+        #[clap(long)]
+        refresh: bool,
+
+        #[command(flatten)]
+        options: ConvertOptionsArgs,
+
+        /// URL or registry name of the font
+        source: String,
+
+        /// destination collection set in the form of a tile collection set specification, see the `convert-set` command
+        to: Option<String>,
+    },
+
+    /// Writes the tiles of the symbols of a symbol directory to a tile directory named after their symbol
+    ///
+    /// The directory is specified as symsetdir:path or symdir:path, see the `convert` and `convert-set` commands.{n}
+    /// The tiles are written to files named NNN_<symbol name>_<K>.png with K the position of the tile in the{n}
+    /// symbol e.g. 030_SYM_SAT_0.png and 031_SYM_SAT_1.png, to a tile set directory for symsetdir:path. The symbol{n}
+    /// names come from the symbol specs files, else from the `--symbol-names` table, else symbol_NNN for the{n}
+    /// symbols spanning several tiles, the other tiles are written as plain NNN.png files. The result loads as{n}
+    /// any tile directory and the `gen-symbol-specs` command rebuilds the symbol specs from its file names.
+    SplitSymbols {
+
+        #[command(flatten)]
+        options: ConvertOptionsArgs,
+
+        /// symbol directory in the form symsetdir:path or symdir:path
+        from: String,
+
+        /// tile directory to write
+        to: PathBuf,
+    },
+
+    /// Writes a symbol specs file describing the symbols of a symbol directory
+    ///
+    /// The directory is specified as symsetdir:path or symdir:path, see the `convert` and `convert-set` commands.{n}
+    /// A spec is written for each symbol spanning several tiles, named from its start tile index e.g. symbol_030{n}
+    /// since the directories do not keep the symbol names. A tile directory written by the `split-symbols` command{n}
+    /// can also be given as tiledir:path, the specs are then rebuilt from its file names with the symbol names.{n}
+    /// The format of the specs file follows its extension: YAML, JSON or TOML.
+    GenSymbolSpecs {
+
+        /// symbol directory in the form symsetdir:path or symdir:path, or split symbol directory as tiledir:path
+        from: String,
+
+        /// specs file to write
+        to: PathBuf,
+    },
+
+    /// Prints an overview of a tile collection or collection set
+    ///
+    /// For each tile kind: the tile dimensions, the number of tiles, the number of bin file pages needed to hold{n}
+    /// them and the number of fully transparent tiles. The detected format variant is also printed e.g. gzip{n}
+    /// compressed bin files. The source is specified the same way as for the `convert` command or the{n}
+    /// `convert-set` command.
+    Info {
+
+        /// source collection or collection set specification
+        from: String,
+    },
+
+    /// Compares two tile collections
+    ///
+    /// Prints the index of each tile which changed, was added or was removed. The collections are specified the{n}
+    /// same way as for the `convert` command and must contain the same kind of tiles.{n}
+    /// With --visual a comparison image (old | new | changed pixels in red) named from the tile index is written for{n}
+    /// each differing tile along with overview.png, a grid of the tiles with the differing ones tinted in red.{n}
+    /// Tiles missing from one of the collections are compared against a hatched placeholder.
+    Diff {
+
+        /// directory to write the comparison images to
+        #[clap(long, value_name = "DIR")]
+        visual: Option<PathBuf>,
+
+        /// old collection in the form of a tile collection specification, see the `convert` command
+        old: String,
+
+        /// new collection in the form of a tile collection specification, see the `convert` command
+        new: String,
+    },
+
+    /// Compares a tile collection or collection set against a reference to gate font changes in CI
+    ///
+    /// For each tile differing from the reference the number of changed pixels and a perceptual difference are{n}
+    /// printed. The perceptual difference is the RMS difference of the luminance of the tiles over a mid gray{n}
+    /// background after a 3x3 blur, in percent of the luminance range, so that a stroke moved by a pixel weighs{n}
+    /// less than a stroke missing. Tiles missing from one of the collections are compared against a transparent{n}
+    /// tile. The command fails with the compare_failed error code when a tile exceeds one of the thresholds, when{n}
+    /// neither is given any difference fails it. The collections are specified the same way as for the `convert`{n}
+    /// command or the `convert-set` command.
+    Compare {
+
+        /// reference collection or collection set specification
+        #[clap(long, value_name = "COLLECTION")]
+        reference: String,
+
+        /// number of changed pixels a tile may have
+        #[clap(long, value_name = "COUNT")]
+        max_changed_pixels: Option<usize>,
+
+        /// perceptual difference in percent a tile may have
+        #[clap(long, value_name = "PERCENT")]
+        max_perceptual_diff: Option<f64>,
+
+        /// write the comparison images (reference | tile | changed pixels in red) of the differing tiles stacked{n}
+        /// below a label naming each tile to this image file
+        #[clap(long, value_name = "FILE")]
+        montage: Option<PathBuf>,
+
+        /// collection or collection set specification to compare
+        from: String,
+    },
+
+    /// Lists the groups of identical or near identical tiles of one or more fonts
+    ///
+    /// Helps collapsing redundant glyphs and spotting tiles pasted at the wrong index. By default the tiles with the{n}
+    /// same pixels are grouped, with --perceptual the tiles whose 64 bit difference hashes of their luminance over{n}
+    /// the video differ by at most --max-distance bits, which tolerates slightly different anti-aliasing. Only the{n}
+    /// tiles of the same kind are grouped, across all the fonts given, and the blank tiles are left out. The fonts{n}
+    /// are specified the same way as for the `convert` command or the `convert-set` command.
+    FindDuplicates {
+
+        /// group the near identical tiles too
+        #[clap(long)]
+        perceptual: bool,
+
+        /// number of differing hash bits up to which the tiles are grouped with --perceptual
+        #[clap(long, value_name = "BITS", default_value_t = 4, requires = "perceptual")]
+        max_distance: u32,
+
+        /// collection or collection set specifications
+        #[clap(required = true)]
+        sources: Vec<String>,
+    },
+
+    /// Lists the idents of the normalized bin files and grid images found in a directory
+    ///
+    /// For each ident the tile kinds found are printed with their tile counts, taken from the number of bin file{n}
+    /// pages or else from the dimensions of the grid images, then the files present are printed along with the{n}
+    /// files missing to complete the bin file set{n}
+    /// and/or the grid set, see the `convert-set` command for the normalized names. Files with a name close to a{n}
+    /// normalized name (wrong case, extra suffix) are listed as unrecognized.
+    ListIdents {
+
+        /// directory to scan
+        dir: PathBuf,
+    },
+
+    /// Prints the shell completion script for the given shell
+    ///
+    /// e.g. `hd_fpv_osd_font_tool completions bash > /etc/bash_completion.d/hd_fpv_osd_font_tool`{n}
+    /// The subcommands, options and their possible values are completed, the collection specifications{n}
+    /// (prefix:path) are left to the default completion of the shell.
+    Completions {
+
+        /// shell to generate the completion script for
+        #[clap(value_enum)]
+        shell: Shell,
     },
 
     #[clap(hide(true))]
@@ -118,8 +938,250 @@ pub enum Commands {
 
 }
 
-#[derive(Getters)]
-pub struct ConvertOptions<'a> {
-    #[getset(get = "pub")]
-    pub symbol_specs_file: &'a PathBuf
-}
\ No newline at end of file
+impl Cli {
+
+    pub fn log_level(&self) -> LogLevel {
+        match (self.verbose, self.quiet) {
+            (0, 0) => self.log_level,
+            (1, _) => LogLevel::Debug,
+            (_, 0) => LogLevel::Trace,
+            (_, 1) => LogLevel::Warn,
+            (_, _) => LogLevel::Error,
+        }
+    }
+
+}
+
+impl Commands {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Commands::Convert { .. } => "convert",
+            Commands::ConvertSet { .. } => "convert-set",
+            Commands::Stats { .. } => "stats",
+            Commands::Check { .. } => "check",
+            Commands::CheckSpecs { .. } => "check-specs",
+            Commands::Fix { .. } => "fix",
+            Commands::Hash { .. } => "hash",
+            Commands::Verify { .. } => "verify",
+            Commands::Info { .. } => "info",
+            Commands::Generate { .. } => "generate",
+            Commands::Merge { .. } => "merge",
+            Commands::MakePatch { .. } => "make-patch",
+            Commands::ApplyPatch { .. } => "apply-patch",
+            Commands::Batch { .. } => "batch",
+            Commands::Remap { .. } => "remap",
+            Commands::ExtractTile { .. } => "extract-tile",
+            Commands::SetTile { .. } => "set-tile",
+            Commands::RenderText { .. } => "render-text",
+            Commands::Preview { .. } => "preview",
+            Commands::PreviewOsd { .. } => "preview-osd",
+            Commands::Show { .. } => "show",
+            Commands::Install { .. } => "install",
+            Commands::Backup { .. } => "backup",
+            Commands::Restore { .. } => "restore",
+            #[cfg(feature = "network")]
+            Commands::Fetch { .. } => "fetch",
+            #[cfg(feature = "tui")]
+            Commands::Tui { .. } => "tui",
+            Commands::SplitSymbols { .. } => "split-symbols",
+            Commands::GenSymbolSpecs { .. } => "gen-symbol-specs",
+            Commands::Diff { .. } => "diff",
+            Commands::Compare { .. } => "compare",
+            Commands::FindDuplicates { .. } => "find-duplicates",
+            Commands::ListIdents { .. } => "list-idents",
+            Commands::Completions { .. } => "completions",
+            Commands::GenerateManPages => "generate-man-pages",
+        }
+    }
+}
+
+#[derive(Args)]
+pub struct ConvertOptionsArgs {
+
+    /// symbol specs file used when writing symbol directories, can be repeated,{n}
+    /// later files override the specs of earlier ones covering the same tiles
+    #[clap(short, long, value_parser, default_value = DEFAULT_SYMBOL_SPECS_FILE)]
+    symbol_specs_file: Vec<PathBuf>,
+
+    /// append the name of the symbols from the built-in table of this firmware to the file names when writing symbol{n}
+    /// directories e.g. 001_SYM_RSSI.png, such directories can still be loaded
+    #[clap(long, value_enum)]
+    symbol_names: Option<SymbolNames>,
+
+    /// adjust tile images which do not have the exact expected dimensions instead of failing (tile directories and grids only)
+    #[clap(long, value_enum)]
+    fit: Option<TileFit>,
+
+    /// accept grid images with a margin of up to 8 pixels on the right/bottom of the grid (e.g. a border added by an editor),{n}
+    /// the margin is ignored, images smaller than a grid are still rejected
+    #[clap(long)]
+    lenient_grids: bool,
+
+    /// arrangement of the tiles in grid images as <columns> or <columns>x<rows> e.g. 32x8 or 1 for a single column,{n}
+    /// the number of rows follows the number of tiles when not given, the grids are completed with transparent tiles{n}
+    /// otherwise (tile grids only)
+    #[clap(long, value_name = "LAYOUT", default_value_t = TileGridLayout::default())]
+    grid_layout: TileGridLayout,
+
+    /// overlay the index of each tile on the written grid images, `pages` also marks the bin file page boundaries{n}
+    /// in red, for documentation images only as annotated grids cannot be loaded back (tile grids only)
+    #[clap(long, value_enum, value_name = "ANNOTATION", num_args = 0..=1, default_missing_value = "indexes")]
+    annotate: Option<TileGridAnnotation>,
+
+    /// fail when the tiles do not fill the bin file pages exactly instead of completing them with transparent tiles{n}
+    /// (bin file sets only)
+    #[clap(long)]
+    no_pad: bool,
+
+    /// template of the bin file names of djibinsetnorm sources and targets, e.g. font_{ident}_{kind}_{page}.bin, with the{n}
+    /// placeholders {ident}, {_ident} (`_` and the ident), {kind} (sd/hd), {_hd} (`_hd` for HD), {page} (1-based) and{n}
+    /// {_page} (`_` and the page number from the second page on) [default: font{_ident}{_hd}{_page}.bin]
+    #[clap(long, value_name = "TEMPLATE")]
+    name_template: Option<NameTemplate>,
+
+    /// format of the written tile/symbol images (tile/symbol directories and normalized grids): png, bmp, tga, webp or tiff,{n}
+    /// the format of a named grid image follows its extension
+    #[clap(long, alias = "output-image-format", default_value_t = ImageFormat::Png)]
+    image_format: ImageFormat,
+
+    /// make the pixels of this color transparent in tile/symbol images without an alpha channel (e.g. 24 bit BMP),{n}
+    /// such images are fully opaque otherwise
+    #[clap(long, value_enum)]
+    color_key: Option<ColorKey>,
+
+    /// how the colors of tile images with an alpha channel relate to it, premultiplied colors are restored on load,{n}
+    /// auto detects premultiplied images and fully transparent pixels are cleared in every case (image based sources only)
+    #[clap(long, value_enum)]
+    alpha: Option<AlphaMode>,
+
+    /// make the semi-transparent pixels with an alpha lower than N transparent and the other ones opaque once loaded,{n}
+    /// e.g. for grids or rendered fonts with anti-aliased edges showing as gray fringes on the goggles
+    #[clap(long, value_name = "N", value_parser = clap::value_parser!(u8).range(1..))]
+    alpha_threshold: Option<u8>,
+
+    /// integer factor the tiles are upscaled by when writing an analog grid or a tile/symbol directory, such grids can still be loaded
+    /// but upscaled directories are for presentation only and cannot be loaded back
+    #[clap(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    scale: u32,
+
+    /// write the files of the tiles identical to a previous tile as hard links, relative symbolic links or copies of{n}
+    /// the file of the first one (tiledir/tilesetdir targets), e.g. to shrink the directories of fonts with many blank tiles
+    #[clap(long, value_enum, value_name = "MODE")]
+    dedup_mode: Option<DedupMode>,
+
+    /// filter used to resample the tiles when the target only holds another tile kind (e.g. HD tiles to an analog grid)
+    #[clap(long, value_enum, default_value_t = RescaleFilter::default())]
+    rescale_filter: RescaleFilter,
+
+    /// fail instead of resampling the tiles when the target only holds another tile kind
+    #[clap(long)]
+    no_rescale: bool,
+
+    /// snap the resampled tiles back to fully transparent or opaque gray pixels so that the edges stay crisp
+    #[clap(long)]
+    requantize: bool,
+
+    /// when a set source only holds SD or HD tiles (normalized bin files or grids, tile/symbol set directories),{n}
+    /// generate the missing tile kind by resampling the other one with this filter instead of failing (convert-set only)
+    #[clap(long, value_enum, value_name = "FILTER")]
+    synthesize_missing_kind: Option<RescaleFilter>,
+
+    /// maximum number of tiles loaded from tile/symbol directories, the files with a higher index are ignored{n}
+    /// [default: 512]
+    #[clap(long, value_name = "COUNT", value_parser = clap::value_parser!(u64).range(1..), conflicts_with = "pages")]
+    tile_count: Option<u64>,
+
+    /// maximum number of tiles loaded from tile/symbol directories given as a number of 256 tiles bin file pages
+    #[clap(long, value_name = "COUNT", value_parser = clap::value_parser!(u64).range(1..=MAX_PAGE_COUNT as u64))]
+    pages: Option<u64>,
+
+    /// how the gaps in the tile indexes of tile directories are filled: error, transparent or placeholder (a crossed box),{n}
+    /// the missing indexes are logged in every case (tiledir, tilesetdir and tile set archive sources only)
+    #[clap(long, value_enum, value_name = "POLICY", default_value_t = MissingTiles::default())]
+    missing_tiles: MissingTiles,
+
+    #[command(flatten)]
+    transform_args: TransformArgs,
+
+}
+
+impl ConvertOptionsArgs {
+    pub fn convert_options(&self) -> anyhow::Result<ConvertOptions> {
+        let options = ConvertOptions::default()
+            .symbol_specs_files(&self.symbol_specs_file)
+            .symbol_names(self.symbol_names)
+            .fit(self.fit)
+            .grid_validation(if self.lenient_grids { TileGridValidation::Lenient } else { TileGridValidation::Strict })
+            .grid_layout(self.grid_layout)
+            .grid_annotation(self.annotate)
+            .bin_file_padding(if self.no_pad { BinFilePadding::Strict } else { BinFilePadding::Pad })
+            .bin_name_template(self.name_template.clone())
+            .image_format(self.image_format)
+            .color_key(self.color_key)
+            .alpha(self.alpha)
+            .alpha_threshold(self.alpha_threshold)
+            .scale(self.scale)
+            .dedup_mode(self.dedup_mode)
+            .rescale_filter((!self.no_rescale).then_some(self.rescale_filter))
+            .requantize(self.requantize)
+            .synthesize_missing_kind(self.synthesize_missing_kind)
+            .missing_tiles(self.missing_tiles)
+            .transforms(self.transform_args.transforms());
+        let options = match (self.tile_count, self.pages) {
+            (Some(tile_count), _) => options.max_tile_count(tile_count as usize),
+            (None, Some(pages)) => options.max_page_count(pages as usize)?,
+            (None, None) => options,
+        };
+        options.validate()?;
+        Ok(options)
+    }
+}
+
+#[derive(Args)]
+pub struct TransformArgs {
+
+    /// transform to apply to the tiles, can be repeated to chain transforms which are applied in order:{n}
+    ///     invert                      swap black and white{n}
+    ///     brightness=<delta>          add delta (-255 to 255) to the shades{n}
+    ///     levels=<black>:<white>      stretch the black..white range of shades to the full range{n}
+    ///     contrast=<percent>          increase (positive) or decrease (-100 to 0) the contrast{n}
+    ///     tint=<color>                multiply the shades by the color e.g. green or amber variants of white fonts{n}
+    ///     grayscale                   convert the shades to gray{n}
+    ///     quantize[=<levels>[:dither]]  restrict the tiles to transparent and <levels> (default 2) gray shades from black{n}
+    ///                                 to white, the OSD palette, optionally with ordered dithering{n}
+    ///     add-outline=<px>[:<color>]  add an outline around the glyphs{n}
+    ///     remove-outline=<px>[:<color>]  remove an outline from around the glyphs{n}
+    ///     drop-shadow=<px>[:<color>]  add a shadow shifted right and down under the glyphs{n}
+    ///     rotate90|rotate180|rotate270  rotate the tiles clockwise, the tiles must end up with their original dimensions{n}
+    ///     flip-h|flip-v               mirror the tiles horizontally/vertically
+    #[clap(long = "transform", value_name = "TRANSFORM")]
+    transforms: Vec<TileTransform>,
+
+    /// add an outline of the given thickness in pixels around the glyphs, the thickness is for SD tiles and is scaled for HD tiles
+    #[clap(long, value_name = "THICKNESS")]
+    add_outline: Option<u32>,
+
+    /// remove an outline of the given thickness in pixels from around the glyphs, the thickness is for SD tiles and is scaled for HD tiles
+    #[clap(long, value_name = "THICKNESS")]
+    remove_outline: Option<u32>,
+
+    /// color of the outline added/removed with --add-outline/--remove-outline: black, white or #RRGGBB[AA], the alpha{n}
+    /// sets the opacity of the added outline
+    #[clap(long, value_parser = parse_color, default_value = "black")]
+    outline_color: Rgba<u8>,
+
+}
+
+impl TransformArgs {
+    // --remove-outline and --add-outline are shortcuts for transforms applied after the ones specified with --transform
+    pub fn transforms(&self) -> Vec<TileTransform> {
+        let mut transforms = self.transforms.clone();
+        if let Some(thickness) = self.remove_outline {
+            transforms.push(TileTransform::RemoveOutline { color: self.outline_color, thickness });
+        }
+        if let Some(thickness) = self.add_outline {
+            transforms.push(TileTransform::AddOutline { color: self.outline_color, thickness });
+        }
+        transforms
+    }
+}
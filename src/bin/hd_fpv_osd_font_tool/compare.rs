@@ -0,0 +1,87 @@
+use std::path::Path;
+
+use hd_fpv_osd_font_tool::{
+    osd::tile::{
+        Image,
+        Kind as TileKind,
+        container::uniq_tile_kind::UniqTileKind,
+        regression::{montage_rows, save_montage, tile_differences, Thresholds, TileDifference},
+    },
+    prelude::*,
+    report::{CollectionReport, CommandReport},
+};
+use strum::IntoEnumIterator;
+use thiserror::Error;
+
+use crate::{
+    convert::load_tiles,
+    convert_set::{is_tile_set_arg, load_tile_set},
+    diff::check_tile_kinds,
+    output::OutputFormat,
+    overwrite::check_outputs,
+};
+
+
+#[derive(Debug, Error)]
+#[error("{failing_count} of the {difference_count} differing tile(s) exceed the thresholds")]
+pub struct CompareError {
+    pub failing_count: usize,
+    pub difference_count: usize,
+}
+
+// differences and montage rows of the tiles of a single kind
+fn compare_tiles(reference: &[Tile], tiles: &[Tile], montage: bool) -> (Vec<TileDifference>, Vec<(String, Image)>) {
+    let differences = tile_differences(reference, tiles);
+    let rows = match montage {
+        true => montage_rows(reference, tiles, &differences),
+        false => vec![],
+    };
+    (differences, rows)
+}
+
+fn print_difference(difference: &TileDifference, exceeds: bool) {
+    println!("{} {:03} {}: {} changed pixels, {:.2}% perceptual difference{}", difference.tile_kind, difference.index, difference.kind,
+        difference.changed_pixels, difference.perceptual_difference, if exceeds { " (exceeds the thresholds)" } else { "" });
+}
+
+pub fn compare_command(from: &str, reference: &str, thresholds: Thresholds, montage: Option<&Path>, output_format: OutputFormat, report: &mut CommandReport) -> anyhow::Result<()> {
+    if let Some(montage) = montage {
+        check_outputs(&[montage])?;
+    }
+    let (differences, rows) = if is_tile_set_arg(reference) {
+        let reference_set = load_tile_set(reference, report)?;
+        let tile_set = load_tile_set(from, report)?;
+        let (mut differences, mut rows) = (vec![], vec![]);
+        for tile_kind in TileKind::iter() {
+            let (kind_differences, kind_rows) = compare_tiles(&reference_set[tile_kind], &tile_set[tile_kind], montage.is_some());
+            differences.extend(kind_differences);
+            rows.extend(kind_rows);
+        }
+        (differences, rows)
+    } else {
+        let reference_tiles = load_tiles(reference, report)?;
+        let tiles = load_tiles(from, report)?;
+        check_tile_kinds(reference_tiles.tile_kind()?, tiles.tile_kind()?)?;
+        compare_tiles(&reference_tiles, &tiles, montage.is_some())
+    };
+
+    if let Some(montage) = montage.filter(|_| !rows.is_empty()) {
+        save_montage(&rows, montage)?;
+        report.outputs.push(CollectionReport::new("montage", &[montage]));
+    }
+    let failing_count = differences.iter().filter(|difference| thresholds.exceeded_by(difference)).count();
+    let difference_count = differences.len();
+    match output_format {
+        OutputFormat::Json => report.comparison = Some(differences),
+        OutputFormat::Human => {
+            for difference in &differences {
+                print_difference(difference, thresholds.exceeded_by(difference));
+            }
+            println!("{difference_count} differing tiles");
+        },
+    }
+    match failing_count {
+        0 => Ok(()),
+        _ => Err(CompareError { failing_count, difference_count }.into()),
+    }
+}
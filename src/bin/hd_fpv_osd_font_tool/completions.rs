@@ -0,0 +1,11 @@
+use clap::CommandFactory;
+use clap_complete::Shell;
+
+use super::{cli::Cli, current_exe_name};
+
+
+pub fn completions_command(shell: Shell) -> anyhow::Result<()> {
+    let mut command = Cli::command();
+    clap_complete::generate(shell, &mut command, current_exe_name()?, &mut std::io::stdout());
+    Ok(())
+}
@@ -0,0 +1,152 @@
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+use clap::Command;
+use serde::Deserialize;
+use thiserror::Error;
+
+// Defaults for the command line options read from $XDG_CONFIG_HOME/hd_fpv_osd_font_tool/config.toml
+// (~/.config/hd_fpv_osd_font_tool/config.toml when not set) or from the file given with --config, the options given on
+// the command line take precedence. The values are checked like the command line ones.
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    // --symbol-specs-file
+    symbol_specs_files: Option<Vec<PathBuf>>,
+    // --target of the install, backup and restore commands
+    font_dir: Option<PathBuf>,
+    // --name-template
+    name_template: Option<String>,
+    // --rescale-filter
+    rescale_filter: Option<String>,
+    // --output
+    output: Option<String>,
+}
+
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error(transparent)]
+    Read(#[from] std::io::Error),
+    #[error("invalid configuration file {}: {error}", .path.to_string_lossy())]
+    Parse { path: PathBuf, error: toml::de::Error },
+}
+
+// commands with a --target option being the directory the SD card is mounted on
+const FONT_DIR_COMMANDS: [&str; 3] = ["install", "backup", "restore"];
+
+pub fn default_config_path() -> PathBuf {
+    let config_home = std::env::var_os("XDG_CONFIG_HOME").map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".config")))
+        .unwrap_or_else(|| PathBuf::from(".config"));
+    config_home.join("hd_fpv_osd_font_tool").join("config.toml")
+}
+
+// the path given with --config, looked up before parsing the command line since the defaults depend on the file
+pub fn config_arg_path<I: IntoIterator<Item = OsString>>(args: I) -> Option<PathBuf> {
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            break;
+        }
+        if arg == "--config" {
+            return args.next().map(PathBuf::from);
+        }
+        if let Some(path) = arg.to_str().and_then(|arg| arg.strip_prefix("--config=")) {
+            return Some(PathBuf::from(path));
+        }
+    }
+    None
+}
+
+// the default file is optional, the file given with --config is not
+pub fn load(path: Option<&Path>) -> Result<Config, ConfigError> {
+    let path = match path {
+        Some(path) => path.to_path_buf(),
+        None => match default_config_path() {
+            path if path.is_file() => path,
+            _ => return Ok(Config::default()),
+        },
+    };
+    let content = fs_err::read_to_string(&path)?;
+    toml::from_str(&content).map_err(|error| ConfigError::Parse { path, error })
+}
+
+// clap only takes static default values, the few leaked strings live as long as the process anyway
+fn leak(value: String) -> &'static str {
+    Box::leak(value.into_boxed_str())
+}
+
+fn path_str(path: &Path) -> &'static str {
+    leak(path.to_string_lossy().to_string())
+}
+
+fn has_arg(command: &Command, arg_id: &str) -> bool {
+    command.get_arguments().any(|arg| arg.get_id() == arg_id)
+}
+
+// sets the default values of the argument in the command and its subcommands with `commands` names, all when empty
+fn set_default_values(command: Command, arg_id: &'static str, values: &[&'static str], commands: &[&str]) -> Command {
+    let values = values.to_vec();
+    let applies = commands.is_empty() || commands.contains(&command.get_name());
+    let command = match applies && has_arg(&command, arg_id) {
+        true => command.mut_arg(arg_id, |arg| arg.default_values(values.clone())),
+        false => command,
+    };
+    let subcommand_names: Vec<String> = command.get_subcommands().map(|subcommand| subcommand.get_name().to_owned()).collect();
+    subcommand_names.iter().fold(command, |command, name| {
+        command.mut_subcommand(name, |subcommand| set_default_values(subcommand, arg_id, &values, commands))
+    })
+}
+
+impl Config {
+
+    pub fn apply(&self, command: Command) -> Command {
+        let mut command = command;
+        if let Some(paths) = &self.symbol_specs_files {
+            let paths: Vec<&'static str> = paths.iter().map(|path| path_str(path)).collect();
+            command = set_default_values(command, "symbol_specs_file", &paths, &[]);
+        }
+        if let Some(font_dir) = &self.font_dir {
+            command = set_default_values(command, "target", &[path_str(font_dir)], &FONT_DIR_COMMANDS);
+        }
+        if let Some(name_template) = &self.name_template {
+            command = set_default_values(command, "name_template", &[leak(name_template.clone())], &[]);
+        }
+        if let Some(rescale_filter) = &self.rescale_filter {
+            command = set_default_values(command, "rescale_filter", &[leak(rescale_filter.clone())], &[]);
+        }
+        if let Some(output) = &self.output {
+            command = set_default_values(command, "output", &[leak(output.clone())], &[]);
+        }
+        command
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use clap::{CommandFactory, FromArgMatches};
+
+    use crate::cli::{Cli, Commands};
+    use crate::output::OutputFormat;
+    use super::{config_arg_path, Config};
+
+    #[test]
+    fn defaults_from_config() {
+        assert_eq!(config_arg_path(["tool", "--config=my.toml", "info"].map(Into::into)), Some(PathBuf::from("my.toml")));
+        assert_eq!(config_arg_path(["tool", "info", "--", "--config"].map(Into::into)), None);
+
+        let config: Config = toml::from_str("output = 'json'\nfont_dir = '/media/goggles'\n").unwrap();
+        let parse = |args: &[&str]| Cli::from_arg_matches(&config.apply(Cli::command()).try_get_matches_from(args).unwrap()).unwrap();
+        let cli = parse(&["tool", "backup"]);
+        assert_eq!(cli.output(), OutputFormat::Json);
+        assert!(matches!(cli.command, Commands::Backup { target: Some(target), .. } if target == Path::new("/media/goggles")));
+        // the command line takes precedence
+        assert_eq!(parse(&["tool", "--output", "human", "info", "djibin:font.bin"]).output(), OutputFormat::Human);
+        assert!(toml::from_str::<Config>("unknown = 1\n").is_err());
+    }
+
+}
@@ -1,10 +1,33 @@
 
-use std::{error::Error, fmt::Display, path::Path};
-
-use hd_fpv_osd_font_tool::prelude::*;
+use std::{error::Error, fmt::Display, path::{Path, PathBuf}};
+
+use hd_fpv_osd_font_tool::{
+    osd::{
+        avatar_file,
+        bin_file::LazyReader,
+        font_metadata::{self, FontMetadata},
+        mcm_file,
+        source_array::{self, Encoding as SourceArrayEncoding, Language as SourceLanguage},
+        tile::{
+            analog_grid,
+            atlas,
+            hdzero_sheet,
+            rescale::rescale_tiles,
+            transform::threshold_alpha,
+            container::{
+                symbol::{Symbol, spec::InvalidSpecsError},
+                uniq_tile_kind::UniqTileKind,
+            },
+        },
+    },
+    prelude::*,
+    report::{CollectionReport, CommandReport, RescaleReport},
+};
 use thiserror::Error;
 
-use crate::ConvertOptions;
+use crate::incremental::IncrementalConversion;
+use crate::overwrite::check_outputs;
+
 
 
 #[derive(Debug)]
@@ -15,6 +38,8 @@ pub enum InvalidConvertArgError {
         extension: Option<String>
     },
     InvalidPath(String),
+    AnalogGridTooManyPaths(String),
+    TargetOnly(String),
     NoPrefix
 }
 
@@ -29,22 +54,75 @@ impl Display for InvalidConvertArgError {
             InvalidImageFileExtension { path, extension: Some(extension) } => write!(f, "invalid image file extension `{}`: {}", extension, path),
             InvalidImageFileExtension { path, extension: None } => write!(f, "image path has no file extension: {}", path),
             InvalidPath(path) => write!(f, "invalid path: {}", path),
+            AnalogGridTooManyPaths(paths) => write!(f, "an analog grid is at most 2 sheets: {}", paths),
+            TargetOnly(prefix) => write!(f, "{prefix} collections can only be written"),
         }
     }
 }
 
-enum ConvertArg<'a> {
-    BinFile(&'a str),
+pub enum ConvertArg<'a> {
+    BinFile {
+        path: &'a str,
+        tile_kind: Option<tile::Kind>,
+    },
     AvatarFile(&'a str),
     TileGrid(&'a str),
     TileDir(&'a str),
     SymbolDir(&'a str),
+    AnalogGrid {
+        path: &'a str,
+        second_path: Option<&'a str>,
+    },
+    McmFile {
+        path: &'a str,
+        tile_kind: Option<tile::Kind>,
+    },
+    HdZeroSheet(&'a str),
+    SourceArray {
+        path: &'a str,
+        language: SourceLanguage,
+        encoding: SourceArrayEncoding,
+    },
+    Atlas(&'a str),
 }
 
-fn check_arg_image_file_extension(path: &str) -> Result<(), InvalidConvertArgError> {
+impl<'a> ConvertArg<'a> {
+    pub fn report(&self) -> CollectionReport {
+        use ConvertArg::*;
+        match self {
+            BinFile { path, .. } => CollectionReport::new("djibin", &[path]),
+            AvatarFile(path) => CollectionReport::new("avatar", &[path]),
+            TileGrid(path) => CollectionReport::new("tilegrid", &[path]),
+            TileDir(path) => CollectionReport::new("tiledir", &[path]),
+            SymbolDir(path) => CollectionReport::new("symdir", &[path]),
+            AnalogGrid { path, second_path } => {
+                let paths: Vec<&str> = std::iter::once(*path).chain(*second_path).collect();
+                CollectionReport::new("analoggrid", &paths)
+            },
+            McmFile { path, .. } => CollectionReport::new("mcm", &[path]),
+            HdZeroSheet(path) => CollectionReport::new("hdzero", &[path]),
+            SourceArray { path, language: SourceLanguage::C, .. } => CollectionReport::new("cheader", &[path]),
+            SourceArray { path, language: SourceLanguage::Rust, .. } => CollectionReport::new("rustsrc", &[path]),
+            Atlas(path) => CollectionReport::new("atlas", &[Path::new(path), atlas::index_path(path).as_path()]),
+        }
+    }
+
+    // tile kind the format can only hold when used as a target
+    fn required_tile_kind(&self) -> Option<tile::Kind> {
+        use ConvertArg::*;
+        match self {
+            BinFile { tile_kind, .. } => *tile_kind,
+            AnalogGrid { .. } => Some(tile::Kind::SD),
+            HdZeroSheet(_) => Some(tile::Kind::HD),
+            AvatarFile(_) | TileGrid(_) | TileDir(_) | SymbolDir(_) | McmFile { .. } | SourceArray { .. } | Atlas(_) => None,
+        }
+    }
+}
+
+pub fn check_arg_image_file_extension(path: &str) -> Result<(), InvalidConvertArgError> {
     match Path::extension(Path::new(path)) {
         Some(os_str) => match os_str.to_str() {
-            Some("png") => Ok(()),
+            Some(extension) if ImageFormat::from_extension(extension).is_some() => Ok(()),
             Some(extension) => Err(InvalidConvertArgError::InvalidImageFileExtension { path: path.to_owned(), extension: Some(extension.to_owned()) }),
             None => Err(InvalidConvertArgError::InvalidPath(path.to_owned()))
         },
@@ -52,9 +130,43 @@ fn check_arg_image_file_extension(path: &str) -> Result<(), InvalidConvertArgErr
     }
 }
 
-fn identify_convert_arg(input: &str) -> Result<ConvertArg, InvalidConvertArgError> {
+fn split_source_array_encoding(path: &str) -> (&str, SourceArrayEncoding) {
+    match path.split_once(':') {
+        Some(("rgba", path)) => (path, SourceArrayEncoding::Rgba),
+        Some(("max7456", path)) => (path, SourceArrayEncoding::Max7456),
+        _ => (path, SourceArrayEncoding::default()),
+    }
+}
+
+fn split_tile_kind(path: &str) -> (&str, Option<tile::Kind>) {
+    match path.split_once(':') {
+        Some(("sd", path)) => (path, Some(tile::Kind::SD)),
+        Some(("hd", path)) => (path, Some(tile::Kind::HD)),
+        _ => (path, None),
+    }
+}
+
+pub fn identify_convert_arg(input: &str) -> Result<ConvertArg<'_>, InvalidConvertArgError> {
     if let Some(path) = input.strip_prefix("djibin:") {
-        Ok(ConvertArg::BinFile(path))
+        Ok(ConvertArg::BinFile { path, tile_kind: None })
+    } else if let Some(path) = input.strip_prefix("bin:") {
+        let (path, tile_kind) = split_tile_kind(path);
+        Ok(ConvertArg::BinFile { path, tile_kind })
+    } else if let Some(path) = input.strip_prefix("mcm:") {
+        let (path, tile_kind) = split_tile_kind(path);
+        Ok(ConvertArg::McmFile { path, tile_kind })
+    } else if let Some(path) = input.strip_prefix("cheader:") {
+        let (path, encoding) = split_source_array_encoding(path);
+        Ok(ConvertArg::SourceArray { path, language: SourceLanguage::C, encoding })
+    } else if let Some(path) = input.strip_prefix("rustsrc:") {
+        let (path, encoding) = split_source_array_encoding(path);
+        Ok(ConvertArg::SourceArray { path, language: SourceLanguage::Rust, encoding })
+    } else if let Some(path) = input.strip_prefix("atlas:") {
+        check_arg_image_file_extension(path)?;
+        Ok(ConvertArg::Atlas(path))
+    } else if let Some(path) = input.strip_prefix("hdzero:") {
+        check_arg_image_file_extension(path)?;
+        Ok(ConvertArg::HdZeroSheet(path))
     } else if let Some(path) = input.strip_prefix("tilegrid:") {
         Ok(ConvertArg::TileGrid(path))
     } else if let Some(path) = input.strip_prefix("tiledir:") {
@@ -63,6 +175,14 @@ fn identify_convert_arg(input: &str) -> Result<ConvertArg, InvalidConvertArgErro
         Ok(ConvertArg::SymbolDir(path))
     } else if let Some(path) = input.strip_prefix("avatar:") {
         Ok(ConvertArg::AvatarFile(path))
+    } else if let Some(paths) = input.strip_prefix("analoggrid:") {
+        let mut split = paths.split(':');
+        let path = split.next().unwrap();
+        let second_path = split.next();
+        if split.next().is_some() {
+            return Err(InvalidConvertArgError::AnalogGridTooManyPaths(paths.to_owned()));
+        }
+        Ok(ConvertArg::AnalogGrid { path, second_path })
     } else if let Some((prefix, _)) = input.split_once(':') {
         Err(InvalidConvertArgError::InvalidPrefix(prefix.to_owned()))
     } else {
@@ -80,83 +200,206 @@ pub enum ConvertError {
 
 fn convert_tiles(tiles: Vec<Tile>, to_arg: &ConvertArg, options: &ConvertOptions) -> anyhow::Result<()> {
     use ConvertArg::*;
+    check_outputs(&to_arg.report().paths)?;
     match to_arg {
         TileGrid(to_path) => {
             check_arg_image_file_extension(to_path).map_err(ConvertError::ToArg)?;
-            tiles.save_to_grid_image(to_path)?
+            tiles.save_to_grid_image_annotated(to_path, options.grid_layout, options.grid_annotation)?
         },
-        TileDir(to_path) => tiles.save_tiles_to_dir(to_path)?,
+        TileDir(to_path) => tiles.save_tiles_to_dir_deduplicated(to_path, options.image_format, options.scale, options.dedup_mode)?,
         SymbolDir(to_path) => {
-            let sym_specs = SymbolSpecs::load_file(options.symbol_specs_file())?;
-            tiles.to_symbols(&sym_specs)?.save_to_dir(to_path)?;
+            let sym_specs = SymbolSpecs::load_files(&options.symbol_specs_files)?;
+            tiles.to_symbols(&sym_specs)?.save_to_dir_named(to_path, options.image_format, options.scale, options.symbol_names)?;
         },
-        BinFile(to_path) => tiles.save_to_bin_file(to_path)?,
+        BinFile { path, tile_kind: Some(tile_kind) } => bin_file::save(path, *tile_kind, &tiles)?,
+        BinFile { path, tile_kind: None } => tiles.save_to_bin_file(path)?,
         AvatarFile(to_path) => tiles.save_to_avatar_file(to_path)?,
+        McmFile { path, .. } => mcm_file::save(&tiles, path)?,
+        HdZeroSheet(path) => hdzero_sheet::save_image(&tiles, path)?,
+        SourceArray { path, language, encoding } => source_array::save(&tiles, path, *language, *encoding)?,
+        Atlas(path) => atlas::save(&tiles, path)?,
+        AnalogGrid { path, second_path } => {
+            check_arg_image_file_extension(path).map_err(ConvertError::ToArg)?;
+            let (first_sheet_tiles, second_sheet_tiles) = tiles.split_at(tiles.len().min(analog_grid::TILE_COUNT));
+            analog_grid::save_image(first_sheet_tiles, path, options.scale)?;
+            match second_path {
+                Some(second_path) => {
+                    check_arg_image_file_extension(second_path).map_err(ConvertError::ToArg)?;
+                    let second_sheet_tiles = match second_sheet_tiles {
+                        [] => vec![Tile::new(tile::Kind::SD)],
+                        tiles => tiles.to_vec(),
+                    };
+                    analog_grid::save_image(&second_sheet_tiles, second_path, options.scale)?;
+                },
+                None => if !second_sheet_tiles.is_empty() {
+                    log::warn!("only the first {} tiles were written, add a second sheet path to write the others", analog_grid::TILE_COUNT);
+                },
+            }
+        },
     }
     Ok(())
 }
 
-fn convert_tile_grid(tile_grid: TileGrid, to_arg: &ConvertArg, options: &ConvertOptions) -> anyhow::Result<()> {
-    use ConvertArg::*;
-    match to_arg {
-        BinFile(to_path) => tile_grid.save_tiles_to_bin_file(to_path)?,
-        TileDir(to_path) => tile_grid.save_tiles_to_dir(to_path)?,
-        SymbolDir(to_path) => {
-            let sym_specs = SymbolSpecs::load_file(options.symbol_specs_file())?;
-            tile_grid.to_symbols(&sym_specs)?.save_to_dir(to_path)?;
-        },
-        TileGrid(to_path) => tile_grid.save_image(to_path)?,
-        AvatarFile(to_path) => tile_grid.save_tiles_to_avatar_file(to_path)?,
+// the directory holding the font.toml metadata of the tile and symbol (set) directories
+pub fn metadata_dir(collection: &CollectionReport) -> Option<&Path> {
+    match collection.format.as_str() {
+        "tiledir" | "symdir" | "tilesetdir" | "symsetdir" => collection.paths.first().map(PathBuf::as_path),
+        _ => None,
+    }
+}
+
+// copies the font.toml of the source directory to the destination directory, the other formats have nowhere to keep it
+pub fn carry_font_metadata(from: &CollectionReport, to: &CollectionReport) -> anyhow::Result<()> {
+    let (from_dir, to_dir) = match (metadata_dir(from), metadata_dir(to)) {
+        (Some(from_dir), Some(to_dir)) => (from_dir, to_dir),
+        _ => return Ok(()),
+    };
+    if let Some(metadata) = FontMetadata::load_from_dir(from_dir)? {
+        metadata.save_to_dir(to_dir)?;
+        log::debug!("font metadata carried over to {}", font_metadata::path(to_dir).to_string_lossy());
     }
     Ok(())
 }
 
-pub fn convert_command(from: &str, to: &str, options: ConvertOptions) -> anyhow::Result<()> {
+fn report_tile_count(report: &mut CommandReport, tiles: &[Tile]) {
+    if let Ok(tile_kind) = tiles.tile_kind() {
+        report.add_tile_count(tile_kind, tiles.len());
+    }
+}
+
+pub fn load_tiles(from: &str, report: &mut CommandReport) -> anyhow::Result<Vec<Tile>> {
+    load_tiles_options(from, &ConvertOptions::default(), report)
+}
+
+pub fn load_tiles_options(from: &str, options: &ConvertOptions, report: &mut CommandReport) -> anyhow::Result<Vec<Tile>> {
     let from_arg = identify_convert_arg(from).map_err(ConvertError::FromArg)?;
-    let to_arg = identify_convert_arg(to).map_err(ConvertError::ToArg)?;
-    log::info!("converting {} -> {}", from, to);
+    report.inputs.push(from_arg.report());
+    let tiles = load_tiles_from_arg(&from_arg, options)?;
+    report_tile_count(report, &tiles);
+    Ok(tiles)
+}
 
-    use ConvertArg::*;
-    match (&from_arg, &to_arg) {
+// memory mapped reader for the commands only looking at some tiles or at their raw bytes, none when the source is not
+// a single bin file
+pub fn open_lazy_bin_file(from: &str, report: &mut CommandReport) -> anyhow::Result<Option<LazyReader>> {
+    let from_arg = identify_convert_arg(from).map_err(ConvertError::FromArg)?;
+    let (path, tile_kind) = match from_arg {
+        ConvertArg::BinFile { path, tile_kind } => (path, tile_kind),
+        _ => return Ok(None),
+    };
+    let reader = LazyReader::open(path)?;
+    if let Some(tile_kind) = tile_kind {
+        if reader.tile_kind() != tile_kind {
+            return Err(BinFileLoadError::tile_kind_mismatch(path, reader.tile_kind(), tile_kind).into());
+        }
+    }
+    report.inputs.push(from_arg.report());
+    report.add_tile_count(reader.tile_kind(), reader.tile_count());
+    Ok(Some(reader))
+}
 
-        (BinFile(from_path), to_arg) => {
-            let tiles = bin_file::load(from_path)?;
-            convert_tiles(tiles, to_arg, &options)?;
-        },
+// symbol images loaded back should match the specs they were generated from, only checked when the specs files exist
+pub fn check_loaded_symbols(symbols: &[Symbol], options: &ConvertOptions) -> anyhow::Result<()> {
+    if options.symbol_specs_files.iter().all(|path| path.is_file()) {
+        let sym_specs = SymbolSpecs::load_files(&options.symbol_specs_files)?;
+        InvalidSpecsError::check(sym_specs.check_symbols(symbols))?;
+    }
+    Ok(())
+}
 
-        (TileGrid(from_path), to_arg) => {
+fn load_tiles_from_arg(from_arg: &ConvertArg, options: &ConvertOptions) -> anyhow::Result<Vec<Tile>> {
+    let fit = options.fit;
+    use ConvertArg::*;
+    let tiles = match from_arg {
+        BinFile { path, tile_kind } => bin_file::load(path, *tile_kind)?,
+        TileGrid(from_path) => {
             check_arg_image_file_extension(from_path).map_err(ConvertError::FromArg)?;
-            let tile_grid = crate::TileGrid::load_from_image(from_path)?;
-            convert_tile_grid(tile_grid, to_arg, &options)?;
+            hd_fpv_osd_font_tool::prelude::TileGrid::load_from_image_decoded(from_path, fit, options.decoding(), options.grid_validation, options.grid_layout)?.to_vec()
         },
-
-        (TileDir(from_path), to_arg) => {
-            let tiles = load_tiles_from_dir(from_path, 512)?;
-            convert_tiles(tiles, to_arg, &options)?;
+        TileDir(from_path) => load_tiles_from_dir_missing(from_path, options.max_tile_count, fit, options.decoding(), options.missing_tiles)?,
+        SymbolDir(from_path) => {
+            let symbols = load_symbols_from_dir(from_path, options.max_tile_count, fit, options.decoding())?;
+            check_loaded_symbols(&symbols, options)?;
+            symbols.into_tiles_vec()
         },
-
-        (SymbolDir(from_path), to_arg) => {
-            let tiles = load_symbols_from_dir(from_path, 512)?.into_tiles_vec();
-            convert_tiles(tiles, to_arg, &options)?;
+        AvatarFile(from_path) => avatar_file::load_decoded(from_path, options.decoding())?,
+        McmFile { path, tile_kind } => mcm_file::load(path, tile_kind.unwrap_or(tile::Kind::SD))?,
+        HdZeroSheet(path) => hdzero_sheet::load_image_decoded(path, options.decoding())?,
+        SourceArray { .. } | Atlas(_) => return Err(ConvertError::FromArg(InvalidConvertArgError::TargetOnly(from_arg.report().format)).into()),
+        AnalogGrid { path, second_path } => {
+            let mut tiles = analog_grid::load_image_decoded(path, options.decoding())?;
+            if let Some(second_path) = second_path {
+                tiles.extend(analog_grid::load_image_decoded(second_path, options.decoding())?);
+            }
+            tiles
         },
+    };
+    Ok(tiles)
+}
 
-        (AvatarFile(from_path), to_arg) => {
-            let tiles = load_avatar_file(from_path)?;
-            convert_tiles(tiles, to_arg, &options)?;
-        }
+// without a rescale filter the tiles are left as is and saving them fails on the tile kind mismatch
+fn rescale_for_target(tiles: Vec<Tile>, to_arg: &ConvertArg, options: &ConvertOptions, report: &mut CommandReport) -> Vec<Tile> {
+    let (target_tile_kind, filter, tile_kind) = match (to_arg.required_tile_kind(), options.rescale_filter, tiles.tile_kind()) {
+        (Some(target_tile_kind), Some(filter), Ok(tile_kind)) if tile_kind != target_tile_kind => (target_tile_kind, filter, tile_kind),
+        _ => return tiles,
+    };
+    let rescale_report = RescaleReport {
+        tile_count: tiles.len(),
+        from: tile_kind.dimensions().to_string(),
+        to: target_tile_kind.dimensions().to_string(),
+    };
+    log::warn!("{rescale_report} ({filter})");
+    report.rescaled = Some(rescale_report);
+    rescale_tiles(&tiles, target_tile_kind, filter, options.requantize)
+}
 
+pub fn report_alpha_threshold(report: &mut CommandReport, modified_count: usize) {
+    log::info!("alpha threshold: {modified_count} semi-transparent pixels made transparent or opaque");
+    *report.alpha_thresholded_pixels.get_or_insert(0) += modified_count;
+}
+
+pub fn convert_command(from: &str, to: &str, options: ConvertOptions, incremental: bool, report: &mut CommandReport) -> anyhow::Result<()> {
+    let from_arg = identify_convert_arg(from).map_err(ConvertError::FromArg)?;
+    let to_arg = identify_convert_arg(to).map_err(ConvertError::ToArg)?;
+    report.inputs.push(from_arg.report());
+    report.outputs.push(to_arg.report());
+    log::info!("converting {} -> {}", from, to);
+    let mut tiles = load_tiles_from_arg(&from_arg, &options)?;
+    report_tile_count(report, &tiles);
+
+    let output_hash = || -> anyhow::Result<String> { Ok(load_tiles(to, &mut CommandReport::default())?.content_hash()) };
+    let incremental = incremental.then(|| IncrementalConversion::new("convert", from, to, &to_arg.report(), &options, tiles.content_hash()));
+    if let Some(incremental) = &incremental {
+        let up_to_date = incremental.is_up_to_date(output_hash);
+        report.up_to_date = Some(up_to_date);
+        if up_to_date {
+            log::info!("{to} is up to date");
+            return Ok(());
+        }
     }
 
+    if let Some(threshold) = options.alpha_threshold {
+        report_alpha_threshold(report, threshold_alpha(&mut tiles, threshold));
+    }
+    apply_transforms(&mut tiles, &options.transforms)?;
+    let tiles = rescale_for_target(tiles, &to_arg, &options, report);
+    convert_tiles(tiles, &to_arg, &options)?;
+    carry_font_metadata(&from_arg.report(), &to_arg.report())?;
+    if let Some(incremental) = incremental {
+        incremental.record(output_hash);
+    }
     Ok(())
 }
 
 #[cfg(test)]
 mod tests {
 
-    use std::path::{PathBuf, Path};
+    use std::path::PathBuf;
     use std::{io, fs};
 
-    use hd_fpv_osd_font_tool::osd::tile;
+    use hd_fpv_osd_font_tool::osd::tile::{self, analog_grid};
+    use hd_fpv_osd_font_tool::prelude::{ConvertOptions, TileGrid, TileTransform};
+    use hd_fpv_osd_font_tool::report::CommandReport;
     use hd_fpv_osd_font_tool::prelude::bin_file::{self, FontPart};
     use strum::IntoEnumIterator;
     use temp_dir::TempDir;
@@ -199,8 +442,8 @@ mod tests {
                 };
                 let to_path = temp_dir.child(to_rel_path);
                 let to_arg = format!("{to_format}:{}", to_path.to_str().unwrap());
-                let options = crate::ConvertOptions { symbol_specs_file: &Path::new("symbol_specs/ardu.yaml").to_path_buf() };
-                convert_command(&from_arg, &to_arg, options).unwrap();
+                let options = ConvertOptions::default().symbol_specs_file("symbol_specs/ardu.yaml");
+                convert_command(&from_arg, &to_arg, options, false, &mut CommandReport::new("convert")).unwrap();
             }
         }
 
@@ -227,8 +470,8 @@ mod tests {
                 let to_path = temp_dir.child(to_rel_path);
                 let from_arg = format!("{from_format}:{}", from_path.to_str().unwrap());
                 let to_arg = format!("{to_format}:{}", to_path.to_str().unwrap());
-                let options = crate::ConvertOptions { symbol_specs_file: &Path::new("symbol_specs/ardu.yaml").to_path_buf() };
-                convert_command(&from_arg, &to_arg, options).unwrap();
+                let options = ConvertOptions::default().symbol_specs_file("symbol_specs/ardu.yaml");
+                convert_command(&from_arg, &to_arg, options, false, &mut CommandReport::new("convert")).unwrap();
             }
         }
 
@@ -238,7 +481,7 @@ mod tests {
 
 
             let generated_files = [ "avatar", "tilegrid", "tiledir", "symdir" ].map(|format| temp_dir.child(format!("djibin_{tile_kind}_from_{format}.bin")));
-            let files = [original_djibin].into_iter().chain(generated_files.into_iter()).collect::<Vec<PathBuf>>();
+            let files = [original_djibin].into_iter().chain(generated_files).collect::<Vec<PathBuf>>();
             assert!(files_are_identical(&files));
 
             // AVATAR
@@ -250,4 +493,51 @@ mod tests {
 
     }
 
-}
\ No newline at end of file
+    #[test]
+    fn analog_grid_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let from_djibin = bin_file::normalized_file_path("test_files/djibinsetnorm", tile::Kind::SD, &None, FontPart::Base);
+        let from_arg = format!("djibin:{}", from_djibin.to_str().unwrap());
+
+        let grid_path = temp_dir.child("grid.png");
+        convert_command(&from_arg, &format!("tilegrid:{}", grid_path.to_str().unwrap()), ConvertOptions::default(), false, &mut CommandReport::new("convert")).unwrap();
+        let grid_tiles = TileGrid::load_from_image(&grid_path).unwrap().to_vec();
+
+        for scale in [1, 3] {
+            let analog_grid_path = temp_dir.child(format!("analog_grid_x{scale}.png"));
+            let options = ConvertOptions::default().scale(scale);
+            convert_command(&from_arg, &format!("analoggrid:{}", analog_grid_path.to_str().unwrap()), options, false, &mut CommandReport::new("convert")).unwrap();
+            let analog_grid_tiles = analog_grid::load_image(&analog_grid_path).unwrap();
+            assert_eq!(analog_grid_tiles.len(), grid_tiles.len());
+            assert!(analog_grid_tiles.iter().zip(grid_tiles.iter()).all(|(left, right)| left.as_raw() == right.as_raw()));
+        }
+    }
+
+    #[test]
+    fn incremental_conversion() {
+        let temp_dir = TempDir::new().unwrap();
+        let from_djibin = bin_file::normalized_file_path("test_files/djibinsetnorm", tile::Kind::SD, &None, FontPart::Base);
+        let from_arg = format!("djibin:{}", from_djibin.to_str().unwrap());
+        let tile_dir = temp_dir.child("tiles");
+        let to_arg = format!("tiledir:{}", tile_dir.to_str().unwrap());
+        let convert = |options: ConvertOptions| {
+            let mut report = CommandReport::new("convert");
+            convert_command(&from_arg, &to_arg, options, true, &mut report).unwrap();
+            report.up_to_date
+        };
+
+        assert_eq!(convert(ConvertOptions::default()), Some(false));
+        assert!(temp_dir.child(".hd_fpv_font_tool_state.json").is_file());
+        assert_eq!(convert(ConvertOptions::default()), Some(true));
+
+        // the options are part of the state
+        assert_eq!(convert(ConvertOptions::default().transform(TileTransform::Invert)), Some(false));
+        assert_eq!(convert(ConvertOptions::default()), Some(false));
+
+        // outputs modified since the last conversion
+        fs::remove_file(tile_dir.join("065.png")).unwrap();
+        assert_eq!(convert(ConvertOptions::default()), Some(false));
+        assert_eq!(convert(ConvertOptions::default()), Some(true));
+    }
+
+}
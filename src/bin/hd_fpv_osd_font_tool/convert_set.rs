@@ -1,20 +1,25 @@
 
 use std::cmp::Ordering;
+use std::path::{Path, PathBuf};
 
 use derive_more::Display;
+use strum::IntoEnumIterator;
 use thiserror::Error;
 
-use crate::ConvertOptions;
 
-use super::convert::InvalidConvertArgError;
-use hd_fpv_osd_font_tool::prelude::*;
+use super::convert::{InvalidConvertArgError, carry_font_metadata, check_arg_image_file_extension, check_loaded_symbols, report_alpha_threshold};
+use crate::incremental::IncrementalConversion;
+use crate::overwrite::check_outputs;
+use hd_fpv_osd_font_tool::{
+    osd::{mcm_file, tile::{combined_grid, hdzero_sheet, container::archive::{ArchiveError, ArchiveFormat}, rescale::rescale_tiles}},
+    prelude::*,
+    report::{CollectionReport, CommandReport},
+};
 
 enum ConvertSetArg<'a> {
     BinFileSet {
-        sd_path: &'a str,
-        sd_2_path: &'a str,
-        hd_path: &'a str,
-        hd_2_path: &'a str,
+        sd_paths: Vec<&'a str>,
+        hd_paths: Vec<&'a str>,
     },
     BinFileSetNorm {
         dir: &'a str,
@@ -30,6 +35,11 @@ enum ConvertSetArg<'a> {
     },
     TileSetDir(&'a str),
     SymbolSetDir(&'a str),
+    TileSetArchive(&'a str),
+    SymbolSetArchive(&'a str),
+    CombinedGrid(&'a str),
+    McmFile(&'a str),
+    HdZeroSheet(&'a str),
 }
 
 #[derive(Debug, Display)]
@@ -37,9 +47,83 @@ pub enum InvalidConvertSetArgError {
     InvalidConvertArgError(InvalidConvertArgError),
     BinSetInvalidArguments(&'static str),
     TileSetGridsInvalidArguments(&'static str),
+    ArchiveFormat(ArchiveError),
+}
+
+impl std::error::Error for InvalidConvertSetArgError {}
+
+impl<'a> ConvertSetArg<'a> {
+    fn report(&self) -> CollectionReport {
+        use ConvertSetArg::*;
+        match self {
+            BinFileSet { sd_paths, hd_paths } => CollectionReport::new("djibinset", &[sd_paths.as_slice(), hd_paths.as_slice()].concat()),
+            BinFileSetNorm { dir, .. } => CollectionReport::new("djibinsetnorm", &[dir]),
+            TileSetGrids { sd_path, hd_path } => CollectionReport::new("tilesetgrids", &[sd_path, hd_path]),
+            TileSetGridsNorm { dir, .. } => CollectionReport::new("tilesetgridsnorm", &[dir]),
+            TileSetDir(dir) => CollectionReport::new("tilesetdir", &[dir]),
+            SymbolSetDir(dir) => CollectionReport::new("symsetdir", &[dir]),
+            TileSetArchive(path) => CollectionReport::new("tilesetzip", &[path]),
+            SymbolSetArchive(path) => CollectionReport::new("symsetzip", &[path]),
+            CombinedGrid(path) => CollectionReport::new("combinedgrid", &[path]),
+            McmFile(path) => CollectionReport::new("mcm", &[path]),
+            HdZeroSheet(path) => CollectionReport::new("hdzero", &[path]),
+        }
+    }
+
+    // the files and directories identifying the set, the normalized bin files are identified by their first page
+    fn paths(&self, image_format: ImageFormat) -> Vec<PathBuf> {
+        use ConvertSetArg::*;
+        match self {
+            BinFileSet { sd_paths, hd_paths } => sd_paths.iter().chain(hd_paths.iter()).map(PathBuf::from).collect(),
+            BinFileSetNorm { dir, ident } => tile::Kind::iter()
+                .map(|tile_kind| bin_file::normalized_page_file_path(dir, tile_kind, ident, 0))
+                .collect(),
+            TileSetGrids { sd_path, hd_path } => vec![PathBuf::from(sd_path), PathBuf::from(hd_path)],
+            TileSetGridsNorm { dir, ident } => tile::Kind::iter()
+                .map(|tile_kind| tile::grid::normalized_image_file_path(dir, tile_kind, ident, image_format))
+                .collect(),
+            TileSetDir(dir) | SymbolSetDir(dir) => vec![PathBuf::from(dir)],
+            TileSetArchive(path) | SymbolSetArchive(path) | CombinedGrid(path) | McmFile(path) | HdZeroSheet(path) => vec![PathBuf::from(path)],
+        }
+    }
+
+    // file or directory holding the tiles of the given kind, only for the formats storing each kind separately. An
+    // ambiguous grid image is left to the loading to report
+    fn tile_kind_path(&self, tile_kind: tile::Kind) -> Option<PathBuf> {
+        use ConvertSetArg::*;
+        match self {
+            BinFileSetNorm { dir, ident } => Some(bin_file::find_page_file_path(dir, tile_kind, ident, 0)),
+            TileSetGridsNorm { dir, ident } => ImageFormat::find_file_path(dir, &tile::grid::normalized_image_file_stem(tile_kind, ident)).ok(),
+            TileSetDir(dir) | SymbolSetDir(dir) => Some(tile_kind.set_dir_path(dir)),
+            BinFileSet { .. } | TileSetGrids { .. } | TileSetArchive(_) | SymbolSetArchive(_) | CombinedGrid(_) | McmFile(_) | HdZeroSheet(_) => None,
+        }
+    }
+}
+
+// the destination files may not exist yet, their directory is resolved instead when possible
+fn resolved_path(path: &Path) -> PathBuf {
+    if let Ok(path) = std::fs::canonicalize(path) {
+        return path;
+    }
+    match (path.parent().map(std::fs::canonicalize), path.file_name()) {
+        (Some(Ok(parent)), Some(file_name)) => parent.join(file_name),
+        _ => path.to_path_buf(),
+    }
 }
 
-fn argument_norm_args(arg: &str) -> Result<(&str, Option<&str>), InvalidConvertSetArgError> {
+// same format conversions are allowed e.g. to re-ident a bin file set as long as they do not write the files they read
+fn check_distinct_paths(from_arg: &ConvertSetArg, to_arg: &ConvertSetArg, image_format: ImageFormat) -> Result<(), ConvertSetError> {
+    let from_paths: Vec<PathBuf> = from_arg.paths(image_format).iter().map(|path| resolved_path(path)).collect();
+    let clashing_paths: Vec<PathBuf> = to_arg.paths(image_format).into_iter()
+        .filter(|path| from_paths.contains(&resolved_path(path)))
+        .collect();
+    match clashing_paths.is_empty() {
+        true => Ok(()),
+        false => Err(ConvertSetError::SamePaths(clashing_paths)),
+    }
+}
+
+pub fn argument_norm_args(arg: &str) -> Result<(&str, Option<&str>), InvalidConvertSetArgError> {
     let args: Vec<&str> = arg.split(':').collect();
     if args.len() > 2 {
         return Err(InvalidConvertSetArgError::BinSetInvalidArguments("too many arguments"))
@@ -51,15 +135,17 @@ fn argument_norm_args(arg: &str) -> Result<(&str, Option<&str>), InvalidConvertS
     Ok((dir, ident))
 }
 
-fn identify_convert_set_arg(input: &str) -> Result<ConvertSetArg, InvalidConvertSetArgError> {
+fn identify_convert_set_arg(input: &str) -> Result<ConvertSetArg<'_>, InvalidConvertSetArgError> {
     if let Some(file_paths) = input.strip_prefix("djibinset:") {
-        let files: Vec<&str> = file_paths.split(':').collect();
-        match files.len().cmp(&4) {
-            Ordering::Less => return Err(InvalidConvertSetArgError::BinSetInvalidArguments("too few arguments")),
-            Ordering::Greater => return Err(InvalidConvertSetArgError::BinSetInvalidArguments("too many arguments")),
-            Ordering::Equal => {},
+        let mut files: Vec<&str> = file_paths.split(':').collect();
+        if files.len() < 2 {
+            return Err(InvalidConvertSetArgError::BinSetInvalidArguments("too few arguments"))
+        }
+        if files.len() % 2 != 0 {
+            return Err(InvalidConvertSetArgError::BinSetInvalidArguments("the number of SD and HD pages must be the same"))
         }
-        Ok(ConvertSetArg::BinFileSet { sd_path: files[0], sd_2_path: files[1], hd_path: files[2], hd_2_path: files[3] })
+        let hd_paths = files.split_off(files.len() / 2);
+        Ok(ConvertSetArg::BinFileSet { sd_paths: files, hd_paths })
 
     } else if let Some(path) = input.strip_prefix("djibinsetnorm:") {
         let (dir, ident) = argument_norm_args(path)?;
@@ -84,6 +170,25 @@ fn identify_convert_set_arg(input: &str) -> Result<ConvertSetArg, InvalidConvert
     } else if let Some(path) = input.strip_prefix("symsetdir:") {
         Ok(ConvertSetArg::SymbolSetDir(path))
 
+    } else if let Some(path) = input.strip_prefix("tilesetzip:") {
+        ArchiveFormat::from_path(path).map_err(InvalidConvertSetArgError::ArchiveFormat)?;
+        Ok(ConvertSetArg::TileSetArchive(path))
+
+    } else if let Some(path) = input.strip_prefix("symsetzip:") {
+        ArchiveFormat::from_path(path).map_err(InvalidConvertSetArgError::ArchiveFormat)?;
+        Ok(ConvertSetArg::SymbolSetArchive(path))
+
+    } else if let Some(path) = input.strip_prefix("combinedgrid:") {
+        check_arg_image_file_extension(path).map_err(InvalidConvertSetArgError::InvalidConvertArgError)?;
+        Ok(ConvertSetArg::CombinedGrid(path))
+
+    } else if let Some(path) = input.strip_prefix("mcm:") {
+        Ok(ConvertSetArg::McmFile(path))
+
+    } else if let Some(path) = input.strip_prefix("hdzero:") {
+        check_arg_image_file_extension(path).map_err(InvalidConvertSetArgError::InvalidConvertArgError)?;
+        Ok(ConvertSetArg::HdZeroSheet(path))
+
     } else if let Some((prefix, _)) = input.split_once(':') {
         Err(InvalidConvertSetArgError::InvalidConvertArgError(InvalidConvertArgError::InvalidPrefix(prefix.to_owned())))
     } else {
@@ -97,77 +202,227 @@ pub enum ConvertSetError {
     FromArg(InvalidConvertSetArgError),
     #[error("invalid `to` argument: {0}")]
     ToArg(InvalidConvertSetArgError),
+    #[error("the source and the destination are the same: {}", .0.iter().map(|path| path.to_string_lossy()).collect::<Vec<_>>().join(", "))]
+    SamePaths(Vec<PathBuf>),
+    #[error("{0} only holds HD tiles and the SD tiles can not be generated from them without a rescale filter")]
+    OnlyHDTiles(&'static str),
 }
 
 fn convert_tile_set(tile_set: TileSet, to_arg: &ConvertSetArg, options: &ConvertOptions) -> anyhow::Result<()> {
     use ConvertSetArg::*;
+    check_outputs(&to_arg.paths(options.image_format))?;
     match to_arg {
-        BinFileSet { sd_path, sd_2_path, hd_path, hd_2_path } => tile_set.save_to_bin_files(sd_path, sd_2_path, hd_path, hd_2_path)?,
-        BinFileSetNorm { dir, ident } => tile_set.save_to_bin_files_norm(dir, ident)?,
-        TileSetGrids { sd_path, hd_path } => tile_set.save_to_grids(sd_path, hd_path)?,
-        TileSetGridsNorm { dir, ident  } => tile_set.save_to_grids_norm(dir, ident)?,
-        TileSetDir(dir) => tile_set.save_tiles_to_dir(dir)?,
+        BinFileSet { sd_paths, hd_paths } => tile_set.save_to_bin_files_padding(sd_paths, hd_paths, options.bin_file_padding)?,
+        BinFileSetNorm { dir, ident } => match &options.bin_name_template {
+            Some(template) => tile_set.save_to_bin_files_templated_padding(dir, ident, template, options.bin_file_padding)?,
+            None => tile_set.save_to_bin_files_norm_padding(dir, ident, options.bin_file_padding)?,
+        },
+        TileSetGrids { sd_path, hd_path } => tile_set.save_to_grids_annotated(sd_path, hd_path, options.grid_layout, options.grid_annotation)?,
+        TileSetGridsNorm { dir, ident  } => tile_set.save_to_grids_norm_annotated(dir, ident, options.image_format, options.grid_layout, options.grid_annotation)?,
+        TileSetDir(dir) => tile_set.save_tiles_to_dir_deduplicated(dir, options.image_format, options.scale, options.dedup_mode)?,
         SymbolSetDir(dir) => {
-            let sym_specs = SymbolSpecs::load_file(options.symbol_specs_file())?;
-            tile_set.into_symbol_set(&sym_specs).unwrap().save_to_dir(dir)?;
+            let sym_specs = SymbolSpecs::load_files(&options.symbol_specs_files)?;
+            tile_set.into_symbol_set(&sym_specs)?.save_to_dir_named(dir, options.image_format, options.scale, options.symbol_names)?;
+        },
+        TileSetArchive(path) => tile_set.save_to_archive(path, options.image_format)?,
+        SymbolSetArchive(path) => {
+            let sym_specs = SymbolSpecs::load_files(&options.symbol_specs_files)?;
+            tile_set.into_symbol_set(&sym_specs)?.save_to_archive(path, options.image_format, options.symbol_names)?;
+        },
+        CombinedGrid(path) => combined_grid::save_image(&tile_set, path)?,
+        McmFile(path) => {
+            log::info!("only the SD tiles are written to the MCM file");
+            mcm_file::save(tile_set.sd_tiles(), path)?
         },
+        HdZeroSheet(path) => hdzero_sheet::save_image(tile_set.hd_tiles(), path)?,
     }
     Ok(())
 }
 
-pub fn convert_set_command(from: &str, to: &str, options: ConvertOptions) -> anyhow::Result<()> {
-    let from_arg = identify_convert_set_arg(from).map_err(ConvertSetError::FromArg)?;
-    let to_arg = identify_convert_set_arg(to).map_err(ConvertSetError::ToArg)?;
-    log::info!("converting {} -> {}", from, to);
-
+fn load_tile_kind_from_arg(from_arg: &ConvertSetArg, tile_kind: tile::Kind, options: &ConvertOptions) -> anyhow::Result<Vec<Tile>> {
+    let fit = options.fit;
     use ConvertSetArg::*;
-    match (&from_arg, &to_arg) {
-
-        (BinFileSet { sd_path, sd_2_path, hd_path, hd_2_path }, to_arg) => {
-            let tile_set = bin_file::load_set(sd_path, sd_2_path, hd_path, hd_2_path)?;
-            convert_tile_set(tile_set, to_arg, &options)
+    let tiles = match from_arg {
+        BinFileSetNorm { dir, ident } => match &options.bin_name_template {
+            Some(template) => bin_file::load_pages_templated(dir, template, tile_kind, ident)?,
+            None => bin_file::load_pages_norm(dir, tile_kind, ident)?,
         },
-
-        (BinFileSetNorm { dir, ident }, to_arg) => {
-            let tile_set = bin_file::load_set_norm(dir, ident)?;
-            convert_tile_set(tile_set, to_arg, &options)
+        TileSetGridsNorm { dir, ident } => TileGrid::load_from_image_norm(dir, tile_kind, ident, fit, options.decoding(), options.grid_validation, options.grid_layout)?.to_vec(),
+        TileSetDir(dir) => load_tiles_from_dir_missing(tile_kind.set_dir_path(dir), options.max_tile_count, fit, options.decoding(), options.missing_tiles)?,
+        SymbolSetDir(dir) => {
+            let symbols = load_symbols_from_dir(tile_kind.set_dir_path(dir), options.max_tile_count, fit, options.decoding())?;
+            check_loaded_symbols(&symbols, options)?;
+            symbols.into_tiles_vec()
         },
+        BinFileSet { .. } | TileSetGrids { .. } | TileSetArchive(_) | SymbolSetArchive(_) | CombinedGrid(_) | McmFile(_) | HdZeroSheet(_) =>
+            unreachable!("only the formats storing each tile kind separately are loaded per tile kind"),
+    };
+    Ok(tiles)
+}
 
-        (TileSetGrids { sd_path, hd_path }, to_arg) => {
-            let tile_grid_set = TileGridSet::load_from_images(sd_path, hd_path)?;
-            convert_tile_set(tile_grid_set.into_tile_set(), to_arg, &options)
-        },
+// generates the tiles of the kind missing from the source by resampling the other kind, the sources holding both
+// kinds or neither are loaded as usual
+fn load_tile_set_synthesizing(from_arg: &ConvertSetArg, filter: RescaleFilter, options: &ConvertOptions) -> anyhow::Result<Option<TileSet>> {
+    let missing_tile_kinds: Vec<tile::Kind> = tile::Kind::iter()
+        .filter(|tile_kind| matches!(from_arg.tile_kind_path(*tile_kind), Some(path) if !path.exists()))
+        .collect();
+    let missing_tile_kind = match missing_tile_kinds.as_slice() {
+        [missing_tile_kind] => *missing_tile_kind,
+        _ => return Ok(None),
+    };
+    let present_tile_kind = match missing_tile_kind {
+        tile::Kind::SD => tile::Kind::HD,
+        tile::Kind::HD => tile::Kind::SD,
+    };
+    let present_tiles = load_tile_kind_from_arg(from_arg, present_tile_kind, options)?;
+    log::info!("the source has no {missing_tile_kind} tiles, synthesizing them from the {present_tile_kind} tiles ({filter})");
+    let synthesized_tiles = rescale_tiles(&present_tiles, missing_tile_kind, filter, options.requantize);
+    let tile_set = match present_tile_kind {
+        tile::Kind::SD => TileSet::try_from_tiles(present_tiles, synthesized_tiles)?,
+        tile::Kind::HD => TileSet::try_from_tiles(synthesized_tiles, present_tiles)?,
+    };
+    Ok(Some(tile_set))
+}
 
-        (TileSetGridsNorm { dir, ident }, to_arg) => {
-            let tile_grid_set = TileGridSet::load_from_images_norm(dir, ident)?;
-            convert_tile_set(tile_grid_set.into_tile_set(), to_arg, &options)
+fn load_tile_set_from_arg(from_arg: &ConvertSetArg, options: &ConvertOptions) -> anyhow::Result<TileSet> {
+    if let Some(filter) = options.synthesize_missing_kind {
+        if let Some(tile_set) = load_tile_set_synthesizing(from_arg, filter, options)? {
+            return Ok(tile_set);
+        }
+    }
+    let fit = options.fit;
+    use ConvertSetArg::*;
+    let tile_set = match from_arg {
+        BinFileSet { sd_paths, hd_paths } => bin_file::load_set(sd_paths, hd_paths)?,
+        BinFileSetNorm { dir, ident } => match &options.bin_name_template {
+            Some(template) => TileSet::load_bin_files_templated(dir, ident, template)?,
+            None => bin_file::load_set_norm(dir, ident)?,
         },
-
-        (TileSetDir(dir), to_arg) => {
-            let tile_set = TileSet::load_from_dir(dir, 512)?;
-            convert_tile_set(tile_set, to_arg, &options)
+        TileSetGrids { sd_path, hd_path } => TileGridSet::load_from_images(sd_path, hd_path, fit, options.decoding(), options.grid_validation, options.grid_layout)?.into_tile_set(),
+        TileSetGridsNorm { dir, ident } => TileGridSet::load_from_images_norm(dir, ident, fit, options.decoding(), options.grid_validation, options.grid_layout)?.into_tile_set(),
+        TileSetDir(dir) => TileSet::load_from_dir_missing(dir, options.max_tile_count, fit, options.decoding(), options.missing_tiles)?,
+        SymbolSetDir(dir) => {
+            let symbol_set = SymbolSet::load_from_dir(dir, options.max_tile_count, fit, options.decoding())?;
+            check_loaded_symbols(symbol_set.sd_symbols(), options)?;
+            check_loaded_symbols(symbol_set.hd_symbols(), options)?;
+            symbol_set.into()
         },
-
-        (SymbolSetDir(dir), to_arg) => {
-            let symbol_set = SymbolSet::load_from_dir(dir, 512)?;
-            convert_tile_set(symbol_set.into(), to_arg, &options)
+        TileSetArchive(path) => TileSet::load_from_archive_missing(path, options.max_tile_count, fit, options.decoding(), options.missing_tiles)?,
+        SymbolSetArchive(path) => {
+            let symbol_set = SymbolSet::load_from_archive(path, options.max_tile_count, fit, options.decoding())?;
+            check_loaded_symbols(symbol_set.sd_symbols(), options)?;
+            check_loaded_symbols(symbol_set.hd_symbols(), options)?;
+            symbol_set.into()
         },
+        CombinedGrid(path) => combined_grid::load_image_decoded(path, options.decoding())?,
+        McmFile(path) => TileSet::try_from_tiles(mcm_file::load(path, tile::Kind::SD)?, mcm_file::load(path, tile::Kind::HD)?)?,
+        // the SD tiles of the set are resampled from the HD tiles
+        HdZeroSheet(path) => {
+            let hd_tiles = hdzero_sheet::load_image_decoded(path, options.decoding())?;
+            let filter = options.rescale_filter.ok_or(ConvertSetError::OnlyHDTiles("a HDZero font"))?;
+            let sd_tiles = rescale_tiles(&hd_tiles, tile::Kind::SD, filter, options.requantize);
+            TileSet::try_from_tiles(sd_tiles, hd_tiles)?
+        },
+    };
+    Ok(tile_set)
+}
+
+pub fn is_tile_set_arg(input: &str) -> bool {
+    identify_convert_set_arg(input).is_ok()
+}
+
+fn report_tile_counts(report: &mut CommandReport, tile_set: &TileSet) {
+    for tile_kind in tile::Kind::iter() {
+        report.add_tile_count(tile_kind, tile_set[tile_kind].len());
+    }
+}
+
+// writes a tile set generated by another command
+pub fn save_tile_set(tile_set: TileSet, to: &str, options: &ConvertOptions, report: &mut CommandReport) -> anyhow::Result<()> {
+    let to_arg = identify_convert_set_arg(to).map_err(ConvertSetError::ToArg)?;
+    report.outputs.push(to_arg.report());
+    report_tile_counts(report, &tile_set);
+    convert_tile_set(tile_set, &to_arg, options)
+}
 
+pub fn load_tile_set(from: &str, report: &mut CommandReport) -> anyhow::Result<TileSet> {
+    load_tile_set_options(from, &ConvertOptions::default(), report)
+}
+
+pub fn load_tile_set_options(from: &str, options: &ConvertOptions, report: &mut CommandReport) -> anyhow::Result<TileSet> {
+    let from_arg = identify_convert_set_arg(from).map_err(ConvertSetError::FromArg)?;
+    report.inputs.push(from_arg.report());
+    let tile_set = load_tile_set_from_arg(&from_arg, options)?;
+    report_tile_counts(report, &tile_set);
+    Ok(tile_set)
+}
+
+// single target shorthand of the fetch command and the tests
+#[cfg(any(test, feature = "network"))]
+pub fn convert_set_command(from: &str, to: &str, options: ConvertOptions, incremental: bool, report: &mut CommandReport) -> anyhow::Result<()> {
+    convert_set_command_targets(from, &[to], options, incremental, report)
+}
+
+// the source is loaded and transformed once then written to each target in turn
+pub fn convert_set_command_targets<S: AsRef<str>>(from: &str, targets: &[S], options: ConvertOptions, incremental: bool, report: &mut CommandReport) -> anyhow::Result<()> {
+    let from_arg = identify_convert_set_arg(from).map_err(ConvertSetError::FromArg)?;
+    let to_args = targets.iter()
+        .map(|to| identify_convert_set_arg(to.as_ref()).map_err(ConvertSetError::ToArg))
+        .collect::<Result<Vec<_>, _>>()?;
+    for to_arg in &to_args {
+        check_distinct_paths(&from_arg, to_arg, options.image_format)?;
+    }
+    report.inputs.push(from_arg.report());
+    report.outputs.extend(to_args.iter().map(ConvertSetArg::report));
+    let target_list = targets.iter().map(AsRef::as_ref).collect::<Vec<_>>().join(", ");
+    log::info!("converting {} -> {}", from, target_list);
+    let mut tile_set = load_tile_set_from_arg(&from_arg, &options)?;
+    report_tile_counts(report, &tile_set);
+
+    let input_hash = tile_set.content_hash();
+    if let Some(threshold) = options.alpha_threshold {
+        report_alpha_threshold(report, tile_set.threshold_alpha(threshold));
     }
+    tile_set.apply_transforms(&options.transforms)?;
+
+    let mut all_up_to_date = true;
+    for (to, to_arg) in targets.iter().map(AsRef::as_ref).zip(to_args.iter()) {
+        let output_hash = || -> anyhow::Result<String> { Ok(load_tile_set(to, &mut CommandReport::default())?.content_hash()) };
+        let incremental = incremental.then(|| IncrementalConversion::new("convert-set", from, to, &to_arg.report(), &options, input_hash.clone()));
+        if let Some(incremental) = &incremental {
+            if incremental.is_up_to_date(output_hash) {
+                log::info!("{to} is up to date");
+                continue;
+            }
+        }
+        all_up_to_date = false;
+        convert_tile_set(tile_set.clone(), to_arg, &options)?;
+        carry_font_metadata(&from_arg.report(), &to_arg.report())?;
+        if let Some(incremental) = incremental {
+            incremental.record(output_hash);
+        }
+    }
+    if incremental {
+        report.up_to_date = Some(all_up_to_date);
+    }
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
 
-    use std::path::Path;
+    use std::path::{Path, PathBuf};
 
     use hd_fpv_osd_font_tool::osd::tile::container::tile_set::TileSet;
+    use hd_fpv_osd_font_tool::prelude::{ConvertOptions, RescaleFilter};
+    use hd_fpv_osd_font_tool::report::CommandReport;
+
     use itertools::Itertools;
     use temp_dir::TempDir;
 
-    use crate::convert_set::convert_set_command;
+    use crate::convert_set::{convert_set_command, convert_set_command_targets};
 
-    use super::{identify_convert_set_arg, convert_tile_set};
+    use super::{identify_convert_set_arg, convert_tile_set, load_tile_set_from_arg, ConvertSetError};
 
     #[test]
     fn convert_set_all() {
@@ -186,8 +441,13 @@ mod tests {
         for format in formats {
             let to_arg_str = [format, temp_dir.child(format).to_str().unwrap()].join(":");
             let to_arg = identify_convert_set_arg(&to_arg_str).unwrap();
-            let options = crate::ConvertOptions { symbol_specs_file: &Path::new("symbol_specs/ardu.yaml").to_path_buf() };
+            let options = ConvertOptions::default().symbol_specs_file("symbol_specs/ardu.yaml");
             convert_tile_set(from_djibinsetnorm.clone(), &to_arg, &options).unwrap();
+            // symbol directories only hold the tiles covered by the symbol specs
+            if format != "symsetdir" {
+                let loaded_tile_set = load_tile_set_from_arg(&to_arg, &options).unwrap();
+                assert_eq!(loaded_tile_set.content_hash(), from_djibinsetnorm.content_hash(), "{format} conversion is not lossless");
+            }
         }
 
         for testing_formats in formats.iter().permutations(2) {
@@ -195,11 +455,118 @@ mod tests {
             println!("testing {from_format} -> {to_format}");
             let from_arg = [from_format, temp_dir.child(from_format).to_str().unwrap()].join(":");
             let to_arg = [to_format, temp_dir.child(to_format).to_str().unwrap()].join(":");
-            let options = crate::ConvertOptions { symbol_specs_file: &Path::new("symbol_specs/ardu.yaml").to_path_buf() };
-            convert_set_command(&from_arg, &to_arg, options).unwrap();
+            let options = ConvertOptions::default().symbol_specs_file("symbol_specs/ardu.yaml");
+            convert_set_command(&from_arg, &to_arg, options, false, &mut CommandReport::new("convert-set")).unwrap();
         }
 
     }
 
+    #[test]
+    fn same_format_conversions() {
+        let temp_dir = TempDir::new().unwrap();
+        let convert = |from: &str, to: &str| convert_set_command(from, to, ConvertOptions::default(), false, &mut CommandReport::new("convert-set"));
+        let renamed_dir = temp_dir.child("renamed");
+        let renamed = format!("djibinsetnorm:{}:new_ident", renamed_dir.to_str().unwrap());
+
+        convert("djibinsetnorm:test_files/djibinsetnorm", &renamed).unwrap();
+        for file_name in ["font_new_ident.bin", "font_new_ident_2.bin", "font_new_ident_hd.bin", "font_new_ident_hd_2.bin"] {
+            assert!(renamed_dir.join(file_name).is_file(), "{file_name} was not written");
+        }
+        let tiles_dir = temp_dir.child("tiles");
+        let reencoded_dir = temp_dir.child("reencoded");
+        convert(&renamed, &format!("tilesetdir:{}", tiles_dir.to_str().unwrap())).unwrap();
+        convert(&format!("tilesetdir:{}", tiles_dir.to_str().unwrap()), &format!("tilesetdir:{}", reencoded_dir.to_str().unwrap())).unwrap();
+
+        let error = convert(&renamed, &format!("djibinsetnorm:{}/../renamed:new_ident", renamed_dir.to_str().unwrap())).unwrap_err();
+        match error.downcast_ref::<ConvertSetError>() {
+            Some(ConvertSetError::SamePaths(paths)) => assert_eq!(paths.len(), 2),
+            _ => panic!("unexpected error: {error}"),
+        }
+    }
 
-}
\ No newline at end of file
+    #[test]
+    fn several_targets() {
+        let temp_dir = TempDir::new().unwrap();
+        let targets = [
+            format!("tilesetdir:{}", temp_dir.child("tiles").to_str().unwrap()),
+            format!("tilesetgridsnorm:{}", temp_dir.child("grids").to_str().unwrap()),
+        ];
+        let mut report = CommandReport::new("convert-set");
+        convert_set_command_targets("djibinsetnorm:test_files/djibinsetnorm", &targets, ConvertOptions::default(), false, &mut report).unwrap();
+        assert_eq!(report.outputs.len(), 2);
+        let source = TileSet::load_bin_files_norm("test_files/djibinsetnorm", &None).unwrap();
+        for target in &targets {
+            let loaded = load_tile_set_from_arg(&identify_convert_set_arg(target).unwrap(), &ConvertOptions::default()).unwrap();
+            assert_eq!(loaded.content_hash(), source.content_hash(), "{target} does not hold the source tiles");
+        }
+    }
+
+    #[test]
+    fn synthesize_missing_kind() {
+        let temp_dir = TempDir::new().unwrap();
+        let tiles_dir = temp_dir.child("tiles");
+        let tiles_arg = format!("tilesetdir:{}", tiles_dir.to_str().unwrap());
+        let bin_dir = temp_dir.child("bin");
+        let bin_arg = format!("djibinsetnorm:{}", bin_dir.to_str().unwrap());
+        convert_set_command("djibinsetnorm:test_files/djibinsetnorm", &tiles_arg, ConvertOptions::default(), false, &mut CommandReport::new("convert-set")).unwrap();
+        std::fs::remove_dir_all(tiles_dir.join("HD")).unwrap();
+
+        assert!(convert_set_command(&tiles_arg, &bin_arg, ConvertOptions::default(), false, &mut CommandReport::new("convert-set")).is_err());
+        let options = ConvertOptions::default().synthesize_missing_kind(Some(RescaleFilter::Nearest));
+        convert_set_command(&tiles_arg, &bin_arg, options, false, &mut CommandReport::new("convert-set")).unwrap();
+        let tile_set = TileSet::load_bin_files_norm(&bin_dir, &None).unwrap();
+        assert_eq!(tile_set.hd_tiles().len(), tile_set.sd_tiles().len());
+    }
+
+    // relative path -> content of every file below `dir`
+    fn dir_contents(dir: &Path) -> Vec<(PathBuf, Vec<u8>)> {
+        let mut contents = vec![];
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            let file_name = PathBuf::from(path.file_name().unwrap());
+            if path.is_dir() {
+                contents.extend(dir_contents(&path).into_iter().map(|(sub_path, content)| (file_name.join(sub_path), content)));
+            } else {
+                contents.push((file_name, std::fs::read(&path).unwrap()));
+            }
+        }
+        contents.sort();
+        contents
+    }
+
+    #[test]
+    fn reproducible_outputs() {
+        let temp_dir = TempDir::new().unwrap();
+        for run in ["first", "second"] {
+            convert_to_image_targets(&temp_dir.child(run));
+        }
+        let first = dir_contents(&temp_dir.child("first"));
+        assert!(first.len() > 4);
+        assert!(first == dir_contents(&temp_dir.child("second")), "the outputs of the two runs differ");
+    }
+
+    // the tile images are read and written on all the cores with the parallel feature
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn parallel_outputs() {
+        let temp_dir = TempDir::new().unwrap();
+        for (run, threads) in [("sequential", 1), ("parallel", 8)] {
+            let pool = rayon::ThreadPoolBuilder::new().num_threads(threads).build().unwrap();
+            pool.install(|| convert_to_image_targets(&temp_dir.child(run)));
+        }
+        let sequential = dir_contents(&temp_dir.child("sequential"));
+        assert!(sequential == dir_contents(&temp_dir.child("parallel")), "the outputs depend on the number of threads");
+    }
+
+    fn convert_to_image_targets(dir: &Path) {
+        for target in ["tilesetdir:tiles", "tilesetgridsnorm:grids", "symsetdir:symbols", "combinedgrid:combined/font.png"] {
+            let (prefix, path) = target.split_once(':').unwrap();
+            let to = format!("{prefix}:{}", dir.join(path).to_str().unwrap());
+            let options = ConvertOptions::default().symbol_specs_file("symbol_specs/ardu.yaml");
+            if prefix == "combinedgrid" {
+                std::fs::create_dir_all(dir.join("combined")).unwrap();
+            }
+            convert_set_command("djibinsetnorm:test_files/djibinsetnorm", &to, options, false, &mut CommandReport::new("convert-set")).unwrap();
+        }
+    }
+}
@@ -0,0 +1,65 @@
+use std::path::Path;
+
+use hd_fpv_osd_font_tool::{
+    osd::tile::{
+        Kind as TileKind,
+        container::uniq_tile_kind::{TileKindError, UniqTileKind},
+        diff::{diff_raw_tiles, diff_tiles, save_visual_diff, TileChange},
+    },
+    report::{CollectionReport, CommandReport},
+};
+
+use crate::{convert::{load_tiles, open_lazy_bin_file}, output::OutputFormat, overwrite::check_outputs};
+
+
+pub fn check_tile_kinds(old_tile_kind: TileKind, new_tile_kind: TileKind) -> Result<(), TileKindError> {
+    match new_tile_kind == old_tile_kind {
+        true => Ok(()),
+        false => Err(TileKindError::LoadedDoesNotMatchRequested { requested: old_tile_kind, loaded: new_tile_kind }),
+    }
+}
+
+// two bin files without visual diff are compared on their raw bytes without decoding the tiles
+fn diff_bin_files(old: &str, new: &str, report: &mut CommandReport) -> anyhow::Result<Option<Vec<TileChange>>> {
+    let mut lazy_report = CommandReport::default();
+    let (old_reader, new_reader) = match (open_lazy_bin_file(old, &mut lazy_report)?, open_lazy_bin_file(new, &mut lazy_report)?) {
+        (Some(old_reader), Some(new_reader)) => (old_reader, new_reader),
+        _ => return Ok(None),
+    };
+    report.inputs.append(&mut lazy_report.inputs);
+    check_tile_kinds(old_reader.tile_kind(), new_reader.tile_kind())?;
+    report.add_tile_count(old_reader.tile_kind(), old_reader.tile_count() + new_reader.tile_count());
+    let (old_tiles, new_tiles): (Vec<&[u8]>, Vec<&[u8]>) = (old_reader.raw_tiles().collect(), new_reader.raw_tiles().collect());
+    Ok(Some(diff_raw_tiles(&old_tiles, &new_tiles)))
+}
+
+pub fn diff_command(old: &str, new: &str, visual: Option<&Path>, output_format: OutputFormat, report: &mut CommandReport) -> anyhow::Result<()> {
+    let lazy_changes = match visual {
+        Some(_) => None,
+        None => diff_bin_files(old, new, report)?,
+    };
+    let changes = match lazy_changes {
+        Some(changes) => changes,
+        None => {
+            let old_tiles = load_tiles(old, report)?;
+            let new_tiles = load_tiles(new, report)?;
+            check_tile_kinds(old_tiles.tile_kind()?, new_tiles.tile_kind()?)?;
+            if let Some(dir) = visual {
+                report.outputs.push(CollectionReport::new("visualdiff", &[dir]));
+                check_outputs(&[dir])?;
+                save_visual_diff(&old_tiles, &new_tiles, dir)?;
+            }
+            diff_tiles(&old_tiles, &new_tiles)
+        },
+    };
+    match output_format {
+        OutputFormat::Json => report.diff = Some(changes),
+        OutputFormat::Human => {
+            for change in &changes {
+                println!("{:03} {}", change.index(), change.kind());
+            }
+            println!("{} differing tiles", changes.len());
+        },
+    }
+    Ok(())
+}
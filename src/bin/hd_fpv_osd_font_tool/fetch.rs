@@ -0,0 +1,133 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use hd_fpv_osd_font_tool::{
+    prelude::*,
+    report::{CollectionReport, CommandReport},
+};
+use thiserror::Error;
+
+use crate::convert_set::convert_set_command;
+
+// Downloads fonts into a local cache so that they can be converted without the manual download and unpack steps.
+// The fonts are given by URL or by name, the names are looked up in a YAML registry file mapping them to URLs e.g.
+//   btfl_hd: https://example.com/fonts/btfl_hd.zip
+// The downloaded files are kept in the cache and only downloaded again with --refresh.
+
+pub const REGISTRY_FILE_NAME: &str = "registry.yaml";
+
+#[derive(Debug, Error)]
+pub enum FetchError {
+    #[error("unknown font `{name}`: not an http(s) URL and not in the registry {}", .registry.to_string_lossy())]
+    UnknownFont { name: String, registry: PathBuf },
+    #[error("cannot determine the file name of the font from the URL {0}")]
+    NoFileName(String),
+    #[error("cannot determine the format of {0}: use --from-format to give the collection set specification prefix e.g. tilesetzip")]
+    UnknownFormat(PathBuf),
+    #[error("failed to download {url}: {error}")]
+    Download { url: String, error: Box<ureq::Error> },
+}
+
+// $XDG_CACHE_HOME/hd_fpv_osd_font_tool, ~/.cache/hd_fpv_osd_font_tool when not set
+pub fn default_cache_dir() -> PathBuf {
+    let cache_home = std::env::var_os("XDG_CACHE_HOME").map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".cache")))
+        .unwrap_or_else(|| PathBuf::from(".cache"));
+    cache_home.join("hd_fpv_osd_font_tool")
+}
+
+fn is_url(source: &str) -> bool {
+    source.starts_with("https://") || source.starts_with("http://")
+}
+
+fn resolve_url(source: &str, registry: &Path) -> anyhow::Result<String> {
+    if is_url(source) {
+        return Ok(source.to_owned())
+    }
+    let unknown_font = || FetchError::UnknownFont { name: source.to_owned(), registry: registry.to_path_buf() };
+    if !registry.is_file() {
+        return Err(unknown_font().into())
+    }
+    let registry_content: BTreeMap<String, String> = serde_yaml::from_str(&fs_err::read_to_string(registry)?)?;
+    Ok(registry_content.get(source).cloned().ok_or_else(unknown_font)?)
+}
+
+// the file is downloaded next to its final path and renamed once complete so that the cache never holds partial files
+fn download(url: &str, path: &Path) -> anyhow::Result<()> {
+    let response = ureq::get(url).call().map_err(|error| FetchError::Download { url: url.to_owned(), error: Box::new(error) })?;
+    let partial_path = path.with_extension("part");
+    let mut file = fs_err::File::create(&partial_path)?;
+    std::io::copy(&mut response.into_reader(), &mut file)?;
+    drop(file);
+    fs_err::rename(&partial_path, path)?;
+    Ok(())
+}
+
+pub fn fetch(source: &str, cache_dir: &Path, registry: &Path, refresh: bool) -> anyhow::Result<PathBuf> {
+    let url = resolve_url(source, registry)?;
+    let file_name = url.split(['?', '#']).next().unwrap_or_default().rsplit('/').next()
+        .filter(|file_name| !file_name.is_empty())
+        .ok_or_else(|| FetchError::NoFileName(url.clone()))?;
+    let path = cache_dir.join(file_name);
+    if path.is_file() && !refresh {
+        log::info!("using cached {}", path.to_string_lossy());
+        return Ok(path)
+    }
+    fs_err::create_dir_all(cache_dir)?;
+    log::info!("downloading {url} -> {}", path.to_string_lossy());
+    download(&url, &path)?;
+    Ok(path)
+}
+
+// collection set specification prefix of a downloaded file from its extension
+fn source_format(path: &Path) -> Option<&'static str> {
+    let file_name = path.file_name()?.to_str()?.to_ascii_lowercase();
+    if file_name.ends_with(".zip") || file_name.ends_with(".tgz") || file_name.ends_with(".tar.gz") {
+        Some("tilesetzip")
+    } else if file_name.ends_with(".mcm") {
+        Some("mcm")
+    } else {
+        None
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn fetch_command(source: &str, to: Option<&str>, from_format: Option<&str>, cache_dir: Option<&Path>, registry: Option<&Path>,
+        refresh: bool, options: ConvertOptions, report: &mut CommandReport) -> anyhow::Result<()> {
+    let cache_dir = cache_dir.map_or_else(default_cache_dir, Path::to_path_buf);
+    let registry = registry.map_or_else(|| cache_dir.join(REGISTRY_FILE_NAME), Path::to_path_buf);
+    let path = fetch(source, &cache_dir, &registry, refresh)?;
+    let to = match to {
+        Some(to) => to,
+        None => {
+            report.outputs.push(CollectionReport::new("file", &[&path]));
+            println!("{}", path.to_string_lossy());
+            return Ok(())
+        },
+    };
+    let from_format = from_format.or_else(|| source_format(&path)).ok_or_else(|| FetchError::UnknownFormat(path.clone()))?;
+    convert_set_command(&format!("{from_format}:{}", path.to_string_lossy()), to, options, false, report)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use temp_dir::TempDir;
+
+    use super::{resolve_url, source_format};
+
+    #[test]
+    fn registry_and_formats() {
+        let temp_dir = TempDir::new().unwrap();
+        let registry = temp_dir.child("registry.yaml");
+        std::fs::write(&registry, "myfont: https://example.com/myfont.zip\n").unwrap();
+        assert_eq!(resolve_url("myfont", &registry).unwrap(), "https://example.com/myfont.zip");
+        assert_eq!(resolve_url("https://example.com/a.mcm", &registry).unwrap(), "https://example.com/a.mcm");
+        assert!(resolve_url("other", &registry).is_err());
+
+        assert_eq!(source_format(Path::new("MyFont.tar.gz")), Some("tilesetzip"));
+        assert_eq!(source_format(Path::new("font.mcm")), Some("mcm"));
+        assert_eq!(source_format(Path::new("font.bin")), None);
+    }
+}
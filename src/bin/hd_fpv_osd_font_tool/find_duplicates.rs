@@ -0,0 +1,51 @@
+use hd_fpv_osd_font_tool::{
+    osd::tile::container::duplicates::{duplicate_groups, DuplicateMatch, TileLocation},
+    prelude::*,
+    report::CommandReport,
+};
+
+use crate::{
+    convert::load_tiles,
+    convert_set::{is_tile_set_arg, load_tile_set},
+    output::OutputFormat,
+};
+
+
+enum Font {
+    Set(TileSet),
+    Collection(Vec<Tile>),
+}
+
+fn location_description(location: &TileLocation, sources: &[String]) -> String {
+    match sources.len() {
+        1 => format!("{} {:03}", location.tile_kind, location.index),
+        _ => format!("{} {} {:03}", sources[location.font], location.tile_kind, location.index),
+    }
+}
+
+// each source is a collection or a collection set, the groups span all of them
+pub fn find_duplicates_command(sources: &[String], matching: DuplicateMatch, output_format: OutputFormat, report: &mut CommandReport) -> anyhow::Result<()> {
+    let loaded = sources.iter()
+        .map(|source| match is_tile_set_arg(source) {
+            true => load_tile_set(source, report).map(Font::Set),
+            false => load_tiles(source, report).map(Font::Collection),
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let fonts: Vec<Vec<(tile::Kind, usize, &Tile)>> = loaded.iter().map(|font| match font {
+        Font::Set(tile_set) => tile_set.iter().collect(),
+        Font::Collection(tiles) => tiles.iter().enumerate().map(|(index, tile)| (tile.kind(), index, tile)).collect(),
+    }).collect();
+
+    let groups = duplicate_groups(&fonts, matching);
+    let duplicate_count: usize = groups.iter().map(|group| group.len() - 1).sum();
+    match output_format {
+        OutputFormat::Json => report.duplicate_groups = Some(groups),
+        OutputFormat::Human => {
+            for group in &groups {
+                println!("{}", group.iter().map(|location| location_description(location, sources)).collect::<Vec<_>>().join(", "));
+            }
+            println!("{} groups, {duplicate_count} tiles duplicating another one", groups.len());
+        },
+    }
+    Ok(())
+}
@@ -0,0 +1,69 @@
+
+use std::path::Path;
+
+use anyhow::anyhow;
+use hd_fpv_osd_font_tool::{
+    osd::tile::{
+        self,
+        container::{
+            dir_check::DirContent,
+            dir_fix::{plan_dir_fixes, FixOptions},
+        },
+    },
+    report::{CollectionReport, CommandReport},
+};
+use strum::IntoEnumIterator;
+
+use crate::output::OutputFormat;
+
+
+fn identify_fix_arg(input: &str) -> anyhow::Result<(&str, DirContent)> {
+    if let Some(dir) = input.strip_prefix("tilesetdir:") {
+        Ok((dir, DirContent::Tiles))
+    } else if let Some(dir) = input.strip_prefix("symsetdir:") {
+        Ok((dir, DirContent::Symbols))
+    } else {
+        Err(anyhow!("invalid fix argument `{input}`: expected tilesetdir:path or symsetdir:path"))
+    }
+}
+
+fn collection_format(content: DirContent) -> &'static str {
+    match content {
+        DirContent::Tiles => "tilesetdir",
+        DirContent::Symbols => "symsetdir",
+    }
+}
+
+pub fn fix_command(target: &str, options: FixOptions, out: Option<&Path>, force: bool, output_format: OutputFormat, report: &mut CommandReport) -> anyhow::Result<()> {
+    let (dir, content) = identify_fix_arg(target)?;
+    if out.is_none() && !force {
+        return Err(anyhow!("refusing to modify {dir} in place without --force, use --out to write the fixed files to another directory"));
+    }
+    report.inputs.push(CollectionReport::new(collection_format(content), &[dir]));
+    report.outputs.push(CollectionReport::new(collection_format(content), &[out.unwrap_or_else(|| Path::new(dir))]));
+
+    // planning everything first so that nothing is modified if any of the fixes is ambiguous
+    let plans = tile::Kind::iter()
+        .map(|tile_kind| plan_dir_fixes(tile_kind.set_dir_path(dir), content, tile_kind, &options))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    for (tile_kind, plan) in tile::Kind::iter().zip(plans.iter()) {
+        for issue in &plan.unfixed {
+            log::warn!("not fixed: {issue}");
+        }
+        let out_dir = out.map(|out| tile_kind.set_dir_path(out));
+        plan.apply(out_dir.as_deref())?;
+        for change in plan.changes() {
+            if output_format == OutputFormat::Human {
+                println!("{change}");
+            }
+            report.changes.push(change.clone());
+        }
+    }
+
+    if report.changes.is_empty() {
+        log::info!("nothing to fix");
+    }
+
+    Ok(())
+}
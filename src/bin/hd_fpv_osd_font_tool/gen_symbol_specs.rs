@@ -0,0 +1,44 @@
+
+use std::path::Path;
+
+use anyhow::anyhow;
+use hd_fpv_osd_font_tool::{
+    convert_options::DEFAULT_MAX_TILE_COUNT,
+    image_format::ImageDecoding,
+    prelude::*,
+    report::{CollectionReport, CommandReport},
+};
+
+use crate::overwrite::check_outputs;
+
+
+fn spans(specs: &SymbolSpecs) -> Vec<(usize, usize)> {
+    specs.iter().map(|spec| (spec.start_tile_index(), spec.span())).collect()
+}
+
+// the SD and HD symbols of a set have to span the same tiles since a specs file applies to both
+pub fn gen_symbol_specs_command(from: &str, to: &Path, report: &mut CommandReport) -> anyhow::Result<()> {
+    let specs = if let Some(dir) = from.strip_prefix("symsetdir:") {
+        report.inputs.push(CollectionReport::new("symsetdir", &[dir]));
+        let symbol_set = SymbolSet::load_from_dir(dir, DEFAULT_MAX_TILE_COUNT, None, ImageDecoding::default())?;
+        let specs = SymbolSpecs::from_symbols(symbol_set.sd_symbols());
+        if spans(&specs) != spans(&SymbolSpecs::from_symbols(symbol_set.hd_symbols())) {
+            return Err(anyhow!("the SD and HD symbols of {dir} do not span the same tiles"));
+        }
+        specs
+    } else if let Some(dir) = from.strip_prefix("symdir:") {
+        report.inputs.push(CollectionReport::new("symdir", &[dir]));
+        SymbolSpecs::from_symbols(&load_symbols_from_dir(dir, DEFAULT_MAX_TILE_COUNT, None, ImageDecoding::default())?)
+    } else if let Some(dir) = from.strip_prefix("tiledir:") {
+        // split symbol directory written by the `split-symbols` command, the file names keep the symbol names
+        report.inputs.push(CollectionReport::new("tiledir", &[dir]));
+        load_split_symbol_specs(dir)?
+    } else {
+        return Err(anyhow!("invalid gen-symbol-specs argument `{from}`: expected symsetdir:path, symdir:path or tiledir:path"));
+    };
+    log::info!("{} symbols spanning several tiles found in {from}", specs.len());
+    report.outputs.push(CollectionReport::new("symbolspecs", &[to]));
+    check_outputs(&[to])?;
+    specs.save_file(to)?;
+    Ok(())
+}
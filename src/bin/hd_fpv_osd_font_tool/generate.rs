@@ -0,0 +1,27 @@
+use std::path::Path;
+
+use hd_fpv_osd_font_tool::{
+    osd::tile::font_render::{ascii_mapping, load_mapping_file, render_font_file},
+    prelude::*,
+    report::{CollectionReport, CommandReport},
+};
+
+use crate::convert::report_alpha_threshold;
+use crate::convert_set::save_tile_set;
+
+
+// without a mapping file the printable ASCII characters are rendered at the index of their code
+pub fn generate_command(font: &Path, mapping: Option<&Path>, margin: u32, to: &str, options: ConvertOptions, report: &mut CommandReport) -> anyhow::Result<()> {
+    let mapping = match mapping {
+        Some(mapping) => load_mapping_file(mapping)?,
+        None => ascii_mapping(),
+    };
+    report.inputs.push(CollectionReport::new("font", &[font]));
+    log::info!("rendering {} characters from {} -> {to}", mapping.len(), font.to_string_lossy());
+    let mut tile_set = render_font_file(font, &mapping, margin)?;
+    if let Some(threshold) = options.alpha_threshold {
+        report_alpha_threshold(report, tile_set.threshold_alpha(threshold));
+    }
+    tile_set.apply_transforms(&options.transforms)?;
+    save_tile_set(tile_set, to, &options, report)
+}
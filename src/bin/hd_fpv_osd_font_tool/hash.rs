@@ -0,0 +1,137 @@
+use std::path::{Path, PathBuf};
+
+use hd_fpv_osd_font_tool::{
+    osd::{
+        ident_scan::scan_idents,
+        tile::container::{content_hash::tile_hashes, manifest::{file_hashes, Manifest}, uniq_tile_kind::UniqTileKind},
+    },
+    prelude::*,
+    report::{CollectionReport, CommandReport},
+    stdio::is_stdio_path,
+};
+use strum::IntoEnumIterator;
+use thiserror::Error;
+
+use crate::{
+    convert::load_tiles,
+    convert_set::{argument_norm_args, is_tile_set_arg, load_tile_set, ConvertSetError},
+    output::OutputFormat,
+    overwrite::check_outputs,
+};
+
+
+#[derive(Debug, Error)]
+#[error("{mismatch_count} difference(s) with the manifest")]
+pub struct VerifyError {
+    pub mismatch_count: usize,
+}
+
+// the files the tiles were loaded from, only the files of the ident for the normalized sets whose directory holds others
+fn source_files(from: &str, input: &CollectionReport) -> anyhow::Result<Vec<PathBuf>> {
+    let norm_args = from.split_once(':').filter(|(prefix, _)| matches!(*prefix, "djibinsetnorm" | "tilesetgridsnorm"));
+    let (prefix, args) = match norm_args {
+        Some(norm_args) => norm_args,
+        None => return Ok(input.paths.iter().filter(|path| !is_stdio_path(path)).cloned().collect()),
+    };
+    let (dir, ident) = argument_norm_args(args).map_err(ConvertSetError::FromArg)?;
+    let scan = scan_idents(dir)?;
+    let files = scan.idents().iter()
+        .filter(|ident_files| ident_files.ident().as_deref() == ident)
+        .flat_map(|ident_files| match prefix {
+            "djibinsetnorm" => ident_files.sd_bin_pages().values().chain(ident_files.hd_bin_pages().values()).cloned().collect::<Vec<_>>(),
+            _ => ident_files.sd_grids().iter().chain(ident_files.hd_grids().iter()).cloned().collect(),
+        })
+        .map(|file_name| Path::new(dir).join(file_name))
+        .collect();
+    Ok(files)
+}
+
+// the hashes of the tiles of each kind
+type PerKindTileHashes = Vec<(tile::Kind, Vec<String>)>;
+
+fn print_tile_hashes(tile_hashes: &[String], prefix: &str) {
+    for (index, hash) in tile_hashes.iter().enumerate() {
+        println!("{prefix}{index:03} {hash}");
+    }
+}
+
+fn compute_hashes(from: &str, report: &mut CommandReport) -> anyhow::Result<(String, PerKindTileHashes)> {
+    Ok(if is_tile_set_arg(from) {
+        let tile_set = load_tile_set(from, report)?;
+        let per_kind_tile_hashes = tile::Kind::iter().map(|tile_kind| (tile_kind, tile_hashes(&tile_set[tile_kind]))).collect::<Vec<_>>();
+        (tile_set.content_hash(), per_kind_tile_hashes)
+    } else {
+        let tiles = load_tiles(from, report)?;
+        let per_kind_tile_hashes = match tiles.tile_kind() {
+            Ok(tile_kind) => vec![(tile_kind, tile_hashes(&tiles))],
+            Err(_) => vec![],
+        };
+        (tiles.content_hash(), per_kind_tile_hashes)
+    })
+}
+
+fn build_manifest(from: &str, content_hash: &str, per_kind_tile_hashes: &[(tile::Kind, Vec<String>)], report: &CommandReport) -> anyhow::Result<Manifest> {
+    let input = report.inputs.last().expect("the loaded collection should be reported");
+    Ok(Manifest {
+        content_hash: content_hash.to_owned(),
+        tile_hashes: per_kind_tile_hashes.iter().map(|(tile_kind, tile_hashes)| (tile_kind.to_string(), tile_hashes.clone())).collect(),
+        files: file_hashes(&source_files(from, input)?)?,
+    })
+}
+
+pub fn hash_command(from: &str, per_tile: bool, manifest: Option<&Path>, output_format: OutputFormat, report: &mut CommandReport) -> anyhow::Result<()> {
+    if let Some(manifest_path) = manifest {
+        check_outputs(&[manifest_path])?;
+    }
+    let (content_hash, per_kind_tile_hashes) = compute_hashes(from, report)?;
+    if let Some(manifest_path) = manifest {
+        let manifest = build_manifest(from, &content_hash, &per_kind_tile_hashes, report)?;
+        manifest.save(manifest_path)?;
+        report.outputs.push(CollectionReport::new("manifest", &[manifest_path]));
+        log::info!("manifest with {} file checksum(s) written to {}", manifest.files.len(), manifest_path.to_string_lossy());
+    }
+
+    match output_format {
+        OutputFormat::Json => {
+            report.content_hash = Some(content_hash);
+            if per_tile {
+                report.tile_hashes = per_kind_tile_hashes.into_iter()
+                    .map(|(tile_kind, tile_hashes)| (tile_kind.to_string(), tile_hashes))
+                    .collect();
+            }
+        },
+        OutputFormat::Human => {
+            println!("content hash: {content_hash}");
+            if per_tile {
+                let print_kind = per_kind_tile_hashes.len() > 1;
+                for (tile_kind, tile_hashes) in &per_kind_tile_hashes {
+                    let prefix = if print_kind { format!("{tile_kind} ") } else { String::new() };
+                    print_tile_hashes(tile_hashes, &prefix);
+                }
+            }
+        },
+    }
+    Ok(())
+}
+
+pub fn verify_command(from: &str, manifest_path: &Path, output_format: OutputFormat, report: &mut CommandReport) -> anyhow::Result<()> {
+    let expected = Manifest::load(manifest_path)?;
+    report.inputs.push(CollectionReport::new("manifest", &[manifest_path]));
+    let (content_hash, per_kind_tile_hashes) = compute_hashes(from, report)?;
+    let actual = build_manifest(from, &content_hash, &per_kind_tile_hashes, report)?;
+    let mismatches: Vec<String> = expected.compare(&actual).iter().map(ToString::to_string).collect();
+    let checked_file_count = expected.files.keys().filter(|path| actual.files.contains_key(*path)).count();
+
+    if output_format == OutputFormat::Human {
+        for mismatch in &mismatches {
+            println!("{mismatch}");
+        }
+    }
+    if mismatches.is_empty() {
+        log::info!("{from} matches the manifest, {checked_file_count} file checksum(s) checked");
+        return Ok(())
+    }
+    let mismatch_count = mismatches.len();
+    report.problems = mismatches;
+    Err(VerifyError { mismatch_count }.into())
+}
@@ -0,0 +1,83 @@
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use hd_fpv_osd_font_tool::{prelude::ConvertOptions, report::CollectionReport};
+use serde::{Deserialize, Serialize};
+
+// Records the content hashes of the inputs and outputs of the conversions so that a conversion can be skipped when
+// its input did not change and its outputs are still there untouched. The hashes are the format independent content
+// hashes so that re-encoding an image without changing the tiles does not trigger a conversion.
+
+const STATE_FILE_NAME: &str = ".hd_fpv_font_tool_state.json";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct ConversionState {
+    // the same input converted with other options gives other outputs
+    options: String,
+    input_hash: String,
+    output_hash: String,
+}
+
+pub struct IncrementalConversion {
+    state_file_path: PathBuf,
+    key: String,
+    options: String,
+    input_hash: String,
+}
+
+impl IncrementalConversion {
+
+    // the state file is written next to the first output path, the conversions are keyed by the command and
+    // the arguments resolved from the current directory
+    pub fn new(command: &str, from: &str, to: &str, output: &CollectionReport, options: &ConvertOptions, input_hash: String) -> Self {
+        let output_dir = output.paths.first().and_then(|path| path.parent()).unwrap_or_else(|| Path::new(""));
+        let state_file_path = output_dir.join(STATE_FILE_NAME);
+        let current_dir = std::env::current_dir().unwrap_or_default();
+        let key = format!("{command} {} {from} {to}", current_dir.to_string_lossy());
+        Self { state_file_path, key, options: format!("{options:?}"), input_hash }
+    }
+
+    // a missing or unreadable state file is the same as an empty one, everything gets converted
+    fn load_states(&self) -> BTreeMap<String, ConversionState> {
+        let content = match std::fs::read_to_string(&self.state_file_path) {
+            Ok(content) => content,
+            Err(_) => return BTreeMap::new(),
+        };
+        serde_json::from_str(&content).unwrap_or_else(|error| {
+            log::warn!("ignoring invalid state file {}: {error}", self.state_file_path.to_string_lossy());
+            BTreeMap::new()
+        })
+    }
+
+    // `output_hash` computes the content hash of the outputs as they currently are, failing when they are missing
+    pub fn is_up_to_date<F: FnOnce() -> anyhow::Result<String>>(&self, output_hash: F) -> bool {
+        let state = match self.load_states().remove(&self.key) {
+            Some(state) => state,
+            None => return false,
+        };
+        if state.options != self.options || state.input_hash != self.input_hash {
+            return false;
+        }
+        matches!(output_hash(), Ok(output_hash) if output_hash == state.output_hash)
+    }
+
+    // failing to record the state is not an error, the next run will just convert again
+    pub fn record<F: FnOnce() -> anyhow::Result<String>>(self, output_hash: F) {
+        let output_hash = match output_hash() {
+            Ok(output_hash) => output_hash,
+            Err(error) => {
+                log::warn!("the outputs cannot be loaded back to record their state, the conversion will not be skipped next time: {error}");
+                return;
+            },
+        };
+        let mut states = self.load_states();
+        states.insert(self.key, ConversionState { options: self.options, input_hash: self.input_hash, output_hash });
+        let result = serde_json::to_string_pretty(&states).map_err(anyhow::Error::from)
+            .and_then(|content| Ok(fs_err::write(&self.state_file_path, content)?));
+        if let Err(error) = result {
+            log::warn!("failed to write the state file {}: {error}", self.state_file_path.to_string_lossy());
+        }
+    }
+
+}
@@ -0,0 +1,70 @@
+use anyhow::anyhow;
+use hd_fpv_osd_font_tool::{
+    osd::{font_metadata::FontMetadata, tile::{analog_grid, container::info::{FontInfo, TileCollectionInfo}}},
+    prelude::*,
+    report::{CollectionReport, CommandReport},
+};
+use strum::IntoEnumIterator;
+
+use crate::{
+    convert::{load_tiles, metadata_dir, open_lazy_bin_file},
+    convert_set::{is_tile_set_arg, load_tile_set},
+    output::OutputFormat,
+};
+
+
+// details which are not part of the collection specification, only looked up when cheap
+fn format_variant(collection: &CollectionReport) -> Option<String> {
+    match collection.format.as_str() {
+        "djibin" | "djibinset" if collection.paths.iter().any(bin_file::is_gzip_path) => Some("gzip compressed".to_owned()),
+        "analoggrid" => {
+            let (width, _) = image::image_dimensions(collection.paths.first()?).ok()?;
+            let scale = width / analog_grid::image_dimensions(1).width;
+            (scale > 1).then(|| format!("{scale}x upscaled sheet"))
+        },
+        _ => None,
+    }
+}
+
+fn print_info(info: &FontInfo) {
+    if let Some(metadata) = &info.metadata {
+        for (label, value) in metadata.fields() {
+            println!("{label}: {value}");
+        }
+    }
+    match &info.variant {
+        Some(variant) => println!("format: {} ({variant})", info.format),
+        None => println!("format: {}", info.format),
+    }
+    for collection in &info.collections {
+        match (collection.tile_kind, collection.tile_dimensions) {
+            (Some(tile_kind), Some(dimensions)) => print!("{tile_kind} tiles ({dimensions}): "),
+            _ => print!("tiles: "),
+        }
+        println!("{} tiles, {} pages, {} fully transparent", collection.tile_count, collection.page_count, collection.transparent_tile_count);
+    }
+}
+
+pub fn info_command(from: &str, output_format: OutputFormat, report: &mut CommandReport) -> anyhow::Result<()> {
+    let collections = if is_tile_set_arg(from) {
+        let tile_set = load_tile_set(from, report)?;
+        tile::Kind::iter().map(|tile_kind| TileCollectionInfo::compute_of_kind(tile_kind, &tile_set[tile_kind])).collect::<Result<Vec<_>, _>>()?
+    } else if let Some(reader) = open_lazy_bin_file(from, report)? {
+        vec![TileCollectionInfo::compute_bin_file(&reader)]
+    } else {
+        let tiles = load_tiles(from, report)?;
+        vec![TileCollectionInfo::compute(&tiles)?]
+    };
+    let input = report.inputs.last().ok_or_else(|| anyhow!("no input reported for {from}"))?;
+    let metadata = match metadata_dir(input) {
+        Some(dir) => FontMetadata::load_from_dir(dir)?,
+        None => None,
+    };
+    let info = FontInfo { format: input.format.clone(), variant: format_variant(input), collections, metadata };
+
+    match output_format {
+        OutputFormat::Json => report.info = Some(info),
+        OutputFormat::Human => print_info(&info),
+    }
+    Ok(())
+}
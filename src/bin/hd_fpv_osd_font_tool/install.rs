@@ -0,0 +1,99 @@
+use std::path::{Path, PathBuf};
+
+use hd_fpv_osd_font_tool::{
+    prelude::*,
+    report::{CollectionReport, CommandReport},
+};
+use thiserror::Error;
+
+use crate::backup::{backup_font_files, default_backup_file_name};
+use crate::convert::report_alpha_threshold;
+use crate::convert_set::{load_tile_set_options, save_tile_set};
+
+// Writes a font to the SD card of the goggles with the file names the DJI goggles firmware (wtfos msp-osd) expects.
+// The SD card is looked for among the mounted removable media: a single mounted volume with a DCIM directory.
+
+#[derive(Debug, Error)]
+pub enum GogglesMediaError {
+    #[error("no goggles SD card found (mounted volume with a DCIM directory), use --target to specify it")]
+    NotFound,
+    #[error("several candidate SD cards found: {}, use --target to specify one", .0.iter().map(|path| path.to_string_lossy()).collect::<Vec<_>>().join(", "))]
+    Ambiguous(Vec<PathBuf>),
+    #[error("target directory {0} does not exist")]
+    TargetNotFound(PathBuf),
+}
+
+// directories removable media are mounted in
+fn mount_roots() -> Vec<PathBuf> {
+    let mut roots = vec![PathBuf::from("/Volumes")];
+    if let Ok(user) = std::env::var("USER") {
+        roots.push(Path::new("/media").join(&user));
+        roots.push(Path::new("/run/media").join(&user));
+    }
+    roots.push(PathBuf::from("/media"));
+    roots
+}
+
+pub fn detect_goggles_media() -> Result<PathBuf, GogglesMediaError> {
+    let mut candidates: Vec<PathBuf> = mount_roots().into_iter()
+        .filter_map(|root| std::fs::read_dir(root).ok())
+        .flat_map(|entries| entries.filter_map(|entry| entry.ok().map(|entry| entry.path())))
+        .filter(|path| path.join("DCIM").is_dir())
+        .collect();
+    candidates.sort();
+    candidates.dedup();
+    match candidates.len() {
+        0 => Err(GogglesMediaError::NotFound),
+        1 => Ok(candidates.remove(0)),
+        _ => Err(GogglesMediaError::Ambiguous(candidates)),
+    }
+}
+
+pub fn goggles_media(target: Option<&Path>) -> Result<PathBuf, GogglesMediaError> {
+    match target {
+        Some(target) if target.is_dir() => Ok(target.to_path_buf()),
+        Some(target) => Err(GogglesMediaError::TargetNotFound(target.to_path_buf())),
+        None => detect_goggles_media(),
+    }
+}
+
+pub fn is_font_file_name(file_name: &str) -> bool {
+    file_name.starts_with("font") && file_name.ends_with(".bin")
+}
+
+// the bin files of all the fonts in the root directory of the media e.g. font.bin, font_btfl_hd_2.bin
+pub fn font_files(media: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let mut files = vec![];
+    for entry in std::fs::read_dir(media)? {
+        let path = entry?.path();
+        let is_font_file = path.file_name().and_then(|file_name| file_name.to_str()).is_some_and(is_font_file_name);
+        if is_font_file && path.is_file() {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+pub fn install_command(from: &str, target: Option<&Path>, ident: Option<&str>, archive_existing: bool, options: ConvertOptions, report: &mut CommandReport) -> anyhow::Result<()> {
+    let media = goggles_media(target)?;
+    // nothing to back up on a card without fonts
+    if archive_existing && !font_files(&media)?.is_empty() {
+        let archive_path = media.join(default_backup_file_name()?);
+        backup_font_files(&media, &archive_path)?;
+        log::info!("backed up the fonts of {} to {}", media.to_string_lossy(), archive_path.to_string_lossy());
+        report.outputs.push(CollectionReport::new("archive", &[archive_path]));
+    }
+
+    let to = match ident {
+        Some(ident) => format!("djibinsetnorm:{}:{ident}", media.to_string_lossy()),
+        None => format!("djibinsetnorm:{}", media.to_string_lossy()),
+    };
+    log::info!("installing {from} -> {to}");
+    let mut tile_set = load_tile_set_options(from, &options, report)?;
+    if let Some(threshold) = options.alpha_threshold {
+        report_alpha_threshold(report, tile_set.threshold_alpha(threshold));
+    }
+    tile_set.apply_transforms(&options.transforms)?;
+    save_tile_set(tile_set, &to, &options, report)
+}
@@ -0,0 +1,41 @@
+use std::path::Path;
+
+use hd_fpv_osd_font_tool::{
+    osd::ident_scan::{scan_idents, IdentScan},
+    report::{CollectionReport, CommandReport},
+};
+use itertools::Itertools;
+
+use crate::output::OutputFormat;
+
+
+fn print_idents(scan: &IdentScan) {
+    for ident_files in scan.idents() {
+        let ident = ident_files.ident().as_deref().unwrap_or("(no ident)");
+        let status = if ident_files.is_complete() { "complete" } else { "incomplete" };
+        println!("{ident}: {status}");
+        let kinds = [("SD", ident_files.sd_tile_count()), ("HD", ident_files.hd_tile_count())].into_iter()
+            .filter_map(|(kind, count)| count.map(|count| format!("{kind} ({count} tiles)")))
+            .join(", ");
+        if !kinds.is_empty() {
+            println!("    kinds: {kinds}");
+        }
+        println!("    present: {}", ident_files.present().iter().map(|path| path.display()).join(", "));
+        if !ident_files.is_complete() {
+            println!("    missing: {}", ident_files.missing().iter().map(|path| path.display()).join(", "));
+        }
+    }
+    if !scan.unrecognized().is_empty() {
+        println!("unrecognized: {}", scan.unrecognized().iter().map(|path| path.display()).join(", "));
+    }
+}
+
+pub fn list_idents_command(dir: &Path, output_format: OutputFormat, report: &mut CommandReport) -> anyhow::Result<()> {
+    report.inputs.push(CollectionReport::new("dir", &[dir]));
+    let scan = scan_idents(dir)?;
+    match output_format {
+        OutputFormat::Json => report.idents = Some(scan),
+        OutputFormat::Human => print_idents(&scan),
+    }
+    Ok(())
+}
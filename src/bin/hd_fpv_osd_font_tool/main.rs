@@ -7,20 +7,85 @@ use std::{
     process::exit
 };
 
-use clap::Parser;
+use clap::{CommandFactory, FromArgMatches};
 use anyhow::anyhow;
 use env_logger::fmt::Color;
-use hd_fpv_osd_font_tool::prelude::*;
+use hd_fpv_osd_font_tool::{log_level::LogLevel, report::{CommandReport, ErrorReport}};
 
 mod convert;
 mod convert_set;
 mod man_pages;
+mod completions;
 mod cli;
+mod stats;
+mod output;
+mod fix;
+mod check;
+mod list_idents;
+mod diff;
+mod hash;
+mod info;
+mod generate;
+mod merge;
+mod patch;
+mod batch;
+mod remap;
+mod tile_edit;
+mod render_text;
+mod preview;
+mod show;
+mod install;
+mod backup;
+#[cfg(feature = "network")]
+mod fetch;
+#[cfg(feature = "tui")]
+mod tui;
+mod split_symbols;
+mod gen_symbol_specs;
+mod incremental;
+mod overwrite;
+mod config;
+mod watch;
+mod compare;
+mod find_duplicates;
 
 use convert::convert_command;
-use convert_set::convert_set_command;
+use convert_set::convert_set_command_targets;
+use fix::fix_command;
+use check::{check_command, check_specs_command};
+use list_idents::list_idents_command;
+use diff::diff_command;
+use hash::{hash_command, verify_command};
+use info::info_command;
+use generate::generate_command;
+use merge::merge_command;
+use patch::{apply_patch_command, make_patch_command};
+use batch::batch_command;
+use remap::remap_command;
+use tile_edit::{extract_tile_command, set_tile_command};
+use render_text::render_text_command;
+use preview::{preview_command, preview_osd_command};
+use show::show_command;
+use install::install_command;
+use backup::{backup_command, restore_command};
+#[cfg(feature = "network")]
+use fetch::fetch_command;
+#[cfg(feature = "tui")]
+use tui::tui_command;
+use split_symbols::split_symbols_command;
+use gen_symbol_specs::gen_symbol_specs_command;
+use hd_fpv_osd_font_tool::osd::tile::container::dir_fix::FixOptions;
+use hd_fpv_osd_font_tool::osd::tile::regression::Thresholds;
+use hd_fpv_osd_font_tool::osd::tile::container::duplicates::DuplicateMatch;
 use man_pages::*;
+use completions::completions_command;
+use stats::stats_command;
 use cli::*;
+use output::{OutputFormat, error_code, init_logger, print_warning_summary, take_warnings, LOG_ENV_VAR};
+use overwrite::ExistingOutputs;
+use watch::watch_command;
+use compare::compare_command;
+use find_duplicates::find_duplicates_command;
 
 fn current_exe_name() -> anyhow::Result<String> {
     let current_exe = current_exe().map_err(|error| anyhow!("failed to get exe name: {error}"))?;
@@ -34,28 +99,138 @@ fn generate_man_pages_command() -> anyhow::Result<()> {
 }
 
 fn main() {
-    let cli = Cli::parse();
+    // the configuration file provides the defaults of the options so it has to be read before parsing the command line
+    let config_path = config::config_arg_path(std::env::args_os());
+    let config = match config::load(config_path.as_deref()) {
+        Ok(config) => config,
+        Err(error) => {
+            eprintln!("{error}");
+            exit(ExitCode::Argument.code());
+        },
+    };
+    let cli = Cli::from_arg_matches(&config.apply(Cli::command()).get_matches()).unwrap_or_else(|error| error.exit());
 
-    env_logger::builder()
-        .format(|buf, record| {
+    // the module logging the messages is only worth showing along with the debug messages
+    let show_targets = cli.log_level() >= LogLevel::Debug;
+    let logger = env_logger::builder()
+        .format(move |buf, record| {
             let level_style = buf.default_level_style(record.level());
             write!(buf, "{:<5}", level_style.value(record.level()))?;
             let mut style = buf.style();
             style.set_color(Color::White).set_bold(true);
             write!(buf, "{}", style.value(" > "))?;
+            if show_targets {
+                write!(buf, "[{}] ", record.target())?;
+            }
             writeln!(buf, "{}", record.args())
         })
         .parse_filters(cli.log_level().to_string().as_str())
-        .init();
+        .parse_env(LOG_ENV_VAR)
+        .build();
+    let log_file = match cli.log_file().as_ref().map(fs_err::File::create).transpose() {
+        Ok(log_file) => log_file,
+        Err(error) => {
+            eprintln!("{error}");
+            exit(ExitCode::Io.code());
+        },
+    };
+    init_logger(logger, log_file);
+    if let Some(config) = cli.config() {
+        log::debug!("option defaults read from {}", config.to_string_lossy());
+    }
+
+    overwrite::set_existing_outputs(ExistingOutputs::from_flags(cli.force(), cli.backup()));
+    let mut report = CommandReport::new(cli.command.name());
 
     let command_result = match &cli.command {
-        Commands::Convert { from, to, symbol_specs_file } => convert_command(from, to, ConvertOptions { symbol_specs_file }),
-        Commands::ConvertSet { from, to, symbol_specs_file } => convert_set_command(from, to, ConvertOptions { symbol_specs_file }),
+        Commands::Convert { from, to, options, incremental, watch } =>
+            options.convert_options().and_then(|options| match watch {
+                true => watch_command(&options.symbol_specs_files, &mut report, |report|
+                    convert_command(from, to, options.clone(), *incremental, report)),
+                false => convert_command(from, to, options, *incremental, &mut report),
+            }),
+        Commands::ConvertSet { from, to, extra_to, options, incremental, watch } => {
+            let targets: Vec<&String> = to.iter().chain(extra_to.iter()).collect();
+            options.convert_options().and_then(|options| match watch {
+                true => watch_command(&options.symbol_specs_files, &mut report, |report|
+                    convert_set_command_targets(from, &targets, options.clone(), *incremental, report)),
+                false => convert_set_command_targets(from, &targets, options, *incremental, &mut report),
+            })
+        },
+        Commands::Stats { from, json, check_margins } => stats_command(from, *json, *check_margins, cli.output(), &mut report),
+        Commands::Fix { renumber, strip_foreign, normalize_png, fit, out, target } => {
+            let options = FixOptions { renumber: *renumber, strip_foreign: *strip_foreign, normalize_png: *normalize_png, fit: *fit };
+            fix_command(target, options, out.as_deref(), cli.force(), cli.output(), &mut report)
+        },
+        Commands::Check { palette, target } => check_command(target, *palette, cli.output(), &mut report),
+        Commands::CheckSpecs { tile_count, files } => check_specs_command(files, *tile_count, cli.output(), &mut report),
+        Commands::Hash { per_tile, manifest, from } => hash_command(from, *per_tile, manifest.as_deref(), cli.output(), &mut report),
+        Commands::Verify { manifest, from } => verify_command(from, manifest, cli.output(), &mut report),
+        Commands::Info { from } => info_command(from, cli.output(), &mut report),
+        Commands::Generate { mapping, margin, options, font, to } =>
+            options.convert_options().and_then(|options| generate_command(font, mapping.as_deref(), *margin, to, options, &mut report)),
+        Commands::Merge { options, out, sources } =>
+            options.convert_options().and_then(|options| merge_command(sources, out, options, &mut report)),
+        Commands::MakePatch { indexes, base, options, from, patch } =>
+            options.convert_options().and_then(|options| make_patch_command(from, patch, indexes.as_ref(), base.as_deref(), options, &mut report)),
+        Commands::ApplyPatch { options, base, patch, to } =>
+            options.convert_options().and_then(|options| apply_patch_command(base, patch, to, options, &mut report)),
+        Commands::Batch { options, manifest } =>
+            options.convert_options().and_then(|options| batch_command(manifest, options, &mut report)),
+        Commands::Remap { options, char_map, from, to } =>
+            options.convert_options().and_then(|options| remap_command(char_map, from, to, options, &mut report)),
+        Commands::ExtractTile { scale, grid_layout, from, index, to } => extract_tile_command(from, *index, to, *scale, *grid_layout, &mut report),
+        Commands::SetTile { fit, grid_layout, target, index, tile } => set_tile_command(target, *index, tile, *fit, *grid_layout, &mut report),
+        Commands::RenderText { mapping, background, scale, from, text, to } =>
+            render_text_command(from, text, mapping.as_deref(), *background, *scale, to, &mut report),
+        Commands::Preview { scale, from, to } => preview_command(from, to, *scale, &mut report),
+        Commands::PreviewOsd { background, from, dir } => preview_osd_command(from, dir, *background, &mut report),
+        Commands::Show { protocol, scale, from, index } => show_command(from, *index, *protocol, *scale, &mut report),
+        #[cfg(feature = "tui")]
+        Commands::Tui { compare, extract_dir, from } => tui_command(from, compare.as_deref(), extract_dir, &mut report),
+        Commands::Install { target, ident, archive_existing, options, from } =>
+            options.convert_options().and_then(|options| install_command(from, target.as_deref(), ident.as_deref(), *archive_existing, options, &mut report)),
+        Commands::Backup { target, out } => backup_command(target.as_deref(), out.as_deref(), &mut report),
+        Commands::Restore { target, archive } => restore_command(archive, target.as_deref(), &mut report),
+        #[cfg(feature = "network")]
+        Commands::Fetch { from_format, cache_dir, registry, refresh, options, source, to } =>
+            options.convert_options().and_then(|options| fetch_command(source, to.as_deref(), from_format.as_deref(), cache_dir.as_deref(),
+                registry.as_deref(), *refresh, options, &mut report)),
+        Commands::SplitSymbols { options, from, to } =>
+            options.convert_options().and_then(|options| split_symbols_command(from, to, options, &mut report)),
+        Commands::GenSymbolSpecs { from, to } => gen_symbol_specs_command(from, to, &mut report),
+        Commands::Diff { visual, old, new } => diff_command(old, new, visual.as_deref(), cli.output(), &mut report),
+        Commands::FindDuplicates { perceptual, max_distance, sources } => {
+            let matching = match perceptual {
+                true => DuplicateMatch::Perceptual { max_distance: *max_distance },
+                false => DuplicateMatch::Exact,
+            };
+            find_duplicates_command(sources, matching, cli.output(), &mut report)
+        },
+        Commands::Compare { reference, max_changed_pixels, max_perceptual_diff, montage, from } => {
+            let thresholds = Thresholds { max_changed_pixels: *max_changed_pixels, max_perceptual_difference: *max_perceptual_diff };
+            compare_command(from, reference, thresholds, montage.as_deref(), cli.output(), &mut report)
+        },
+        Commands::ListIdents { dir } => list_idents_command(dir, cli.output(), &mut report),
+        Commands::Completions { shell } => completions_command(*shell),
         Commands::GenerateManPages => generate_man_pages_command(),
     };
 
+    report.success = command_result.is_ok();
+    report.warnings = take_warnings();
+    if cli.output() == OutputFormat::Human && cli.log_level() >= LogLevel::Warn {
+        print_warning_summary(&report.warnings);
+    }
+    if let Err(error) = &command_result {
+        report.error = Some(ErrorReport { code: error_code(error).to_owned(), message: error.to_string() });
+    }
+
+    if cli.output() == OutputFormat::Json {
+        println!("{}", report.to_json().expect("command report should serialize to JSON"));
+    }
+
     if let Err(error) = command_result {
         log::error!("{}", error);
-        exit(1);
+        exit(ExitCode::for_error(&error).code());
     }
 }
@@ -29,7 +29,7 @@ pub fn generate_exe_man_page(exe_name: &str) -> anyhow::Result<()> {
 
 pub fn generate_man_page_for_subcommands(exe_name: &str) -> anyhow::Result<()> {
     let command = Cli::command();
-    let exclusions = ["generate-shell-autocompletion-files", "generate-man-pages"];
+    let exclusions = ["completions", "generate-man-pages"];
     for subcommand in command.get_subcommands() {
         if ! exclusions.contains(&subcommand.get_name()) {
             let mut file = File::create(command_man_page_path(exe_name, Some(subcommand)))?;
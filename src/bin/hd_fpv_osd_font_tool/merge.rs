@@ -0,0 +1,21 @@
+use hd_fpv_osd_font_tool::{
+    prelude::*,
+    report::CommandReport,
+};
+
+use crate::convert_set::{load_tile_set_options, save_tile_set};
+
+
+// the first source is the base, the non-blank tiles of each following source replace the tiles at the same index
+pub fn merge_command(sources: &[String], to: &str, options: ConvertOptions, report: &mut CommandReport) -> anyhow::Result<()> {
+    let (base, layers) = sources.split_first().expect("at least one source should be given");
+    log::info!("merging {} -> {to}", sources.join(" + "));
+    let mut tile_set = load_tile_set_options(base, &options, report)?;
+    for layer in layers {
+        let layer_tile_set = load_tile_set_options(layer, &options, report)?;
+        let overlaid_count = tile_set.overlay(&layer_tile_set);
+        log::info!("{layer}: {overlaid_count} tiles overlaid");
+    }
+    tile_set.apply_transforms(&options.transforms)?;
+    save_tile_set(tile_set, to, &options, report)
+}
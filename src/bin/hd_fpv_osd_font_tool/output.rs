@@ -0,0 +1,221 @@
+
+use std::io::Write;
+use std::sync::Mutex;
+
+use clap::ValueEnum;
+use hd_fpv_osd_font_tool::{
+    osd::{
+        avatar_file,
+        mcm_file,
+        ident_scan::ScanIdentsError,
+        tile::{analog_grid, combined_grid, hdzero_sheet, font_render, diff::SaveVisualDiffError, regression::SaveMontageError, transform::TransformedDimensionsError},
+        tile::container::{
+            CapacityError,
+            char_map::{LoadCharMapError, RemapError},
+            dir_check::ScanDirError,
+            dir_fix::{ApplyFixesError, PlanFixesError},
+            load_symbols_from_dir::LoadSymbolsFromDirError,
+            load_tiles_from_dir::LoadTilesFromDirError,
+            manifest::{LoadManifestError, SaveManifestError},
+            patch::{LoadPatchError, SavePatchError},
+            save_symbols_to_dir::SaveSymbolsToDirError,
+            save_tiles_to_dir::SaveTilesToDirError,
+            save_to_bin_file::SaveTilesToBinFileError,
+            ToSymbolsError,
+            symbol::{set::LoadFromDirError as LoadSymbolSetFromDirError, spec::{InvalidSpecsError, LoadSpecsFileError, SaveSpecsFileError}},
+            tile_set::{LoadFromTileGridsError, LoadTileSetTilesFromDirError},
+            uniq_tile_kind::TileKindError,
+        },
+    },
+    prelude::*,
+};
+use lazy_static::lazy_static;
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use strum::Display;
+
+use crate::{batch::BatchError, check::CheckError, compare::CompareError, convert::ConvertError, convert_set::ConvertSetError, hash::VerifyError, overwrite::{BackupOutputError, ExistingOutputsError}, tile_edit::TileEditError};
+
+
+#[derive(Copy, Clone, Debug, Display, PartialEq, Eq, ValueEnum)]
+#[strum(serialize_all = "lowercase")]
+pub enum OutputFormat {
+    Human,
+    Json,
+}
+
+lazy_static! {
+    static ref WARNINGS: Mutex<Vec<String>> = Mutex::new(vec![]);
+}
+
+// environment variable with per module log filters in the env_logger syntax, applied after the log level
+pub const LOG_ENV_VAR: &str = "HD_FPV_FONT_TOOL_LOG";
+
+// forwards the records to env_logger (stderr) and to the log file, keeps the warnings to include them in the JSON
+// report and in the summary printed at the end of the run
+struct WarningRecordingLogger {
+    logger: env_logger::Logger,
+    log_file: Option<Mutex<fs_err::File>>,
+}
+
+impl WarningRecordingLogger {
+
+    fn logs_to_file(&self, metadata: &Metadata) -> bool {
+        self.log_file.is_some() && metadata.level() <= Level::Debug
+    }
+
+}
+
+impl Log for WarningRecordingLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() == Level::Warn || self.logger.enabled(metadata) || self.logs_to_file(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if record.level() == Level::Warn {
+            WARNINGS.lock().unwrap().push(record.args().to_string());
+        }
+        if self.logger.enabled(record.metadata()) {
+            self.logger.log(record);
+        }
+        if let (Some(log_file), true) = (&self.log_file, self.logs_to_file(record.metadata())) {
+            // failing to write the log file must not fail the command
+            let _ = writeln!(log_file.lock().unwrap(), "{:<5} [{}] {}", record.level(), record.target(), record.args());
+        }
+    }
+
+    fn flush(&self) {
+        self.logger.flush();
+        if let Some(log_file) = &self.log_file {
+            let _ = log_file.lock().unwrap().flush();
+        }
+    }
+}
+
+pub fn init_logger(logger: env_logger::Logger, log_file: Option<fs_err::File>) {
+    let file_level = match log_file {
+        Some(_) => LevelFilter::Debug,
+        None => LevelFilter::Off,
+    };
+    let max_level = logger.filter().max(LevelFilter::Warn).max(file_level);
+    let logger = WarningRecordingLogger { logger, log_file: log_file.map(Mutex::new) };
+    log::set_boxed_logger(Box::new(logger)).expect("logger should only be initialized once");
+    log::set_max_level(max_level);
+}
+
+pub fn take_warnings() -> Vec<String> {
+    std::mem::take(&mut WARNINGS.lock().unwrap())
+}
+
+// the warnings are repeated once the command finishes since they are easily missed among the other messages
+pub fn print_warning_summary(warnings: &[String]) {
+    if warnings.is_empty() {
+        return;
+    }
+    eprintln!("{} warning{}:", warnings.len(), if warnings.len() > 1 { "s" } else { "" });
+    for warning in warnings {
+        eprintln!("    {warning}");
+    }
+}
+
+// stable error codes for the JSON report, do not change existing codes
+pub fn error_code(error: &anyhow::Error) -> &'static str {
+    if error.is::<ConvertError>() || error.is::<ConvertSetError>() {
+        "invalid_argument"
+    } else if error.is::<ExistingOutputsError>() {
+        "existing_output"
+    } else if error.is::<BackupOutputError>() {
+        "backup_output"
+    } else if error.is::<CheckError>() {
+        "check_failed"
+    } else if error.is::<VerifyError>() {
+        "verify_failed"
+    } else if error.is::<CompareError>() {
+        "compare_failed"
+    } else if let Some(BatchError::ConversionsFailed { .. }) = error.downcast_ref::<BatchError>() {
+        "batch_failed"
+    } else if error.is::<BatchError>() {
+        "batch_manifest_load"
+    } else if error.is::<InvalidConvertOptionsError>() {
+        "invalid_options"
+    } else if error.is::<TileEditError>() {
+        "tile_edit"
+    } else if error.is::<BinFileLoadError>() || error.is::<bin_file::OpenError>() || error.is::<bin_file::SeekReadError>() {
+        "bin_file_load"
+    } else if error.is::<SaveTilesToBinFileError>() || error.is::<bin_file::WriteTileAtError>() {
+        "bin_file_save"
+    } else if error.is::<avatar_file::LoadError>() {
+        "avatar_load"
+    } else if error.is::<avatar_file::SaveError>() {
+        "avatar_save"
+    } else if error.is::<mcm_file::LoadError>() {
+        "mcm_load"
+    } else if error.is::<mcm_file::SaveError>() {
+        "mcm_save"
+    } else if error.is::<GridLoadError>() || error.is::<LoadFromTileGridsError>() {
+        "grid_load"
+    } else if error.is::<GridSaveImageError>() {
+        "grid_save"
+    } else if error.is::<analog_grid::LoadError>() {
+        "analog_grid_load"
+    } else if error.is::<analog_grid::SaveImageError>() {
+        "analog_grid_save"
+    } else if error.is::<combined_grid::LoadError>() {
+        "combined_grid_load"
+    } else if error.is::<combined_grid::SaveImageError>() {
+        "combined_grid_save"
+    } else if error.is::<hdzero_sheet::LoadError>() {
+        "hdzero_load"
+    } else if error.is::<hdzero_sheet::SaveImageError>() {
+        "hdzero_save"
+    } else if error.is::<font_render::LoadFontError>() || error.is::<font_render::RenderError>() {
+        "font_load"
+    } else if error.is::<font_render::LoadMappingError>() {
+        "character_mapping_load"
+    } else if error.is::<LoadCharMapError>() {
+        "char_map_load"
+    } else if error.is::<RemapError>() {
+        "remap"
+    } else if error.is::<tile::LoadError>() {
+        "tile_load"
+    } else if error.is::<LoadManifestError>() {
+        "manifest_load"
+    } else if error.is::<SaveManifestError>() {
+        "manifest_save"
+    } else if error.is::<LoadPatchError>() {
+        "patch_load"
+    } else if error.is::<SavePatchError>() {
+        "patch_save"
+    } else if error.is::<LoadTilesFromDirError>() || error.is::<LoadTileSetTilesFromDirError>() {
+        "tile_dir_load"
+    } else if error.is::<SaveTilesToDirError>() {
+        "tile_dir_save"
+    } else if error.is::<LoadSymbolsFromDirError>() || error.is::<LoadSymbolSetFromDirError>() {
+        "symbol_dir_load"
+    } else if error.is::<SaveSymbolsToDirError>() {
+        "symbol_dir_save"
+    } else if error.is::<LoadSpecsFileError>() {
+        "symbol_specs_load"
+    } else if error.is::<SaveSpecsFileError>() {
+        "symbol_specs_save"
+    } else if error.is::<InvalidSpecsError>() || matches!(error.downcast_ref::<ToSymbolsError>(), Some(ToSymbolsError::InvalidSpecs(_))) {
+        "invalid_symbol_specs"
+    } else if error.is::<TileKindError>() {
+        "tile_kind"
+    } else if let Some(PlanFixesError::Ambiguous(_)) = error.downcast_ref::<PlanFixesError>() {
+        "ambiguous_fix"
+    } else if error.is::<PlanFixesError>() || error.is::<ScanDirError>() || error.is::<ScanIdentsError>() {
+        "dir_scan"
+    } else if error.is::<ApplyFixesError>() {
+        "fix_apply"
+    } else if error.is::<SaveVisualDiffError>() {
+        "diff_save"
+    } else if error.is::<SaveMontageError>() {
+        "montage_save"
+    } else if error.is::<TransformedDimensionsError>() {
+        "transformed_dimensions"
+    } else if error.is::<CapacityError>() {
+        "capacity"
+    } else {
+        "other"
+    }
+}
@@ -0,0 +1,152 @@
+use std::fmt::{self, Display};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use hd_fpv_osd_font_tool::stdio::is_stdio_path;
+use lazy_static::lazy_static;
+use thiserror::Error;
+
+// Guard against replacing existing outputs e.g. the only copy of a font when the source and destination arguments were
+// swapped. The command line refuses to write over existing files and non empty directories unless --force is given or
+// --backup which renames them aside first, the commands called from the tests replace them as the library does.
+
+// number of existing files listed in the error, the others are counted
+const LISTED_FILE_COUNT: usize = 20;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ExistingOutputs {
+    Refuse,
+    Replace,
+    // renamed to <name>.bak, <name>.bak.1, ...
+    Backup,
+}
+
+impl ExistingOutputs {
+
+    pub fn from_flags(force: bool, backup: bool) -> Self {
+        match (force, backup) {
+            (_, true) => Self::Backup,
+            (true, false) => Self::Replace,
+            (false, false) => Self::Refuse,
+        }
+    }
+
+}
+
+lazy_static! {
+    static ref EXISTING_OUTPUTS: Mutex<ExistingOutputs> = Mutex::new(ExistingOutputs::Replace);
+}
+
+#[derive(Debug, Error)]
+pub struct ExistingOutputsError(Vec<PathBuf>);
+
+impl Display for ExistingOutputsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} existing files would be replaced, pass --force to replace them or --backup to rename them aside first:", self.0.len())?;
+        for path in self.0.iter().take(LISTED_FILE_COUNT) {
+            writeln!(f, "    {}", path.to_string_lossy())?;
+        }
+        if self.0.len() > LISTED_FILE_COUNT {
+            writeln!(f, "    and {} more", self.0.len() - LISTED_FILE_COUNT)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("failed to rename {} aside to {}: {error}", .path.to_string_lossy(), .backup_path.to_string_lossy())]
+pub struct BackupOutputError {
+    path: PathBuf,
+    backup_path: PathBuf,
+    error: std::io::Error,
+}
+
+pub fn set_existing_outputs(existing_outputs: ExistingOutputs) {
+    *EXISTING_OUTPUTS.lock().unwrap() = existing_outputs;
+}
+
+// the files under the directory in path order
+fn dir_files(dir: &Path) -> Vec<PathBuf> {
+    let mut entries: Vec<PathBuf> = match std::fs::read_dir(dir) {
+        Ok(entries) => entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()).collect(),
+        Err(_) => return vec![],
+    };
+    entries.sort();
+    entries.into_iter().flat_map(|path| match path.is_dir() {
+        true => dir_files(&path),
+        false => vec![path],
+    }).collect()
+}
+
+// the existing file or the files of the existing directory
+fn existing_files(path: &Path) -> Vec<PathBuf> {
+    match path.is_dir() {
+        true => dir_files(path),
+        false if path.exists() => vec![path.to_path_buf()],
+        false => vec![],
+    }
+}
+
+fn backup_path(path: &Path) -> PathBuf {
+    let file_name = path.file_name().map(|file_name| file_name.to_string_lossy().to_string()).unwrap_or_default();
+    std::iter::once(format!("{file_name}.bak"))
+        .chain((1..).map(|number| format!("{file_name}.bak.{number}")))
+        .map(|backup_file_name| path.with_file_name(backup_file_name))
+        .find(|backup_path| !backup_path.exists())
+        .unwrap()
+}
+
+fn back_up(path: &Path) -> Result<(), BackupOutputError> {
+    let backup_path = backup_path(path);
+    std::fs::rename(path, &backup_path).map_err(|error| BackupOutputError { path: path.to_path_buf(), backup_path: backup_path.clone(), error })?;
+    log::info!("renamed {} -> {}", path.to_string_lossy(), backup_path.to_string_lossy());
+    Ok(())
+}
+
+// to be called before writing anything, stdout is never checked
+pub fn check_outputs<P: AsRef<Path>>(paths: &[P]) -> anyhow::Result<()> {
+    let existing_outputs = *EXISTING_OUTPUTS.lock().unwrap();
+    if existing_outputs == ExistingOutputs::Replace {
+        return Ok(());
+    }
+    let paths: Vec<&Path> = paths.iter().map(AsRef::as_ref)
+        .filter(|path| !is_stdio_path(path) && !existing_files(path).is_empty())
+        .collect();
+    match existing_outputs {
+        ExistingOutputs::Refuse if !paths.is_empty() =>
+            Err(ExistingOutputsError(paths.into_iter().flat_map(existing_files).collect()).into()),
+        ExistingOutputs::Backup => {
+            for path in paths {
+                back_up(path)?;
+            }
+            Ok(())
+        },
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use temp_dir::TempDir;
+
+    use super::{backup_path, existing_files};
+
+    #[test]
+    fn existing_outputs() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.child("tiles");
+        assert!(existing_files(&dir).is_empty());
+        std::fs::create_dir(&dir).unwrap();
+        // an empty directory has nothing to lose
+        assert!(existing_files(&dir).is_empty());
+        std::fs::create_dir(dir.join("HD")).unwrap();
+        std::fs::write(dir.join("HD").join("000.png"), b"").unwrap();
+        std::fs::write(dir.join("000.png"), b"").unwrap();
+        assert_eq!(existing_files(&dir), vec![dir.join("000.png"), dir.join("HD").join("000.png")]);
+
+        assert_eq!(backup_path(&dir), temp_dir.child("tiles.bak"));
+        std::fs::write(temp_dir.child("tiles.bak"), b"").unwrap();
+        assert_eq!(backup_path(&dir), temp_dir.child("tiles.bak.1"));
+    }
+
+}
@@ -0,0 +1,38 @@
+use std::path::Path;
+
+use hd_fpv_osd_font_tool::{
+    prelude::*,
+    report::{CollectionReport, CommandReport},
+};
+
+use crate::convert_set::{load_tile_set_options, save_tile_set};
+use crate::overwrite::check_outputs;
+
+
+// the tiles at the given indexes when indexes are given, otherwise the tiles which differ from the base
+pub fn make_patch_command(from: &str, patch_path: &Path, indexes: Option<&IndexList>, base: Option<&str>, options: ConvertOptions, report: &mut CommandReport) -> anyhow::Result<()> {
+    let tile_set = load_tile_set_options(from, &options, report)?;
+    let patch = match (indexes, base) {
+        (Some(indexes), _) => Patch::from_tile_set_indexes(&tile_set, indexes),
+        (None, Some(base)) => Patch::from_tile_set_changes(&load_tile_set_options(base, &options, report)?, &tile_set),
+        (None, None) => unreachable!("the indexes or the base should be given"),
+    };
+    if patch.is_empty() {
+        log::warn!("the patch holds no tile");
+    }
+    check_outputs(&[patch_path])?;
+    patch.save(patch_path, options.image_format)?;
+    log::info!("{} SD and {} HD tiles written to {}", patch.indexes(tile::Kind::SD).len(), patch.indexes(tile::Kind::HD).len(), patch_path.to_string_lossy());
+    report.outputs.push(CollectionReport::new("patch", &[patch_path]));
+    Ok(())
+}
+
+pub fn apply_patch_command(base: &str, patch_path: &Path, to: &str, options: ConvertOptions, report: &mut CommandReport) -> anyhow::Result<()> {
+    let mut tile_set = load_tile_set_options(base, &options, report)?;
+    report.inputs.push(CollectionReport::new("patch", &[patch_path]));
+    let patch = Patch::load(patch_path, options.max_tile_count, options.fit, options.decoding())?;
+    let patched_count = patch.apply(&mut tile_set);
+    log::info!("{}: {patched_count} tiles patched", patch_path.to_string_lossy());
+    tile_set.apply_transforms(&options.transforms)?;
+    save_tile_set(tile_set, to, &options, report)
+}
@@ -0,0 +1,30 @@
+use std::path::Path;
+
+use hd_fpv_osd_font_tool::{
+    osd::tile::{osd_mock::save_osd_mocks, preview::save_contact_sheet},
+    report::{CollectionReport, CommandReport},
+};
+use image::Rgba;
+
+use crate::convert::load_tiles;
+use crate::convert_set::load_tile_set;
+use crate::overwrite::check_outputs;
+
+
+pub fn preview_command(from: &str, to: &Path, scale: u32, report: &mut CommandReport) -> anyhow::Result<()> {
+    let tiles = load_tiles(from, report)?;
+    log::info!("writing contact sheet of {from} -> {}", to.to_string_lossy());
+    report.outputs.push(CollectionReport::new("preview", &[to]));
+    check_outputs(&[to])?;
+    save_contact_sheet(&tiles, to, scale)?;
+    Ok(())
+}
+
+pub fn preview_osd_command(from: &str, dir: &Path, background: Option<Rgba<u8>>, report: &mut CommandReport) -> anyhow::Result<()> {
+    let tile_set = load_tile_set(from, report)?;
+    log::info!("rendering OSD mocks with {from} -> {}", dir.to_string_lossy());
+    report.outputs.push(CollectionReport::new("osdmock", &[dir]));
+    check_outputs(&[dir])?;
+    save_osd_mocks(&tile_set, dir, background)?;
+    Ok(())
+}
@@ -0,0 +1,19 @@
+use std::path::Path;
+
+use hd_fpv_osd_font_tool::{
+    prelude::*,
+    report::CommandReport,
+};
+
+use crate::convert_set::{load_tile_set_options, save_tile_set};
+
+
+pub fn remap_command(char_map_path: &Path, from: &str, to: &str, options: ConvertOptions, report: &mut CommandReport) -> anyhow::Result<()> {
+    log::info!("remapping {from} -> {to} with {}", char_map_path.to_string_lossy());
+    let char_map = CharMap::load_file(char_map_path)?;
+    let tile_set = load_tile_set_options(from, &options, report)?;
+    let mut tile_set = tile_set.remap(&char_map)?;
+    log::info!("{} tiles moved", char_map.destinations().len());
+    tile_set.apply_transforms(&options.transforms)?;
+    save_tile_set(tile_set, to, &options, report)
+}
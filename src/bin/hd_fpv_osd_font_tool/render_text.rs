@@ -0,0 +1,25 @@
+use std::path::Path;
+
+use hd_fpv_osd_font_tool::{
+    osd::tile::{font_render::{ascii_mapping, load_mapping_file}, text_render::save_text_image},
+    report::{CollectionReport, CommandReport},
+};
+use image::Rgba;
+
+use crate::convert::load_tiles;
+use crate::overwrite::check_outputs;
+
+
+// without a mapping file the printable ASCII characters are looked up at the index of their code
+pub fn render_text_command(from: &str, text: &str, mapping: Option<&Path>, background: Rgba<u8>, scale: u32, to: &Path, report: &mut CommandReport) -> anyhow::Result<()> {
+    let mapping = match mapping {
+        Some(mapping) => load_mapping_file(mapping)?,
+        None => ascii_mapping(),
+    };
+    let tiles = load_tiles(from, report)?;
+    log::info!("rendering {text:?} with {from} -> {}", to.to_string_lossy());
+    report.outputs.push(CollectionReport::new("image", &[to]));
+    check_outputs(&[to])?;
+    save_text_image(&tiles, &mapping, text, background, scale, to)?;
+    Ok(())
+}
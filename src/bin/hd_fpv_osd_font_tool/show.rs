@@ -0,0 +1,31 @@
+use std::io::Write;
+
+use hd_fpv_osd_font_tool::{
+    prelude::*,
+    report::CommandReport,
+    terminal_graphics::{encode, Protocol},
+};
+use image::imageops::{self, FilterType};
+
+use crate::convert::load_tiles;
+use crate::tile_edit::TileEditError;
+
+
+// the whole collection is shown as a grid when no tile index is given
+pub fn show_command(from: &str, index: Option<usize>, protocol: Option<Protocol>, scale: u32, report: &mut CommandReport) -> anyhow::Result<()> {
+    let tiles = load_tiles(from, report)?;
+    let image = match index {
+        Some(index) => tiles.get(index).ok_or(TileEditError::IndexOutOfRange { index, tile_count: tiles.len() })?.image().clone(),
+        None => TileGrid::from(tiles).generate_image()?,
+    };
+    let image = match scale {
+        1 => image,
+        _ => imageops::resize(&image, image.width() * scale, image.height() * scale, FilterType::Nearest),
+    };
+    let protocol = protocol.unwrap_or_else(Protocol::detect);
+    log::debug!("showing {from} with the {protocol} protocol");
+    let mut stdout = std::io::stdout().lock();
+    stdout.write_all(encode(&image, protocol).as_bytes())?;
+    stdout.flush()?;
+    Ok(())
+}
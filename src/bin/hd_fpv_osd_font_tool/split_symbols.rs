@@ -0,0 +1,33 @@
+
+use std::path::{Path, PathBuf};
+
+use anyhow::anyhow;
+use hd_fpv_osd_font_tool::{
+    prelude::*,
+    report::{CollectionReport, CommandReport},
+};
+
+use crate::overwrite::check_outputs;
+
+
+// the names of the symbols come from the specs files which exist, e.g. the default specs file is not required
+pub fn split_symbols_command(from: &str, to: &Path, options: ConvertOptions, report: &mut CommandReport) -> anyhow::Result<()> {
+    let specs_files: Vec<&PathBuf> = options.symbol_specs_files.iter().filter(|path| path.exists()).collect();
+    let specs = SymbolSpecs::load_files(&specs_files)?;
+    check_outputs(&[to])?;
+    if let Some(dir) = from.strip_prefix("symsetdir:") {
+        report.inputs.push(CollectionReport::new("symsetdir", &[dir]));
+        let symbol_set = SymbolSet::load_from_dir(dir, options.max_tile_count, options.fit, options.decoding())?;
+        symbol_set.split_to_dir(to, options.image_format, Some(&specs), options.symbol_names)?;
+        report.outputs.push(CollectionReport::new("tilesetdir", &[to]));
+    } else if let Some(dir) = from.strip_prefix("symdir:") {
+        report.inputs.push(CollectionReport::new("symdir", &[dir]));
+        let symbols = load_symbols_from_dir(dir, options.max_tile_count, options.fit, options.decoding())?;
+        symbols.split_to_dir(to, options.image_format, Some(&specs), options.symbol_names)?;
+        report.outputs.push(CollectionReport::new("tiledir", &[to]));
+    } else {
+        return Err(anyhow!("invalid split-symbols argument `{from}`: expected symsetdir:path or symdir:path"));
+    }
+    log::info!("symbols of {from} split into the tiles of {}", to.to_string_lossy());
+    Ok(())
+}
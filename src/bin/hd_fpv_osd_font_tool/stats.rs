@@ -0,0 +1,58 @@
+
+use hd_fpv_osd_font_tool::{
+    osd::tile::container::stats::{MarginReport, DEFAULT_SAFE_MARGIN},
+    prelude::*,
+    report::CommandReport,
+};
+use itertools::Itertools;
+
+use crate::{convert::load_tiles, output::OutputFormat};
+
+
+fn print_index_list(indices: &[usize]) {
+    if !indices.is_empty() {
+        println!("    {}", indices.iter().join(", "));
+    }
+}
+
+fn print_margin_report(report: &MarginReport) {
+    for (edges, intrusions) in [("left/right", report.horizontal()), ("top/bottom", report.vertical())] {
+        println!("tiles with visible pixels in the {}px safe margin along the {edges} edges: {}", report.margin(), intrusions.values().map(Vec::len).sum::<usize>());
+        // deepest intrusions first, they are the most likely to collide with the neighbor tiles
+        for (depth, indices) in intrusions.iter().rev() {
+            println!("  {depth}px deep:");
+            print_index_list(indices);
+        }
+    }
+}
+
+fn print_stats(stats: &FontStats) {
+    println!("total tiles: {}", stats.tile_count());
+    for page in stats.pages() {
+        println!("page {}: {} tiles, {} non-blank", page.index(), page.tile_count(), page.non_blank_tile_count());
+    }
+    println!("duplicate tile groups: {}", stats.duplicate_groups().len());
+    for group in stats.duplicate_groups() {
+        print_index_list(group);
+    }
+    println!("tiles touching the tile edge: {}", stats.edge_touching_tiles().len());
+    print_index_list(stats.edge_touching_tiles());
+    print_margin_report(stats.margin_intrusions());
+    println!("palette violations: {} pixels in {} tiles", stats.palette_violation_pixel_count(), stats.palette_violation_tiles().len());
+    print_index_list(stats.palette_violation_tiles());
+}
+
+// with `check_margins` only the safe margin report is printed
+pub fn stats_command(from: &str, json: bool, check_margins: Option<u32>, output_format: OutputFormat, report: &mut CommandReport) -> anyhow::Result<()> {
+    let tiles = load_tiles(from, report)?;
+    let stats = FontStats::compute_with_margin(&tiles, check_margins.unwrap_or(DEFAULT_SAFE_MARGIN));
+    match (output_format, check_margins) {
+        // the stats are part of the command report
+        (OutputFormat::Json, _) => report.stats = Some(stats),
+        (OutputFormat::Human, Some(_)) if json => println!("{}", serde_json::to_string_pretty(stats.margin_intrusions())?),
+        (OutputFormat::Human, Some(_)) => print_margin_report(stats.margin_intrusions()),
+        (OutputFormat::Human, None) if json => println!("{}", serde_json::to_string_pretty(&stats)?),
+        (OutputFormat::Human, None) => print_stats(&stats),
+    }
+    Ok(())
+}
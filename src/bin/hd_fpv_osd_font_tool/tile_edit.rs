@@ -0,0 +1,175 @@
+
+use std::path::Path;
+
+use hd_fpv_osd_font_tool::{
+    image_format::ImageDecoding,
+    osd::bin_file::write_tile_at,
+    prelude::*,
+    report::{CollectionReport, CommandReport},
+};
+use thiserror::Error;
+
+use crate::overwrite::check_outputs;
+use crate::convert::{check_arg_image_file_extension, identify_convert_arg, load_tiles_options, open_lazy_bin_file, ConvertArg, ConvertError};
+
+// Reads or replaces a single tile of a collection. Bin files are read and written in place, tile grids are loaded
+// and saved back whole since the image has to be re-encoded anyway.
+
+#[derive(Debug, Error)]
+pub enum TileEditError {
+    #[error("tile index {index} out of range: the collection contains {tile_count} tiles")]
+    IndexOutOfRange { index: usize, tile_count: usize },
+    #[error("cannot replace a tile in `{0}`: only bin files (bin:, djibin:) and tile grids (tilegrid:) are supported")]
+    UnsupportedTarget(String),
+    #[error("cannot put a {tile_kind} tile in a collection of {collection_tile_kind} tiles")]
+    TileKindMismatch { tile_kind: tile::Kind, collection_tile_kind: tile::Kind },
+}
+
+fn check_index(index: usize, tile_count: usize) -> Result<(), TileEditError> {
+    match index < tile_count {
+        true => Ok(()),
+        false => Err(TileEditError::IndexOutOfRange { index, tile_count }),
+    }
+}
+
+pub fn extract_tile_command(from: &str, index: usize, to: &Path, scale: u32, grid_layout: TileGridLayout, report: &mut CommandReport) -> anyhow::Result<()> {
+    log::info!("extracting tile {index} from {from} -> {}", to.to_string_lossy());
+    let tile = match open_lazy_bin_file(from, report)? {
+        // only the requested tile is decoded
+        Some(reader) => {
+            check_index(index, reader.tile_count())?;
+            reader.tile(index).unwrap()
+        },
+        None => {
+            let tiles = load_tiles_options(from, &ConvertOptions::default().grid_layout(grid_layout), report)?;
+            check_index(index, tiles.len())?;
+            tiles[index].clone()
+        },
+    };
+    report.outputs.push(CollectionReport::new("tile", &[to]));
+    check_outputs(&[to])?;
+    tile.save_image_file_scaled(to, scale)?;
+    Ok(())
+}
+
+pub fn set_tile_command(target: &str, index: usize, tile_path: &Path, fit: Option<TileFit>, grid_layout: TileGridLayout, report: &mut CommandReport) -> anyhow::Result<()> {
+    let target_arg = identify_convert_arg(target).map_err(ConvertError::ToArg)?;
+    report.inputs.push(CollectionReport::new("tile", &[tile_path]));
+    report.outputs.push(target_arg.report());
+    log::info!("replacing tile {index} of {target} with {}", tile_path.to_string_lossy());
+    let tile = Tile::load_image_file_fit(tile_path, fit)?;
+    report.add_tile_count(tile.kind(), 1);
+    match target_arg {
+        ConvertArg::BinFile { path, tile_kind } => {
+            if let Some(collection_tile_kind) = tile_kind.filter(|tile_kind| *tile_kind != tile.kind()) {
+                return Err(TileEditError::TileKindMismatch { tile_kind: tile.kind(), collection_tile_kind }.into());
+            }
+            write_tile_at(path, index, &tile)?
+        },
+        ConvertArg::TileGrid(path) => {
+            check_arg_image_file_extension(path).map_err(ConvertError::ToArg)?;
+            let mut tiles = TileGrid::load_from_image_decoded(path, None, ImageDecoding::default(), TileGridValidation::Strict, grid_layout)?.to_vec();
+            check_index(index, tiles.len())?;
+            if tiles[index].kind() != tile.kind() {
+                return Err(TileEditError::TileKindMismatch { tile_kind: tile.kind(), collection_tile_kind: tiles[index].kind() }.into());
+            }
+            tiles[index] = tile;
+            TileGrid::from(tiles).save_image_annotated(path, grid_layout, None)?
+        },
+        _ => return Err(TileEditError::UnsupportedTarget(target.to_owned()).into()),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use hd_fpv_osd_font_tool::{image_format::ImageDecoding, osd::bin_file::{self, FontPart}, prelude::*, report::CommandReport};
+    use image::Rgba;
+    use temp_dir::TempDir;
+
+    use super::{extract_tile_command, set_tile_command, TileEditError};
+
+    fn marked_tile(tile_kind: tile::Kind) -> Tile {
+        let mut tile = Tile::new(tile_kind);
+        tile.put_pixel(3, 4, Rgba([255, 255, 255, 255]));
+        tile.put_pixel(5, 6, Rgba([0, 0, 0, 255]));
+        tile
+    }
+
+    // sets a tile of the collection then extracts it back, the other tiles are left untouched
+    fn check_round_trip(collection_arg: &str, grid_layout: TileGridLayout, load: impl Fn() -> Vec<Tile>, temp_dir: &TempDir) {
+        let original_tiles = load();
+        let tile = marked_tile(original_tiles[0].kind());
+        let tile_path = temp_dir.child("tile.png");
+        tile.save_image_file(&tile_path).unwrap();
+
+        set_tile_command(collection_arg, 42, &tile_path, None, grid_layout, &mut CommandReport::new("set-tile")).unwrap();
+        let tiles = load();
+        assert_eq!(tiles.len(), original_tiles.len());
+        for (index, (loaded, original)) in tiles.iter().zip(&original_tiles).enumerate() {
+            let expected = if index == 42 { &tile } else { original };
+            assert!(**loaded == **expected, "tile {index}");
+        }
+
+        let extracted_path = temp_dir.child("extracted.png");
+        extract_tile_command(collection_arg, 42, &extracted_path, 1, grid_layout, &mut CommandReport::new("extract-tile")).unwrap();
+        assert!(*Tile::load_image_file(&extracted_path).unwrap() == *tile);
+        fs::remove_file(&extracted_path).unwrap();
+    }
+
+    #[test]
+    fn bin_file_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let bin_path = temp_dir.child("font_hd.bin");
+        fs::copy(bin_file::normalized_file_path("test_files/djibinsetnorm", tile::Kind::HD, &None, FontPart::Base), &bin_path).unwrap();
+        let collection_arg = format!("bin:{}", bin_path.to_str().unwrap());
+        check_round_trip(&collection_arg, TileGridLayout::default(), || bin_file::load(&bin_path, None).unwrap(), &temp_dir);
+    }
+
+    #[test]
+    fn tile_grid_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let grid_path = temp_dir.child("font.png");
+        let tiles = bin_file::load(bin_file::normalized_file_path("test_files/djibinsetnorm", tile::Kind::SD, &None, FontPart::Base), None).unwrap();
+        TileGrid::from(tiles).save_image(&grid_path).unwrap();
+        let collection_arg = format!("tilegrid:{}", grid_path.to_str().unwrap());
+        check_round_trip(&collection_arg, TileGridLayout::default(), || TileGrid::load_from_image(&grid_path).unwrap().to_vec(), &temp_dir);
+    }
+
+    #[test]
+    fn tile_grid_layout_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let grid_path = temp_dir.child("font.png");
+        let layout = TileGridLayout::new(32, None);
+        let tiles = bin_file::load(bin_file::normalized_file_path("test_files/djibinsetnorm", tile::Kind::SD, &None, FontPart::Base), None).unwrap();
+        TileGrid::from(tiles).save_image_annotated(&grid_path, layout, None).unwrap();
+        let collection_arg = format!("tilegrid:{}", grid_path.to_str().unwrap());
+        let load = || TileGrid::load_from_image_decoded(&grid_path, None, ImageDecoding::default(), TileGridValidation::Strict, layout).unwrap().to_vec();
+        check_round_trip(&collection_arg, layout, load, &temp_dir);
+        // written back with the same layout: 32 columns and 8 rows of tiles separated by 2 pixels
+        let tile_dimensions = tile::Kind::SD.dimensions();
+        let expected_dimensions = (32 * tile_dimensions.width() + 31 * 2, 8 * tile_dimensions.height() + 7 * 2);
+        assert_eq!(image::image_dimensions(&grid_path).unwrap(), expected_dimensions);
+    }
+
+    #[test]
+    fn invalid_edits() {
+        let temp_dir = TempDir::new().unwrap();
+        let bin_path = temp_dir.child("font.bin");
+        fs::copy(bin_file::normalized_file_path("test_files/djibinsetnorm", tile::Kind::SD, &None, FontPart::Base), &bin_path).unwrap();
+        let collection_arg = format!("bin:sd:{}", bin_path.to_str().unwrap());
+
+        let error = extract_tile_command(&collection_arg, 256, &temp_dir.child("tile.png"), 1, TileGridLayout::default(), &mut CommandReport::new("extract-tile")).unwrap_err();
+        assert!(matches!(error.downcast_ref(), Some(TileEditError::IndexOutOfRange { index: 256, tile_count: 256 })));
+
+        let hd_tile_path = temp_dir.child("hd_tile.png");
+        marked_tile(tile::Kind::HD).save_image_file(&hd_tile_path).unwrap();
+        let error = set_tile_command(&collection_arg, 0, &hd_tile_path, None, TileGridLayout::default(), &mut CommandReport::new("set-tile")).unwrap_err();
+        assert!(matches!(error.downcast_ref(), Some(TileEditError::TileKindMismatch { .. })));
+
+        let error = set_tile_command(&format!("tiledir:{}", temp_dir.path().to_str().unwrap()), 0, &hd_tile_path, None, TileGridLayout::default(), &mut CommandReport::new("set-tile")).unwrap_err();
+        assert!(matches!(error.downcast_ref(), Some(TileEditError::UnsupportedTarget(_))));
+    }
+}
@@ -0,0 +1,197 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+};
+
+use hd_fpv_osd_font_tool::{
+    osd::tile::diff::{diff_tiles, ChangeKind},
+    prelude::*,
+    report::{CollectionReport, CommandReport},
+};
+use image::Rgba;
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, List, ListItem, ListState, Paragraph},
+    DefaultTerminal, Frame,
+};
+
+use crate::convert::load_tiles;
+
+// Interactive browser of the tiles of a collection, optionally compared with a second one: the tiles are listed with
+// their state on the left and previewed with half block characters on the right. Tiles can be marked and the marked
+// tiles extracted as images, one file per tile named from its index.
+
+const HELP: &str = "↑/↓ j/k move  PgUp/PgDn page  space mark  n next difference  e extract marked  q quit";
+const PAGE_LENGTH: usize = 16;
+
+struct Browser {
+    from: String,
+    tiles: Vec<Tile>,
+    compared: Option<(String, Vec<Tile>)>,
+    changes: BTreeMap<usize, ChangeKind>,
+    marked: BTreeSet<usize>,
+    list_state: ListState,
+    extract_dir: PathBuf,
+    extracted: bool,
+    status: String,
+}
+
+fn color(pixel: &Rgba<u8>) -> Option<Color> {
+    (pixel[3] >= 128).then(|| Color::Rgb(pixel[0], pixel[1], pixel[2]))
+}
+
+// two pixels per character cell, the transparent pixels show the terminal background
+fn tile_lines(tile: &Tile) -> Vec<Line<'static>> {
+    (0..tile.height()).step_by(2).map(|y| {
+        Line::from((0..tile.width()).map(|x| {
+            let top = color(tile.get_pixel(x, y));
+            let bottom = tile.get_pixel_checked(x, y + 1).and_then(color);
+            match (top, bottom) {
+                (None, None) => Span::raw(" "),
+                (Some(top), None) => Span::styled("\u{2580}", Style::default().fg(top)),
+                (None, Some(bottom)) => Span::styled("\u{2584}", Style::default().fg(bottom)),
+                (Some(top), Some(bottom)) => Span::styled("\u{2580}", Style::default().fg(top).bg(bottom)),
+            }
+        }).collect::<Vec<_>>())
+    }).collect()
+}
+
+impl Browser {
+
+    fn tile_count(&self) -> usize {
+        self.compared.as_ref().map_or(0, |(_, tiles)| tiles.len()).max(self.tiles.len())
+    }
+
+    fn selected(&self) -> usize {
+        self.list_state.selected().unwrap_or(0)
+    }
+
+    fn select(&mut self, index: usize) {
+        self.list_state.select(Some(index.min(self.tile_count().saturating_sub(1))));
+    }
+
+    fn list_item(&self, index: usize) -> ListItem<'static> {
+        let mark = if self.marked.contains(&index) { '*' } else { ' ' };
+        let state = match (self.changes.get(&index), self.tiles.get(index)) {
+            (Some(change), _) => change.to_string(),
+            (None, Some(tile)) if tile.pixels().all(|pixel| pixel[3] == 0) => "empty".to_owned(),
+            _ => String::new(),
+        };
+        let style = match self.changes.contains_key(&index) {
+            true => Style::default().fg(Color::Yellow),
+            false => Style::default(),
+        };
+        ListItem::new(format!("{mark} {index:03} {state}")).style(style)
+    }
+
+    fn next_difference(&mut self) {
+        let selected = self.selected();
+        match self.changes.range(selected + 1..).next().or_else(|| self.changes.iter().next()) {
+            Some((index, _)) => {
+                let index = *index;
+                self.select(index);
+            },
+            None => self.status = "no differences".to_owned(),
+        }
+    }
+
+    fn extract_marked(&mut self) -> anyhow::Result<()> {
+        if self.marked.is_empty() {
+            self.status = "no marked tiles".to_owned();
+            return Ok(())
+        }
+        std::fs::create_dir_all(&self.extract_dir)?;
+        for index in &self.marked {
+            if let Some(tile) = self.tiles.get(*index) {
+                tile.save_image_file(self.extract_dir.join(format!("{index:03}.png")))?;
+            }
+        }
+        self.extracted = true;
+        self.status = format!("extracted {} tile(s) to {}", self.marked.len(), self.extract_dir.to_string_lossy());
+        Ok(())
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main_area, status_area] = Layout::vertical([Constraint::Min(0), Constraint::Length(2)]).areas(frame.area());
+        let [list_area, preview_area] = Layout::horizontal([Constraint::Length(20), Constraint::Min(0)]).areas(main_area);
+
+        let items: Vec<ListItem> = (0..self.tile_count()).map(|index| self.list_item(index)).collect();
+        let list = List::new(items)
+            .block(Block::bordered().title(format!("Tiles ({})", self.marked.len())))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, list_area, &mut self.list_state);
+
+        let index = self.selected();
+        let panels: Vec<(&String, Option<&Tile>)> = [(&self.from, self.tiles.get(index))].into_iter()
+            .chain(self.compared.iter().map(|(name, tiles)| (name, tiles.get(index))))
+            .collect();
+        let panel_areas = Layout::horizontal(vec![Constraint::Ratio(1, panels.len() as u32); panels.len()]).split(preview_area);
+        for ((name, tile), area) in panels.into_iter().zip(panel_areas.iter()) {
+            let lines = tile.map_or_else(|| vec![Line::from("missing")], tile_lines);
+            frame.render_widget(Paragraph::new(lines).block(Block::bordered().title(format!("{name} #{index}"))), *area);
+        }
+
+        frame.render_widget(Paragraph::new(vec![Line::from(self.status.clone()), Line::from(HELP)]), status_area);
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> anyhow::Result<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            let key = match event::read()? {
+                Event::Key(key) if key.kind == KeyEventKind::Press => key,
+                _ => continue,
+            };
+            self.status.clear();
+            let selected = self.selected();
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Down | KeyCode::Char('j') => self.select(selected + 1),
+                KeyCode::Up | KeyCode::Char('k') => self.select(selected.saturating_sub(1)),
+                KeyCode::PageDown => self.select(selected + PAGE_LENGTH),
+                KeyCode::PageUp => self.select(selected.saturating_sub(PAGE_LENGTH)),
+                KeyCode::Home => self.select(0),
+                KeyCode::End => self.select(usize::MAX),
+                KeyCode::Char(' ') => {
+                    if !self.marked.remove(&selected) {
+                        self.marked.insert(selected);
+                    }
+                    self.select(selected + 1);
+                },
+                KeyCode::Char('n') => self.next_difference(),
+                KeyCode::Char('e') => self.extract_marked()?,
+                _ => {},
+            }
+        }
+    }
+
+}
+
+pub fn tui_command(from: &str, compare: Option<&str>, extract_dir: &Path, report: &mut CommandReport) -> anyhow::Result<()> {
+    let tiles = load_tiles(from, report)?;
+    let compared = compare.map(|other| load_tiles(other, report).map(|tiles| (other.to_owned(), tiles))).transpose()?;
+    let changes = compared.as_ref()
+        .map(|(_, compared_tiles)| diff_tiles(&tiles, compared_tiles).into_iter().map(|change| (change.index(), change.kind())).collect())
+        .unwrap_or_default();
+    let mut browser = Browser {
+        from: from.to_owned(),
+        tiles,
+        compared,
+        changes,
+        marked: BTreeSet::new(),
+        list_state: ListState::default().with_selected(Some(0)),
+        extract_dir: extract_dir.to_path_buf(),
+        extracted: false,
+        status: String::new(),
+    };
+
+    let mut terminal = ratatui::init();
+    let result = browser.run(&mut terminal);
+    ratatui::restore();
+    if browser.extracted {
+        report.outputs.push(CollectionReport::new("tiledir", &[extract_dir]));
+    }
+    result
+}
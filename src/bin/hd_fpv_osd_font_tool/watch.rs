@@ -0,0 +1,142 @@
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError};
+use std::time::Duration;
+
+use hd_fpv_osd_font_tool::{report::CommandReport, stdio::is_stdio_path};
+use notify::{Event, RecursiveMode, Watcher};
+use thiserror::Error;
+
+use crate::overwrite::{set_existing_outputs, ExistingOutputs};
+
+// Runs a conversion again each time one of its sources changes so that the bin files are regenerated while editing the
+// tiles in an image editor. The parent directories of the source files are watched rather than the files themselves
+// since the editors usually save by writing a new file renamed over the old one which ends the watch of a file.
+
+// editors write a file in several steps, the conversion runs once the events stop for this long
+const SETTLE_DELAY: Duration = Duration::from_millis(300);
+
+#[derive(Debug, Error)]
+pub enum WatchError {
+    #[error("nothing to watch, the sources are read from stdin")]
+    NoSource,
+    #[error("failed to watch {}: {error}", .path.to_string_lossy())]
+    Watch { path: PathBuf, error: notify::Error },
+    #[error("file system watcher error: {0}")]
+    Watcher(#[from] notify::Error),
+    #[error("file system watcher stopped")]
+    Stopped,
+}
+
+// the source paths of the conversion and the symbol specs files it read
+fn watched_paths(report: &CommandReport, extra_paths: &[PathBuf]) -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = report.inputs.iter()
+        .flat_map(|input| input.paths.iter().cloned())
+        .chain(extra_paths.iter().cloned())
+        .filter(|path| !is_stdio_path(path))
+        .collect();
+    paths.sort();
+    paths.dedup();
+    paths
+}
+
+// the outputs are left out in case they are written next to the sources
+fn is_source_change(event: &Event, sources: &[PathBuf], outputs: &[PathBuf]) -> bool {
+    if event.kind.is_access() {
+        return false;
+    }
+    event.paths.iter().any(|path|
+        sources.iter().any(|source| path.starts_with(source))
+            && !outputs.iter().any(|output| path.starts_with(output))
+            && !is_temp_file(path)
+    )
+}
+
+// .<name>.<pid>.tmp files written by the conversion and the hidden files of the editors
+fn is_temp_file(path: &Path) -> bool {
+    path.file_name().and_then(|file_name| file_name.to_str()).is_some_and(|file_name| file_name.starts_with('.'))
+}
+
+// blocks until a source changed and the events settled
+fn wait_for_change(events: &Receiver<notify::Result<Event>>, sources: &[PathBuf], outputs: &[PathBuf]) -> Result<(), WatchError> {
+    loop {
+        if is_source_change(&events.recv().map_err(|_| WatchError::Stopped)??, sources, outputs) {
+            break;
+        }
+    }
+    loop {
+        match events.recv_timeout(SETTLE_DELAY) {
+            Ok(event) => { event?; },
+            Err(RecvTimeoutError::Timeout) => return Ok(()),
+            Err(RecvTimeoutError::Disconnected) => return Err(WatchError::Stopped),
+        }
+    }
+}
+
+fn log_result(result: &anyhow::Result<()>) {
+    match result {
+        Ok(()) => log::info!("conversion done, waiting for changes"),
+        Err(error) => log::error!("{error:#}, waiting for changes"),
+    }
+}
+
+// runs `convert` then again on each change of its sources until interrupted, the failed conversions are logged and
+// the next change retried. The outputs written by the first run are then replaced whatever --force and --backup.
+pub fn watch_command<F>(extra_paths: &[PathBuf], report: &mut CommandReport, mut convert: F) -> anyhow::Result<()>
+where
+    F: FnMut(&mut CommandReport) -> anyhow::Result<()>,
+{
+    let result = convert(report);
+    log_result(&result);
+    let sources = watched_paths(report, extra_paths);
+    if sources.is_empty() {
+        return Err(WatchError::NoSource.into());
+    }
+    // the events name the paths under the watched ones which are made absolute so that every file has a parent
+    let current_dir = std::env::current_dir()?;
+    let sources: Vec<PathBuf> = sources.iter().map(|path| current_dir.join(path)).collect();
+    let outputs: Vec<PathBuf> = report.outputs.iter().flat_map(|output| output.paths.iter()).map(|path| current_dir.join(path)).collect();
+    set_existing_outputs(ExistingOutputs::Replace);
+
+    let (sender, events) = channel();
+    let mut watcher = notify::recommended_watcher(sender).map_err(WatchError::Watcher)?;
+    for source in &sources {
+        let (path, mode) = match (source.is_dir(), source.parent()) {
+            (false, Some(parent)) => (parent.to_path_buf(), RecursiveMode::NonRecursive),
+            _ => (source.clone(), RecursiveMode::Recursive),
+        };
+        watcher.watch(&path, mode).map_err(|error| WatchError::Watch { path, error })?;
+        log::info!("watching {}", source.to_string_lossy());
+    }
+
+    loop {
+        wait_for_change(&events, &sources, &outputs)?;
+        log::info!("sources changed, converting again");
+        *report = CommandReport::new(&report.command);
+        let result = convert(report);
+        log_result(&result);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use notify::{event::{AccessKind, CreateKind}, Event, EventKind};
+
+    use super::is_source_change;
+
+    #[test]
+    fn source_changes() {
+        let sources = [PathBuf::from("/fonts/tiles"), PathBuf::from("/fonts/grid.png")];
+        let outputs = [PathBuf::from("/fonts/tiles/out")];
+        let event = |kind, path: &str| Event::new(kind).add_path(PathBuf::from(path));
+        let created = EventKind::Create(CreateKind::File);
+        assert!(is_source_change(&event(created, "/fonts/tiles/HD/001.png"), &sources, &outputs));
+        assert!(is_source_change(&event(created, "/fonts/grid.png"), &sources, &outputs));
+        assert!(!is_source_change(&event(created, "/fonts/font_hd.bin"), &sources, &outputs));
+        assert!(!is_source_change(&event(created, "/fonts/tiles/out/000.png"), &sources, &outputs));
+        assert!(!is_source_change(&event(created, "/fonts/.grid.png.1234.tmp"), &sources, &outputs));
+        assert!(!is_source_change(&event(EventKind::Access(AccessKind::Any), "/fonts/grid.png"), &sources, &outputs));
+    }
+
+}
@@ -0,0 +1,26 @@
+
+use image::Rgba;
+use thiserror::Error;
+
+
+#[derive(Debug, Error)]
+#[error("invalid color: {0}, expected a color name (black, white, transparent) or #RRGGBB / #RRGGBBAA")]
+pub struct ParseColorError(String);
+
+pub fn parse_color(input: &str) -> Result<Rgba<u8>, ParseColorError> {
+    match input {
+        "black" => return Ok(Rgba([0, 0, 0, 255])),
+        "white" => return Ok(Rgba([255, 255, 255, 255])),
+        "transparent" => return Ok(Rgba([0, 0, 0, 0])),
+        _ => {}
+    }
+    let hex = input.strip_prefix('#').ok_or_else(|| ParseColorError(input.to_owned()))?;
+    if !(hex.len() == 6 || hex.len() == 8) || !hex.is_ascii() {
+        return Err(ParseColorError(input.to_owned()));
+    }
+    let mut channels = [255; 4];
+    for (index, channel) in channels.iter_mut().enumerate().take(hex.len() / 2) {
+        *channel = u8::from_str_radix(&hex[index * 2..index * 2 + 2], 16).map_err(|_| ParseColorError(input.to_owned()))?;
+    }
+    Ok(Rgba(channels))
+}
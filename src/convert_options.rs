@@ -0,0 +1,294 @@
+
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+
+use crate::image_format::{AlphaMode, ColorKey, ImageDecoding, ImageFormat};
+use crate::osd::bin_file;
+use crate::osd::name_template::NameTemplate;
+use crate::osd::tile::{
+    container::load_tiles_from_dir::MissingTiles,
+    container::save_to_bin_file::Padding as BinFilePadding,
+    container::save_tiles_to_dir::DedupMode,
+    container::symbol::names::SymbolNames,
+    fit::Fit,
+    grid::{Layout as GridLayout, Validation as GridValidation},
+    grid_annotation::Annotation as GridAnnotation,
+    rescale::RescaleFilter,
+    transform::TileTransform,
+};
+
+
+pub const DEFAULT_SYMBOL_SPECS_FILE: &str = "sym_specs.yaml";
+// base and extended bin file pages
+pub const DEFAULT_MAX_TILE_COUNT: usize = 2 * bin_file::TILE_COUNT;
+// bound of the maximum tile count given as a number of bin file pages, well above the page count of any font
+pub const MAX_PAGE_COUNT: usize = 256;
+
+#[derive(Debug, Error)]
+pub enum InvalidConvertOptionsError {
+    #[error("invalid transform {0:?}: the black point must be lower than the white point")]
+    InvalidLevels(TileTransform),
+    #[error("invalid scale {0}: should be at least 1")]
+    InvalidScale(u32),
+    #[error("invalid maximum tile count {0}: should be at least 1")]
+    InvalidMaxTileCount(usize),
+    #[error("invalid maximum page count {0}: should be between 1 and {MAX_PAGE_COUNT}")]
+    InvalidMaxPageCount(usize),
+}
+
+// Options for the conversions between tile collection formats
+//
+// Build from the default options and set the ones which need changing e.g.
+// `ConvertOptions::default().symbol_specs_file("specs.yaml").fit(Some(Fit::Crop))`
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct ConvertOptions {
+    // symbol specifications used when the target is a symbol directory (symdir / symsetdir), ignored otherwise,
+    // the files are merged in order with the later ones overriding the specs of the same tiles
+    pub symbol_specs_files: Vec<PathBuf>,
+
+    // built-in symbol names table used to name the files when the target is a symbol directory (symdir / symsetdir),
+    // the files are only named from their tile indices when not set
+    pub symbol_names: Option<SymbolNames>,
+
+    // how to adjust wrongly sized tile images, only used when the source is a tile/symbol directory or a grid image,
+    // wrongly sized images are an error when not set
+    pub fit: Option<Fit>,
+
+    // how the dimensions of grid images are checked, only used when the source is a grid image,
+    // lenient accepts images with a small margin on the right/bottom of the grid
+    pub grid_validation: GridValidation,
+
+    // arrangement of the tiles in grid images, only used when the source or the target is a grid image,
+    // the number of rows of a loaded grid follows the image height
+    pub grid_layout: GridLayout,
+
+    // overlay of the tile indexes and optionally the bin file page separators when the target is a grid image,
+    // annotated grids are for presentation only and cannot be loaded back
+    pub grid_annotation: Option<GridAnnotation>,
+
+    // how the pages are completed when the target is a bin file set, strict padding makes a tile count not filling
+    // the pages exactly an error
+    pub bin_file_padding: BinFilePadding,
+
+    // names of the bin files of the normalized bin file sets (djibinsetnorm) for the firmware expecting other names,
+    // the default normalized names are used when not set
+    pub bin_name_template: Option<NameTemplate>,
+
+    // transforms applied in order to the tiles between loading the source and saving the target, for any source/target
+    pub transforms: Vec<TileTransform>,
+
+    // format of the written images when the target is a tile/symbol directory or normalized grid images,
+    // the format of explicitly named grid images follows their extension
+    pub image_format: ImageFormat,
+
+    // transparency rule for images without an alpha channel, only used when the source is a tile/symbol directory,
+    // such images are fully opaque when not set
+    pub color_key: Option<ColorKey>,
+
+    // how the colors of the images with an alpha channel relate to it, only used when the source is image based,
+    // the pixels are kept exactly as stored when not set
+    pub alpha: Option<AlphaMode>,
+
+    // the semi-transparent pixels with an alpha lower than the threshold become transparent and the other ones opaque
+    // after loading the source and before the transforms, for the OSD systems showing them as gray fringes
+    pub alpha_threshold: Option<u8>,
+
+    // integer upscaling factor of the tiles, only used when the target is an analog grid or a tile/symbol directory,
+    // upscaled directories are for presentation only and cannot be loaded back
+    pub scale: u32,
+
+    // how the files of the tiles identical to a previous tile are written when the target is a tile directory
+    // (tiledir / tilesetdir), every tile has its own file when not set
+    pub dedup_mode: Option<DedupMode>,
+
+    // filter used to resample the tiles when the target only holds another tile kind e.g. an analog grid, Catmull-Rom
+    // by default. Such conversions are an error when set to `None`, which is what `--no-rescale` does
+    pub rescale_filter: Option<RescaleFilter>,
+
+    // snap the rescaled tiles back to fully transparent or opaque gray pixels
+    pub requantize: bool,
+
+    // filter used to generate the tiles of the missing kind when a set source only holds SD or HD tiles,
+    // such sources are an error when not set
+    pub synthesize_missing_kind: Option<RescaleFilter>,
+
+    // maximum number of tiles loaded from a tile/symbol directory, the files with a higher index are ignored
+    pub max_tile_count: usize,
+
+    // how the gaps in the tile indexes are filled, only used when the source is a tile directory (tiledir / tilesetdir
+    // or a tile set archive)
+    pub missing_tiles: MissingTiles,
+}
+
+impl Default for ConvertOptions {
+    fn default() -> Self {
+        Self {
+            symbol_specs_files: vec![PathBuf::from(DEFAULT_SYMBOL_SPECS_FILE)],
+            symbol_names: None,
+            fit: None,
+            grid_validation: GridValidation::default(),
+            grid_layout: GridLayout::default(),
+            grid_annotation: None,
+            bin_file_padding: BinFilePadding::default(),
+            bin_name_template: None,
+            transforms: vec![],
+            image_format: ImageFormat::default(),
+            color_key: None,
+            alpha: None,
+            alpha_threshold: None,
+            scale: 1,
+            dedup_mode: None,
+            rescale_filter: Some(RescaleFilter::default()),
+            requantize: false,
+            synthesize_missing_kind: None,
+            max_tile_count: DEFAULT_MAX_TILE_COUNT,
+            missing_tiles: MissingTiles::default(),
+        }
+    }
+}
+
+impl ConvertOptions {
+
+    pub fn symbol_specs_file<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.symbol_specs_files = vec![path.as_ref().to_path_buf()];
+        self
+    }
+
+    pub fn symbol_specs_files<P: AsRef<Path>>(mut self, paths: &[P]) -> Self {
+        self.symbol_specs_files = paths.iter().map(|path| path.as_ref().to_path_buf()).collect();
+        self
+    }
+
+    pub fn symbol_names(mut self, symbol_names: Option<SymbolNames>) -> Self {
+        self.symbol_names = symbol_names;
+        self
+    }
+
+    pub fn fit(mut self, fit: Option<Fit>) -> Self {
+        self.fit = fit;
+        self
+    }
+
+    pub fn grid_validation(mut self, grid_validation: GridValidation) -> Self {
+        self.grid_validation = grid_validation;
+        self
+    }
+
+    pub fn grid_layout(mut self, grid_layout: GridLayout) -> Self {
+        self.grid_layout = grid_layout;
+        self
+    }
+
+    pub fn grid_annotation(mut self, grid_annotation: Option<GridAnnotation>) -> Self {
+        self.grid_annotation = grid_annotation;
+        self
+    }
+
+    pub fn bin_file_padding(mut self, bin_file_padding: BinFilePadding) -> Self {
+        self.bin_file_padding = bin_file_padding;
+        self
+    }
+
+    pub fn bin_name_template(mut self, bin_name_template: Option<NameTemplate>) -> Self {
+        self.bin_name_template = bin_name_template;
+        self
+    }
+
+    pub fn transforms(mut self, transforms: Vec<TileTransform>) -> Self {
+        self.transforms = transforms;
+        self
+    }
+
+    pub fn transform(mut self, transform: TileTransform) -> Self {
+        self.transforms.push(transform);
+        self
+    }
+
+    pub fn image_format(mut self, image_format: ImageFormat) -> Self {
+        self.image_format = image_format;
+        self
+    }
+
+    pub fn color_key(mut self, color_key: Option<ColorKey>) -> Self {
+        self.color_key = color_key;
+        self
+    }
+
+    pub fn alpha(mut self, alpha: Option<AlphaMode>) -> Self {
+        self.alpha = alpha;
+        self
+    }
+
+    pub fn alpha_threshold(mut self, alpha_threshold: Option<u8>) -> Self {
+        self.alpha_threshold = alpha_threshold;
+        self
+    }
+
+    pub fn decoding(&self) -> ImageDecoding {
+        ImageDecoding { color_key: self.color_key, alpha: self.alpha }
+    }
+
+    pub fn scale(mut self, scale: u32) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    pub fn dedup_mode(mut self, dedup_mode: Option<DedupMode>) -> Self {
+        self.dedup_mode = dedup_mode;
+        self
+    }
+
+    pub fn rescale_filter(mut self, rescale_filter: Option<RescaleFilter>) -> Self {
+        self.rescale_filter = rescale_filter;
+        self
+    }
+
+    pub fn requantize(mut self, requantize: bool) -> Self {
+        self.requantize = requantize;
+        self
+    }
+
+    pub fn synthesize_missing_kind(mut self, synthesize_missing_kind: Option<RescaleFilter>) -> Self {
+        self.synthesize_missing_kind = synthesize_missing_kind;
+        self
+    }
+
+    pub fn max_tile_count(mut self, max_tile_count: usize) -> Self {
+        self.max_tile_count = max_tile_count;
+        self
+    }
+
+    // maximum tile count given as a number of bin file pages
+    pub fn max_page_count(self, max_page_count: usize) -> Result<Self, InvalidConvertOptionsError> {
+        match max_page_count.checked_mul(bin_file::TILE_COUNT) {
+            Some(max_tile_count) if (1..=MAX_PAGE_COUNT).contains(&max_page_count) => Ok(self.max_tile_count(max_tile_count)),
+            _ => Err(InvalidConvertOptionsError::InvalidMaxPageCount(max_page_count)),
+        }
+    }
+
+    pub fn missing_tiles(mut self, missing_tiles: MissingTiles) -> Self {
+        self.missing_tiles = missing_tiles;
+        self
+    }
+
+    // checks that the options are consistent with each other
+    pub fn validate(&self) -> Result<(), InvalidConvertOptionsError> {
+        if self.scale == 0 {
+            return Err(InvalidConvertOptionsError::InvalidScale(self.scale));
+        }
+        if self.max_tile_count == 0 {
+            return Err(InvalidConvertOptionsError::InvalidMaxTileCount(self.max_tile_count));
+        }
+        for transform in &self.transforms {
+            if let TileTransform::Levels { black_point, white_point } = transform {
+                if black_point >= white_point {
+                    return Err(InvalidConvertOptionsError::InvalidLevels(transform.clone()));
+                }
+            }
+        }
+        Ok(())
+    }
+
+}
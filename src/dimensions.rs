@@ -4,6 +4,7 @@ use std::{ops::Mul, fmt::Display, str::FromStr};
 use derive_more::{From, Sub, Div};
 use getset::CopyGetters;
 use regex::Regex;
+use serde::{Serialize, Serializer};
 use thiserror::Error;
 use lazy_static::lazy_static;
 
@@ -27,6 +28,13 @@ impl<T: PartialEq + Eq + PartialOrd + Ord + Copy + Display> Display for Dimensio
     }
 }
 
+// serialized the same way as displayed e.g. "24x36"
+impl<T: PartialEq + Eq + PartialOrd + Ord + Copy + Display> Serialize for Dimensions<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
 // impl<T: PartialOrd + Ord + Copy + Div<Output = T>> Div<T> for Dimensions<T> {
 //     type Output = Self;
 
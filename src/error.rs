@@ -0,0 +1,287 @@
+
+use thiserror::Error;
+
+use crate::{
+    color::ParseColorError,
+    convert_options::InvalidConvertOptionsError,
+    dimensions::FormatError as DimensionsFormatError,
+    image_format::ParseImageFormatError,
+    osd::{
+        avatar_file,
+        bin_file,
+        mcm_file,
+        ident_scan::ScanIdentsError,
+        name_template::ParseNameTemplateError,
+        tile::{
+            self,
+            analog_grid,
+            combined_grid,
+            hdzero_sheet,
+            font_render,
+            text_render::SaveTextImageError,
+            preview::SaveContactSheetError,
+            osd_mock::SaveOsdMocksError,
+            diff::SaveVisualDiffError,
+            grid,
+            transform::{ParseTransformError, TransformedDimensionsError},
+            container::{
+                CapacityError,
+                ToSymbolsError,
+                dir_check::ScanDirError,
+                dir_fix::{ApplyFixesError, PlanFixesError},
+                load_symbols_from_dir::LoadSymbolsFromDirError,
+                load_tiles_from_dir::LoadTilesFromDirError,
+                patch::{LoadPatchError, ParseIndexListError, SavePatchError},
+                save_symbols_to_dir::SaveSymbolsToDirError,
+                save_tiles_to_dir::SaveTilesToDirError,
+                save_to_bin_file::SaveTilesToBinFileError,
+                symbol::{self, set::LoadFromDirError as LoadSymbolSetFromDirError, spec::{ConflictingSpecsError, InvalidSpecsError, LoadSpecsFileError}},
+                tile_set::{LoadFromTileGridsError, LoadTileSetTilesFromDirError},
+                uniq_tile_kind::TileKindError,
+            },
+        },
+    },
+};
+
+
+// Every error of the library modules converts into this type so that callers can handle them broadly with `?`
+// and still match the module errors when they need to
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error(transparent)]
+    BinFileOpen(#[from] bin_file::OpenError),
+    #[error(transparent)]
+    BinFileSeek(#[from] bin_file::SeekError),
+    #[error(transparent)]
+    BinFileSeekRead(#[from] bin_file::SeekReadError),
+    #[error(transparent)]
+    BinFileLoad(#[from] bin_file::LoadError),
+    #[error(transparent)]
+    BinFileSave(#[from] SaveTilesToBinFileError),
+    #[error(transparent)]
+    BinFileWriteTileAt(#[from] bin_file::WriteTileAtError),
+    #[error(transparent)]
+    AvatarFileLoad(#[from] avatar_file::LoadError),
+    #[error(transparent)]
+    AvatarFileSave(#[from] avatar_file::SaveError),
+    #[error(transparent)]
+    McmFileLoad(#[from] mcm_file::LoadError),
+    #[error(transparent)]
+    McmFileSave(#[from] mcm_file::SaveError),
+    #[error(transparent)]
+    TileLoad(#[from] tile::LoadError),
+    #[error(transparent)]
+    TileSize(#[from] tile::InvalidSizeError),
+    #[error(transparent)]
+    TileDimensions(#[from] tile::InvalidDimensionsError),
+    #[error(transparent)]
+    TileHeight(#[from] tile::InvalidHeightError),
+    #[error(transparent)]
+    TileKind(#[from] TileKindError),
+    #[error(transparent)]
+    Capacity(#[from] CapacityError),
+    #[error(transparent)]
+    GridLoad(#[from] grid::LoadError),
+    #[error(transparent)]
+    GridSave(#[from] grid::SaveImageError),
+    #[error(transparent)]
+    GridDimensions(#[from] grid::InvalidImageDimensionsError),
+    #[error(transparent)]
+    AnalogGridLoad(#[from] analog_grid::LoadError),
+    #[error(transparent)]
+    AnalogGridSave(#[from] analog_grid::SaveImageError),
+    #[error(transparent)]
+    CombinedGridLoad(#[from] combined_grid::LoadError),
+    #[error(transparent)]
+    CombinedGridSave(#[from] combined_grid::SaveImageError),
+    #[error(transparent)]
+    HdZeroSheetLoad(#[from] hdzero_sheet::LoadError),
+    #[error(transparent)]
+    HdZeroSheetSave(#[from] hdzero_sheet::SaveImageError),
+    #[error(transparent)]
+    FontRender(#[from] font_render::RenderError),
+    #[error(transparent)]
+    FontLoad(#[from] font_render::LoadFontError),
+    #[error(transparent)]
+    CharacterMappingLoad(#[from] font_render::LoadMappingError),
+    #[error(transparent)]
+    TextImageSave(#[from] SaveTextImageError),
+    #[error(transparent)]
+    ContactSheetSave(#[from] SaveContactSheetError),
+    #[error(transparent)]
+    OsdMocksSave(#[from] SaveOsdMocksError),
+    #[error(transparent)]
+    TileDirLoad(#[from] LoadTilesFromDirError),
+    #[error(transparent)]
+    PatchLoad(#[from] LoadPatchError),
+    #[error(transparent)]
+    PatchSave(#[from] SavePatchError),
+    #[error(transparent)]
+    ParseIndexList(#[from] ParseIndexListError),
+    #[error(transparent)]
+    TileDirSave(#[from] SaveTilesToDirError),
+    #[error(transparent)]
+    TileSetDirLoad(#[from] LoadTileSetTilesFromDirError),
+    #[error(transparent)]
+    TileSetGridsLoad(#[from] LoadFromTileGridsError),
+    #[error(transparent)]
+    SymbolLoad(#[from] symbol::LoadError),
+    #[error(transparent)]
+    SymbolDirLoad(#[from] LoadSymbolsFromDirError),
+    #[error(transparent)]
+    SymbolDirSave(#[from] SaveSymbolsToDirError),
+    #[error(transparent)]
+    SymbolSetDirLoad(#[from] LoadSymbolSetFromDirError),
+    #[error(transparent)]
+    ToSymbols(#[from] ToSymbolsError),
+    #[error(transparent)]
+    SymbolSpecsLoad(#[from] LoadSpecsFileError),
+    #[error(transparent)]
+    InvalidSymbolSpecs(#[from] InvalidSpecsError),
+    #[error(transparent)]
+    ConflictingSymbolSpecs(#[from] ConflictingSpecsError),
+    #[error(transparent)]
+    DirScan(#[from] ScanDirError),
+    #[error(transparent)]
+    PlanFixes(#[from] PlanFixesError),
+    #[error(transparent)]
+    ApplyFixes(#[from] ApplyFixesError),
+    #[error(transparent)]
+    IdentScan(#[from] ScanIdentsError),
+    #[error(transparent)]
+    VisualDiffSave(#[from] SaveVisualDiffError),
+    #[error(transparent)]
+    TransformedDimensions(#[from] TransformedDimensionsError),
+    #[error(transparent)]
+    ParseTransform(#[from] ParseTransformError),
+    #[error(transparent)]
+    InvalidConvertOptions(#[from] InvalidConvertOptionsError),
+    #[error(transparent)]
+    ParseImageFormat(#[from] ParseImageFormatError),
+    #[error(transparent)]
+    ParseColor(#[from] ParseColorError),
+    #[error(transparent)]
+    ParseNameTemplate(#[from] ParseNameTemplateError),
+    #[error(transparent)]
+    ParseDimensions(#[from] DimensionsFormatError),
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use image::{ImageBuffer, Rgba};
+    use temp_dir::TempDir;
+
+    use crate::image_format::ImageDecoding;
+    use crate::osd::{
+        avatar_file,
+        bin_file,
+        mcm_file,
+        tile::{
+            Tile,
+            Kind as TileKind,
+            analog_grid,
+            hdzero_sheet,
+            fit::Fit,
+            grid::{Grid, Layout as GridLayout, Validation as GridValidation},
+            container::{
+                load_symbols_from_dir::load_symbols_from_dir,
+                load_tiles_from_dir::load_tiles_from_dir,
+                symbol::{Symbol, spec::Specs},
+            },
+        },
+    };
+
+    // deterministic pseudo random bytes so that failures can be reproduced
+    fn garbage(len: usize, seed: u64) -> Vec<u8> {
+        let mut state = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (0..len).map(|_| {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (state >> 33) as u8
+        }).collect()
+    }
+
+    fn png_bytes(width: u32, height: u32) -> Vec<u8> {
+        let image: ImageBuffer<Rgba<u8>, Vec<u8>> = ImageBuffer::from_pixel(width, height, Rgba([255, 255, 255, 255]));
+        let mut bytes = std::io::Cursor::new(vec![]);
+        image.write_to(&mut bytes, image::ImageOutputFormat::Png).unwrap();
+        bytes.into_inner()
+    }
+
+    fn load_with_every_image_loader(path: &Path) {
+        let _ = Tile::load_image_file(path);
+        let _ = Tile::load_image_file_fit(path, Some(Fit::Crop));
+        let _ = Tile::load_image_file_fit(path, Some(Fit::Scale));
+        let _ = Symbol::load_image_file(path);
+        let _ = Symbol::load_image_file_fit(path, Some(Fit::Pad));
+        for validation in [GridValidation::Strict, GridValidation::Lenient] {
+            let _ = Grid::load_from_image_decoded(path, None, ImageDecoding::default(), validation, GridLayout::default());
+        }
+        let _ = Grid::load_from_image_fit(path, Some(Fit::Scale));
+        let _ = analog_grid::load_image(path);
+        let _ = hdzero_sheet::load_image(path);
+        let _ = avatar_file::load(path);
+    }
+
+    #[test]
+    fn garbage_inputs_do_not_panic() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let sd_page_size = TileKind::SD.bin_file_size_bytes();
+        let mut bin_files = vec![
+            vec![],
+            garbage(100, 1),
+            garbage(sd_page_size - 1, 2),
+            garbage(sd_page_size, 3),
+            garbage(TileKind::HD.bin_file_size_bytes() + 7, 4),
+            [&[0x1f, 0x8b][..], &garbage(500, 5)].concat(),
+        ];
+        bin_files.push(bin_files[3][..sd_page_size / 2].to_vec());
+        for (index, content) in bin_files.iter().enumerate() {
+            for file_name in [format!("font_{index}.bin"), format!("font_{index}.bin.gz")] {
+                let path = temp_dir.child(&file_name);
+                std::fs::write(&path, content).unwrap();
+                let _ = bin_file::load(&path, None);
+                let _ = bin_file::load(&path, Some(TileKind::HD));
+                let _ = bin_file::load_extended(&path, &path);
+                let _ = mcm_file::load(&path, TileKind::SD);
+                if let Ok(mut reader) = bin_file::BinFileReader::open(&path) {
+                    let _ = reader.seek_read_tile(bin_file::SeekFrom::End(0));
+                    let _ = reader.seek_read_tile(bin_file::SeekFrom::Current(1));
+                    let _ = reader.read_tiles();
+                }
+            }
+        }
+        let _ = bin_file::load_pages_norm(temp_dir.path(), TileKind::SD, &Some("0"));
+
+        let valid_png = png_bytes(36, 54);
+        let image_files = [
+            vec![],
+            garbage(1000, 6),
+            [&valid_png[..8], &garbage(200, 7)].concat(),
+            valid_png[..valid_png.len() / 2].to_vec(),
+            png_bytes(1, 1),
+            png_bytes(606, 10),
+            png_bytes(24, 1000),
+            png_bytes(1000, 3),
+        ];
+        let images_dir = temp_dir.child("images");
+        std::fs::create_dir(&images_dir).unwrap();
+        for (index, content) in image_files.iter().enumerate() {
+            let path = images_dir.join(format!("{index:03}.png"));
+            std::fs::write(&path, content).unwrap();
+            load_with_every_image_loader(&path);
+        }
+        std::fs::write(images_dir.join("010-005.png"), &valid_png).unwrap();
+        let _ = load_tiles_from_dir(&images_dir, 512, Some(Fit::Crop), ImageDecoding::default());
+        let _ = load_symbols_from_dir(&images_dir, 512, None, ImageDecoding::default());
+
+        let specs_path = temp_dir.child("sym_specs.yaml");
+        for specs in ["a: 0xZZ:1", "a: 3:0", "a: 18446744073709551615:2", "[1, 2", ""] {
+            std::fs::write(&specs_path, specs).unwrap();
+            let _ = Specs::load_file(&specs_path);
+        }
+    }
+}
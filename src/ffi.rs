@@ -0,0 +1,200 @@
+// C interface for the tools embedding the crate e.g. goggles configuration software, see ffi/hd_fpv_osd_font_tool.h
+//
+// The tile sets are opaque handles created by the load functions and released with hd_fpv_tile_set_free. The functions
+// returning an int return 0 on success and -1 on failure, the functions returning a pointer return NULL on failure,
+// the message of the last failure of the calling thread is returned by hd_fpv_last_error. The pointer arguments have to
+// be NULL or valid: nul terminated strings, handles returned by the load functions and not freed yet.
+
+#![allow(clippy::missing_safety_doc)]
+
+use std::cell::RefCell;
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_int};
+use std::path::PathBuf;
+
+use crate::convert_options::DEFAULT_MAX_TILE_COUNT;
+use crate::image_format::{ImageDecoding, ImageFormat};
+use crate::osd::tile::{container::{save_tiles_to_dir::SaveTilesToDir, tile_set::TileSet}, Kind as TileKind};
+
+
+pub struct HdFpvTileSet(TileSet);
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: String) {
+    let message = CString::new(message.replace('\0', " ")).unwrap();
+    LAST_ERROR.with(|last_error| *last_error.borrow_mut() = Some(message));
+}
+
+fn status<E: std::fmt::Display>(result: Result<(), E>) -> c_int {
+    match result {
+        Ok(()) => 0,
+        Err(error) => {
+            set_last_error(error.to_string());
+            -1
+        },
+    }
+}
+
+fn tile_set_handle<E: std::fmt::Display>(result: Result<TileSet, E>) -> *mut HdFpvTileSet {
+    match result {
+        Ok(tile_set) => Box::into_raw(Box::new(HdFpvTileSet(tile_set))),
+        Err(error) => {
+            set_last_error(error.to_string());
+            std::ptr::null_mut()
+        },
+    }
+}
+
+// the pointer has to be NULL or point to a nul terminated string
+unsafe fn optional_str<'a>(string: *const c_char) -> Result<Option<&'a str>, String> {
+    if string.is_null() {
+        return Ok(None)
+    }
+    CStr::from_ptr(string).to_str().map(Some).map_err(|error| format!("invalid UTF-8 string argument: {error}"))
+}
+
+unsafe fn path(path: *const c_char) -> Result<PathBuf, String> {
+    optional_str(path)?.map(PathBuf::from).ok_or_else(|| "path argument is NULL".to_owned())
+}
+
+fn tile_kind(kind: u32) -> Result<TileKind, String> {
+    match kind {
+        0 => Ok(TileKind::SD),
+        1 => Ok(TileKind::HD),
+        _ => Err(format!("invalid tile kind {kind}: expected 0 (SD) or 1 (HD)")),
+    }
+}
+
+// message of the last failure of the calling thread, NULL if none, valid until the next failing call of the thread
+#[no_mangle]
+pub extern "C" fn hd_fpv_last_error() -> *const c_char {
+    LAST_ERROR.with(|last_error| last_error.borrow().as_ref().map_or(std::ptr::null(), |message| message.as_ptr()))
+}
+
+// normalized bin files of a directory e.g. font_001.bin, font_hd_001.bin, `ident` may be NULL
+#[no_mangle]
+pub unsafe extern "C" fn hd_fpv_tile_set_load_bin_files_norm(dir: *const c_char, ident: *const c_char) -> *mut HdFpvTileSet {
+    let result = path(dir).and_then(|dir| {
+        let ident = optional_str(ident)?;
+        TileSet::load_bin_files_norm(dir, &ident).map_err(|error| error.to_string())
+    });
+    tile_set_handle(result)
+}
+
+// tile set directory with the SD and HD sub-directories
+#[no_mangle]
+pub unsafe extern "C" fn hd_fpv_tile_set_load_dir(dir: *const c_char) -> *mut HdFpvTileSet {
+    let result = path(dir).and_then(|dir|
+        TileSet::load_from_dir(dir, DEFAULT_MAX_TILE_COUNT, None, ImageDecoding::default()).map_err(|error| error.to_string())
+    );
+    tile_set_handle(result)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn hd_fpv_tile_set_free(tile_set: *mut HdFpvTileSet) {
+    if !tile_set.is_null() {
+        drop(Box::from_raw(tile_set));
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn hd_fpv_tile_set_tile_count(tile_set: *const HdFpvTileSet, kind: u32) -> usize {
+    match (tile_set.as_ref(), tile_kind(kind)) {
+        (Some(tile_set), Ok(kind)) => tile_set.0.tiles(kind).len(),
+        _ => 0,
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn hd_fpv_tile_width(kind: u32) -> u32 {
+    tile_kind(kind).map_or(0, |kind| kind.dimensions().width)
+}
+
+#[no_mangle]
+pub extern "C" fn hd_fpv_tile_height(kind: u32) -> u32 {
+    tile_kind(kind).map_or(0, |kind| kind.dimensions().height)
+}
+
+// RGBA pixels of a tile row by row, `size` receives the number of bytes, the buffer belongs to the tile set
+#[no_mangle]
+pub unsafe extern "C" fn hd_fpv_tile_set_tile_rgba(tile_set: *const HdFpvTileSet, kind: u32, index: usize, size: *mut usize) -> *const u8 {
+    let tile = tile_set.as_ref().ok_or_else(|| "tile set argument is NULL".to_owned()).and_then(|tile_set| {
+        let kind = tile_kind(kind)?;
+        tile_set.0.tiles(kind).get(index).ok_or_else(|| format!("no {kind} tile at index {index}"))
+    });
+    match tile {
+        Ok(tile) => {
+            if let Some(size) = size.as_mut() {
+                *size = tile.as_raw().len();
+            }
+            tile.as_raw().as_ptr()
+        },
+        Err(error) => {
+            set_last_error(error);
+            std::ptr::null()
+        },
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn hd_fpv_tile_set_save_bin_files_norm(tile_set: *const HdFpvTileSet, dir: *const c_char, ident: *const c_char) -> c_int {
+    status(tile_set.as_ref().ok_or_else(|| "tile set argument is NULL".to_owned()).and_then(|tile_set| {
+        let ident = optional_str(ident)?;
+        tile_set.0.save_to_bin_files_norm(path(dir)?, &ident).map_err(|error| error.to_string())
+    }))
+}
+
+// normalized PNG grid images e.g. font.png, font_hd.png
+#[no_mangle]
+pub unsafe extern "C" fn hd_fpv_tile_set_save_grids_norm(tile_set: *const HdFpvTileSet, dir: *const c_char, ident: *const c_char) -> c_int {
+    status(tile_set.as_ref().ok_or_else(|| "tile set argument is NULL".to_owned()).and_then(|tile_set| {
+        let ident = optional_str(ident)?;
+        tile_set.0.save_to_grids_norm(path(dir)?, &ident, ImageFormat::Png).map_err(|error| error.to_string())
+    }))
+}
+
+// PNG tile set directory with the SD and HD sub-directories
+#[no_mangle]
+pub unsafe extern "C" fn hd_fpv_tile_set_save_dir(tile_set: *const HdFpvTileSet, dir: *const c_char) -> c_int {
+    status(tile_set.as_ref().ok_or_else(|| "tile set argument is NULL".to_owned()).and_then(|tile_set|
+        tile_set.0.save_tiles_to_dir(path(dir)?, ImageFormat::Png).map_err(|error| error.to_string())
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::{CStr, CString};
+
+    use temp_dir::TempDir;
+
+    use crate::osd::tile::Tile;
+
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = CString::new(temp_dir.path().to_str().unwrap()).unwrap();
+        unsafe {
+            assert!(hd_fpv_tile_set_load_bin_files_norm(dir.as_ptr(), std::ptr::null()).is_null());
+            assert!(!hd_fpv_last_error().is_null());
+
+            let tile_set = TileSet::try_from_tiles(vec![Tile::new(TileKind::SD)], vec![Tile::new(TileKind::HD)]).unwrap();
+            let tile_set = tile_set_handle::<String>(Ok(tile_set));
+            assert_eq!(hd_fpv_tile_set_save_bin_files_norm(tile_set, dir.as_ptr(), std::ptr::null()), 0);
+            hd_fpv_tile_set_free(tile_set);
+
+            let tile_set = hd_fpv_tile_set_load_bin_files_norm(dir.as_ptr(), std::ptr::null());
+            assert!(!tile_set.is_null(), "{:?}", CStr::from_ptr(hd_fpv_last_error()));
+            assert_eq!(hd_fpv_tile_set_tile_count(tile_set, 1), 256);
+            let mut size = 0;
+            assert!(!hd_fpv_tile_set_tile_rgba(tile_set, 1, 255, &mut size).is_null());
+            assert_eq!(size, (hd_fpv_tile_width(1) * hd_fpv_tile_height(1) * 4) as usize);
+            assert!(hd_fpv_tile_set_tile_rgba(tile_set, 2, 0, &mut size).is_null());
+            hd_fpv_tile_set_free(tile_set);
+        }
+    }
+}
@@ -1,13 +1,24 @@
 
 use std::path::{Path, PathBuf};
-use std::io::Error as IOError;
+use std::io::{Cursor, Error as IOError, Write};
 use std::ops::Deref;
 
 use derive_more::From;
 use thiserror::Error;
-use image::{DynamicImage, ImageError, EncodableLayout, ImageBuffer, PixelWithColorType};
+use image::{DynamicImage, ImageError, ImageEncoder, EncodableLayout, ImageBuffer, PixelWithColorType, RgbaImage};
+use image::codecs::png::{CompressionType, FilterType as PngFilterType, PngEncoder};
+use image::imageops::{self, FilterType};
 use image::io::Reader as ImageReader;
 
+use crate::atomic_file;
+use crate::image_format::{ImageDecoding, ImageFormat, normalize_transparent_pixels};
+use crate::stdio::{is_stdio_path, read_stdin};
+
+// the PNG encoder settings are pinned so that the same tiles are always written as the same bytes whatever the version
+// of the image crate defaults, the encoder does not write ancillary chunks e.g. timestamps
+const PNG_COMPRESSION: CompressionType = CompressionType::Best;
+const PNG_FILTER: PngFilterType = PngFilterType::Adaptive;
+
 
 #[derive(Debug, Error, From)]
 pub enum ReadError {
@@ -36,11 +47,51 @@ impl ReadError {
     }
 }
 
+// the format of the images read from stdin is detected from their content
 pub fn read_image_file<P: AsRef<Path>>(path: P) -> Result<DynamicImage, ReadError> {
+    if is_stdio_path(&path) {
+        let data = read_stdin().map_err(|error| ReadError::open_error(&path, error))?;
+        let reader = ImageReader::new(Cursor::new(data)).with_guessed_format().map_err(|error| ReadError::open_error(&path, error))?;
+        return reader.decode().map_err(|error| ReadError::decode_error(&path, error))
+    }
     let reader = ImageReader::open(&path).map_err(|error| ReadError::open_error(&path, error))?;
     reader.decode().map_err(|error| ReadError::decode_error(&path, error) )
 }
 
+// the shared decoding path of the tile images, the color key is only applied to images decoded without an alpha channel
+// and the alpha mode only to the images with one
+pub fn read_rgba_image_file<P: AsRef<Path>>(path: P, decoding: ImageDecoding) -> Result<RgbaImage, ReadError> {
+    let image = read_image_file(&path)?;
+    Ok(decode_rgba_image(path, image, decoding))
+}
+
+// image file read in memory e.g. from an archive, the format follows the extension of the path and is detected from the
+// content for unknown extensions
+#[cfg(feature = "fs")]
+pub fn read_rgba_image_bytes<P: AsRef<Path>>(path: P, bytes: &[u8], decoding: ImageDecoding) -> Result<RgbaImage, ReadError> {
+    let reader = match image::ImageFormat::from_path(&path) {
+        Ok(format) => ImageReader::with_format(Cursor::new(bytes), format),
+        Err(_) => ImageReader::new(Cursor::new(bytes)).with_guessed_format().map_err(|error| ReadError::open_error(&path, error))?,
+    };
+    let image = reader.decode().map_err(|error| ReadError::decode_error(&path, error))?;
+    Ok(decode_rgba_image(path, image, decoding))
+}
+
+fn decode_rgba_image<P: AsRef<Path>>(path: P, image: DynamicImage, decoding: ImageDecoding) -> RgbaImage {
+    let has_alpha = image.color().has_alpha();
+    let mut image = image.into_rgba8();
+    if let (false, Some(color_key)) = (has_alpha, decoding.color_key) {
+        color_key.apply(&mut image);
+    }
+    if let Some(alpha) = decoding.alpha {
+        if has_alpha {
+            alpha.apply(&path, &mut image);
+        }
+        normalize_transparent_pixels(&mut image);
+    }
+    image
+}
+
 #[derive(Debug, From, Error)]
 #[error("failed to write image {file_path}: {error}")]
 pub struct WriteError {
@@ -54,6 +105,29 @@ impl WriteError {
     }
 }
 
+// integer nearest neighbor upscaling so that the pixels stay crisp e.g. for presentation copies
+pub fn upscale(image: &RgbaImage, scale: u32) -> RgbaImage {
+    match scale {
+        1 => image.clone(),
+        _ => imageops::resize(image, image.width() * scale, image.height() * scale, FilterType::Nearest),
+    }
+}
+
+// encoded image file content e.g. for an archive, the PNG images are encoded like the written files
+pub fn encode_image<P: AsRef<Path>>(image: &RgbaImage, path: P, image_format: ImageFormat) -> Result<Vec<u8>, WriteError> {
+    let mut bytes = vec![];
+    let result = match image_format {
+        ImageFormat::Png => PngEncoder::new_with_quality(&mut bytes, PNG_COMPRESSION, PNG_FILTER)
+            .write_image(image.as_raw(), image.width(), image.height(), image::ColorType::Rgba8),
+        _ => {
+            let format = image::ImageFormat::from_extension(image_format.extension()).expect("image crate format for every image format");
+            image.write_to(&mut Cursor::new(&mut bytes), format)
+        },
+    };
+    result.map_err(|error| WriteError::new(&path, error))?;
+    Ok(bytes)
+}
+
 pub trait WriteImageFile {
     fn write_image_file<Q: AsRef<Path>>(&self, path: Q) -> Result<(), WriteError>;
 }
@@ -64,7 +138,23 @@ where
     [P::Subpixel]: EncodableLayout,
     Container: Deref<Target = [P::Subpixel]>,
 {
+    // the format follows the extension of the path, PNG when writing to stdout, the files are replaced atomically
     fn write_image_file<Q: AsRef<Path>>(&self, path: Q) -> Result<(), WriteError> {
-        self.save(&path).map_err(|error| WriteError::new(&path, error) )
+        let is_stdout = is_stdio_path(&path);
+        if !is_stdout && ImageFormat::from_path(&path) != Some(ImageFormat::Png) {
+            let format = image::ImageFormat::from_path(&path).map_err(|error| WriteError::new(&path, error))?;
+            return atomic_file::write_with(&path, |temp_path| self.save_with_format(temp_path, format))
+                .map_err(|error| WriteError::new(&path, error));
+        }
+        let mut bytes = vec![];
+        PngEncoder::new_with_quality(&mut bytes, PNG_COMPRESSION, PNG_FILTER)
+            .write_image(self.as_raw().as_bytes(), self.width(), self.height(), P::COLOR_TYPE)
+            .map_err(|error| WriteError::new(&path, error))?;
+        let written = if is_stdout {
+            std::io::stdout().lock().write_all(&bytes)
+        } else {
+            atomic_file::write(&path, bytes)
+        };
+        written.map_err(|error| WriteError::new(&path, ImageError::IoError(error)))
     }
 }
@@ -0,0 +1,244 @@
+
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use clap::ValueEnum;
+use image::{Rgba, RgbaImage};
+use strum::{Display, EnumIter, IntoEnumIterator};
+use thiserror::Error;
+
+
+// extensions of the image files read from tile/symbol directories, only the first frame of GIF files is used
+pub const READABLE_EXTENSIONS: [&str; 7] = ["png", "bmp", "tga", "webp", "tiff", "tif", "gif"];
+
+pub fn is_readable_image_path<P: AsRef<Path>>(path: P) -> bool {
+    match path.as_ref().extension().and_then(|extension| extension.to_str()) {
+        Some(extension) => READABLE_EXTENSIONS.iter().any(|readable| extension.eq_ignore_ascii_case(readable)),
+        None => false,
+    }
+}
+
+
+#[derive(Debug, Error)]
+pub enum ParseImageFormatError {
+    #[error("image format `{0}` can not represent transparency which is required for tile images, use png, bmp, tga, webp or tiff")]
+    NoAlphaSupport(String),
+    #[error("unknown image format `{0}`, valid formats are png, bmp, tga, webp and tiff")]
+    Unknown(String),
+}
+
+#[derive(Debug, Error)]
+#[error("several image files could be meant, keep only one of: {}", .paths.iter().map(|path| path.to_string_lossy()).collect::<Vec<_>>().join(", "))]
+pub struct AmbiguousImageFileError {
+    pub paths: Vec<PathBuf>,
+}
+
+// Formats tile images can be written to, all of them support an alpha channel
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, EnumIter, Display)]
+#[strum(serialize_all = "lowercase")]
+pub enum ImageFormat {
+    #[default]
+    Png,
+    Bmp,
+    Tga,
+    Webp,
+    Tiff,
+}
+
+impl ImageFormat {
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageFormat::Png => "png",
+            ImageFormat::Bmp => "bmp",
+            ImageFormat::Tga => "tga",
+            ImageFormat::Webp => "webp",
+            ImageFormat::Tiff => "tiff",
+        }
+    }
+
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        if extension.eq_ignore_ascii_case("tif") {
+            return Some(Self::Tiff);
+        }
+        Self::iter().find(|format| extension.eq_ignore_ascii_case(format.extension()))
+    }
+
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Self> {
+        Self::from_extension(path.as_ref().extension()?.to_str()?)
+    }
+
+    // path of the image file named `file_stem` in `dir` with the extension of this format
+    pub fn file_path<P: AsRef<Path>>(&self, dir: P, file_stem: &str) -> PathBuf {
+        [dir.as_ref(), Path::new(&format!("{file_stem}.{}", self.extension()))].iter().collect()
+    }
+
+    // existing image file named `file_stem` in `dir` with any of the supported extensions, an error when several exist
+    // since either could be the one meant. Defaults to the PNG file path if none exists so that errors mention the usual
+    // file name
+    pub fn find_file_path<P: AsRef<Path>>(dir: P, file_stem: &str) -> Result<PathBuf, AmbiguousImageFileError> {
+        let mut paths: Vec<PathBuf> = Self::iter()
+            .map(|format| format.file_path(&dir, file_stem))
+            .filter(|path| path.is_file())
+            .collect();
+        match paths.len() {
+            0 => Ok(Self::Png.file_path(&dir, file_stem)),
+            1 => Ok(paths.remove(0)),
+            _ => Err(AmbiguousImageFileError { paths }),
+        }
+    }
+
+}
+
+impl FromStr for ImageFormat {
+    type Err = ParseImageFormatError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        if let Some(format) = Self::from_extension(input) {
+            return Ok(format);
+        }
+        match input.to_ascii_lowercase().as_str() {
+            "jpg" | "jpeg" | "pnm" | "ppm" | "pgm" => Err(ParseImageFormatError::NoAlphaSupport(input.to_owned())),
+            _ => Err(ParseImageFormatError::Unknown(input.to_owned())),
+        }
+    }
+}
+
+// Transparency rule for images decoded from files without an alpha channel (e.g. 24 bit BMP or TGA):
+// the pixels exactly matching the key color become fully transparent and all the other pixels are opaque.
+// Without a color key such images are fully opaque.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum, Display)]
+#[strum(serialize_all = "lowercase")]
+pub enum ColorKey {
+    Magenta,
+    Black,
+}
+
+impl ColorKey {
+
+    pub fn color(&self) -> Rgba<u8> {
+        match self {
+            ColorKey::Magenta => Rgba([255, 0, 255, 255]),
+            ColorKey::Black => Rgba([0, 0, 0, 255]),
+        }
+    }
+
+    pub fn apply(&self, image: &mut RgbaImage) {
+        let key = self.color();
+        for pixel in image.pixels_mut() {
+            if *pixel == key {
+                *pixel = Rgba([0, 0, 0, 0]);
+            }
+        }
+    }
+
+}
+
+// How the color channels of the decoded images relate to their alpha channel
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum, Display)]
+#[strum(serialize_all = "lowercase")]
+pub enum AlphaMode {
+    Straight,
+    // the color channels have been multiplied by alpha e.g. images exported by some macOS tools
+    Premultiplied,
+    // premultiplied unless a semi-transparent pixel has a color channel greater than its alpha
+    Auto,
+}
+
+fn is_premultiplied(image: &RgbaImage) -> bool {
+    let mut semi_transparent = image.pixels().filter(|pixel| pixel.0[3] != 0 && pixel.0[3] != 255).peekable();
+    semi_transparent.peek().is_some() && semi_transparent.all(|pixel| pixel.0[..3].iter().all(|channel| *channel <= pixel.0[3]))
+}
+
+fn unpremultiply(image: &mut RgbaImage) {
+    for pixel in image.pixels_mut() {
+        let alpha = pixel.0[3] as u32;
+        if alpha != 0 && alpha != 255 {
+            for channel in &mut pixel.0[..3] {
+                *channel = ((*channel as u32 * 255 + alpha / 2) / alpha).min(255) as u8;
+            }
+        }
+    }
+}
+
+impl AlphaMode {
+
+    // returns whether the image was considered premultiplied
+    pub fn apply<P: AsRef<Path>>(&self, path: P, image: &mut RgbaImage) -> bool {
+        let premultiplied = match self {
+            AlphaMode::Straight => false,
+            AlphaMode::Premultiplied => true,
+            AlphaMode::Auto => {
+                let premultiplied = is_premultiplied(image);
+                if premultiplied {
+                    log::debug!("{}: premultiplied alpha detected", path.as_ref().to_string_lossy());
+                } else {
+                    log::debug!("{}: straight alpha assumed", path.as_ref().to_string_lossy());
+                }
+                premultiplied
+            },
+        };
+        if premultiplied {
+            unpremultiply(image);
+        }
+        premultiplied
+    }
+
+}
+
+// fully transparent pixels all end up as 0,0,0,0 whatever color they were stored with so that the content hashes
+// do not depend on the tool the images were exported with
+pub fn normalize_transparent_pixels(image: &mut RgbaImage) {
+    for pixel in image.pixels_mut() {
+        if pixel.0[3] == 0 {
+            *pixel = Rgba([0, 0, 0, 0]);
+        }
+    }
+}
+
+// Rules applied to every image tiles are decoded from
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct ImageDecoding {
+    pub color_key: Option<ColorKey>,
+    // the pixels are kept exactly as stored when not set
+    pub alpha: Option<AlphaMode>,
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{Rgba, RgbaImage};
+    use temp_dir::TempDir;
+
+    use super::{AlphaMode, ImageFormat, ParseImageFormatError};
+
+    #[test]
+    fn parse() {
+        assert_eq!("webp".parse::<ImageFormat>().unwrap(), ImageFormat::Webp);
+        assert_eq!("BMP".parse::<ImageFormat>().unwrap(), ImageFormat::Bmp);
+        assert!(matches!("jpeg".parse::<ImageFormat>(), Err(ParseImageFormatError::NoAlphaSupport(_))));
+        assert!(matches!("xyz".parse::<ImageFormat>(), Err(ParseImageFormatError::Unknown(_))));
+        assert_eq!(ImageFormat::from_path("tiles/012.tga"), Some(ImageFormat::Tga));
+        assert_eq!(ImageFormat::from_path("grid.TIF"), Some(ImageFormat::Tiff));
+    }
+
+    #[test]
+    fn find_file_path() {
+        let temp_dir = TempDir::new().unwrap();
+        assert_eq!(ImageFormat::find_file_path(temp_dir.path(), "grid_hd").unwrap(), temp_dir.child("grid_hd.png"));
+        std::fs::write(temp_dir.child("grid_hd.bmp"), []).unwrap();
+        assert_eq!(ImageFormat::find_file_path(temp_dir.path(), "grid_hd").unwrap(), temp_dir.child("grid_hd.bmp"));
+        std::fs::write(temp_dir.child("grid_hd.webp"), []).unwrap();
+        assert_eq!(ImageFormat::find_file_path(temp_dir.path(), "grid_hd").unwrap_err().paths.len(), 2);
+    }
+
+    #[test]
+    fn auto_alpha() {
+        let mut image = RgbaImage::from_pixel(2, 1, Rgba([64, 64, 64, 128]));
+        assert!(AlphaMode::Auto.apply("premultiplied.png", &mut image));
+        assert_eq!(*image.get_pixel(0, 0), Rgba([128, 128, 128, 128]));
+
+        let mut image = RgbaImage::from_pixel(2, 1, Rgba([200, 200, 200, 128]));
+        assert!(!AlphaMode::Auto.apply("straight.png", &mut image));
+        assert_eq!(*image.get_pixel(0, 0), Rgba([200, 200, 200, 128]));
+    }
+}
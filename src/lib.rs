@@ -1,9 +1,27 @@
 
-#![forbid(unsafe_code)]
+// the unsafe code is limited to the C interface, the code generated for the Python module and the memory mapping of
+// the bin files
+#![deny(unsafe_code)]
 
 pub mod dimensions;
 pub mod osd;
 pub mod prelude;
 pub mod log_level;
+pub mod color;
+pub mod convert_options;
+pub mod report;
+pub mod image_format;
+pub mod error;
+pub mod stdio;
+pub mod terminal_graphics;
+pub mod atomic_file;
 mod image;
-mod create_path;
\ No newline at end of file
+mod create_path;
+mod base64;
+mod parallel;
+#[cfg(feature = "ffi")]
+#[allow(unsafe_code)]
+pub mod ffi;
+#[cfg(feature = "python")]
+#[allow(unsafe_code)]
+pub mod python;
\ No newline at end of file
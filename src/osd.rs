@@ -1,4 +1,10 @@
 
 pub mod tile;
 pub mod bin_file;
-pub mod avatar_file;
\ No newline at end of file
+pub mod avatar_file;
+pub mod mcm_file;
+pub mod source_array;
+pub mod ident_scan;
+pub mod font_dir;
+pub mod name_template;
+pub mod font_metadata;
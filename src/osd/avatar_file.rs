@@ -5,20 +5,24 @@ use std::{
 };
 
 use derive_more::From;
-use image::{GenericImageView, GenericImage, ImageBuffer, Rgba};
+use image::{GenericImageView, GenericImage, ImageBuffer, ImageError, Rgba};
 use thiserror::Error;
 use strum::IntoEnumIterator;
 
 use super::tile::{
     Tile,
     Kind as TileKind,
-    container::uniq_tile_kind::{TileKindError, UniqTileKind},
+    container::{
+        CapacityError,
+        uniq_tile_kind::{TileKindError, UniqTileKind},
+    },
 };
 
 use crate::{
     dimensions,
+    image_format::ImageDecoding,
     image::{
-        read_image_file,
+        read_rgba_image_file,
         ReadError as ImageReadError,
         WriteImageFile,
         WriteError as ImageWriteError,
@@ -80,20 +84,22 @@ impl LoadError {
 }
 
 pub fn load<P: AsRef<Path>>(path: P) -> Result<Vec<Tile>, LoadError> {
-    let image = read_image_file(&path)?;
+    load_decoded(path, ImageDecoding::default())
+}
+
+pub fn load_decoded<P: AsRef<Path>>(path: P, decoding: ImageDecoding) -> Result<Vec<Tile>, LoadError> {
+    let image = read_rgba_image_file(&path, decoding)?;
     let tile_kind = TileKind::for_avatar_image_dimensions(image.dimensions().into())
             .map_err(|error| {
                 let InvalidDimensionsError { dimensions } = error;
                 LoadError::invalid_dimensions(&path, dimensions)
             })?;
     let tile_dimensions = tile_kind.dimensions();
-    let mut tiles = vec![Tile::new(tile_kind); TILE_COUNT];
-    for (tile_index, tile) in tiles.iter_mut().enumerate() {
-        let tile_y = tile_index as u32 * tile_dimensions.height;
+    (0..TILE_COUNT as u32).map(|tile_index| {
+        let tile_y = tile_index * tile_dimensions.height;
         let tile_from_image = image.view(0, tile_y, tile_dimensions.width, tile_dimensions.height).to_image();
-        tile.copy_from(&tile_from_image, 0, 0).unwrap();
-    }
-    Ok(tiles)
+        Tile::try_from(tile_from_image).map_err(|InvalidDimensionsError { dimensions }| LoadError::invalid_dimensions(&path, dimensions))
+    }).collect()
 }
 
 #[derive(Debug, From, Error)]
@@ -104,21 +110,24 @@ pub enum SaveError {
     ImageWriteError(ImageWriteError),
     #[error("not enough tiles, Avatar tile collection must contain 256 tiles")]
     WrongCollectionSize(usize),
+    #[error(transparent)]
+    CapacityError(CapacityError),
+    #[from(ignore)]
+    #[error("failed to copy tile {index} to the avatar image: {error}")]
+    TileCopyError { index: usize, error: ImageError },
 }
 
 pub fn save<P: AsRef<Path>>(tiles: &[Tile], path: P) -> Result<(), SaveError> {
     if tiles.len() < TILE_COUNT {
         return Err(SaveError::WrongCollectionSize(tiles.len()));
     }
-    if tiles.len() > TILE_COUNT {
-        log::warn!("Avatar font files can only contain 256 tiles but the source collection contains {}", tiles.len());
-    }
+    CapacityError::check(tiles.len(), TILE_COUNT)?;
     let tile_kind = tiles.tile_kind()?;
     let img_dim = tile_kind.avatar_image_dimensions();
     let mut image = Image::new(img_dim.width(), img_dim.height());
     for (tile_index, tile) in tiles[0..TILE_COUNT].iter().enumerate() {
         let tile_y = tile_index as u32 * tile_kind.dimensions().height;
-        image.copy_from(tile.image(), 0, tile_y).unwrap();
+        image.copy_from(tile.image(), 0, tile_y).map_err(|error| SaveError::TileCopyError { index: tile_index, error })?;
     }
     image.write_image_file(path)?;
     Ok(())
@@ -1,12 +1,13 @@
 
 use std::path::{Path, PathBuf};
-use std::io::{Error as IOError, Read, Seek, Write};
+use std::io::{Cursor, Error as IOError, Read, Seek, Write};
 
 use derive_more::From;
 use thiserror::Error;
 use getset::Getters;
 use strum::{IntoEnumIterator, Display};
-use fs_err::File;
+use fs_err::{File, OpenOptions};
+use flate2::{Compression, read::GzDecoder, write::GzEncoder};
 
 use super::tile::{
     self,
@@ -14,17 +15,64 @@ use super::tile::{
     Kind as TileKind,
     grid::Grid as TileGrid,
     container::{
+        CapacityError,
         into_tile_grid::IntoTileGrid,
         tile_set::TileSet,
-        uniq_tile_kind::UniqTileKind,
+        uniq_tile_kind::{TileKindError, UniqTileKind},
+        save_to_bin_file::{save_page_to_bin_file, SaveTilesToBinFileError},
     },
 };
 
+use crate::osd::name_template::NameTemplate;
 use crate::osd::tile::InvalidSizeError;
+use crate::atomic_file::AtomicFile;
+use crate::stdio::is_stdio_path;
 
 
 pub const TILE_COUNT: usize = 256;
 
+// the bytes of the SD and HD pages of a tile set
+pub type SetPages = (Vec<Vec<u8>>, Vec<Vec<u8>>);
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+// stands for the path in the messages and errors about the bin files not read from a file
+const IN_MEMORY_NAME: &str = "<bin file bytes>";
+
+pub fn is_gzip_path<P: AsRef<Path>>(path: P) -> bool {
+    matches!(path.as_ref().extension(), Some(extension) if extension == "gz")
+}
+
+// raw bin files can start with the gzip magic bytes, they are only looked at when the size is not a valid bin size
+fn is_gzip_data<P: AsRef<Path>>(path: P, size: u64, start: &[u8]) -> bool {
+    is_gzip_path(path) || (TileKind::for_bin_file_size_bytes(size).is_err() && start.starts_with(&GZIP_MAGIC))
+}
+
+fn is_gzip_file<P: AsRef<Path>>(path: P, file: &mut File) -> Result<bool, IOError> {
+    let mut magic = [0; 2];
+    let magic_size = file.read(&mut magic)?;
+    file.rewind()?;
+    Ok(is_gzip_data(path, file.metadata()?.len(), &magic[..magic_size]))
+}
+
+fn gzip_path<P: AsRef<Path>>(path: P) -> PathBuf {
+    let mut path = path.as_ref().as_os_str().to_owned();
+    path.push(".gz");
+    PathBuf::from(path)
+}
+
+// the largest valid bin file size plus one byte: more is never read from a gzip stream so that a decompression bomb
+// fails the size check instead of filling the memory
+fn max_decompressed_size_bytes() -> u64 {
+    TileKind::iter().map(|kind| kind.bin_file_size_bytes() as u64).max().unwrap_or_default() + 1
+}
+
+fn decompress<R: Read>(reader: R) -> Result<Vec<u8>, IOError> {
+    let mut data = vec![];
+    GzDecoder::new(reader).take(max_decompressed_size_bytes()).read_to_end(&mut data)?;
+    Ok(data)
+}
+
 impl TileKind {
 
     pub fn bin_file_size_bytes(&self) -> usize {
@@ -93,6 +141,8 @@ pub enum LoadError {
     LoadedTileKindDoesNotMatchRequested { file_path: PathBuf, loaded: TileKind, requested: TileKind },
     #[error("File size does not match a valid bin file size: file {file_path}, size {size}B")]
     WrongSizeError { file_path: PathBuf, size: u64 },
+    #[error(transparent)]
+    TileKindError(TileKindError),
 }
 
 impl LoadError {
@@ -114,10 +164,35 @@ pub enum SeekFrom {
     Current(isize)
 }
 
+// gzip compressed files are decompressed in memory since seeking inside the gzip stream is not supported,
+// stdin is read in memory as well
+enum BinFileSource {
+    File(File),
+    Memory(Cursor<Vec<u8>>),
+}
+
+impl Read for BinFileSource {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            BinFileSource::File(file) => file.read(buf),
+            BinFileSource::Memory(data) => data.read(buf),
+        }
+    }
+}
+
+impl Seek for BinFileSource {
+    fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+        match self {
+            BinFileSource::File(file) => file.seek(pos),
+            BinFileSource::Memory(data) => data.seek(pos),
+        }
+    }
+}
+
 #[derive(Getters)]
 pub struct BinFileReader {
     file_path: PathBuf,
-    file: File,
+    file: BinFileSource,
 
     #[getset(get = "pub")]
     tile_kind: tile::Kind,
@@ -128,14 +203,48 @@ pub struct BinFileReader {
 
 impl BinFileReader {
 
+    // the file is detected as gzip compressed from its extension or, when its size is not a valid bin size, from its
+    // magic bytes, the size is checked once decompressed
     pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, OpenError> {
-        let file = File::open(&path)?;
-        let tile_kind = tile::Kind::for_bin_file_size_bytes(file.metadata().unwrap().len())
+        if is_stdio_path(&path) {
+            return Self::from_reader(path, std::io::stdin().lock())
+        }
+        let mut file = File::open(&path)?;
+        let is_gzip = is_gzip_file(&path, &mut file)?;
+        let (file, size) = if is_gzip {
+            let data = decompress(file)?;
+            let size = data.len() as u64;
+            (BinFileSource::Memory(Cursor::new(data)), size)
+        } else {
+            let size = file.metadata()?.len();
+            (BinFileSource::File(file), size)
+        };
+        Self::from_source(path, file, size)
+    }
+
+    // bin file read from any source e.g. an in-memory buffer or an archive entry, `name` stands for the path in the
+    // messages and errors, the content is read in memory and decompressed like the files given to `open`
+    pub fn from_reader<P: AsRef<Path>, R: Read>(name: P, mut reader: R) -> Result<Self, OpenError> {
+        let mut data = vec![];
+        reader.read_to_end(&mut data)?;
+        Self::from_bytes(name, data)
+    }
+
+    pub fn from_bytes<P: AsRef<Path>>(name: P, mut data: Vec<u8>) -> Result<Self, OpenError> {
+        if is_gzip_data(&name, data.len() as u64, &data) {
+            data = decompress(data.as_slice())?;
+        }
+        let size = data.len() as u64;
+        Self::from_source(name, BinFileSource::Memory(Cursor::new(data)), size)
+    }
+
+    fn from_source<P: AsRef<Path>>(path: P, file: BinFileSource, size: u64) -> Result<Self, OpenError> {
+        let tile_kind = tile::Kind::for_bin_file_size_bytes(size)
             .map_err(|error| {
                 let InvalidSizeError(size) = error;
                 OpenError::invalid_size(&path, size)
             })?;
-        log::info!("detected {} kind of tiles in {}", tile_kind, path.as_ref().to_string_lossy());
+        log::debug!("detected {} kind of tiles in {}", tile_kind, path.as_ref().to_string_lossy());
         Ok(Self { file, file_path: path.as_ref().to_path_buf(), tile_kind, pos: 0 })
     }
 
@@ -166,8 +275,8 @@ impl BinFileReader {
         if new_pos < 0 || new_pos >= TILE_COUNT as isize {
             return Err(SeekError::out_of_bounds(&self.file_path, new_pos));
         }
-        let new_pos= new_pos * self.tile_kind.raw_rgba_size_bytes() as isize;
-        self.file.seek(std::io::SeekFrom::Start(new_pos as u64))?;
+        let new_byte_pos = new_pos as u64 * self.tile_kind.raw_rgba_size_bytes() as u64;
+        self.file.seek(std::io::SeekFrom::Start(new_byte_pos))?;
         self.pos = new_pos as usize;
         Ok(self.pos)
     }
@@ -218,59 +327,221 @@ impl IntoIterator for BinFileReader {
     }
 }
 
-pub fn load<P: AsRef<Path>>(path: P) -> Result<Vec<Tile>, LoadError> {
-    Ok(BinFileReader::open(path)?.read_tiles()?)
+#[cfg(feature = "fs")]
+enum LazyData {
+    Mapped(memmap2::Mmap),
+    Memory(Vec<u8>),
+}
+
+#[cfg(feature = "fs")]
+impl std::ops::Deref for LazyData {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            LazyData::Mapped(mmap) => mmap,
+            LazyData::Memory(data) => data,
+        }
+    }
+}
+
+// Bin file reader decoding the tiles on demand from the memory mapped file, for the commands only looking at some
+// tiles or at the raw bytes of the tiles. The gzip compressed files and stdin are decompressed in memory instead.
+#[cfg(feature = "fs")]
+pub struct LazyReader {
+    file_path: PathBuf,
+    data: LazyData,
+    tile_kind: TileKind,
+}
+
+#[cfg(feature = "fs")]
+impl LazyReader {
+
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, OpenError> {
+        if is_stdio_path(&path) {
+            let mut data = vec![];
+            std::io::stdin().lock().read_to_end(&mut data)?;
+            return Self::from_data(path, LazyData::Memory(data))
+        }
+        let mut file = File::open(&path)?;
+        let is_gzip = is_gzip_file(&path, &mut file)?;
+        if is_gzip {
+            return Self::from_data(path, LazyData::Memory(decompress(file)?))
+        }
+        // checked before mapping since empty files cannot be mapped
+        let size = file.metadata()?.len();
+        tile::Kind::for_bin_file_size_bytes(size).map_err(|_| OpenError::invalid_size(&path, size))?;
+        // safety: the file is only read, it is expected not to be modified by another process while it is mapped
+        #[allow(unsafe_code)]
+        let mmap = unsafe { memmap2::Mmap::map(file.file())? };
+        Self::from_data(path, LazyData::Mapped(mmap))
+    }
+
+    fn from_data<P: AsRef<Path>>(path: P, data: LazyData) -> Result<Self, OpenError> {
+        let tile_kind = tile::Kind::for_bin_file_size_bytes(data.len() as u64)
+            .map_err(|_| OpenError::invalid_size(&path, data.len() as u64))?;
+        Ok(Self { file_path: path.as_ref().to_path_buf(), data, tile_kind })
+    }
+
+    pub fn file_path(&self) -> &Path {
+        &self.file_path
+    }
+
+    pub fn tile_kind(&self) -> TileKind {
+        self.tile_kind
+    }
+
+    pub fn tile_count(&self) -> usize {
+        TILE_COUNT
+    }
+
+    // raw RGBA bytes of the tile, none when the index is out of range
+    pub fn tile_bytes(&self, index: usize) -> Option<&[u8]> {
+        let size = self.tile_kind.raw_rgba_size_bytes();
+        self.data.get(index * size..(index + 1) * size)
+    }
+
+    pub fn raw_tiles(&self) -> impl Iterator<Item = &[u8]> {
+        self.data.chunks(self.tile_kind.raw_rgba_size_bytes())
+    }
+
+    // only this tile is decoded
+    pub fn tile(&self, index: usize) -> Option<Tile> {
+        self.tile_bytes(index).map(|bytes| Tile::try_from(bytes.to_vec()).unwrap())
+    }
+
+    // checks the alpha bytes without decoding the tile
+    pub fn is_blank(&self, index: usize) -> Option<bool> {
+        self.tile_bytes(index).map(|bytes| bytes.chunks(4).all(|pixel| pixel[3] == 0))
+    }
+
+    // decodes every tile
+    pub fn tiles(&self) -> Vec<Tile> {
+        (0..TILE_COUNT).filter_map(|index| self.tile(index)).collect()
+    }
+
+}
+
+// loads a single page, the tile kind is inferred from the file size and has to match the requested one if any
+pub fn load<P: AsRef<Path>>(path: P, tile_kind: Option<TileKind>) -> Result<Vec<Tile>, LoadError> {
+    read_checked(BinFileReader::open(&path)?, path, tile_kind)
+}
+
+// in-memory counterpart of `load`, the bytes may be gzip compressed
+pub fn from_bytes(bytes: &[u8], tile_kind: Option<TileKind>) -> Result<Vec<Tile>, LoadError> {
+    read_checked(BinFileReader::from_bytes(IN_MEMORY_NAME, bytes.to_vec())?, IN_MEMORY_NAME, tile_kind)
+}
+
+fn read_checked<P: AsRef<Path>>(reader: BinFileReader, path: P, tile_kind: Option<TileKind>) -> Result<Vec<Tile>, LoadError> {
+    let loaded_tile_kind = *reader.tile_kind();
+    if let Some(requested_tile_kind) = tile_kind {
+        if loaded_tile_kind != requested_tile_kind {
+            return Err(LoadError::tile_kind_mismatch(&path, loaded_tile_kind, requested_tile_kind));
+        }
+    }
+    Ok(reader.read_tiles()?)
+}
+
+// saves a single page, tiles have to be of the requested kind and the remaining space is filled with blank tiles
+pub fn save<P: AsRef<Path>>(path: P, tile_kind: TileKind, tiles: &[Tile]) -> Result<(), SaveTilesToBinFileError> {
+    if let Some(tile) = tiles.iter().find(|tile| tile.kind() != tile_kind) {
+        return Err(TileKindError::LoadedDoesNotMatchRequested { requested: tile_kind, loaded: tile.kind() }.into());
+    }
+    CapacityError::check(tiles.len(), TILE_COUNT)?;
+    save_page_to_bin_file(tiles, tile_kind, path)
+}
+
+// in-memory counterpart of `save`, the bytes are not compressed
+pub fn to_bytes(tile_kind: TileKind, tiles: &[Tile]) -> Result<Vec<u8>, SaveTilesToBinFileError> {
+    if let Some(tile) = tiles.iter().find(|tile| tile.kind() != tile_kind) {
+        return Err(TileKindError::LoadedDoesNotMatchRequested { requested: tile_kind, loaded: tile.kind() }.into());
+    }
+    CapacityError::check(tiles.len(), TILE_COUNT)?;
+    let transparent_tile = Tile::new(tile_kind);
+    let mut bytes = Vec::with_capacity(tile_kind.bin_file_size_bytes());
+    for tile in tiles.iter().chain(std::iter::repeat(&transparent_tile).take(TILE_COUNT - tiles.len())) {
+        bytes.extend_from_slice(tile.as_raw());
+    }
+    Ok(bytes)
 }
 
 pub fn load_norm<P: AsRef<Path>>(dir: P, tile_kind: TileKind, ident: &Option<&str>, part: FontPart) -> Result<Vec<Tile>, LoadError> {
-    let file_path = normalized_file_path(&dir, tile_kind, ident, part);
-    let tiles = load(&file_path)?;
-    let loaded_tile_kind = tiles.tile_kind().unwrap();
-    if loaded_tile_kind != tile_kind {
-        return Err(LoadError::tile_kind_mismatch(&file_path, loaded_tile_kind, tile_kind));
+    load_page_norm(dir, tile_kind, ident, part.page_index())
+}
+
+pub fn load_page_norm<P: AsRef<Path>>(dir: P, tile_kind: TileKind, ident: &Option<&str>, page_index: usize) -> Result<Vec<Tile>, LoadError> {
+    load(find_page_file_path(&dir, tile_kind, ident, page_index), Some(tile_kind))
+}
+
+// loads every page with a normalized name from the first one until a page file is missing, the first page is required
+pub fn load_pages_norm<P: AsRef<Path>>(dir: P, tile_kind: TileKind, ident: &Option<&str>) -> Result<Vec<Tile>, LoadError> {
+    let mut tiles = load_page_norm(&dir, tile_kind, ident, 0)?;
+    for page_index in 1.. {
+        match load_page_norm(&dir, tile_kind, ident, page_index) {
+            Ok(page_tiles) => tiles.extend(page_tiles),
+            Err(error) if error.because_file_is_missing() => break,
+            Err(error) => return Err(error),
+        }
     }
     Ok(tiles)
 }
 
-pub fn load_extended<P: AsRef<Path>>(base_path: P, ext_path: P) -> Result<Vec<Tile>, LoadError> {
-    let base_tiles = load(&base_path)?;
-    let base_tile_kind = base_tiles.tile_kind().expect("should not fail for collections from bin files");
-    let ext_tiles = load(&ext_path)?;
-    let ext_tile_kind = ext_tiles.tile_kind().expect("should not fail for collections from bin files");
-    if ext_tile_kind != base_tile_kind {
-        return Err(LoadError::tile_kind_mismatch(&ext_path, ext_tile_kind, base_tile_kind))
+// same as load_pages_norm with the file names given by the template, the gzip compressed pages are not looked for
+pub fn load_pages_templated<P: AsRef<Path>>(dir: P, template: &NameTemplate, tile_kind: TileKind, ident: &Option<&str>) -> Result<Vec<Tile>, LoadError> {
+    let mut tiles = load(template.file_path(&dir, tile_kind, ident, 0), Some(tile_kind))?;
+    for page_index in 1.. {
+        match load(template.file_path(&dir, tile_kind, ident, page_index), Some(tile_kind)) {
+            Ok(page_tiles) => tiles.extend(page_tiles),
+            Err(error) if error.because_file_is_missing() => break,
+            Err(error) => return Err(error),
+        }
     }
-    let tiles = [base_tiles, ext_tiles].into_iter().flatten().collect();
     Ok(tiles)
 }
 
-pub fn load_extended_check_kind<P: AsRef<Path>>(base_path: P, ext_path: P, requested_tile_kind: TileKind) -> Result<Vec<Tile>, LoadError> {
-
-    fn check_tile_kind<P: AsRef<Path>>(file_path: P, tiles: &[Tile], expected_tile_kind: TileKind) -> Result<(), LoadError> {
-        let tile_kind = tiles.tile_kind().expect("should not fail for collections from bin files");
-        if tile_kind != expected_tile_kind {
-            return Err(LoadError::tile_kind_mismatch(file_path, tile_kind, expected_tile_kind))
-        }
-        Ok(())
+pub fn load_pages_check_kind<P: AsRef<Path>>(paths: &[P], requested_tile_kind: TileKind) -> Result<Vec<Tile>, LoadError> {
+    let mut tiles = vec![];
+    for path in paths {
+        tiles.extend(load(path, Some(requested_tile_kind))?);
     }
+    Ok(tiles)
+}
 
-    let base_tiles = load(&base_path)?;
-    check_tile_kind(&base_path, &base_tiles, requested_tile_kind)?;
-    let ext_tiles = load(&ext_path)?;
-    check_tile_kind(&ext_path, &ext_tiles, requested_tile_kind)?;
+pub fn load_extended<P: AsRef<Path>>(base_path: P, ext_path: P) -> Result<Vec<Tile>, LoadError> {
+    let base_tiles = load(&base_path, None)?;
+    let base_tile_kind = base_tiles.tile_kind()?;
+    let ext_tiles = load(&ext_path, Some(base_tile_kind))?;
     let tiles = [base_tiles, ext_tiles].into_iter().flatten().collect();
     Ok(tiles)
 }
 
+pub fn load_extended_check_kind<P: AsRef<Path>>(base_path: P, ext_path: P, requested_tile_kind: TileKind) -> Result<Vec<Tile>, LoadError> {
+    load_pages_check_kind(&[base_path, ext_path], requested_tile_kind)
+}
+
 pub enum FontPart {
     Base,
     Ext
 }
 
+impl FontPart {
+    pub const fn page_index(&self) -> usize {
+        match self {
+            FontPart::Base => 0,
+            FontPart::Ext => 1,
+        }
+    }
+}
+
 pub fn normalized_file_name(tile_kind: TileKind, ident: &Option<&str>, part: FontPart) -> PathBuf {
-    let font_part_str = match part {
-        FontPart::Base => "",
-        FontPart::Ext => "_2",
+    normalized_page_file_name(tile_kind, ident, part.page_index())
+}
+
+// the first page has no suffix and the following pages are suffixed with their 1-based number e.g. font_2.bin, font_3.bin
+pub fn normalized_page_file_name(tile_kind: TileKind, ident: &Option<&str>, page_index: usize) -> PathBuf {
+    let font_part_str = match page_index {
+        0 => "".to_owned(),
+        page_index => format!("_{}", page_index + 1),
     };
     let tile_kind_str = match tile_kind {
         TileKind::SD => "",
@@ -284,7 +555,29 @@ pub fn normalized_file_name(tile_kind: TileKind, ident: &Option<&str>, part: Fon
 }
 
 pub fn normalized_file_path<P: AsRef<Path>>(dir: P, tile_kind: TileKind, ident: &Option<&str>, part: FontPart) -> PathBuf {
-    [dir.as_ref().to_path_buf(), normalized_file_name(tile_kind, ident, part)].into_iter().collect()
+    normalized_page_file_path(dir, tile_kind, ident, part.page_index())
+}
+
+pub fn normalized_page_file_path<P: AsRef<Path>>(dir: P, tile_kind: TileKind, ident: &Option<&str>, page_index: usize) -> PathBuf {
+    [dir.as_ref().to_path_buf(), normalized_page_file_name(tile_kind, ident, page_index)].into_iter().collect()
+}
+
+// finds the page file either uncompressed or gzip compressed, the uncompressed one is preferred when both exist
+pub fn find_page_file_path<P: AsRef<Path>>(dir: P, tile_kind: TileKind, ident: &Option<&str>, page_index: usize) -> PathBuf {
+    let path = normalized_page_file_path(dir, tile_kind, ident, page_index);
+    let compressed_path = gzip_path(&path);
+    match (path.exists(), compressed_path.exists()) {
+        (true, true) => {
+            log::warn!("both {} and {} exist, using the uncompressed file", path.to_string_lossy(), compressed_path.to_string_lossy());
+            path
+        },
+        (false, true) => compressed_path,
+        _ => path,
+    }
+}
+
+pub fn page_count(tile_count: usize) -> usize {
+    tile_count.div_ceil(TILE_COUNT).max(1)
 }
 
 pub fn load_base_norm<P: AsRef<Path>>(dir: P, tile_kind: TileKind, ident: &Option<&str>) -> Result<Vec<Tile>, LoadError> {
@@ -300,28 +593,66 @@ pub fn load_extended_norm<P: AsRef<Path>>(dir: P, tile_kind: TileKind, ident: &O
 
 impl TileSet {
 
-    pub fn load_bin_files<P: AsRef<Path>>(sd_path: P, sd_2_path: P, hd_path: P, hd_2_path: P) -> Result<Self, LoadError> {
-        let sd_tiles = load_extended_check_kind(&sd_path, &sd_2_path, TileKind::SD)?;
-        let hd_tiles = load_extended_check_kind(&hd_path, &hd_2_path, TileKind::HD)?;
+    // each path is a page of tiles, pages are loaded in order
+    pub fn load_bin_files<P: AsRef<Path>>(sd_paths: &[P], hd_paths: &[P]) -> Result<Self, LoadError> {
+        let sd_tiles = load_pages_check_kind(sd_paths, TileKind::SD)?;
+        let hd_tiles = load_pages_check_kind(hd_paths, TileKind::HD)?;
+        Ok(Self { sd_tiles, hd_tiles })
+    }
+
+    pub fn load_bin_files_templated<P: AsRef<Path>>(dir: P, ident: &Option<&str>, template: &NameTemplate) -> Result<Self, LoadError> {
+        let sd_tiles = load_pages_templated(&dir, template, TileKind::SD, ident)?;
+        let hd_tiles = load_pages_templated(&dir, template, TileKind::HD, ident)?;
         Ok(Self { sd_tiles, hd_tiles })
     }
 
     pub fn load_bin_files_norm<P: AsRef<Path>>(dir: P, ident: &Option<&str>) -> Result<Self, LoadError> {
-        let sd_tiles = load_extended_norm(&dir, TileKind::SD, ident)?;
-        let hd_tiles = load_extended_norm(&dir, TileKind::HD, ident)?;
+        let sd_tiles = load_pages_norm(&dir, TileKind::SD, ident)?;
+        let hd_tiles = load_pages_norm(&dir, TileKind::HD, ident)?;
         Ok(Self { sd_tiles, hd_tiles })
     }
 
+    // in-memory counterpart of `load_bin_files`, each byte slice is a page
+    pub fn from_bin_bytes(sd_pages: &[&[u8]], hd_pages: &[&[u8]]) -> Result<Self, LoadError> {
+        let load_pages = |pages: &[&[u8]], tile_kind| -> Result<Vec<Tile>, LoadError> {
+            let mut tiles = vec![];
+            for page in pages {
+                tiles.extend(from_bytes(page, Some(tile_kind))?);
+            }
+            Ok(tiles)
+        };
+        Ok(Self { sd_tiles: load_pages(sd_pages, TileKind::SD)?, hd_tiles: load_pages(hd_pages, TileKind::HD)? })
+    }
+
+    // in-memory counterpart of `save_to_bin_files_norm`, the SD and HD pages with the same number of pages for both kinds
+    pub fn to_bin_bytes(&self) -> Result<SetPages, SaveTilesToBinFileError> {
+        let page_count = page_count(self.sd_tiles.len().max(self.hd_tiles.len()));
+        let to_pages = |tiles: &[Tile], tile_kind| -> Result<Vec<Vec<u8>>, SaveTilesToBinFileError> {
+            let mut pages: Vec<&[Tile]> = tiles.chunks(TILE_COUNT).collect();
+            pages.resize(page_count, &[]);
+            pages.into_iter().map(|page| to_bytes(tile_kind, page)).collect()
+        };
+        Ok((to_pages(&self.sd_tiles, TileKind::SD)?, to_pages(&self.hd_tiles, TileKind::HD)?))
+    }
+
 }
 
-pub fn load_set<P: AsRef<Path>>(sd_path: P, sd_2_path: P, hd_path: P, hd_2_path: P) -> Result<TileSet, LoadError> {
-    TileSet::load_bin_files(sd_path, sd_2_path, hd_path, hd_2_path)
+pub fn load_set<P: AsRef<Path>>(sd_paths: &[P], hd_paths: &[P]) -> Result<TileSet, LoadError> {
+    TileSet::load_bin_files(sd_paths, hd_paths)
 }
 
 pub fn load_set_norm<P: AsRef<Path>>(dir: P, ident: &Option<&str>) -> Result<TileSet, LoadError> {
     TileSet::load_bin_files_norm(dir, ident)
 }
 
+pub fn set_from_bytes(sd_pages: &[&[u8]], hd_pages: &[&[u8]]) -> Result<TileSet, LoadError> {
+    TileSet::from_bin_bytes(sd_pages, hd_pages)
+}
+
+pub fn set_to_bytes(tile_set: &TileSet) -> Result<SetPages, SaveTilesToBinFileError> {
+    tile_set.to_bin_bytes()
+}
+
 #[derive(Debug, From, Error)]
 pub enum TileWriteError {
     #[error(transparent)]
@@ -348,20 +679,105 @@ pub enum FillRemainingSpaceError {
     Empty
 }
 
+enum BinFileSink {
+    File(File),
+    Compressed(Box<GzEncoder<File>>),
+    Writer(Box<dyn Write + Send + Sync>),
+}
+
+impl std::fmt::Debug for BinFileSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BinFileSink::File(file) => f.debug_tuple("File").field(file).finish(),
+            BinFileSink::Compressed(encoder) => f.debug_tuple("Compressed").field(encoder).finish(),
+            BinFileSink::Writer(_) => f.debug_tuple("Writer").finish(),
+        }
+    }
+}
+
+impl BinFileSink {
+    fn close(self) -> Result<(), IOError> {
+        match self {
+            BinFileSink::File(file) => file.close(),
+            BinFileSink::Compressed(encoder) => encoder.finish()?.close(),
+            BinFileSink::Writer(mut writer) => writer.flush(),
+        }
+    }
+}
+
+impl Write for BinFileSink {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            BinFileSink::File(file) => file.write(buf),
+            BinFileSink::Compressed(encoder) => encoder.write(buf),
+            BinFileSink::Writer(writer) => writer.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            BinFileSink::File(file) => file.flush(),
+            BinFileSink::Compressed(encoder) => encoder.flush(),
+            BinFileSink::Writer(writer) => writer.flush(),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct BinFileWriter {
-    file: File,
+    file: BinFileSink,
     tile_count: usize,
     tile_kind: Option<TileKind>,
+    // file written to a temporary path and renamed to its final path by `finish`
+    atomic_file: Option<AtomicFile>,
 }
 
 impl BinFileWriter {
 
+    // the output is gzip compressed when the path ends with .gz, written to stdout when the path is `-`, an existing
+    // file is only replaced once the writer is finished
     pub fn create<P: AsRef<Path>>(path: P) -> Result<Self, IOError> {
+        if is_stdio_path(&path) {
+            return Ok(Self::from_writer(std::io::stdout()))
+        }
+        let atomic_file = AtomicFile::new(&path);
+        let file = File::create(atomic_file.temp_path())?;
+        let file = if is_gzip_path(&path) {
+            BinFileSink::Compressed(Box::new(GzEncoder::new(file, Compression::default())))
+        } else {
+            BinFileSink::File(file)
+        };
+        Ok(Self {
+            file,
+            tile_count: 0,
+            tile_kind: None,
+            atomic_file: Some(atomic_file),
+        })
+    }
+
+    // bin file written to any destination e.g. an in-memory buffer or an archive entry
+    pub fn from_writer<W: Write + Send + Sync + 'static>(writer: W) -> Self {
+        Self {
+            file: BinFileSink::Writer(Box::new(writer)),
+            tile_count: 0,
+            tile_kind: None,
+            atomic_file: None,
+        }
+    }
+
+    // same as create but fails if the file already exists instead of overwriting it
+    pub fn create_new<P: AsRef<Path>>(path: P) -> Result<Self, IOError> {
+        let file = OpenOptions::new().write(true).create_new(true).open(&path)?;
+        let file = if is_gzip_path(&path) {
+            BinFileSink::Compressed(Box::new(GzEncoder::new(file, Compression::default())))
+        } else {
+            BinFileSink::File(file)
+        };
         Ok(Self {
-            file: File::create(path)?,
+            file,
             tile_count: 0,
-            tile_kind: None
+            tile_kind: None,
+            atomic_file: None,
         })
     }
 
@@ -398,7 +814,381 @@ impl BinFileWriter {
             return Err(TileWriteError::NotEnoughTiles(self));
         }
         self.file.close()?;
+        if let Some(atomic_file) = self.atomic_file {
+            atomic_file.persist()?;
+        }
         Ok(())
     }
 
-}
\ No newline at end of file
+}
+
+#[derive(Debug, From, Error)]
+pub enum WriterError {
+    #[error(transparent)]
+    CreateError(IOError),
+    #[error(transparent)]
+    TileWriteError(TileWriteError),
+    #[error(transparent)]
+    FillRemainingSpaceError(FillRemainingSpaceError),
+    #[from(ignore)]
+    #[error("the tiles do not fit in the {page_count} bin file pages of the writer ({} tiles maximum)", page_count * TILE_COUNT)]
+    TooManyTiles { page_count: usize },
+}
+
+enum PagePaths {
+    List(Vec<PathBuf>),
+    Norm { dir: PathBuf, ident: Option<String> },
+}
+
+impl PagePaths {
+    fn page_path(&self, tile_kind: TileKind, page_index: usize) -> Option<PathBuf> {
+        match self {
+            PagePaths::List(paths) => paths.get(page_index).cloned(),
+            PagePaths::Norm { dir, ident } => Some(normalized_page_file_path(dir, tile_kind, &ident.as_deref(), page_index)),
+        }
+    }
+}
+
+// Streaming writer of the bin file pages of a font: the tiles are written one at a time as they are pushed, a page file
+// being created when its first tile is pushed, so that generators do not need to hold the whole font in memory.
+// `close` completes the last page with transparent tiles, a font without any tile is written as a transparent page.
+pub struct Writer {
+    page_paths: PagePaths,
+    tile_kind: TileKind,
+    page: Option<BinFileWriter>,
+    tile_count: usize,
+}
+
+impl Writer {
+
+    // each path is a page, pushing more tiles than the pages can hold is an error
+    pub fn create_pages<P: AsRef<Path>>(paths: &[P], tile_kind: TileKind) -> Self {
+        let paths = paths.iter().map(|path| path.as_ref().to_path_buf()).collect();
+        Self { page_paths: PagePaths::List(paths), tile_kind, page: None, tile_count: 0 }
+    }
+
+    // pages with the normalized names in `dir`, as many as needed
+    pub fn create_norm<P: AsRef<Path>>(dir: P, tile_kind: TileKind, ident: &Option<&str>) -> Self {
+        let page_paths = PagePaths::Norm { dir: dir.as_ref().to_path_buf(), ident: ident.map(str::to_owned) };
+        Self { page_paths, tile_kind, page: None, tile_count: 0 }
+    }
+
+    pub fn tile_count(&self) -> usize {
+        self.tile_count
+    }
+
+    fn open_page(&mut self, page_index: usize) -> Result<BinFileWriter, WriterError> {
+        let path = self.page_paths.page_path(self.tile_kind, page_index).ok_or(WriterError::TooManyTiles { page_count: page_index })?;
+        Ok(BinFileWriter::create(path)?)
+    }
+
+    pub fn push_tile(&mut self, tile: &Tile) -> Result<(), WriterError> {
+        if tile.kind() != self.tile_kind {
+            return Err(TileWriteError::TileKindMismatchError { written_kind: self.tile_kind, writing_kind: tile.kind() }.into());
+        }
+        if self.tile_count % TILE_COUNT == 0 {
+            if let Some(page) = self.page.take() {
+                page.finish()?;
+            }
+            self.page = Some(self.open_page(self.tile_count / TILE_COUNT)?);
+        }
+        self.page.as_mut().unwrap().write_tile(tile)?;
+        self.tile_count += 1;
+        Ok(())
+    }
+
+    pub fn close(mut self) -> Result<(), WriterError> {
+        let mut page = match self.page.take() {
+            Some(page) => page,
+            // pushing a tile failed after the last page was finished, the pages written are complete
+            None if self.tile_count > 0 => return Ok(()),
+            None => {
+                let mut page = self.open_page(0)?;
+                page.write_tile(&Tile::new(self.tile_kind))?;
+                page
+            },
+        };
+        page.fill_remaining_space()?;
+        page.finish()?;
+        Ok(())
+    }
+
+}
+
+#[derive(Debug, From, Error)]
+pub enum WriteTileAtError {
+    #[error(transparent)]
+    FileError(IOError),
+    #[from(ignore)]
+    #[error("cannot write a tile in place in the gzip compressed file {0}")]
+    Compressed(PathBuf),
+    #[from(ignore)]
+    #[error("file {file_path} has a size ({size}B) which does not match a valid bin file size")]
+    InvalidSize { file_path: PathBuf, size: u64 },
+    #[from(ignore)]
+    #[error("cannot write a {writing_kind} tile in {file_path} which contains {file_kind} tiles")]
+    TileKindMismatch { file_path: PathBuf, file_kind: TileKind, writing_kind: TileKind },
+    #[from(ignore)]
+    #[error("tile index {0} out of range: a bin file contains 256 tiles")]
+    IndexOutOfRange(usize),
+}
+
+// replaces a single tile of an existing bin file keeping the other tiles
+pub fn write_tile_at<P: AsRef<Path>>(path: P, index: usize, tile: &Tile) -> Result<(), WriteTileAtError> {
+    let file_path = path.as_ref().to_path_buf();
+    if is_gzip_path(&path) {
+        return Err(WriteTileAtError::Compressed(file_path));
+    }
+    if index >= TILE_COUNT {
+        return Err(WriteTileAtError::IndexOutOfRange(index));
+    }
+    // opened for writing so that a file which can not be modified is reported as such instead of being replaced
+    let mut file = OpenOptions::new().read(true).write(true).open(&path)?;
+    let mut bytes = vec![];
+    file.read_to_end(&mut bytes)?;
+    drop(file);
+    let size = bytes.len() as u64;
+    let file_kind = TileKind::for_bin_file_size_bytes(size).map_err(|_| WriteTileAtError::InvalidSize { file_path: file_path.clone(), size })?;
+    if file_kind != tile.kind() {
+        return Err(WriteTileAtError::TileKindMismatch { file_path, file_kind, writing_kind: tile.kind() });
+    }
+    let offset = index * file_kind.raw_rgba_size_bytes();
+    bytes[offset..offset + file_kind.raw_rgba_size_bytes()].copy_from_slice(tile.as_raw());
+    crate::atomic_file::write(&path, bytes)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::{Compression, write::GzEncoder};
+    use image::Rgba;
+    use temp_dir::TempDir;
+
+    use crate::osd::tile::{
+        Tile,
+        Kind as TileKind,
+        container::{
+            stats::is_blank,
+            save_to_bin_file::{Padding, SaveTilesToBinFileError},
+            tile_set::TileSet,
+        },
+    };
+
+    use super::{max_decompressed_size_bytes, load, load_page_norm, load_set_norm, load_pages_norm, save, to_bytes, write_tile_at, BinFileReader, BinFileWriter, LoadError, OpenError, WriteTileAtError, Writer, WriterError, TILE_COUNT, GZIP_MAGIC, IN_MEMORY_NAME};
+    #[cfg(feature = "fs")]
+    use super::LazyReader;
+
+    fn page_slices(pages: &[Vec<u8>]) -> Vec<&[u8]> {
+        pages.iter().map(Vec::as_slice).collect()
+    }
+
+    #[test]
+    fn set_bytes_round_trip() {
+        let mut glyph = Tile::new(TileKind::HD);
+        glyph.put_pixel(2, 3, Rgba([255, 255, 255, 255]));
+        let tile_set = TileSet::try_from_tiles(vec![Tile::new(TileKind::SD); 2], vec![glyph; TILE_COUNT + 1]).unwrap();
+        let (sd_pages, hd_pages) = tile_set.to_bin_bytes().unwrap();
+        assert_eq!((sd_pages.len(), hd_pages.len()), (2, 2));
+        assert_eq!(hd_pages[0].len(), TileKind::HD.bin_file_size_bytes());
+
+        let loaded = TileSet::from_bin_bytes(&page_slices(&sd_pages), &page_slices(&hd_pages)).unwrap();
+        assert_eq!(loaded.hd_tiles()[TILE_COUNT].as_raw(), tile_set.hd_tiles()[TILE_COUNT].as_raw());
+        assert!(is_blank(&loaded.hd_tiles()[TILE_COUNT + 1]));
+        assert!(matches!(TileSet::from_bin_bytes(&page_slices(&hd_pages), &[]), Err(LoadError::LoadedTileKindDoesNotMatchRequested { .. })));
+        assert!(to_bytes(TileKind::SD, tile_set.hd_tiles()).is_err());
+    }
+
+    #[test]
+    fn in_memory() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.child("font.bin");
+        let mut glyph = Tile::new(TileKind::SD);
+        glyph.put_pixel(2, 3, Rgba([255, 255, 255, 255]));
+        let mut writer = BinFileWriter::from_writer(std::fs::File::create(&path).unwrap());
+        writer.write_tile(&glyph).unwrap();
+        writer.fill_remaining_space().unwrap();
+        writer.finish().unwrap();
+
+        let tiles = BinFileReader::from_bytes("font.bin", std::fs::read(&path).unwrap()).unwrap().read_tiles().unwrap();
+        assert_eq!(tiles.len(), TILE_COUNT);
+        assert_eq!(tiles[0].as_raw(), glyph.as_raw());
+        match BinFileReader::from_bytes("truncated.bin", vec![0; 10]) {
+            Err(OpenError::InvalidSizeError { file_path, size }) => assert_eq!((file_path.to_str().unwrap(), size), ("truncated.bin", 10)),
+            _ => panic!("a truncated bin file should be rejected"),
+        }
+    }
+
+    #[test]
+    fn single_page_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.child("font_hd.bin");
+        let mut glyph = Tile::new(TileKind::HD);
+        glyph.put_pixel(5, 5, Rgba([255, 255, 255, 255]));
+
+        save(&path, TileKind::HD, &[Tile::new(TileKind::HD), glyph.clone()]).unwrap();
+        assert!(save(&path, TileKind::SD, &[glyph.clone()]).is_err());
+
+        let tiles = load(&path, None).unwrap();
+        assert_eq!(tiles.len(), TILE_COUNT);
+        assert_eq!(tiles[1].as_raw(), glyph.as_raw());
+        assert!(tiles.iter().all(|tile| tile.kind() == TileKind::HD));
+        assert!(matches!(load(&path, Some(TileKind::SD)), Err(LoadError::LoadedTileKindDoesNotMatchRequested { .. })));
+    }
+
+    #[test]
+    fn gzip_compressed_page() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut glyph = Tile::new(TileKind::SD);
+        glyph.put_pixel(5, 5, Rgba([255, 255, 255, 255]));
+        save(temp_dir.child("font_test.bin.gz"), TileKind::SD, &[glyph.clone()]).unwrap();
+        assert!(std::fs::metadata(temp_dir.child("font_test.bin.gz")).unwrap().len() < TileKind::SD.bin_file_size_bytes() as u64);
+
+        let tiles = load_page_norm(temp_dir.path(), TileKind::SD, &Some("test"), 0).unwrap();
+        assert_eq!(tiles.len(), TILE_COUNT);
+        assert_eq!(tiles[0].as_raw(), glyph.as_raw());
+    }
+
+    #[test]
+    fn gzip_decompression_limit() {
+        let mut encoder = GzEncoder::new(vec![], Compression::default());
+        encoder.write_all(&vec![0; 64 * TileKind::SD.bin_file_size_bytes()]).unwrap();
+        let compressed = encoder.finish().unwrap();
+        let error = BinFileReader::from_bytes("bomb.bin.gz", compressed).err().unwrap();
+        assert!(matches!(error, OpenError::InvalidSizeError { size, .. } if size == max_decompressed_size_bytes()));
+    }
+
+    #[test]
+    fn raw_page_starting_with_gzip_magic() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.child("font.bin");
+        let mut glyph = Tile::new(TileKind::SD);
+        glyph.put_pixel(0, 0, Rgba([GZIP_MAGIC[0], GZIP_MAGIC[1], 0, 255]));
+        save(&path, TileKind::SD, &[glyph.clone()]).unwrap();
+        assert!(std::fs::read(&path).unwrap().starts_with(&GZIP_MAGIC));
+
+        assert_eq!(load(&path, None).unwrap()[0].as_raw(), glyph.as_raw());
+        let mut reader = BinFileReader::from_bytes(IN_MEMORY_NAME, std::fs::read(&path).unwrap()).unwrap();
+        assert_eq!(reader.read_tile().unwrap().as_raw(), glyph.as_raw());
+    }
+
+    #[test]
+    fn in_place_tile_write() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.child("font.bin");
+        let mut glyph = Tile::new(TileKind::SD);
+        glyph.put_pixel(5, 5, Rgba([255, 255, 255, 255]));
+
+        save(&path, TileKind::SD, &[glyph.clone(), glyph.clone()]).unwrap();
+        assert_eq!(BinFileWriter::create_new(&path).unwrap_err().kind(), std::io::ErrorKind::AlreadyExists);
+
+        write_tile_at(&path, 1, &Tile::new(TileKind::SD)).unwrap();
+        write_tile_at(&path, 200, &glyph).unwrap();
+        let tiles = load(&path, None).unwrap();
+        assert_eq!(tiles.len(), TILE_COUNT);
+        assert_eq!(tiles[0].image(), glyph.image());
+        assert!(tiles[1].pixels().all(|pixel| pixel.0[3] == 0));
+        assert_eq!(tiles[200].image(), glyph.image());
+
+        assert!(write_tile_at(&path, 0, &Tile::new(TileKind::HD)).is_err());
+        assert!(write_tile_at(&path, TILE_COUNT, &glyph).is_err());
+        assert!(write_tile_at(temp_dir.child("missing.bin"), 0, &glyph).is_err());
+        assert!(!temp_dir.child("missing.bin").exists());
+    }
+
+    #[test]
+    fn in_place_tile_write_read_only() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.child("font.bin");
+        save(&path, TileKind::SD, &[Tile::new(TileKind::SD)]).unwrap();
+        let mut permissions = std::fs::metadata(&path).unwrap().permissions();
+        permissions.set_readonly(true);
+        std::fs::set_permissions(&path, permissions).unwrap();
+        // the permissions do not apply to a privileged user
+        if std::fs::OpenOptions::new().write(true).open(&path).is_ok() {
+            return;
+        }
+
+        let mut glyph = Tile::new(TileKind::SD);
+        glyph.put_pixel(5, 5, Rgba([255, 255, 255, 255]));
+        let error = write_tile_at(&path, 0, &glyph).unwrap_err();
+        assert!(matches!(&error, WriteTileAtError::FileError(error) if error.kind() == std::io::ErrorKind::PermissionDenied));
+        assert!(load(&path, None).unwrap()[0].pixels().all(|pixel| pixel.0[3] == 0));
+    }
+
+    #[test]
+    fn padded_tile_set() {
+        let temp_dir = TempDir::new().unwrap();
+        let glyph = |tile_kind| {
+            let mut tile = Tile::new(tile_kind);
+            tile.put_pixel(5, 5, Rgba([255, 255, 255, 255]));
+            tile
+        };
+        let tile_set = TileSet::try_from_tiles(vec![glyph(TileKind::SD); 300], vec![glyph(TileKind::HD); 100]).unwrap();
+
+        assert!(matches!(tile_set.save_to_bin_files_norm_padding(temp_dir.path(), &None, Padding::Strict),
+            Err(SaveTilesToBinFileError::TileCountMismatchError(_))));
+        tile_set.save_to_bin_files_norm_padding(temp_dir.path(), &None, Padding::Pad).unwrap();
+        let loaded = load_set_norm(temp_dir.path(), &None).unwrap();
+        for (tiles, glyph_count) in [(loaded.sd_tiles(), 300), (loaded.hd_tiles(), 100)] {
+            assert_eq!(tiles.len(), 2 * TILE_COUNT);
+            assert!(tiles[..glyph_count].iter().all(|tile| !is_blank(tile)));
+            assert!(tiles[glyph_count..].iter().all(is_blank));
+        }
+
+        let too_large = TileSet::try_from_tiles(vec![glyph(TileKind::SD); 600], vec![glyph(TileKind::HD); 600]).unwrap();
+        let paths = [temp_dir.child("a.bin"), temp_dir.child("b.bin")];
+        let error = too_large.save_to_bin_files(&paths, &paths).unwrap_err();
+        assert!(error.to_string().contains("source has 600 tiles but the target holds at most 512 tiles"));
+    }
+
+    #[test]
+    fn streaming_writer() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut glyph = Tile::new(TileKind::HD);
+        glyph.put_pixel(1, 1, Rgba([255, 255, 255, 255]));
+        let mut writer = Writer::create_norm(temp_dir.path(), TileKind::HD, &None);
+        for _ in 0..TILE_COUNT + 10 {
+            writer.push_tile(&glyph).unwrap();
+        }
+        assert!(writer.push_tile(&Tile::new(TileKind::SD)).is_err());
+        writer.close().unwrap();
+        let tiles = load_pages_norm(temp_dir.path(), TileKind::HD, &None).unwrap();
+        assert_eq!(tiles.len(), 2 * TILE_COUNT);
+        assert!(!is_blank(&tiles[TILE_COUNT + 9]));
+        assert!(is_blank(&tiles[TILE_COUNT + 10]));
+
+        let mut sd_glyph = Tile::new(TileKind::SD);
+        sd_glyph.put_pixel(1, 1, Rgba([255, 255, 255, 255]));
+        let mut writer = Writer::create_pages(&[temp_dir.child("single.bin")], TileKind::SD);
+        for _ in 0..TILE_COUNT {
+            writer.push_tile(&sd_glyph).unwrap();
+        }
+        assert!(matches!(writer.push_tile(&sd_glyph), Err(WriterError::TooManyTiles { page_count: 1 })));
+        // the full page is not overwritten when closing after the overflow
+        writer.close().unwrap();
+        let tiles = load(temp_dir.child("single.bin"), None).unwrap();
+        assert!(tiles.iter().all(|tile| tile.image() == sd_glyph.image()));
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn lazy_reader() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut glyph = Tile::new(TileKind::SD);
+        glyph.put_pixel(3, 4, Rgba([255, 255, 255, 255]));
+        for file_name in ["font.bin", "font.bin.gz"] {
+            let path = temp_dir.child(file_name);
+            save(&path, TileKind::SD, &[Tile::new(TileKind::SD), glyph.clone()]).unwrap();
+            let reader = LazyReader::open(&path).unwrap();
+            assert_eq!(reader.tile_kind(), TileKind::SD);
+            assert_eq!(reader.tile(1).unwrap().as_raw(), glyph.as_raw());
+            assert_eq!((reader.is_blank(0), reader.is_blank(1)), (Some(true), Some(false)));
+            assert!(reader.tile(TILE_COUNT).is_none());
+            assert_eq!(reader.raw_tiles().count(), TILE_COUNT);
+        }
+        std::fs::write(temp_dir.child("empty.bin"), []).unwrap();
+        assert!(matches!(LazyReader::open(temp_dir.child("empty.bin")), Err(OpenError::InvalidSizeError { .. })));
+    }
+}
@@ -0,0 +1,139 @@
+use std::path::{Path, PathBuf};
+
+use derive_more::From;
+use thiserror::Error;
+
+use super::{
+    bin_file,
+    ident_scan::{scan_idents, IdentFiles, IdentScan, ScanIdentsError},
+    tile::{Kind as TileKind, Tile, grid::{self, Grid}},
+};
+
+// Library of fonts in a user directory, $XDG_DATA_HOME/hd_fpv_osd_font_tool/fonts by default, holding bin files and
+// grid images with normalized names e.g. font_btfl_hd.bin, grid_inav.png. The fonts are resolved by ident and tile
+// kind so that the consumers e.g. OSD overlay renderers pick the variant matching the recording automatically. Like
+// the goggles, the font without ident e.g. font_hd.bin is used when the directory has no font for the ident.
+
+#[derive(Debug, Error)]
+pub enum OpenFontDirError {
+    #[error("cannot determine the default font directory: neither XDG_DATA_HOME nor HOME is set")]
+    NoDefaultDir,
+    #[error(transparent)]
+    ScanError(#[from] ScanIdentsError),
+}
+
+#[derive(Debug, Error, From)]
+pub enum LoadFontError {
+    #[from(ignore)]
+    #[error("no {tile_kind} font for ident {} in {}", .ident.as_deref().unwrap_or("<none>"), .dir.to_string_lossy())]
+    NotFound { dir: PathBuf, ident: Option<String>, tile_kind: TileKind },
+    #[error(transparent)]
+    BinFileLoadError(bin_file::LoadError),
+    #[error(transparent)]
+    GridLoadError(grid::LoadError),
+}
+
+#[derive(Debug, Clone)]
+pub struct FontDir {
+    path: PathBuf,
+    scan: IdentScan,
+}
+
+impl FontDir {
+
+    pub fn default_path() -> Option<PathBuf> {
+        let data_home = std::env::var_os("XDG_DATA_HOME").map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".local").join("share")))?;
+        Some(data_home.join("hd_fpv_osd_font_tool").join("fonts"))
+    }
+
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, ScanIdentsError> {
+        let scan = scan_idents(&path)?;
+        Ok(Self { path: path.as_ref().to_path_buf(), scan })
+    }
+
+    pub fn open_default() -> Result<Self, OpenFontDirError> {
+        Ok(Self::open(Self::default_path().ok_or(OpenFontDirError::NoDefaultDir)?)?)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    // None for the font without ident
+    pub fn idents(&self) -> impl Iterator<Item = Option<&str>> {
+        self.scan.idents().iter().map(|ident_files| ident_files.ident().as_deref())
+    }
+
+    fn ident_files(&self, ident: Option<&str>, tile_kind: TileKind) -> Option<&IdentFiles> {
+        self.scan.idents().iter()
+            .find(|ident_files| ident_files.ident().as_deref() == ident)
+            .filter(|ident_files| has_tile_kind(ident_files, tile_kind))
+    }
+
+    // ident of the font used for the requested ident after the fallback on the font without ident, None if no font is found
+    pub fn resolve(&self, ident: Option<&str>, tile_kind: TileKind) -> Option<Option<&str>> {
+        self.ident_files(ident, tile_kind).or_else(|| self.ident_files(None, tile_kind))
+            .map(|ident_files| ident_files.ident().as_deref())
+    }
+
+    // the bin files are preferred over the grid images when both are present
+    pub fn load(&self, ident: Option<&str>, tile_kind: TileKind) -> Result<Vec<Tile>, LoadFontError> {
+        let ident_files = self.ident_files(ident, tile_kind).or_else(|| self.ident_files(None, tile_kind))
+            .ok_or_else(|| LoadFontError::NotFound { dir: self.path.clone(), ident: ident.map(str::to_owned), tile_kind })?;
+        let bin_pages = match tile_kind {
+            TileKind::SD => ident_files.sd_bin_pages(),
+            TileKind::HD => ident_files.hd_bin_pages(),
+        };
+        if !bin_pages.is_empty() {
+            let mut tiles = vec![];
+            // pages are only taken up to the first missing one
+            for (page_index, (index, file_name)) in bin_pages.iter().enumerate() {
+                if page_index != *index {
+                    break
+                }
+                log::debug!("loading page {page_index} of the {tile_kind} font from {}", file_name.to_string_lossy());
+                tiles.extend(bin_file::load(self.path.join(file_name), Some(tile_kind))?);
+            }
+            return Ok(tiles)
+        }
+        let grids = match tile_kind {
+            TileKind::SD => ident_files.sd_grids(),
+            TileKind::HD => ident_files.hd_grids(),
+        };
+        Ok(Grid::load_from_image(self.path.join(&grids[0]))?.to_vec())
+    }
+
+}
+
+fn has_tile_kind(ident_files: &IdentFiles, tile_kind: TileKind) -> bool {
+    match tile_kind {
+        TileKind::SD => !ident_files.sd_bin_pages().is_empty() || !ident_files.sd_grids().is_empty(),
+        TileKind::HD => !ident_files.hd_bin_pages().is_empty() || !ident_files.hd_grids().is_empty(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use temp_dir::TempDir;
+
+    use crate::osd::tile::{Kind as TileKind, Tile, container::save_to_bin_file::SaveToBinFile};
+
+    use super::{FontDir, LoadFontError};
+
+    #[test]
+    fn resolve_and_load() {
+        let temp_dir = TempDir::new().unwrap();
+        let hd_tiles = vec![Tile::new(TileKind::HD); 256];
+        hd_tiles.save_to_bin_file(temp_dir.child("font_btfl_hd.bin")).unwrap();
+        hd_tiles.save_to_bin_file(temp_dir.child("font_hd.bin")).unwrap();
+
+        let font_dir = FontDir::open(temp_dir.path()).unwrap();
+        assert_eq!(font_dir.idents().collect::<Vec<_>>(), vec![None, Some("btfl")]);
+        assert_eq!(font_dir.resolve(Some("btfl"), TileKind::HD), Some(Some("btfl")));
+        assert_eq!(font_dir.resolve(Some("inav"), TileKind::HD), Some(None));
+        assert_eq!(font_dir.resolve(Some("btfl"), TileKind::SD), None);
+        assert_eq!(font_dir.load(Some("btfl"), TileKind::HD).unwrap().len(), 256);
+        assert!(matches!(font_dir.load(None, TileKind::SD), Err(LoadFontError::NotFound { .. })));
+    }
+}
@@ -0,0 +1,117 @@
+use std::io::Error as IOError;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::atomic_file;
+
+// Description of a font kept in a font.toml file at the root of the tile and symbol (set) directories e.g.
+//
+// name = "Conthrax"
+// author = "Jane Doe"
+// version = "1.2.0"
+// license = "CC-BY-4.0"
+// target_systems = ["dji", "walksnail"]
+//
+// The file is carried over by the conversions between directories and ignored when loading the tiles.
+
+pub const FILE_NAME: &str = "font.toml";
+
+#[derive(Debug, Error)]
+pub enum LoadFontMetadataError {
+    #[error(transparent)]
+    Read(#[from] IOError),
+    #[error("invalid font metadata file {}: {error}", .path.to_string_lossy())]
+    Parse { path: PathBuf, error: toml::de::Error },
+}
+
+#[derive(Debug, Error)]
+pub enum SaveFontMetadataError {
+    #[error(transparent)]
+    Write(#[from] IOError),
+    #[error("failed to serialize the font metadata: {0}")]
+    Serialize(#[from] toml::ser::Error),
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FontMetadata {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    // SPDX identifier or free form
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+    // goggles or firmware the font is made for e.g. dji, walksnail, hdzero
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub target_systems: Vec<String>,
+}
+
+pub fn path<P: AsRef<Path>>(dir: P) -> PathBuf {
+    dir.as_ref().join(FILE_NAME)
+}
+
+pub fn is_metadata_file<P: AsRef<Path>>(path: P) -> bool {
+    path.as_ref().file_name().is_some_and(|file_name| file_name == FILE_NAME)
+}
+
+impl FontMetadata {
+
+    // fields as (label, value) in file order, the empty ones left out
+    pub fn fields(&self) -> Vec<(&'static str, String)> {
+        [
+            ("name", self.name.clone()),
+            ("author", self.author.clone()),
+            ("version", self.version.clone()),
+            ("license", self.license.clone()),
+            ("target systems", (!self.target_systems.is_empty()).then(|| self.target_systems.join(", "))),
+        ].into_iter().filter_map(|(label, value)| value.map(|value| (label, value))).collect()
+    }
+
+    // None when the directory has no font.toml
+    pub fn load_from_dir<P: AsRef<Path>>(dir: P) -> Result<Option<Self>, LoadFontMetadataError> {
+        let path = path(dir);
+        if !path.is_file() {
+            return Ok(None);
+        }
+        let content = fs_err::read_to_string(&path)?;
+        toml::from_str(&content).map(Some).map_err(|error| LoadFontMetadataError::Parse { path, error })
+    }
+
+    pub fn save_to_dir<P: AsRef<Path>>(&self, dir: P) -> Result<(), SaveFontMetadataError> {
+        let content = toml::to_string(self)?;
+        atomic_file::write(path(dir), content)?;
+        Ok(())
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use temp_dir::TempDir;
+
+    use super::FontMetadata;
+
+    #[test]
+    fn save_load() {
+        let temp_dir = TempDir::new().unwrap();
+        assert_eq!(FontMetadata::load_from_dir(temp_dir.path()).unwrap(), None);
+        let metadata = FontMetadata {
+            name: Some("Conthrax".to_owned()),
+            version: Some("1.2.0".to_owned()),
+            target_systems: vec!["dji".to_owned(), "walksnail".to_owned()],
+            ..Default::default()
+        };
+        metadata.save_to_dir(temp_dir.path()).unwrap();
+        assert_eq!(FontMetadata::load_from_dir(temp_dir.path()).unwrap(), Some(metadata.clone()));
+        assert_eq!(metadata.fields(), vec![("name", "Conthrax".to_owned()), ("version", "1.2.0".to_owned()),
+            ("target systems", "dji, walksnail".to_owned())]);
+        std::fs::write(temp_dir.child("font.toml"), "nme = 'typo'\n").unwrap();
+        assert!(FontMetadata::load_from_dir(temp_dir.path()).is_err());
+    }
+
+}
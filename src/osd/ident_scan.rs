@@ -0,0 +1,232 @@
+
+use std::collections::BTreeMap;
+use std::io::Error as IOError;
+use std::path::{Path, PathBuf};
+
+use getset::Getters;
+use lazy_static::lazy_static;
+use regex::{Captures, Regex};
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::image_format::{ImageFormat, is_readable_image_path};
+use super::{bin_file, tile::{Kind as TileKind, grid::{self, Grid, ImageDimensions}}};
+
+// Lists the idents of the bin files and grid images with normalized names found in a directory
+
+
+#[derive(Debug, Error)]
+#[error("failed to list files from directory {dir_path}: {error}")]
+pub struct ScanIdentsError {
+    dir_path: PathBuf,
+    error: IOError,
+}
+
+impl ScanIdentsError {
+    fn new<P: AsRef<Path>>(dir_path: P, error: IOError) -> Self {
+        Self { dir_path: dir_path.as_ref().to_path_buf(), error }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum NormFile {
+    BinPage { ident: Option<String>, tile_kind: TileKind, page_index: usize },
+    Grid { ident: Option<String>, tile_kind: TileKind },
+}
+
+impl NormFile {
+
+    fn parse(file_name: &str) -> Option<Self> {
+        lazy_static! {
+            // the ident is matched lazily so that the HD and page suffixes are not taken as part of it,
+            // pages suffixes start at 2 so that font_1.bin is the SD base page of ident 1
+            static ref BIN_FILE_RE: Regex = Regex::new(r"\Afont(?:_(?P<ident>.+?))??(?P<hd>_hd)?(?:_(?P<page>[2-9]|[1-9]\d+))?\.bin(?:\.gz)?\z").unwrap();
+            static ref GRID_FILE_RE: Regex = Regex::new(r"\Agrid(?:_(?P<ident>.+?))??(?P<hd>_hd)?\.[^.]+\z").unwrap();
+        }
+        fn ident_and_tile_kind(captures: &Captures) -> (Option<String>, TileKind) {
+            let ident = captures.name("ident").map(|ident| ident.as_str().to_owned());
+            let tile_kind = if captures.name("hd").is_some() { TileKind::HD } else { TileKind::SD };
+            (ident, tile_kind)
+        }
+        if let Some(captures) = BIN_FILE_RE.captures(file_name) {
+            let (ident, tile_kind) = ident_and_tile_kind(&captures);
+            let page_index = match captures.name("page") {
+                Some(page) => page.as_str().parse::<usize>().ok()? - 1,
+                None => 0,
+            };
+            return Some(Self::BinPage { ident, tile_kind, page_index })
+        }
+        if is_readable_image_path(file_name) {
+            if let Some(captures) = GRID_FILE_RE.captures(file_name) {
+                let (ident, tile_kind) = ident_and_tile_kind(&captures);
+                return Some(Self::Grid { ident, tile_kind })
+            }
+        }
+        None
+    }
+
+}
+
+// files almost matching a normalized name e.g. with the wrong case or an extra suffix like font_hd.bin.bak
+fn is_near_miss(file_name: &str) -> bool {
+    let file_name = file_name.to_lowercase();
+    file_name.starts_with("font") || file_name.starts_with("grid")
+}
+
+#[derive(Debug, Clone, Default, Serialize, Getters)]
+#[getset(get = "pub")]
+pub struct IdentFiles {
+    // None for the files without ident e.g. font.bin
+    ident: Option<String>,
+    // bin file page index -> file name
+    sd_bin_pages: BTreeMap<usize, PathBuf>,
+    hd_bin_pages: BTreeMap<usize, PathBuf>,
+    // a grid can be present with several image formats
+    sd_grids: Vec<PathBuf>,
+    hd_grids: Vec<PathBuf>,
+    // files needed to complete the bin file set and/or the grid set
+    missing: Vec<PathBuf>,
+    // tiles provided for each kind, from the bin pages or else from the dimensions of the first readable grid
+    sd_tile_count: Option<usize>,
+    hd_tile_count: Option<usize>,
+}
+
+impl IdentFiles {
+
+    fn new(ident: Option<String>) -> Self {
+        Self { ident, ..Default::default() }
+    }
+
+    fn bin_pages_mut(&mut self, tile_kind: TileKind) -> &mut BTreeMap<usize, PathBuf> {
+        match tile_kind {
+            TileKind::SD => &mut self.sd_bin_pages,
+            TileKind::HD => &mut self.hd_bin_pages,
+        }
+    }
+
+    fn grids_mut(&mut self, tile_kind: TileKind) -> &mut Vec<PathBuf> {
+        match tile_kind {
+            TileKind::SD => &mut self.sd_grids,
+            TileKind::HD => &mut self.hd_grids,
+        }
+    }
+
+    // a bin file set needs the same pages for SD and HD without gap, a grid set needs both grids
+    fn find_missing(&mut self) {
+        let ident = self.ident.as_deref();
+        let mut missing = vec![];
+        let page_count = self.sd_bin_pages.keys().chain(self.hd_bin_pages.keys()).max().map(|max_index| max_index + 1);
+        if let Some(page_count) = page_count {
+            for (tile_kind, pages) in [(TileKind::SD, &self.sd_bin_pages), (TileKind::HD, &self.hd_bin_pages)] {
+                for page_index in (0..page_count).filter(|page_index| !pages.contains_key(page_index)) {
+                    missing.push(bin_file::normalized_page_file_name(tile_kind, &ident, page_index));
+                }
+            }
+        }
+        if !self.sd_grids.is_empty() || !self.hd_grids.is_empty() {
+            for (tile_kind, grids) in [(TileKind::SD, &self.sd_grids), (TileKind::HD, &self.hd_grids)] {
+                if grids.is_empty() {
+                    missing.push(grid::normalized_image_file_name(tile_kind, &ident, ImageFormat::default()));
+                }
+            }
+        }
+        self.missing = missing;
+    }
+
+    fn count_tiles<P: AsRef<Path>>(&mut self, dir: P) {
+        fn tile_count(dir: &Path, tile_kind: TileKind, bin_pages: &BTreeMap<usize, PathBuf>, grids: &[PathBuf]) -> Option<usize> {
+            if !bin_pages.is_empty() {
+                return Some(bin_pages.len() * bin_file::TILE_COUNT)
+            }
+            grids.iter().find_map(|grid| {
+                let (width, height) = image::image_dimensions(dir.join(grid)).ok()?;
+                match Grid::image_tile_kind_and_count(ImageDimensions { width, height }) {
+                    Ok((grid_tile_kind, count)) if grid_tile_kind == tile_kind => Some(count),
+                    _ => None,
+                }
+            })
+        }
+        let dir = dir.as_ref();
+        self.sd_tile_count = tile_count(dir, TileKind::SD, &self.sd_bin_pages, &self.sd_grids);
+        self.hd_tile_count = tile_count(dir, TileKind::HD, &self.hd_bin_pages, &self.hd_grids);
+    }
+
+    pub fn present(&self) -> Vec<&PathBuf> {
+        self.sd_bin_pages.values().chain(self.hd_bin_pages.values())
+            .chain(self.sd_grids.iter()).chain(self.hd_grids.iter())
+            .collect()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.missing.is_empty()
+    }
+
+}
+
+#[derive(Debug, Clone, Default, Serialize, Getters)]
+#[getset(get = "pub")]
+pub struct IdentScan {
+    // the files without ident come first then the idents in alphabetical order
+    idents: Vec<IdentFiles>,
+    unrecognized: Vec<PathBuf>,
+}
+
+pub fn scan_idents<P: AsRef<Path>>(dir: P) -> Result<IdentScan, ScanIdentsError> {
+    let entries = std::fs::read_dir(&dir).map_err(|error| ScanIdentsError::new(&dir, error))?;
+    let mut file_names = vec![];
+    for entry in entries {
+        let path = entry.map_err(|error| ScanIdentsError::new(&dir, error))?.path();
+        if path.is_file() {
+            if let Some(file_name) = path.file_name().and_then(|file_name| file_name.to_str()) {
+                file_names.push(file_name.to_owned());
+            }
+        }
+    }
+    file_names.sort();
+
+    let mut idents: BTreeMap<Option<String>, IdentFiles> = BTreeMap::new();
+    let mut unrecognized = vec![];
+    for file_name in file_names {
+        match NormFile::parse(&file_name) {
+            Some(NormFile::BinPage { ident, tile_kind, page_index }) => {
+                idents.entry(ident.clone()).or_insert_with(|| IdentFiles::new(ident))
+                    .bin_pages_mut(tile_kind).insert(page_index, PathBuf::from(file_name));
+            },
+            Some(NormFile::Grid { ident, tile_kind }) => {
+                idents.entry(ident.clone()).or_insert_with(|| IdentFiles::new(ident))
+                    .grids_mut(tile_kind).push(PathBuf::from(file_name));
+            },
+            None if is_near_miss(&file_name) => unrecognized.push(PathBuf::from(file_name)),
+            None => {},
+        }
+    }
+
+    let idents = idents.into_values().map(|mut ident_files| {
+        ident_files.find_missing();
+        ident_files.count_tiles(&dir);
+        ident_files
+    }).collect();
+    Ok(IdentScan { idents, unrecognized })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::osd::tile::Kind as TileKind;
+
+    use super::NormFile;
+
+    #[test]
+    fn parse_normalized_names() {
+        let bin_page = |ident: Option<&str>, tile_kind, page_index| Some(NormFile::BinPage { ident: ident.map(str::to_owned), tile_kind, page_index });
+        assert_eq!(NormFile::parse("font.bin"), bin_page(None, TileKind::SD, 0));
+        assert_eq!(NormFile::parse("font_2.bin"), bin_page(None, TileKind::SD, 1));
+        assert_eq!(NormFile::parse("font_hd_2.bin"), bin_page(None, TileKind::HD, 1));
+        assert_eq!(NormFile::parse("font_1.bin"), bin_page(Some("1"), TileKind::SD, 0));
+        assert_eq!(NormFile::parse("font_inav_hd.bin"), bin_page(Some("inav"), TileKind::HD, 0));
+        assert_eq!(NormFile::parse("font_bf_4_3_hd_3.bin"), bin_page(Some("bf_4_3"), TileKind::HD, 2));
+        assert_eq!(NormFile::parse("grid_ardu_hd.png"), Some(NormFile::Grid { ident: Some("ardu".to_owned()), tile_kind: TileKind::HD }));
+        assert_eq!(NormFile::parse("Font_hd.bin"), None);
+        assert_eq!(NormFile::parse("font_hd.bin.bak"), None);
+        assert_eq!(NormFile::parse("grid.jpg"), None);
+    }
+}
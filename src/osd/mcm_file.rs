@@ -0,0 +1,208 @@
+
+use std::{
+    io::Error as IOError,
+    path::{Path, PathBuf},
+};
+
+use derive_more::From;
+use image::Rgba;
+use thiserror::Error;
+
+use crate::atomic_file;
+use super::tile::{
+    Tile,
+    Kind as TileKind,
+    container::uniq_tile_kind::{TileKindError, UniqTileKind},
+};
+
+// MAX7456 character file as used by the analog OSD of Betaflight and INAV: a `MAX7456` header line followed by one
+// line per byte written as 8 binary digits. Each character is 64 bytes of which the first 54 hold the 12x18 pixels,
+// 2 bits per pixel from the most significant bits, the last 10 bytes are unused. The characters are loaded as tiles
+// by upscaling them by an integer factor, 3 for SD tiles and 2 for HD tiles, and saved back by sampling the center of
+// each upscaled pixel.
+
+pub const HEADER: &str = "MAX7456";
+pub const CHARACTER_WIDTH: u32 = 12;
+pub const CHARACTER_HEIGHT: u32 = 18;
+pub const CHARACTER_COUNT: usize = 256;
+
+const CHARACTER_DATA_BYTES: usize = (CHARACTER_WIDTH * CHARACTER_HEIGHT / 4) as usize;
+const CHARACTER_BYTES: usize = 64;
+// the unused bytes and the bytes of blank characters are written as fully transparent pixels
+const TRANSPARENT_BYTE: u8 = 0b01010101;
+
+const BLACK: Rgba<u8> = Rgba([0, 0, 0, 255]);
+const WHITE: Rgba<u8> = Rgba([255, 255, 255, 255]);
+const TRANSPARENT: Rgba<u8> = Rgba([0, 0, 0, 0]);
+
+impl TileKind {
+
+    pub const fn mcm_scale(&self) -> u32 {
+        match self {
+            TileKind::SD => 3,
+            TileKind::HD => 2,
+        }
+    }
+
+}
+
+#[derive(Debug, From, Error)]
+pub enum LoadError {
+    #[error(transparent)]
+    FileError(IOError),
+    #[from(ignore)]
+    #[error("file {0} is not a MCM file: the first line should be `{HEADER}`")]
+    InvalidHeader(PathBuf),
+    #[from(ignore)]
+    #[error("invalid MCM byte line {line_number} in file {file_path}: `{line}`")]
+    InvalidLine {
+        file_path: PathBuf,
+        line_number: usize,
+        line: String,
+    },
+    #[from(ignore)]
+    #[error("file {file_path} has {line_count} byte lines which is not a whole number of {CHARACTER_BYTES} bytes characters")]
+    IncompleteCharacter {
+        file_path: PathBuf,
+        line_count: usize,
+    },
+}
+
+#[derive(Debug, From, Error)]
+pub enum SaveError {
+    #[error(transparent)]
+    FileError(IOError),
+    #[error(transparent)]
+    TileKindError(TileKindError),
+}
+
+fn pixel_color(bits: u8) -> Rgba<u8> {
+    match bits {
+        0b00 => BLACK,
+        0b10 => WHITE,
+        _ => TRANSPARENT,
+    }
+}
+
+// mostly transparent pixels are transparent and the other pixels are black or white depending on their brightness
+fn pixel_bits(color: &Rgba<u8>) -> u8 {
+    let Rgba([red, green, blue, alpha]) = *color;
+    if alpha < 128 {
+        0b01
+    } else if (red as u32 + green as u32 + blue as u32) / 3 >= 128 {
+        0b10
+    } else {
+        0b00
+    }
+}
+
+fn character_tile(bytes: &[u8], tile_kind: TileKind) -> Tile {
+    let scale = tile_kind.mcm_scale();
+    let mut tile = Tile::new(tile_kind);
+    for pixel_index in 0..(CHARACTER_WIDTH * CHARACTER_HEIGHT) {
+        let byte = bytes[pixel_index as usize / 4];
+        let color = pixel_color((byte >> (6 - 2 * (pixel_index % 4))) & 0b11);
+        let (x, y) = ((pixel_index % CHARACTER_WIDTH) * scale, (pixel_index / CHARACTER_WIDTH) * scale);
+        for (dx, dy) in itertools::iproduct!(0..scale, 0..scale) {
+            tile.put_pixel(x + dx, y + dy, color);
+        }
+    }
+    tile
+}
+
+pub(crate) fn tile_character(tile: &Tile) -> Vec<u8> {
+    let scale = tile.kind().mcm_scale();
+    let mut bytes = vec![0; CHARACTER_DATA_BYTES];
+    for pixel_index in 0..(CHARACTER_WIDTH * CHARACTER_HEIGHT) {
+        let (x, y) = ((pixel_index % CHARACTER_WIDTH) * scale + scale / 2, (pixel_index / CHARACTER_WIDTH) * scale + scale / 2);
+        bytes[pixel_index as usize / 4] |= pixel_bits(tile.get_pixel(x, y)) << (6 - 2 * (pixel_index % 4));
+    }
+    bytes.resize(CHARACTER_BYTES, TRANSPARENT_BYTE);
+    bytes
+}
+
+// some files hold more than 256 characters e.g. the 512 characters fonts of INAV, every character is loaded
+pub fn load<P: AsRef<Path>>(path: P, tile_kind: TileKind) -> Result<Vec<Tile>, LoadError> {
+    let content = fs_err::read_to_string(&path)?;
+    let mut lines = content.lines().map(str::trim).enumerate().filter(|(_, line)| !line.is_empty());
+    match lines.next() {
+        Some((_, HEADER)) => {},
+        _ => return Err(LoadError::InvalidHeader(path.as_ref().to_path_buf())),
+    }
+
+    let bytes = lines.map(|(line_index, line)| match line.len() == 8 {
+        true => u8::from_str_radix(line, 2).ok(),
+        false => None,
+    }.ok_or_else(|| LoadError::InvalidLine { file_path: path.as_ref().to_path_buf(), line_number: line_index + 1, line: line.to_owned() }))
+        .collect::<Result<Vec<u8>, LoadError>>()?;
+    if bytes.is_empty() || bytes.len() % CHARACTER_BYTES != 0 {
+        return Err(LoadError::IncompleteCharacter { file_path: path.as_ref().to_path_buf(), line_count: bytes.len() });
+    }
+
+    Ok(bytes.chunks(CHARACTER_BYTES).map(|character| character_tile(character, tile_kind)).collect())
+}
+
+// the characters are padded with blank characters to a multiple of 256
+pub fn save<P: AsRef<Path>>(tiles: &[Tile], path: P) -> Result<(), SaveError> {
+    tiles.tile_kind()?;
+    let character_count = tiles.len().div_ceil(CHARACTER_COUNT) * CHARACTER_COUNT;
+    let mut content = format!("{HEADER}\n");
+    for character_index in 0..character_count {
+        let bytes = match tiles.get(character_index) {
+            Some(tile) => tile_character(tile),
+            None => vec![TRANSPARENT_BYTE; CHARACTER_BYTES],
+        };
+        for byte in bytes {
+            content.push_str(&format!("{byte:08b}\n"));
+        }
+    }
+    atomic_file::write(&path, content)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+    use strum::IntoEnumIterator;
+    use temp_dir::TempDir;
+
+    use crate::osd::tile::{Tile, Kind as TileKind};
+
+    use super::{load, save, LoadError, CHARACTER_COUNT};
+
+    #[test]
+    fn round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        for tile_kind in TileKind::iter() {
+            let scale = tile_kind.mcm_scale();
+            let mut tile = Tile::new(tile_kind);
+            for (x, y) in itertools::iproduct!(0..scale, 0..scale) {
+                tile.put_pixel(x, y, Rgba([255, 255, 255, 255]));
+                tile.put_pixel(11 * scale + x, 17 * scale + y, Rgba([0, 0, 0, 255]));
+            }
+            let path = temp_dir.child(format!("font_{tile_kind}.mcm"));
+            save(&[Tile::new(tile_kind), tile.clone()], &path).unwrap();
+
+            let content = std::fs::read_to_string(&path).unwrap();
+            assert_eq!(content.lines().count(), 1 + CHARACTER_COUNT * 64);
+            assert_eq!(content.lines().nth(1 + 64), Some("10010101"));
+
+            let tiles = load(&path, tile_kind).unwrap();
+            assert_eq!(tiles.len(), CHARACTER_COUNT);
+            assert_eq!(tiles[1].image(), tile.image());
+            assert!(tiles[0].pixels().all(|pixel| pixel.0[3] == 0));
+
+            let hd_tiles = load(&path, TileKind::HD).unwrap();
+            assert_eq!(*hd_tiles[1].get_pixel(1, 1), Rgba([255, 255, 255, 255]));
+            assert_eq!(*hd_tiles[1].get_pixel(23, 35), Rgba([0, 0, 0, 255]));
+        }
+
+        let path = temp_dir.child("invalid.mcm");
+        std::fs::write(&path, "MAX7456\n01010101\n0101010\n").unwrap();
+        assert!(matches!(load(&path, TileKind::SD), Err(LoadError::InvalidLine { line_number: 3, .. })));
+        std::fs::write(&path, "MAX7456\n01010101\n").unwrap();
+        assert!(matches!(load(&path, TileKind::SD), Err(LoadError::IncompleteCharacter { line_count: 1, .. })));
+        std::fs::write(&path, "01010101\n").unwrap();
+        assert!(matches!(load(&path, TileKind::SD), Err(LoadError::InvalidHeader(_))));
+    }
+}
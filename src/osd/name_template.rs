@@ -0,0 +1,123 @@
+use std::{fmt::Display, path::{Path, PathBuf}, str::FromStr};
+
+use thiserror::Error;
+
+use super::tile::Kind as TileKind;
+
+// Templates of the bin file names for the goggles firmware not following the default normalized naming. The
+// placeholders are replaced with:
+//   {ident}  the font ident, empty without ident         {_ident}  `_` and the ident, empty without ident
+//   {kind}   `sd` or `hd`                                {_hd}     `_hd` for the HD pages, empty for the SD pages
+//   {page}   the 1-based page number                     {_page}   `_` and the page number from the second page on
+// The default template "font{_ident}{_hd}{_page}.bin" gives the normalized names e.g. font_btfl_hd_2.bin. A template
+// needs a tile kind and a page placeholder so that every page of a set has its own file.
+
+pub const DEFAULT_BIN_FILE_NAME_TEMPLATE: &str = "font{_ident}{_hd}{_page}.bin";
+
+const PLACEHOLDERS: [&str; 6] = ["ident", "_ident", "kind", "_hd", "page", "_page"];
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ParseNameTemplateError {
+    #[error("unknown placeholder {{{placeholder}}} in name template {template}, expected one of {}", PLACEHOLDERS.map(|placeholder| format!("{{{placeholder}}}")).join(", "))]
+    UnknownPlaceholder { template: String, placeholder: String },
+    #[error("unclosed placeholder in name template {0}")]
+    UnclosedPlaceholder(String),
+    #[error("name template {0} needs a tile kind placeholder ({{kind}} or {{_hd}}) and a page placeholder ({{page}} or {{_page}})")]
+    MissingPlaceholder(String),
+    #[error("name template {0} should be a file name without directory")]
+    NotAFileName(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NameTemplate(String);
+
+impl NameTemplate {
+
+    pub fn parse(template: &str) -> Result<Self, ParseNameTemplateError> {
+        if template.contains('/') || template.contains('\\') {
+            return Err(ParseNameTemplateError::NotAFileName(template.to_owned()))
+        }
+        let mut placeholders = vec![];
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            let end = rest[start..].find('}').ok_or_else(|| ParseNameTemplateError::UnclosedPlaceholder(template.to_owned()))?;
+            let placeholder = &rest[start + 1..start + end];
+            if !PLACEHOLDERS.contains(&placeholder) {
+                return Err(ParseNameTemplateError::UnknownPlaceholder { template: template.to_owned(), placeholder: placeholder.to_owned() })
+            }
+            placeholders.push(placeholder);
+            rest = &rest[start + end + 1..];
+        }
+        let has_any = |names: &[&str]| placeholders.iter().any(|placeholder| names.contains(placeholder));
+        if !has_any(&["kind", "_hd"]) || !has_any(&["page", "_page"]) {
+            return Err(ParseNameTemplateError::MissingPlaceholder(template.to_owned()))
+        }
+        Ok(Self(template.to_owned()))
+    }
+
+    pub fn file_name(&self, tile_kind: TileKind, ident: &Option<&str>, page_index: usize) -> PathBuf {
+        let (kind, hd_suffix) = match tile_kind {
+            TileKind::SD => ("sd", ""),
+            TileKind::HD => ("hd", "_hd"),
+        };
+        let page_suffix = match page_index {
+            0 => String::new(),
+            page_index => format!("_{}", page_index + 1),
+        };
+        let file_name = self.0
+            .replace("{_ident}", &ident.map(|ident| format!("_{ident}")).unwrap_or_default())
+            .replace("{ident}", ident.unwrap_or_default())
+            .replace("{kind}", kind)
+            .replace("{_hd}", hd_suffix)
+            .replace("{_page}", &page_suffix)
+            .replace("{page}", &(page_index + 1).to_string());
+        PathBuf::from(file_name)
+    }
+
+    pub fn file_path<P: AsRef<Path>>(&self, dir: P, tile_kind: TileKind, ident: &Option<&str>, page_index: usize) -> PathBuf {
+        dir.as_ref().join(self.file_name(tile_kind, ident, page_index))
+    }
+
+}
+
+impl Default for NameTemplate {
+    fn default() -> Self {
+        Self(DEFAULT_BIN_FILE_NAME_TEMPLATE.to_owned())
+    }
+}
+
+impl FromStr for NameTemplate {
+    type Err = ParseNameTemplateError;
+
+    fn from_str(template: &str) -> Result<Self, Self::Err> {
+        Self::parse(template)
+    }
+}
+
+impl Display for NameTemplate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::osd::{bin_file, tile::Kind as TileKind};
+
+    use super::{NameTemplate, ParseNameTemplateError};
+
+    #[test]
+    fn file_names() {
+        let default = NameTemplate::default();
+        for (tile_kind, ident, page_index) in [(TileKind::SD, None, 0), (TileKind::HD, Some("btfl"), 1), (TileKind::HD, None, 2)] {
+            assert_eq!(default.file_name(tile_kind, &ident, page_index), bin_file::normalized_page_file_name(tile_kind, &ident, page_index));
+        }
+        let template: NameTemplate = "font_{ident}_{kind}_{page}.bin".parse().unwrap();
+        assert_eq!(template.file_name(TileKind::HD, &Some("inav"), 0).to_str(), Some("font_inav_hd_1.bin"));
+
+        assert!(matches!(NameTemplate::parse("font_{id}_{kind}_{page}.bin"), Err(ParseNameTemplateError::UnknownPlaceholder { .. })));
+        assert!(matches!(NameTemplate::parse("font_{kind}.bin"), Err(ParseNameTemplateError::MissingPlaceholder(_))));
+        assert!(matches!(NameTemplate::parse("font_{kind_{page}.bin"), Err(ParseNameTemplateError::UnknownPlaceholder { .. })));
+        assert!(matches!(NameTemplate::parse("font_{kind}_{page.bin"), Err(ParseNameTemplateError::UnclosedPlaceholder(_))));
+    }
+}
@@ -0,0 +1,150 @@
+use std::{
+    fmt::Write,
+    io::Error as IOError,
+    path::Path,
+};
+
+use clap::ValueEnum;
+use derive_more::From;
+use thiserror::Error;
+
+use crate::atomic_file;
+use super::mcm_file;
+use super::tile::{
+    Tile,
+    container::uniq_tile_kind::{TileKindError, UniqTileKind},
+};
+
+// Fonts written as source code arrays to be embedded in firmware builds: a C header with a `const uint8_t` array or a
+// Rust source file with a `pub const` array, one sub-array per tile. The tiles are either raw RGBA, 4 bytes per
+// pixel row by row as in the bin files, or MAX7456 characters, 64 bytes per character as in the MCM files (see
+// `mcm_file`). The identifiers are derived from the file name e.g. FONT_HD_TILE_COUNT and font_hd for font_hd.h.
+
+const BYTES_PER_LINE: usize = 16;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum, strum::Display)]
+#[strum(serialize_all = "kebab-case")]
+pub enum Language {
+    C,
+    Rust,
+}
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, ValueEnum, strum::Display)]
+#[strum(serialize_all = "kebab-case")]
+pub enum Encoding {
+    #[default]
+    Rgba,
+    // the tiles are downsampled to the 12x18 pixels black, white and transparent characters of the analog OSDs
+    Max7456,
+}
+
+#[derive(Debug, From, Error)]
+pub enum SaveError {
+    #[error(transparent)]
+    FileError(IOError),
+    #[error(transparent)]
+    TileKindError(TileKindError),
+}
+
+// letters, digits and underscores only, not starting with a digit
+fn identifier(name: &str) -> String {
+    let identifier: String = name.chars().map(|char| if char.is_ascii_alphanumeric() { char } else { '_' }).collect();
+    match identifier.chars().next() {
+        Some(first_char) if !first_char.is_ascii_digit() => identifier,
+        _ => format!("_{identifier}"),
+    }
+}
+
+fn tile_bytes(tile: &Tile, encoding: Encoding) -> Vec<u8> {
+    match encoding {
+        Encoding::Rgba => tile.as_raw().clone(),
+        Encoding::Max7456 => mcm_file::tile_character(tile),
+    }
+}
+
+fn write_bytes(content: &mut String, bytes: &[u8], indent: &str) {
+    for line in bytes.chunks(BYTES_PER_LINE) {
+        let line: Vec<String> = line.iter().map(|byte| format!("0x{byte:02x}")).collect();
+        writeln!(content, "{indent}{},", line.join(", ")).unwrap();
+    }
+}
+
+// source code of the array named from `name`
+pub fn generate(tiles: &[Tile], name: &str, language: Language, encoding: Encoding) -> Result<String, TileKindError> {
+    let tile_kind = tiles.tile_kind()?;
+    let (width, height) = match encoding {
+        Encoding::Rgba => (tile_kind.dimensions().width, tile_kind.dimensions().height),
+        Encoding::Max7456 => (mcm_file::CHARACTER_WIDTH, mcm_file::CHARACTER_HEIGHT),
+    };
+    let tile_bytes: Vec<Vec<u8>> = tiles.iter().map(|tile| tile_bytes(tile, encoding)).collect();
+    let byte_count = tile_bytes.first().map_or(0, Vec::len);
+    let (array_name, prefix) = (identifier(name).to_lowercase(), identifier(name).to_uppercase());
+    let description = format!("{} {tile_kind} tiles of {width}x{height} pixels, {} encoding", tiles.len(), encoding.to_string().to_uppercase());
+
+    let mut content = String::new();
+    match language {
+        Language::C => {
+            writeln!(content, "// generated by hd_fpv_font_tool: {description}").unwrap();
+            writeln!(content, "#ifndef {prefix}_H\n#define {prefix}_H\n\n#include <stdint.h>\n").unwrap();
+            writeln!(content, "#define {prefix}_TILE_COUNT {}", tiles.len()).unwrap();
+            writeln!(content, "#define {prefix}_TILE_WIDTH {width}").unwrap();
+            writeln!(content, "#define {prefix}_TILE_HEIGHT {height}").unwrap();
+            writeln!(content, "#define {prefix}_TILE_BYTES {byte_count}\n").unwrap();
+            writeln!(content, "const uint8_t {array_name}[{prefix}_TILE_COUNT][{prefix}_TILE_BYTES] = {{").unwrap();
+            for bytes in &tile_bytes {
+                content.push_str("    {\n");
+                write_bytes(&mut content, bytes, "        ");
+                content.push_str("    },\n");
+            }
+            content.push_str("};\n\n#endif\n");
+        },
+        Language::Rust => {
+            writeln!(content, "// generated by hd_fpv_font_tool: {description}\n").unwrap();
+            writeln!(content, "pub const {prefix}_TILE_COUNT: usize = {};", tiles.len()).unwrap();
+            writeln!(content, "pub const {prefix}_TILE_WIDTH: usize = {width};").unwrap();
+            writeln!(content, "pub const {prefix}_TILE_HEIGHT: usize = {height};").unwrap();
+            writeln!(content, "pub const {prefix}_TILE_BYTES: usize = {byte_count};\n").unwrap();
+            writeln!(content, "pub const {prefix}: [[u8; {prefix}_TILE_BYTES]; {prefix}_TILE_COUNT] = [").unwrap();
+            for bytes in &tile_bytes {
+                content.push_str("    [\n");
+                write_bytes(&mut content, bytes, "        ");
+                content.push_str("    ],\n");
+            }
+            content.push_str("];\n");
+        },
+    }
+    Ok(content)
+}
+
+// the identifiers are derived from the file stem
+pub fn save<P: AsRef<Path>>(tiles: &[Tile], path: P, language: Language, encoding: Encoding) -> Result<(), SaveError> {
+    let name = path.as_ref().file_stem().map(|file_stem| file_stem.to_string_lossy().to_string()).unwrap_or_default();
+    let content = generate(tiles, &name, language, encoding)?;
+    atomic_file::write(&path, content)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::osd::tile::{Tile, Kind as TileKind};
+
+    use super::{generate, identifier, Encoding, Language};
+
+    #[test]
+    fn identifiers() {
+        assert_eq!(identifier("font-hd"), "font_hd");
+        assert_eq!(identifier("2nd"), "_2nd");
+    }
+
+    #[test]
+    fn max7456_array() {
+        let tiles = vec![Tile::new(TileKind::SD); 2];
+        let content = generate(&tiles, "font", Language::C, Encoding::Max7456).unwrap();
+        assert!(content.contains("#define FONT_TILE_BYTES 64\n"));
+        assert!(content.contains("const uint8_t font[FONT_TILE_COUNT][FONT_TILE_BYTES] = {"));
+        assert_eq!(content.matches("0x55").count(), 2 * 64);
+        let content = generate(&tiles, "font", Language::Rust, Encoding::Rgba).unwrap();
+        assert!(content.contains("pub const FONT: [[u8; FONT_TILE_BYTES]; FONT_TILE_COUNT] = ["));
+    }
+
+}
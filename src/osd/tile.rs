@@ -1,27 +1,49 @@
 
 pub mod grid;
+pub mod grid_annotation;
+pub mod analog_grid;
+pub mod combined_grid;
+pub mod hdzero_sheet;
+pub mod atlas;
+pub mod font_render;
+pub mod text_render;
+pub mod preview;
+pub mod osd_mock;
+pub mod diff;
+pub mod regression;
 pub mod container;
+pub mod fit;
+pub mod transform;
+pub mod rescale;
 
 use std::{
     io::Error as IOError,
+    ops::{Deref, DerefMut},
     path::{Path, PathBuf},
+    sync::Arc,
 };
 
-use derive_more::{Deref,DerefMut, From};
+use derive_more::From;
 use getset::{Getters, CopyGetters};
 use strum::{EnumIter,IntoEnumIterator, Display};
-use image::{ImageBuffer, Rgba, GenericImageView, GenericImage};
+use image::{ImageBuffer, Rgba};
+use serde::Serialize;
 use thiserror::Error;
 
 use crate::{
     dimensions,
     image::{
-        read_image_file,
+        read_rgba_image_file,
+        upscale,
+        WriteImageFile,
         ReadError as ImageReadError,
-    }
+        WriteError as ImageWriteError,
+    },
+    image_format::ImageDecoding,
 };
 
 use super::bin_file::BinFileReader;
+use fit::{Fit, fit_image};
 
 
 pub type Dimensions = dimensions::Dimensions<u32>;
@@ -42,7 +64,14 @@ pub struct InvalidSizeError(pub u64);
 #[error("height does not match any tile kind: {0}")]
 pub struct InvalidHeightError(pub u32);
 
-#[derive(Debug, Copy, Clone, EnumIter, PartialEq, Eq, Display)]
+#[derive(Debug, Error)]
+#[error("{dimensions} could be a scaled export of {}", .candidates.iter().map(|(kind, scale)| format!("{kind} tiles by {scale}x")).collect::<Vec<_>>().join(" or "))]
+pub struct AmbiguousScaleError {
+    pub dimensions: Dimensions,
+    pub candidates: Vec<(Kind, u32)>,
+}
+
+#[derive(Debug, Copy, Clone, EnumIter, PartialEq, Eq, Display, Serialize)]
 pub enum Kind {
     SD,
     HD
@@ -50,6 +79,8 @@ pub enum Kind {
 
 impl Kind {
 
+    // fixed to the two DJI kinds rather than user-defined dimensions: the bin file sizes, the grids, the sets and the
+    // firmware layouts are all defined by them
     pub const fn dimensions(&self) -> Dimensions {
         match self {
             Kind::SD => SD_DIMENSIONS,
@@ -91,6 +122,35 @@ impl Kind {
         Err(InvalidHeightError(height))
     }
 
+    pub fn closest_to_dimensions(dimensions: Dimensions) -> Self {
+        Self::iter().min_by_key(|kind| {
+            let kind_dimensions = kind.dimensions();
+            (kind_dimensions.width as i64 - dimensions.width as i64).abs() + (kind_dimensions.height as i64 - dimensions.height as i64).abs()
+        }).unwrap()
+    }
+
+    pub fn closest_to_height(height: u32) -> Self {
+        Self::iter().min_by_key(|kind| (kind.dimensions().height as i64 - height as i64).abs()).unwrap()
+    }
+
+    // tile kind and factor of an image upscaled by an integer factor of at least 2 e.g. written with --scale,
+    // the width only needs to be a multiple of the scaled tile width so that it also matches symbol images. The scaled
+    // dimensions of several kinds can coincide e.g. 72x108 is both 2x SD and 3x HD, which is an error
+    pub fn for_scaled_dimensions(dimensions: Dimensions) -> Result<Option<(Self, u32)>, AmbiguousScaleError> {
+        let mut candidates: Vec<(Self, u32)> = Self::iter().filter_map(|kind| {
+            let Dimensions { width, height } = kind.dimensions();
+            let scale = dimensions.height / height;
+            if scale >= 2 && dimensions.height % height == 0 && dimensions.width > 0 && dimensions.width % (scale * width) == 0 {
+                Some((kind, scale))
+            } else {
+                None
+            }
+        }).collect();
+        match candidates.len() {
+            0 | 1 => Ok(candidates.pop()),
+            _ => Err(AmbiguousScaleError { dimensions, candidates }),
+        }
+    }
 
 }
 
@@ -119,43 +179,149 @@ pub enum LoadError {
     ImageReadError(ImageReadError),
     #[error("invalid tile image size in file {file_path}: {dimensions}")]
     InvalidDimensionsError { file_path: PathBuf, dimensions: Dimensions },
+    #[error("invalid tile image size in file {file_path}: {dimensions}, looks like a {scale}x scaled export of {tile_kind} tiles which is for presentation only, export again without scaling to get loadable tiles")]
+    ScaledExportError { file_path: PathBuf, dimensions: Dimensions, tile_kind: Kind, scale: u32 },
+    #[error("invalid tile image size in file {file_path}: {error} which are for presentation only, export again without scaling to get loadable tiles")]
+    AmbiguousScaledExportError { file_path: PathBuf, error: AmbiguousScaleError },
 }
 
 impl LoadError {
     pub fn invalid_dimensions<P: AsRef<Path>>(file_path: P, dimensions: Dimensions) -> Self {
         Self::InvalidDimensionsError { file_path: file_path.as_ref().to_path_buf(), dimensions }
     }
+
+    // points out the scaled exports which would otherwise only be reported as having invalid dimensions
+    pub fn unexpected_dimensions<P: AsRef<Path>>(file_path: P, dimensions: Dimensions) -> Self {
+        match Kind::for_scaled_dimensions(dimensions) {
+            Ok(Some((tile_kind, scale))) => Self::ScaledExportError { file_path: file_path.as_ref().to_path_buf(), dimensions, tile_kind, scale },
+            Ok(None) => Self::invalid_dimensions(file_path, dimensions),
+            Err(error) => Self::AmbiguousScaledExportError { file_path: file_path.as_ref().to_path_buf(), error },
+        }
+    }
 }
 
 pub type Bytes = Vec<u8>;
 pub type Image = ImageBuffer<Rgba<u8>, Vec<u8>>;
 
-#[derive(Deref, DerefMut, Clone, Debug, Getters, CopyGetters)]
+// Bounding box of the visible pixels of a tile, the coordinates are inclusive
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, CopyGetters)]
+#[getset(get_copy = "pub")]
+pub struct ContentBounds {
+    left: u32,
+    top: u32,
+    right: u32,
+    bottom: u32,
+}
+
+impl ContentBounds {
+
+    // empty space between the content and the left/right edges of a tile of the given dimensions, none on the right
+    // when the content reaches beyond them
+    pub fn horizontal_margins(&self, tile_dimensions: Dimensions) -> (u32, u32) {
+        (self.left, tile_dimensions.width.saturating_sub(self.right + 1))
+    }
+
+    // empty space between the content and the top/bottom edges of a tile of the given dimensions, none at the bottom
+    // when the content reaches beyond them
+    pub fn vertical_margins(&self, tile_dimensions: Dimensions) -> (u32, u32) {
+        (self.top, tile_dimensions.height.saturating_sub(self.bottom + 1))
+    }
+
+}
+
+// The pixels are shared between the clones of a tile, they are only copied when one of the clones is modified
+#[derive(Clone, Debug, CopyGetters)]
 pub struct Tile {
     #[getset(get_copy = "pub")]
     kind: Kind,
 
-    #[deref]
-    #[deref_mut]
-    #[getset(get = "pub")]
-    image: Image,
+    image: Arc<Image>,
+}
+
+impl Deref for Tile {
+    type Target = Image;
+
+    fn deref(&self) -> &Self::Target {
+        &self.image
+    }
+}
+
+impl DerefMut for Tile {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        Arc::make_mut(&mut self.image)
+    }
 }
 
 impl Tile {
 
     pub fn new(kind: Kind) -> Self {
         let Dimensions { width, height } = kind.dimensions();
-        Self { kind, image: ImageBuffer::new(width, height)}
+        Self { kind, image: Arc::new(ImageBuffer::new(width, height)) }
+    }
+
+    pub fn image(&self) -> &Image {
+        &self.image
+    }
+
+    pub(crate) fn set_image(&mut self, image: Image) {
+        self.image = Arc::new(image);
+    }
+
+    pub fn shares_pixels_with(&self, other: &Tile) -> bool {
+        Arc::ptr_eq(&self.image, &other.image)
+    }
+
+    // None for a blank tile
+    pub fn content_bounds(&self) -> Option<ContentBounds> {
+        let mut bounds: Option<ContentBounds> = None;
+        for (x, y, _) in self.enumerate_pixels().filter(|(_, _, pixel)| pixel.0[3] != 0) {
+            bounds = Some(match bounds {
+                None => ContentBounds { left: x, top: y, right: x, bottom: y },
+                Some(bounds) => ContentBounds {
+                    left: bounds.left.min(x),
+                    top: bounds.top.min(y),
+                    right: bounds.right.max(x),
+                    bottom: bounds.bottom.max(y),
+                },
+            });
+        }
+        bounds
     }
 
     pub fn load_image_file<P: AsRef<Path>>(path: P) -> Result<Self, LoadError> {
-        let image = read_image_file(&path)?;
-        let kind = Kind::try_from(Dimensions::from(image.dimensions()))
-            .map_err(|error| {
-                let InvalidDimensionsError { dimensions } = error;
-                LoadError::invalid_dimensions(&path, dimensions)
-            })?;
-        Ok(Self { kind, image: image.into_rgba8() })
+        Self::load_image_file_fit(path, None)
+    }
+
+    pub fn load_image_file_fit<P: AsRef<Path>>(path: P, fit: Option<Fit>) -> Result<Self, LoadError> {
+        Self::load_image_file_decoded(path, fit, ImageDecoding::default())
+    }
+
+    pub fn load_image_file_decoded<P: AsRef<Path>>(path: P, fit: Option<Fit>, decoding: ImageDecoding) -> Result<Self, LoadError> {
+        let image = read_rgba_image_file(&path, decoding)?;
+        Self::from_image_fit(path, image, fit)
+    }
+
+    // the path is only used in the messages
+    pub(crate) fn from_image_fit<P: AsRef<Path>>(path: P, image: Image, fit: Option<Fit>) -> Result<Self, LoadError> {
+        let dimensions = Dimensions::from(image.dimensions());
+        match (Kind::try_from(dimensions), fit) {
+            (Ok(kind), _) => Ok(Self { kind, image: Arc::new(image) }),
+            (Err(_), Some(fit)) => {
+                let kind = Kind::closest_to_dimensions(dimensions);
+                let image = fit_image(&path, &image, kind.dimensions(), fit)
+                    .ok_or_else(|| LoadError::invalid_dimensions(&path, dimensions))?;
+                Ok(Self { kind, image: Arc::new(image) })
+            },
+            (Err(_), None) => Err(LoadError::unexpected_dimensions(&path, dimensions)),
+        }
+    }
+
+    pub fn save_image_file<P: AsRef<Path>>(&self, path: P) -> Result<(), ImageWriteError> {
+        self.save_image_file_scaled(path, 1)
+    }
+
+    pub fn save_image_file_scaled<P: AsRef<Path>>(&self, path: P, scale: u32) -> Result<(), ImageWriteError> {
+        upscale(self.image(), scale).write_image_file(path)
     }
 
     pub fn read_from_bin_file(file: &mut BinFileReader) -> Result<Self, LoadError> {
@@ -169,7 +335,7 @@ impl TryFrom<Bytes> for Tile {
 
     fn try_from(bytes: Bytes) -> Result<Self, Self::Error> {
         let kind = Kind::for_size_bytes(bytes.len() as u64)?;
-        Ok(Self { kind, image: ImageBuffer::from_raw(kind.dimensions().width(), kind.dimensions().height(), bytes).unwrap() })
+        Ok(Self { kind, image: Arc::new(ImageBuffer::from_raw(kind.dimensions().width(), kind.dimensions().height(), bytes).unwrap()) })
     }
 }
 
@@ -179,9 +345,7 @@ impl TryFrom<Image> for Tile {
     fn try_from(image: Image) -> Result<Self, Self::Error> {
         let (width, height) = image.dimensions();
         let kind = Kind::try_from(Dimensions { width, height })?;
-        let mut tile = Self::new(kind);
-        tile.image.copy_from(&image, 0, 0).unwrap();
-        Ok(tile)
+        Ok(Self { kind, image: Arc::new(image) })
     }
 }
 
@@ -198,11 +362,17 @@ mod tests {
     use std::path::{PathBuf, Path};
     use std::io::ErrorKind as IOErrorKind;
 
+    use image::Rgba;
     use strum::IntoEnumIterator;
 
     use crate::image::ReadError as ImageReadError;
+    use crate::osd::tile::{
+        grid::{Grid, Set as GridSet},
+        container::{ToSymbols, symbol::spec::{Spec, Specs}},
+    };
 
     use super::{Tile, Kind, Dimensions, LoadError, InvalidSizeError};
+    use crate::image::{upscale, WriteImageFile};
 
     const TEST_FILES_DIR: &str = "test_files";
 
@@ -211,7 +381,7 @@ mod tests {
     }
 
     fn test_tile_file_path(tile_kind: Kind) -> PathBuf {
-        test_file_path(&format!("{}_tile.png", tile_kind.to_string().to_lowercase()))
+        test_file_path(format!("{}_tile.png", tile_kind.to_string().to_lowercase()))
     }
 
     #[test]
@@ -242,12 +412,31 @@ mod tests {
         }
     }
 
+    #[test]
+    fn load_scaled_export() {
+        let temp_dir = temp_dir::TempDir::new().unwrap();
+        let path = temp_dir.child("scaled.png");
+        upscale(Tile::new(Kind::HD).image(), 4).write_image_file(&path).unwrap();
+        let error = Tile::load_image_file(&path).unwrap_err();
+        assert!(matches!(error, LoadError::ScaledExportError { tile_kind: Kind::HD, scale: 4, .. }));
+        assert!(error.to_string().contains("4x scaled export of HD tiles"));
+
+        assert_eq!(Kind::for_scaled_dimensions(Dimensions::new(36 * 3, 54 * 3)).unwrap(), Some((Kind::SD, 3)));
+        assert_eq!(Kind::for_scaled_dimensions(Dimensions::new(36, 54)).unwrap(), None);
+        assert_eq!(Kind::for_scaled_dimensions(Dimensions::new(40, 60)).unwrap(), None);
+        let error = Kind::for_scaled_dimensions(Dimensions::new(72, 108)).unwrap_err();
+        assert_eq!(error.candidates, vec![(Kind::SD, 2), (Kind::HD, 3)]);
+
+        upscale(Tile::new(Kind::SD).image(), 2).write_image_file(&path).unwrap();
+        assert!(matches!(Tile::load_image_file(&path).unwrap_err(), LoadError::AmbiguousScaledExportError { .. }));
+    }
+
     #[test]
     fn try_from_bytes() {
         for kind in Kind::iter() {
             let bytes = vec![0; kind.raw_rgba_size_bytes()];
             let result = Tile::try_from(bytes);
-            assert!(matches!(result, Ok(_)))
+            assert!(result.is_ok())
         }
 
         let bytes = vec![1, 2, 3];
@@ -256,4 +445,44 @@ mod tests {
         assert!(matches!(result, Err(InvalidSizeError(size)) if size == bytes_len))
     }
 
-}
\ No newline at end of file
+    #[test]
+    fn conversions_share_pixels() {
+        let tiles: Vec<Tile> = (0..512).map(|index| {
+            let mut tile = Tile::new(Kind::HD);
+            tile.put_pixel(index % 24, index / 24 % 36, Rgba([255, 255, 255, 255]));
+            tile
+        }).collect();
+        let shares_every_buffer = |converted: &[Tile]| converted.iter().zip(&tiles).all(|(converted, tile)| converted.shares_pixels_with(tile));
+
+        let grid_tiles = Grid::from(tiles.as_slice()).to_vec();
+        assert!(shares_every_buffer(&grid_tiles));
+
+        let tile_set = GridSet { sd_grid: Grid::from(vec![Tile::new(Kind::SD)]), hd_grid: Grid::from(tiles.clone()) }.into_tile_set();
+        assert!(shares_every_buffer(&tile_set.hd_tiles));
+
+        let specs = Specs::from(vec![Spec::new("wide", 10, 3)]);
+        let symbol_tiles: Vec<Tile> = tiles.to_symbols(&specs).unwrap().into_iter().flat_map(|symbol| symbol.into_tiles()).collect();
+        assert!(shares_every_buffer(&symbol_tiles));
+
+        // copy on write: only the modified tile gets its own pixels
+        let mut modified = grid_tiles;
+        modified[3].invert();
+        assert!(!modified[3].shares_pixels_with(&tiles[3]));
+        assert_ne!(modified[3].image(), tiles[3].image());
+        assert!(modified[4].shares_pixels_with(&tiles[4]));
+    }
+
+    #[test]
+    fn content_margins() {
+        let mut tile = Tile::new(Kind::SD);
+        assert!(tile.content_bounds().is_none());
+        tile.put_pixel(30, 50, Rgba([255, 255, 255, 255]));
+        let bounds = tile.content_bounds().unwrap();
+        assert_eq!(bounds.horizontal_margins(Kind::SD.dimensions()), (30, 5));
+        assert_eq!(bounds.vertical_margins(Kind::SD.dimensions()), (50, 3));
+        // content beyond smaller dimensions leaves no margin
+        assert_eq!(bounds.horizontal_margins(Kind::HD.dimensions()), (30, 0));
+        assert_eq!(bounds.vertical_margins(Kind::HD.dimensions()), (50, 0));
+    }
+
+}
@@ -0,0 +1,133 @@
+
+use std::path::Path;
+
+use image::{GenericImage, GenericImageView, imageops::{self, FilterType}};
+use thiserror::Error;
+
+use super::{Tile, Kind as TileKind, Image};
+
+use crate::{
+    dimensions,
+    image_format::ImageDecoding,
+    osd::tile::container::{CapacityError, uniq_tile_kind::{UniqTileKind, TileKindError}},
+    image::{
+        read_rgba_image_file,
+        WriteImageFile,
+        ReadError as ImageLoadError,
+        WriteError as ImageWriteError,
+    },
+};
+
+// Sheet of 16x16 SD tiles without separators as used by the analog OSD tooling,
+// the tiles can be upscaled by an integer factor for presentation copies which are still loadable
+
+pub type ImageDimensions = dimensions::Dimensions<u32>;
+
+pub const WIDTH: u32 = 16;
+pub const HEIGHT: u32 = 16;
+pub const TILE_COUNT: usize = (WIDTH * HEIGHT) as usize;
+
+#[derive(Debug, Error)]
+pub enum LoadError {
+    #[error(transparent)]
+    ImageLoadError(#[from] ImageLoadError),
+    #[error("analog grid image dimensions {0} are not a multiple of the 16x16 SD tiles sheet dimensions")]
+    InvalidImageDimensions(ImageDimensions),
+}
+
+#[derive(Debug, Error)]
+pub enum SaveImageError {
+    #[error(transparent)]
+    ImageWriteError(#[from] ImageWriteError),
+    #[error(transparent)]
+    TileKindError(#[from] TileKindError),
+    #[error("analog grids only contain SD tiles, got {0} tiles")]
+    NotSDTiles(TileKind),
+    #[error(transparent)]
+    CapacityError(#[from] CapacityError),
+}
+
+pub fn image_dimensions(scale: u32) -> ImageDimensions {
+    let tile_dimensions = TileKind::SD.dimensions();
+    ImageDimensions::new(WIDTH * tile_dimensions.width * scale, HEIGHT * tile_dimensions.height * scale)
+}
+
+fn tile_position(index: usize) -> (u32, u32) {
+    let tile_dimensions = TileKind::SD.dimensions();
+    let index = index as u32;
+    ((index % WIDTH) * tile_dimensions.width, (index / WIDTH) * tile_dimensions.height)
+}
+
+// the scale is detected from the image dimensions
+pub fn load_image<P: AsRef<Path>>(path: P) -> Result<Vec<Tile>, LoadError> {
+    load_image_decoded(path, ImageDecoding::default())
+}
+
+pub fn load_image_decoded<P: AsRef<Path>>(path: P, decoding: ImageDecoding) -> Result<Vec<Tile>, LoadError> {
+    let image = read_rgba_image_file(&path, decoding)?;
+    let dimensions = ImageDimensions::from(image.dimensions());
+    let unscaled = image_dimensions(1);
+    let scale = dimensions.width / unscaled.width;
+    if scale == 0 || dimensions != image_dimensions(scale) {
+        return Err(LoadError::InvalidImageDimensions(dimensions));
+    }
+    log::debug!("detected analog grid with a scale of {scale} in {}", path.as_ref().to_string_lossy());
+
+    let image = match scale {
+        1 => image,
+        _ => imageops::resize(&image, unscaled.width, unscaled.height, FilterType::Nearest),
+    };
+
+    let tile_dimensions = TileKind::SD.dimensions();
+    let tiles = (0..TILE_COUNT).map(|index| {
+        let (x, y) = tile_position(index);
+        Tile::try_from(image.view(x, y, tile_dimensions.width, tile_dimensions.height).to_image()).unwrap()
+    }).collect();
+
+    Ok(tiles)
+}
+
+pub fn generate_image(tiles: &[Tile], scale: u32) -> Result<Image, SaveImageError> {
+    let tile_kind = tiles.tile_kind()?;
+    if tile_kind != TileKind::SD {
+        return Err(SaveImageError::NotSDTiles(tile_kind));
+    }
+    CapacityError::check(tiles.len(), TILE_COUNT)?;
+
+    let unscaled = image_dimensions(1);
+    let mut image = Image::new(unscaled.width, unscaled.height);
+    for (index, tile) in tiles.iter().enumerate() {
+        let (x, y) = tile_position(index);
+        image.copy_from(tile.image(), x, y).unwrap();
+    }
+
+    Ok(match scale {
+        1 => image,
+        _ => imageops::resize(&image, unscaled.width * scale, unscaled.height * scale, FilterType::Nearest),
+    })
+}
+
+pub fn save_image<P: AsRef<Path>>(tiles: &[Tile], path: P, scale: u32) -> Result<(), SaveImageError> {
+    generate_image(tiles, scale)?.write_image_file(path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+
+    use crate::osd::tile::{Tile, Kind};
+
+    use super::{generate_image, TILE_COUNT};
+
+    #[test]
+    fn scaled_sheet() {
+        let mut tiles = vec![Tile::new(Kind::SD); TILE_COUNT];
+        tiles[17].put_pixel(3, 5, Rgba([255, 255, 255, 255]));
+        let image = generate_image(&tiles, 2).unwrap();
+        assert_eq!(image.dimensions(), (16 * 36 * 2, 16 * 54 * 2));
+        let (x, y) = (36 * 2 + 3 * 2, 54 * 2 + 5 * 2);
+        assert_eq!(*image.get_pixel(x + 1, y + 1), Rgba([255, 255, 255, 255]));
+        assert_eq!(*image.get_pixel(x + 2, y), Rgba([0, 0, 0, 0]));
+    }
+}
@@ -0,0 +1,182 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::Error as IOError,
+    path::{Path, PathBuf},
+};
+
+use image::{GenericImage, Rgba};
+use serde::Serialize;
+use thiserror::Error;
+
+use super::{Tile, Image};
+
+use crate::{
+    atomic_file,
+    image::{WriteImageFile, WriteError as ImageWriteError},
+    osd::tile::container::uniq_tile_kind::{UniqTileKind, TileKindError},
+};
+
+// Sprite atlas for the game engine based OSD overlay renderers and the web viewers: the distinct tiles packed without
+// separators in a roughly square image, with a JSON index in the TexturePacker hash format next to it (same path with
+// a .json extension) mapping each tile number, 0 padded to 3 digits, to the rectangle of its image. Identical tiles,
+// e.g. the many blank tiles of a font, share the same rectangle.
+
+#[derive(Debug, Error)]
+pub enum SaveError {
+    #[error(transparent)]
+    ImageWriteError(#[from] ImageWriteError),
+    #[error(transparent)]
+    TileKindError(#[from] TileKindError),
+    #[error("failed to serialize the atlas index: {0}")]
+    Serialize(#[from] serde_json::Error),
+    #[error("failed to write the atlas index {file_path}: {error}")]
+    IndexWrite {
+        file_path: PathBuf,
+        error: IOError,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Rect {
+    pub x: u32,
+    pub y: u32,
+    pub w: u32,
+    pub h: u32,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+struct Size {
+    w: u32,
+    h: u32,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Frame {
+    frame: Rect,
+    rotated: bool,
+    trimmed: bool,
+    sprite_source_size: Rect,
+    source_size: Size,
+}
+
+#[derive(Debug, Serialize)]
+struct Meta {
+    app: &'static str,
+    version: &'static str,
+    image: String,
+    format: &'static str,
+    size: Size,
+    scale: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+struct Index {
+    frames: BTreeMap<String, Frame>,
+    meta: Meta,
+}
+
+pub struct Atlas {
+    pub image: Image,
+    // rectangle of the image of each tile in the atlas, in tile order
+    pub rects: Vec<Rect>,
+}
+
+// number of columns of the most square atlas holding `count` tiles
+fn columns(count: usize) -> usize {
+    let mut columns = 1;
+    while columns * columns < count {
+        columns += 1;
+    }
+    columns
+}
+
+pub fn pack(tiles: &[Tile]) -> Result<Atlas, TileKindError> {
+    let tile_dimensions = tiles.tile_kind()?.dimensions();
+    let mut distinct_tiles: Vec<&Tile> = vec![];
+    let mut distinct_indexes: HashMap<&[u8], usize> = HashMap::new();
+    let tile_distinct_indexes: Vec<usize> = tiles.iter().map(|tile| *distinct_indexes.entry(tile.as_raw().as_slice()).or_insert_with(|| {
+        distinct_tiles.push(tile);
+        distinct_tiles.len() - 1
+    })).collect();
+
+    let columns = columns(distinct_tiles.len());
+    let rows = distinct_tiles.len().div_ceil(columns);
+    let mut image = Image::from_pixel(columns as u32 * tile_dimensions.width, rows as u32 * tile_dimensions.height, Rgba([0, 0, 0, 0]));
+    let distinct_rects: Vec<Rect> = distinct_tiles.iter().enumerate().map(|(index, tile)| {
+        let rect = Rect {
+            x: (index % columns) as u32 * tile_dimensions.width,
+            y: (index / columns) as u32 * tile_dimensions.height,
+            w: tile_dimensions.width,
+            h: tile_dimensions.height,
+        };
+        image.copy_from(tile.image(), rect.x, rect.y).unwrap();
+        rect
+    }).collect();
+    let rects = tile_distinct_indexes.into_iter().map(|distinct_index| distinct_rects[distinct_index]).collect();
+    Ok(Atlas { image, rects })
+}
+
+impl Atlas {
+
+    // JSON index in the TexturePacker hash format, `image_file_name` is the name of the atlas image it refers to
+    pub fn index_json(&self, image_file_name: &str) -> Result<String, serde_json::Error> {
+        let frames = self.rects.iter().enumerate().map(|(index, rect)| {
+            let frame = Frame {
+                frame: *rect,
+                rotated: false,
+                trimmed: false,
+                sprite_source_size: Rect { x: 0, y: 0, w: rect.w, h: rect.h },
+                source_size: Size { w: rect.w, h: rect.h },
+            };
+            (format!("{index:03}"), frame)
+        }).collect();
+        let meta = Meta {
+            app: env!("CARGO_PKG_NAME"),
+            version: env!("CARGO_PKG_VERSION"),
+            image: image_file_name.to_owned(),
+            format: "RGBA8888",
+            size: Size { w: self.image.width(), h: self.image.height() },
+            scale: "1",
+        };
+        serde_json::to_string_pretty(&Index { frames, meta })
+    }
+
+}
+
+pub fn index_path<P: AsRef<Path>>(image_path: P) -> PathBuf {
+    image_path.as_ref().with_extension("json")
+}
+
+pub fn save<P: AsRef<Path>>(tiles: &[Tile], image_path: P) -> Result<(), SaveError> {
+    let atlas = pack(tiles)?;
+    let image_file_name = image_path.as_ref().file_name().map(|file_name| file_name.to_string_lossy().to_string()).unwrap_or_default();
+    let index = atlas.index_json(&image_file_name)?;
+    atlas.image.write_image_file(&image_path)?;
+    let index_path = index_path(&image_path);
+    atomic_file::write(&index_path, index).map_err(|error| SaveError::IndexWrite { file_path: index_path, error })?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+
+    use crate::osd::tile::{Tile, Kind as TileKind};
+
+    use super::{columns, pack};
+
+    #[test]
+    fn packing() {
+        assert_eq!(columns(1), 1);
+        assert_eq!(columns(5), 3);
+        let mut tiles = vec![Tile::new(TileKind::SD); 4];
+        tiles[2].put_pixel(0, 0, Rgba([255, 255, 255, 255]));
+        let atlas = pack(&tiles).unwrap();
+        // the blank tiles share the first rectangle
+        assert_eq!(atlas.rects[0], atlas.rects[3]);
+        assert_ne!(atlas.rects[0], atlas.rects[2]);
+        assert_eq!((atlas.image.width(), atlas.image.height()), (2 * 12 * 3, 18 * 3));
+    }
+
+}
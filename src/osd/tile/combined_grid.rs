@@ -0,0 +1,209 @@
+
+use std::path::Path;
+
+use image::{GenericImage, GenericImageView, Rgba};
+use strum::IntoEnumIterator;
+use thiserror::Error;
+
+use super::{
+    Tile,
+    Kind as TileKind,
+    grid::{Grid, Image},
+    container::{tile_set::TileSet, uniq_tile_kind::TileKindError},
+};
+
+use crate::{
+    dimensions,
+    image_format::ImageDecoding,
+    osd::bin_file,
+    image::{
+        read_rgba_image_file,
+        WriteImageFile,
+        ReadError as ImageLoadError,
+        WriteError as ImageWriteError,
+    },
+};
+
+// Single image holding every page of a tile set as bands stacked top to bottom: the SD pages in order then the
+// HD pages in order, with as many HD pages as SD pages. Each band is a label strip naming the page (e.g. "SD 1")
+// followed by the 16x16 grid of the page laid out like a grid image. The image is as wide as the SD grids and the
+// narrower HD grids are aligned on the left.
+
+pub type ImageDimensions = dimensions::Dimensions<u32>;
+
+pub const LABEL_HEIGHT: u32 = 14;
+const LABEL_SCALE: u32 = 2;
+const LABEL_MARGIN: u32 = 2;
+const LABEL_COLOR: Rgba<u8> = Rgba([255, 255, 255, 255]);
+const BACKGROUND_COLOR: Rgba<u8> = Rgba([0, 0, 0, 255]);
+const GRID_HEIGHT: usize = bin_file::TILE_COUNT / 16;
+
+// 3x5 glyphs of the characters used in the labels, one row per byte with the leftmost pixel as bit 2
+const LABEL_GLYPHS: [(char, [u8; 5]); 13] = [
+    ('S', [0b011, 0b100, 0b010, 0b001, 0b110]),
+    ('D', [0b110, 0b101, 0b101, 0b101, 0b110]),
+    ('H', [0b101, 0b101, 0b111, 0b101, 0b101]),
+    ('0', [0b111, 0b101, 0b101, 0b101, 0b111]),
+    ('1', [0b010, 0b110, 0b010, 0b010, 0b111]),
+    ('2', [0b111, 0b001, 0b111, 0b100, 0b111]),
+    ('3', [0b111, 0b001, 0b111, 0b001, 0b111]),
+    ('4', [0b101, 0b101, 0b111, 0b001, 0b001]),
+    ('5', [0b111, 0b100, 0b111, 0b001, 0b111]),
+    ('6', [0b111, 0b100, 0b111, 0b101, 0b111]),
+    ('7', [0b111, 0b001, 0b001, 0b001, 0b001]),
+    ('8', [0b111, 0b101, 0b111, 0b101, 0b111]),
+    ('9', [0b111, 0b101, 0b111, 0b001, 0b111]),
+];
+
+#[derive(Debug, Error)]
+pub enum LoadError {
+    #[error(transparent)]
+    ImageLoadError(#[from] ImageLoadError),
+    #[error("combined grid image dimensions {0} do not match the layout: {layout}", layout = layout_description())]
+    InvalidImageDimensions(ImageDimensions),
+}
+
+#[derive(Debug, Error)]
+pub enum SaveImageError {
+    #[error(transparent)]
+    ImageWriteError(#[from] ImageWriteError),
+    #[error(transparent)]
+    TileKindError(#[from] TileKindError),
+}
+
+fn band_height(tile_kind: TileKind) -> u32 {
+    LABEL_HEIGHT + Grid::image_dimensions(&tile_kind, GRID_HEIGHT).height
+}
+
+fn layout_description() -> String {
+    format!("{} pixels wide, N SD bands of {} pixels then N HD bands of {} pixels, each band being a {LABEL_HEIGHT} pixels label strip followed by a 16x16 grid",
+        image_dimensions(1).width, band_height(TileKind::SD), band_height(TileKind::HD))
+}
+
+pub fn image_dimensions(page_count: usize) -> ImageDimensions {
+    let width = TileKind::iter().map(|tile_kind| Grid::image_dimensions(&tile_kind, GRID_HEIGHT).width).max().unwrap();
+    let page_pair_height: u32 = TileKind::iter().map(band_height).sum();
+    ImageDimensions::new(width, page_count as u32 * page_pair_height)
+}
+
+// position of the top of the band of each page, SD pages first
+fn band_positions(page_count: usize) -> Vec<(TileKind, usize, u32)> {
+    let mut y = 0;
+    let mut positions = Vec::with_capacity(2 * page_count);
+    for tile_kind in [TileKind::SD, TileKind::HD] {
+        for page_index in 0..page_count {
+            positions.push((tile_kind, page_index, y));
+            y += band_height(tile_kind);
+        }
+    }
+    positions
+}
+
+pub(crate) fn draw_label(image: &mut Image, y: u32, text: &str) {
+    let glyph_width = 3 * LABEL_SCALE + LABEL_SCALE;
+    for (char_index, char) in text.chars().enumerate() {
+        let rows = match LABEL_GLYPHS.iter().find(|(glyph_char, _)| *glyph_char == char) {
+            Some((_, rows)) => rows,
+            None => continue,
+        };
+        let char_x = LABEL_MARGIN + char_index as u32 * glyph_width;
+        for (row_index, row) in rows.iter().enumerate() {
+            for column in (0..3).filter(|column| row & (0b100 >> column) != 0) {
+                for (dx, dy) in itertools::iproduct!(0..LABEL_SCALE, 0..LABEL_SCALE) {
+                    image.put_pixel(char_x + column * LABEL_SCALE + dx, y + LABEL_MARGIN + row_index as u32 * LABEL_SCALE + dy, LABEL_COLOR);
+                }
+            }
+        }
+    }
+}
+
+pub fn load_image<P: AsRef<Path>>(path: P) -> Result<TileSet, LoadError> {
+    load_image_decoded(path, ImageDecoding::default())
+}
+
+// the number of pages is detected from the image height
+pub fn load_image_decoded<P: AsRef<Path>>(path: P, decoding: ImageDecoding) -> Result<TileSet, LoadError> {
+    let image = read_rgba_image_file(&path, decoding)?;
+    let dimensions = ImageDimensions::from(image.dimensions());
+    let page_count = (dimensions.height / image_dimensions(1).height) as usize;
+    if page_count == 0 || dimensions != image_dimensions(page_count) {
+        return Err(LoadError::InvalidImageDimensions(dimensions));
+    }
+    log::debug!("detected combined grid with {page_count} pages per tile kind in {}", path.as_ref().to_string_lossy());
+
+    let mut tile_set = TileSet { sd_tiles: vec![], hd_tiles: vec![] };
+    for (tile_kind, _, y) in band_positions(page_count) {
+        let grid_dimensions = Grid::image_dimensions(&tile_kind, GRID_HEIGHT);
+        let grid_image = image.view(0, y + LABEL_HEIGHT, grid_dimensions.width, grid_dimensions.height).to_image();
+        let tiles = match tile_kind {
+            TileKind::SD => &mut tile_set.sd_tiles,
+            TileKind::HD => &mut tile_set.hd_tiles,
+        };
+        tiles.extend(Grid::split_image(&grid_image, tile_kind, GRID_HEIGHT));
+    }
+    Ok(tile_set)
+}
+
+// the pages not filled by the tiles are completed with transparent tiles
+pub fn generate_image(tile_set: &TileSet) -> Result<Image, SaveImageError> {
+    let page_count = bin_file::page_count(tile_set.sd_tiles.len().max(tile_set.hd_tiles.len()));
+    let dimensions = image_dimensions(page_count);
+    let mut image = Image::from_pixel(dimensions.width, dimensions.height, BACKGROUND_COLOR);
+    for (tile_kind, page_index, y) in band_positions(page_count) {
+        let mut page_tiles: Vec<Tile> = tile_set[tile_kind].iter().skip(page_index * bin_file::TILE_COUNT).take(bin_file::TILE_COUNT).cloned().collect();
+        page_tiles.resize(bin_file::TILE_COUNT, Tile::new(tile_kind));
+        draw_label(&mut image, y, &format!("{tile_kind} {}", page_index + 1));
+        image.copy_from(&Grid::from(page_tiles).generate_image()?, 0, y + LABEL_HEIGHT).unwrap();
+    }
+    Ok(image)
+}
+
+pub fn save_image<P: AsRef<Path>>(tile_set: &TileSet, path: P) -> Result<(), SaveImageError> {
+    generate_image(tile_set)?.write_image_file(path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+    use temp_dir::TempDir;
+
+    use crate::image::WriteImageFile;
+    use crate::osd::bin_file::TILE_COUNT;
+    use crate::osd::tile::{Tile, Kind as TileKind, container::tile_set::TileSet};
+
+    use super::{generate_image, image_dimensions, load_image, LoadError, LABEL_HEIGHT};
+
+    #[test]
+    fn round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let glyph = |tile_kind, index: u32| {
+            let mut tile = Tile::new(tile_kind);
+            tile.put_pixel(index % 20, 3, Rgba([255, 255, 255, 255]));
+            tile
+        };
+        let sd_tiles = (0..300).map(|index| glyph(TileKind::SD, index)).collect();
+        let hd_tiles = (0..20).map(|index| glyph(TileKind::HD, index)).collect();
+        let tile_set = TileSet::try_from_tiles(sd_tiles, hd_tiles).unwrap();
+
+        let image = generate_image(&tile_set).unwrap();
+        assert_eq!(image.dimensions(), (606, 2 * (LABEL_HEIGHT + 894 + LABEL_HEIGHT + 606)));
+        assert!(itertools::iproduct!(0..40, 0..LABEL_HEIGHT).any(|(x, y)| *image.get_pixel(x, y) == Rgba([255, 255, 255, 255])));
+        let path = temp_dir.child("combined.png");
+        image.write_image_file(&path).unwrap();
+
+        let loaded = load_image(&path).unwrap();
+        assert_eq!(loaded.sd_tiles().len(), 2 * TILE_COUNT);
+        assert_eq!(loaded.hd_tiles().len(), 2 * TILE_COUNT);
+        for (loaded_tiles, tiles) in [(loaded.sd_tiles(), tile_set.sd_tiles()), (loaded.hd_tiles(), tile_set.hd_tiles())] {
+            assert!(loaded_tiles.iter().zip(tiles).all(|(loaded_tile, tile)| loaded_tile.image() == tile.image()));
+            assert!(loaded_tiles[tiles.len()..].iter().all(|tile| tile.pixels().all(|pixel| pixel.0[3] == 0)));
+        }
+
+        let truncated = image::imageops::crop_imm(&image, 0, 0, 606, image_dimensions(1).height + 10).to_image();
+        truncated.write_image_file(&path).unwrap();
+        let error = load_image(&path).err().unwrap();
+        assert!(matches!(error, LoadError::InvalidImageDimensions(_)));
+        assert!(error.to_string().contains("606 pixels wide"));
+    }
+}
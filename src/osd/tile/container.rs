@@ -11,12 +11,27 @@ pub mod symbol_tiles_iter;
 pub mod load_symbols_from_dir;
 pub mod save_to_grid;
 pub mod save_to_avatar_file;
+pub mod stats;
+pub mod duplicates;
+pub mod info;
+pub mod content_hash;
+pub mod manifest;
+pub mod dir_check;
+pub mod dir_fix;
+pub mod char_map;
+pub mod patch;
+pub mod split_symbols;
+#[cfg(feature = "fs")]
+pub mod archive;
+mod dir_files_iter;
+mod image_files;
 
 use tap::Tap;
+use thiserror::Error;
 
 use symbol::{
     Symbol,
-    spec::Specs as SymbolSpecs
+    spec::{Specs as SymbolSpecs, InvalidSpecsError},
 };
 
 use symbol_tiles_iter::IntoSymbolsTilesIter;
@@ -45,18 +60,28 @@ impl<'a> AsTilesVec<'a> for &[Symbol] {
     }
 }
 
+#[derive(Debug, Error)]
+pub enum ToSymbolsError {
+    #[error(transparent)]
+    TileKindError(#[from] TileKindError),
+    #[error(transparent)]
+    InvalidSpecs(#[from] InvalidSpecsError),
+}
+
 pub trait ToSymbols {
-    fn to_symbols(&self, specs: &SymbolSpecs) -> Result<Vec<Symbol>, TileKindError>;
+    fn to_symbols(&self, specs: &SymbolSpecs) -> Result<Vec<Symbol>, ToSymbolsError>;
 }
 
 impl ToSymbols for &[Tile] {
-    fn to_symbols(&self, specs: &SymbolSpecs) -> Result<Vec<Symbol>, TileKindError> {
+    fn to_symbols(&self, specs: &SymbolSpecs) -> Result<Vec<Symbol>, ToSymbolsError> {
+        let tile_kind = self.tile_kind()?;
+        InvalidSpecsError::check(specs.check_tiles(self, tile_kind))?;
         let mut tile_index = 0;
         let mut symbols = vec![];
         while tile_index < self.len() {
             let symbol = match specs.find_start_index(tile_index) {
                 Some(sym_spec) =>
-                    Symbol::try_from(Vec::from(&self[sym_spec.tile_index_range()]))?
+                    Symbol::try_from_tiles_columns(Vec::from(&self[sym_spec.tile_index_range()]), sym_spec.columns())?
                         .tap(|_| tile_index += sym_spec.span()),
                 None =>
                     Symbol::from(self[tile_index].clone())
@@ -69,7 +94,23 @@ impl ToSymbols for &[Tile] {
 }
 
 impl ToSymbols for Vec<Tile> {
-    fn to_symbols(&self, specs: &SymbolSpecs) -> Result<Vec<Symbol>, TileKindError> {
+    fn to_symbols(&self, specs: &SymbolSpecs) -> Result<Vec<Symbol>, ToSymbolsError> {
         self.as_slice().to_symbols(specs)
     }
 }
+
+#[derive(Debug, Error)]
+#[error("too many tiles: source has {tile_count} tiles but the target holds at most {capacity} tiles, {} tiles overflowing", tile_count - capacity)]
+pub struct CapacityError {
+    pub tile_count: usize,
+    pub capacity: usize,
+}
+
+impl CapacityError {
+    pub fn check(tile_count: usize, capacity: usize) -> Result<(), Self> {
+        if tile_count > capacity {
+            return Err(Self { tile_count, capacity });
+        }
+        Ok(())
+    }
+}
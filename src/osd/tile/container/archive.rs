@@ -0,0 +1,332 @@
+
+use std::collections::BTreeMap;
+use std::io::{Error as IOError, ErrorKind as IOErrorKind, Read, Write};
+use std::path::{Component, Path, PathBuf};
+
+use flate2::{Compression, read::GzDecoder, write::GzEncoder};
+use fs_err::File;
+use image::RgbaImage;
+use strum::IntoEnumIterator;
+use thiserror::Error;
+
+use crate::atomic_file::AtomicFile;
+use crate::image::{read_rgba_image_bytes, ReadError as ImageReadError, WriteError as ImageWriteError};
+use crate::image_format::ImageDecoding;
+use crate::osd::tile::Kind as TileKind;
+use super::image_files::ImageFiles;
+
+// the tile and symbol images are a few KiB, the limits only guard against decompression bombs
+pub const DEFAULT_MAX_ENTRY_SIZE: u64 = 16 * 1024 * 1024;
+pub const DEFAULT_MAX_TOTAL_SIZE: u64 = 256 * 1024 * 1024;
+
+#[derive(Debug, Error)]
+pub enum ArchiveError {
+    #[error("unknown archive format for {0}: expected a .zip, .tar.gz or .tgz file")]
+    UnknownFormat(PathBuf),
+    #[error("failed to read archive {file_path}: {error}")]
+    ReadError { file_path: PathBuf, error: IOError },
+    #[error("failed to write archive {file_path}: {error}")]
+    WriteError { file_path: PathBuf, error: IOError },
+    #[error("invalid zip archive {file_path}: {error}")]
+    ZipError { file_path: PathBuf, error: zip::result::ZipError },
+    #[error("entry {entry_path} of archive {file_path} is larger than {max_size}B")]
+    EntryTooLarge { file_path: PathBuf, entry_path: PathBuf, max_size: u64 },
+    #[error("the entries of archive {file_path} are larger than {max_size}B in total")]
+    TooLarge { file_path: PathBuf, max_size: u64 },
+    #[error("directory {dir_path} is not in archive {file_path}")]
+    DirNotInArchive { file_path: PathBuf, dir_path: PathBuf },
+}
+
+impl ArchiveError {
+    fn read_error<P: AsRef<Path>>(file_path: P, error: IOError) -> Self {
+        Self::ReadError { file_path: file_path.as_ref().to_path_buf(), error }
+    }
+
+    fn write_error<P: AsRef<Path>>(file_path: P, error: IOError) -> Self {
+        Self::WriteError { file_path: file_path.as_ref().to_path_buf(), error }
+    }
+
+    fn zip_error<P: AsRef<Path>>(file_path: P, error: zip::result::ZipError) -> Self {
+        Self::ZipError { file_path: file_path.as_ref().to_path_buf(), error }
+    }
+}
+
+#[derive(Debug, derive_more::Error, derive_more::Display, derive_more::From)]
+pub enum SaveToArchiveError {
+    ArchiveError(ArchiveError),
+    ImageWriteError(ImageWriteError),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    Zip,
+    TarGz,
+}
+
+impl ArchiveFormat {
+
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, ArchiveError> {
+        let file_name = path.as_ref().file_name().map(|file_name| file_name.to_string_lossy().to_ascii_lowercase()).unwrap_or_default();
+        if file_name.ends_with(".zip") {
+            Ok(Self::Zip)
+        } else if file_name.ends_with(".tar.gz") || file_name.ends_with(".tgz") {
+            Ok(Self::TarGz)
+        } else {
+            Err(ArchiveError::UnknownFormat(path.as_ref().to_path_buf()))
+        }
+    }
+
+}
+
+// maximum decompressed sizes of the entries read from an archive
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ArchiveLimits {
+    pub max_entry_size: u64,
+    pub max_total_size: u64,
+}
+
+impl Default for ArchiveLimits {
+    fn default() -> Self {
+        Self { max_entry_size: DEFAULT_MAX_ENTRY_SIZE, max_total_size: DEFAULT_MAX_TOTAL_SIZE }
+    }
+}
+
+// only the regular path components are kept so that the entries cannot point outside of the archive
+fn safe_entry_path(path: &Path) -> Option<PathBuf> {
+    let mut entry_path = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(component) => entry_path.push(component),
+            Component::CurDir => {},
+            _ => return None,
+        }
+    }
+    (!entry_path.as_os_str().is_empty()).then_some(entry_path)
+}
+
+// Files of a zip or tar.gz archive held in memory with the structure of the tile/symbol set directories
+// e.g. SD/001.png. The archives where the set is inside a single top directory e.g. MyFont/SD/001.png are also accepted.
+#[derive(Debug, Clone)]
+pub struct Archive {
+    path: PathBuf,
+    entries: BTreeMap<PathBuf, Vec<u8>>,
+}
+
+impl Archive {
+
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self { path: path.as_ref().to_path_buf(), entries: BTreeMap::new() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn load_file<P: AsRef<Path>>(path: P) -> Result<Self, ArchiveError> {
+        Self::load_file_limited(path, ArchiveLimits::default())
+    }
+
+    pub fn load_file_limited<P: AsRef<Path>>(path: P, limits: ArchiveLimits) -> Result<Self, ArchiveError> {
+        let path = path.as_ref();
+        let format = ArchiveFormat::from_path(path)?;
+        let file = File::open(path).map_err(|error| ArchiveError::read_error(path, error))?;
+        let mut archive = Self::new(path);
+        let mut total_size = 0;
+        // the sizes given by the headers cannot be trusted, the entries are read up to the limit
+        let mut read_entry = |entry: &mut dyn Read, entry_path: &Path, size_hint: u64| -> Result<Vec<u8>, ArchiveError> {
+            let mut content = Vec::with_capacity(size_hint.min(limits.max_entry_size) as usize);
+            entry.take(limits.max_entry_size + 1).read_to_end(&mut content).map_err(|error| ArchiveError::read_error(path, error))?;
+            if content.len() as u64 > limits.max_entry_size {
+                return Err(ArchiveError::EntryTooLarge { file_path: path.to_path_buf(), entry_path: entry_path.to_path_buf(), max_size: limits.max_entry_size })
+            }
+            total_size += content.len() as u64;
+            if total_size > limits.max_total_size {
+                return Err(ArchiveError::TooLarge { file_path: path.to_path_buf(), max_size: limits.max_total_size })
+            }
+            Ok(content)
+        };
+        match format {
+            ArchiveFormat::Zip => {
+                let mut zip = zip::ZipArchive::new(file).map_err(|error| ArchiveError::zip_error(path, error))?;
+                for index in 0..zip.len() {
+                    let mut entry = zip.by_index(index).map_err(|error| ArchiveError::zip_error(path, error))?;
+                    let entry_path = match entry.enclosed_name().and_then(safe_entry_path) {
+                        Some(entry_path) if entry.is_file() => entry_path,
+                        _ => continue,
+                    };
+                    let size_hint = entry.size();
+                    let content = read_entry(&mut entry, &entry_path, size_hint)?;
+                    archive.entries.insert(entry_path, content);
+                }
+            },
+            ArchiveFormat::TarGz => {
+                let mut tar = tar::Archive::new(GzDecoder::new(file));
+                for entry in tar.entries().map_err(|error| ArchiveError::read_error(path, error))? {
+                    let mut entry = entry.map_err(|error| ArchiveError::read_error(path, error))?;
+                    let entry_path = entry.path().map_err(|error| ArchiveError::read_error(path, error))?.into_owned();
+                    let entry_path = match safe_entry_path(&entry_path) {
+                        Some(entry_path) if entry.header().entry_type().is_file() => entry_path,
+                        _ => continue,
+                    };
+                    let size_hint = entry.header().size().unwrap_or(0);
+                    let content = read_entry(&mut entry, &entry_path, size_hint)?;
+                    archive.entries.insert(entry_path, content);
+                }
+            },
+        }
+        archive.strip_top_dir();
+        Ok(archive)
+    }
+
+    // removes the single top directory holding the set directories
+    fn strip_top_dir(&mut self) {
+        let is_set_dir = |component: &std::ffi::OsStr| TileKind::iter().any(|tile_kind| component == tile_kind.set_dir_name());
+        let mut top_dirs = self.entries.keys().map(|entry_path| entry_path.components().next());
+        let top_dir = match top_dirs.next() {
+            Some(Some(Component::Normal(top_dir))) if !is_set_dir(top_dir) => PathBuf::from(top_dir),
+            _ => return,
+        };
+        let in_top_dir = |entry_path: &Path| entry_path.starts_with(&top_dir) && entry_path != top_dir;
+        if !self.entries.keys().all(|entry_path| in_top_dir(entry_path)) {
+            return
+        }
+        self.entries = std::mem::take(&mut self.entries).into_iter()
+            .map(|(entry_path, content)| (entry_path.strip_prefix(&top_dir).unwrap().to_path_buf(), content))
+            .collect();
+    }
+
+    // the path is relative to the root of the archive e.g. SD/001.png
+    pub fn insert<P: AsRef<Path>>(&mut self, path: P, content: Vec<u8>) {
+        self.entries.insert(path.as_ref().to_path_buf(), content);
+    }
+
+    pub fn get<P: AsRef<Path>>(&self, path: P) -> Option<&[u8]> {
+        self.entries.get(path.as_ref()).map(Vec::as_slice)
+    }
+
+    pub fn entry_paths(&self) -> impl Iterator<Item = &PathBuf> {
+        self.entries.keys()
+    }
+
+    // the entries are written in path order without timestamps so that the same files always give the same archive, an
+    // existing archive is only replaced once the new one is complete
+    pub fn save_file(&self) -> Result<(), ArchiveError> {
+        let path = &self.path;
+        let format = ArchiveFormat::from_path(path)?;
+        let atomic_file = AtomicFile::new(path);
+        let file = File::create(atomic_file.temp_path()).map_err(|error| ArchiveError::write_error(path, error))?;
+        match format {
+            ArchiveFormat::Zip => {
+                let mut zip = zip::ZipWriter::new(file);
+                let options = zip::write::FileOptions::default()
+                    .compression_method(zip::CompressionMethod::Deflated)
+                    .last_modified_time(zip::DateTime::default());
+                for (entry_path, content) in &self.entries {
+                    let name = entry_path.components().map(|component| component.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/");
+                    zip.start_file(name, options).map_err(|error| ArchiveError::zip_error(path, error))?;
+                    zip.write_all(content).map_err(|error| ArchiveError::write_error(path, error))?;
+                }
+                zip.finish().map_err(|error| ArchiveError::zip_error(path, error))?;
+            },
+            ArchiveFormat::TarGz => {
+                let mut tar = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+                for (entry_path, content) in &self.entries {
+                    let mut header = tar::Header::new_gnu();
+                    header.set_size(content.len() as u64);
+                    header.set_mode(0o644);
+                    header.set_mtime(0);
+                    tar.append_data(&mut header, entry_path, content.as_slice()).map_err(|error| ArchiveError::write_error(path, error))?;
+                }
+                let encoder = tar.into_inner().map_err(|error| ArchiveError::write_error(path, error))?;
+                encoder.finish().map_err(|error| ArchiveError::write_error(path, error))?;
+            },
+        }
+        atomic_file.persist().map_err(|error| ArchiveError::write_error(path, error))?;
+        Ok(())
+    }
+
+    pub(crate) fn dir_image_files<P: AsRef<Path>>(&self, dir: P) -> ArchiveDirImageFiles<'_> {
+        ArchiveDirImageFiles { archive: self, dir_path: self.path.join(dir) }
+    }
+
+}
+
+// directory of an archive, the paths are prefixed with the archive path e.g. fonts.zip/SD/001.png
+pub(crate) struct ArchiveDirImageFiles<'a> {
+    archive: &'a Archive,
+    dir_path: PathBuf,
+}
+
+impl<'a> ImageFiles for ArchiveDirImageFiles<'a> {
+
+    fn dir_path(&self) -> &Path {
+        &self.dir_path
+    }
+
+    fn file_paths(&self) -> Result<Vec<PathBuf>, IOError> {
+        let dir = self.dir_path.strip_prefix(&self.archive.path).map_err(|_| {
+            let error = ArchiveError::DirNotInArchive { file_path: self.archive.path.clone(), dir_path: self.dir_path.clone() };
+            IOError::new(IOErrorKind::InvalidInput, error)
+        })?;
+        let file_paths: Vec<PathBuf> = self.archive.entries.keys()
+            .filter(|entry_path| entry_path.parent() == Some(dir))
+            .map(|entry_path| self.archive.path.join(entry_path))
+            .collect();
+        if file_paths.is_empty() {
+            return Err(IOError::new(IOErrorKind::NotFound, "no such directory in the archive"));
+        }
+        Ok(file_paths)
+    }
+
+    fn read_rgba_image(&self, path: &Path, decoding: ImageDecoding) -> Result<RgbaImage, ImageReadError> {
+        let content = path.strip_prefix(&self.archive.path).ok()
+            .and_then(|entry_path| self.archive.get(entry_path))
+            .ok_or_else(|| ImageReadError::open_error(path, IOError::new(IOErrorKind::NotFound, "no such file in the archive")))?;
+        read_rgba_image_bytes(path, content, decoding)
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+
+    use temp_dir::TempDir;
+
+    use super::{Archive, ArchiveError, ArchiveFormat, ArchiveLimits};
+
+    #[test]
+    fn round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        for file_name in ["font.zip", "font.tar.gz"] {
+            let path = temp_dir.child(file_name);
+            let mut archive = Archive::new(&path);
+            archive.insert("MyFont/SD/000.png", vec![1, 2, 3]);
+            archive.insert("MyFont/HD/000.png", vec![4, 5]);
+            archive.save_file().unwrap();
+            let loaded = Archive::load_file(&path).unwrap();
+            assert_eq!(loaded.get("SD/000.png"), Some([1, 2, 3].as_slice()));
+            assert_eq!(loaded.get("HD/000.png"), Some([4, 5].as_slice()));
+        }
+        assert!(ArchiveFormat::from_path("font.tar").is_err());
+    }
+
+    #[test]
+    fn size_limits() {
+        let temp_dir = TempDir::new().unwrap();
+        for file_name in ["font.zip", "font.tar.gz"] {
+            let path = temp_dir.child(file_name);
+            let mut archive = Archive::new(&path);
+            archive.insert("SD/000.png", vec![0; 100]);
+            archive.insert("SD/001.png", vec![0; 100]);
+            archive.save_file().unwrap();
+
+            let limits = ArchiveLimits { max_entry_size: 100, max_total_size: 200 };
+            assert!(Archive::load_file_limited(&path, limits).is_ok());
+            let limits = ArchiveLimits { max_entry_size: 99, max_total_size: 200 };
+            assert!(matches!(Archive::load_file_limited(&path, limits), Err(ArchiveError::EntryTooLarge { max_size: 99, .. })));
+            let limits = ArchiveLimits { max_entry_size: 100, max_total_size: 199 };
+            assert!(matches!(Archive::load_file_limited(&path, limits), Err(ArchiveError::TooLarge { max_size: 199, .. })));
+        }
+    }
+
+}
@@ -0,0 +1,150 @@
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::File,
+    io::Error as IOError,
+    path::{Path, PathBuf},
+};
+
+use derive_more::From;
+use parse_int::parse;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::osd::tile::Tile;
+
+
+#[derive(Debug, Error, From)]
+pub enum LoadCharMapError {
+    #[error("failed to open char map file: {0}")]
+    OpenError(IOError),
+    #[from(ignore)]
+    #[error("failed to parse char map file {file_path}: {error}")]
+    FileStructureError { file_path: PathBuf, error: serde_yaml::Error },
+    #[from(ignore)]
+    #[error("invalid char map entry in file {file_path}: {source_index} -> {destination_index}")]
+    InvalidEntry { file_path: PathBuf, source_index: String, destination_index: String },
+    #[from(ignore)]
+    #[error("tile {destination_index} is the destination of both tile {first_source_index} and tile {second_source_index} in char map file {file_path}")]
+    DuplicateDestination { file_path: PathBuf, destination_index: usize, first_source_index: usize, second_source_index: usize },
+}
+
+#[derive(Debug, Error)]
+#[error("source tile {index} of the char map is out of range, the collection holds {tile_count} tiles")]
+pub struct RemapError {
+    pub index: usize,
+    pub tile_count: usize,
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Deserialize)]
+#[serde(untagged)]
+enum CharMapIndex {
+    Number(usize),
+    Text(String),
+}
+
+impl CharMapIndex {
+
+    fn parse(&self) -> Option<usize> {
+        match self {
+            CharMapIndex::Number(index) => Some(*index),
+            CharMapIndex::Text(text) => parse(text).ok(),
+        }
+    }
+
+    fn text(&self) -> String {
+        match self {
+            CharMapIndex::Number(index) => index.to_string(),
+            CharMapIndex::Text(text) => text.clone(),
+        }
+    }
+
+}
+
+// Moves tiles between the layouts of different firmwares e.g. INAV -> Betaflight.
+// Char map files map the source tile indices to the destination tile indices, both can be hexadecimal
+// e.g. `0x08: 0x1E`. The tiles which are not the destination of an entry keep the tile at the same index.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CharMap(BTreeMap<usize, usize>);
+
+impl CharMap {
+
+    pub fn load_file<P: AsRef<Path>>(path: P) -> Result<Self, LoadCharMapError> {
+        let path = path.as_ref();
+        let file_content: HashMap<CharMapIndex, CharMapIndex> = serde_yaml::from_reader(File::open(path)?)
+            .map_err(|error| LoadCharMapError::FileStructureError { file_path: path.to_path_buf(), error })?;
+        let mut destinations = BTreeMap::new();
+        for (source_index, destination_index) in file_content {
+            let (source, destination) = match source_index.parse().zip(destination_index.parse()) {
+                Some(indices) => indices,
+                None => return Err(LoadCharMapError::InvalidEntry {
+                    file_path: path.to_path_buf(),
+                    source_index: source_index.text(),
+                    destination_index: destination_index.text(),
+                }),
+            };
+            if let Some(other_source) = destinations.insert(destination, source) {
+                return Err(LoadCharMapError::DuplicateDestination {
+                    file_path: path.to_path_buf(),
+                    destination_index: destination,
+                    first_source_index: other_source.min(source),
+                    second_source_index: other_source.max(source),
+                });
+            }
+        }
+        Ok(Self(destinations))
+    }
+
+    // destination tile index -> source tile index
+    pub fn from_destinations(destinations: BTreeMap<usize, usize>) -> Self {
+        Self(destinations)
+    }
+
+    pub fn destinations(&self) -> &BTreeMap<usize, usize> {
+        &self.0
+    }
+
+    // the remapped tiles are extended with blank tiles when a destination is past the end of the source tiles
+    pub fn remap(&self, tiles: &[Tile]) -> Result<Vec<Tile>, RemapError> {
+        let mut remapped = tiles.to_vec();
+        let tile_kind = match tiles.first() {
+            Some(tile) => tile.kind(),
+            None => return Ok(remapped),
+        };
+        for (destination, source) in &self.0 {
+            let tile = tiles.get(*source).ok_or(RemapError { index: *source, tile_count: tiles.len() })?;
+            if *destination >= remapped.len() {
+                remapped.resize(destination + 1, Tile::new(tile_kind));
+            }
+            remapped[*destination] = tile.clone();
+        }
+        Ok(remapped)
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::collections::BTreeMap;
+
+    use crate::osd::tile::{Tile, Kind as TileKind};
+
+    use super::CharMap;
+
+    #[test]
+    fn remap() {
+        let mut tiles = vec![Tile::new(TileKind::SD); 4];
+        tiles[1].put_pixel(0, 0, image::Rgba([255, 255, 255, 255]));
+        let char_map = CharMap::from_destinations(BTreeMap::from([(3, 1), (5, 1)]));
+        let remapped = char_map.remap(&tiles).unwrap();
+        assert_eq!(remapped.len(), 6);
+        assert_eq!(*remapped[1], *tiles[1]);
+        assert_eq!(*remapped[3], *tiles[1]);
+        assert_eq!(*remapped[5], *tiles[1]);
+        assert_eq!(*remapped[4], *Tile::new(TileKind::SD));
+        let char_map = CharMap::from_destinations(BTreeMap::from([(0, 4)]));
+        assert!(char_map.remap(&tiles).is_err());
+    }
+
+}
@@ -0,0 +1,88 @@
+
+use sha2::{Digest, Sha256};
+
+use crate::osd::tile::{Tile, Kind as TileKind};
+use super::{stats::is_blank, tile_set::TileSet};
+
+// Content hashes are computed over the raw RGBA data of the tiles in index order so that they do not depend on the
+// format the tiles were loaded from. The trailing blank tiles are ignored since each format pads the collections
+// differently e.g. bin file pages always hold 256 tiles and grids are 16 tiles wide.
+
+
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn canonical_tiles(tiles: &[Tile]) -> &[Tile] {
+    let len = tiles.iter().rposition(|tile| !is_blank(tile)).map_or(0, |last_index| last_index + 1);
+    &tiles[..len]
+}
+
+fn update_hasher(hasher: &mut Sha256, tiles: &[Tile]) {
+    for tile in canonical_tiles(tiles) {
+        hasher.update(tile.as_raw());
+    }
+}
+
+pub fn tile_hash(tile: &Tile) -> String {
+    to_hex(&Sha256::digest(tile.as_raw()))
+}
+
+// hashes of each tile up to the last non-blank one
+pub fn tile_hashes(tiles: &[Tile]) -> Vec<String> {
+    canonical_tiles(tiles).iter().map(tile_hash).collect()
+}
+
+pub trait ContentHash {
+    fn content_hash(&self) -> String;
+}
+
+impl ContentHash for &[Tile] {
+    fn content_hash(&self) -> String {
+        let mut hasher = Sha256::new();
+        update_hasher(&mut hasher, self);
+        to_hex(&hasher.finalize())
+    }
+}
+
+impl ContentHash for Vec<Tile> {
+    fn content_hash(&self) -> String {
+        self.as_slice().content_hash()
+    }
+}
+
+impl TileSet {
+
+    // the SD tiles then the HD tiles, each preceded by their kind so that the tiles cannot be attributed to the other kind
+    pub fn content_hash(&self) -> String {
+        let mut hasher = Sha256::new();
+        for (tile_kind, tiles) in [(TileKind::SD, &self.sd_tiles), (TileKind::HD, &self.hd_tiles)] {
+            hasher.update(tile_kind.to_string().as_bytes());
+            update_hasher(&mut hasher, tiles);
+        }
+        to_hex(&hasher.finalize())
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+
+    use crate::osd::tile::{Tile, Kind as TileKind};
+
+    use super::{ContentHash, tile_hashes};
+
+    #[test]
+    fn trailing_blank_tiles_ignored() {
+        let mut glyph = Tile::new(TileKind::SD);
+        glyph.put_pixel(1, 2, Rgba([255, 255, 255, 255]));
+        let tiles = vec![Tile::new(TileKind::SD), glyph];
+        let mut padded_tiles = tiles.clone();
+        padded_tiles.resize(256, Tile::new(TileKind::SD));
+
+        assert_eq!(tiles.content_hash(), padded_tiles.content_hash());
+        assert_ne!(tiles.content_hash(), tiles[..1].to_vec().content_hash());
+        assert_eq!(tile_hashes(&padded_tiles).len(), 2);
+    }
+}
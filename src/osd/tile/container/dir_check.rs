@@ -0,0 +1,251 @@
+
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::io::{Error as IOError, ErrorKind};
+use std::path::{Path, PathBuf};
+
+use image::{ColorType, DynamicImage, GenericImageView};
+use lazy_static::lazy_static;
+use regex::Regex;
+use thiserror::Error;
+
+use crate::dimensions::Dimensions;
+use crate::image::read_image_file;
+use crate::image_format::is_readable_image_path;
+use crate::osd::tile::Kind as TileKind;
+use super::dir_files_iter::dir_files_iter;
+use super::split_symbols::parse_split_tile_file_stem;
+
+// Detection of the common problems of tile and symbol directories, shared by the commands checking and fixing them
+
+
+pub type ImageDimensions = Dimensions<u32>;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DirContent {
+    Tiles,
+    Symbols,
+}
+
+#[derive(Debug, Error)]
+#[error("failed to list files from directory {dir_path}: {error}")]
+pub struct ScanDirError {
+    dir_path: PathBuf,
+    error: IOError,
+}
+
+impl ScanDirError {
+    fn new<P: AsRef<Path>>(dir_path: P, error: IOError) -> Self {
+        Self { dir_path: dir_path.as_ref().to_path_buf(), error }
+    }
+}
+
+// index(es) and extension parsed from the name of a tile/symbol image file, with or without the zero padding
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageFileName {
+    pub start_index: usize,
+    pub end_index: Option<usize>,
+    // columns and rows of tiles of the symbols spanning several rows e.g. 2x2 for 256-259_2x2.png
+    pub shape: Option<(usize, usize)>,
+    // symbol name suffix of named symbol directories e.g. SYM_RSSI for 001_SYM_RSSI.png
+    pub symbol_name: Option<String>,
+    pub extension: String,
+}
+
+impl ImageFileName {
+
+    fn parse<P: AsRef<Path>>(path: P, content: DirContent) -> Option<Self> {
+        lazy_static! {
+            static ref FILE_STEM_RE: Regex = Regex::new(r"\A(?P<start_index>\d+)(?:-(?P<end_index>\d+)(?:_(?P<columns>\d+)x(?P<rows>\d+))?)?(?:_(?P<symbol_name>\w+))?\z").unwrap();
+        }
+        if !is_readable_image_path(&path) {
+            return None
+        }
+        let file_stem = path.as_ref().file_stem()?.to_str()?;
+        let captures = FILE_STEM_RE.captures(file_stem)?;
+        let start_index = captures.name("start_index")?.as_str().parse().ok()?;
+        let end_index = match (captures.name("end_index"), content) {
+            (Some(end_index), DirContent::Symbols) => Some(end_index.as_str().parse().ok()?),
+            (Some(_), DirContent::Tiles) => return None,
+            (None, _) => None,
+        };
+        let shape = match captures.name("columns").zip(captures.name("rows")) {
+            Some((columns, rows)) => Some((columns.as_str().parse().ok()?, rows.as_str().parse().ok()?)),
+            None => None,
+        };
+        let symbol_name = match (captures.name("symbol_name"), content) {
+            (Some(symbol_name), DirContent::Symbols) => Some(symbol_name.as_str().to_owned()),
+            // tiles of split symbol directories e.g. 031_SYM_SAT_1.png
+            (Some(_), DirContent::Tiles) if parse_split_tile_file_stem(file_stem).is_some() => Some(captures["symbol_name"].to_owned()),
+            (Some(_), DirContent::Tiles) => return None,
+            (None, _) => None,
+        };
+        if let Some(end_index) = end_index {
+            if end_index < start_index {
+                return None
+            }
+        }
+        let extension = path.as_ref().extension()?.to_str()?.to_owned();
+        Some(Self { start_index, end_index, shape, symbol_name, extension })
+    }
+
+    pub fn span(&self) -> usize {
+        match self.end_index {
+            Some(end_index) => end_index - self.start_index + 1,
+            None => 1,
+        }
+    }
+
+    pub fn normalized(&self) -> String {
+        let mut stem = match self.end_index {
+            Some(end_index) => format!("{:03}-{:03}", self.start_index, end_index),
+            None => format!("{:03}", self.start_index),
+        };
+        if let Some((columns, rows)) = self.shape {
+            stem = format!("{stem}_{columns}x{rows}");
+        }
+        if let Some(symbol_name) = &self.symbol_name {
+            stem = format!("{stem}_{symbol_name}");
+        }
+        format!("{stem}.{}", self.extension)
+    }
+
+    pub fn expected_dimensions(&self, tile_kind: TileKind) -> ImageDimensions {
+        let tile_dimensions = tile_kind.dimensions();
+        let (columns, rows) = self.shape.unwrap_or((self.span(), 1));
+        ImageDimensions::new(columns as u32 * tile_dimensions.width, rows as u32 * tile_dimensions.height)
+    }
+
+}
+
+// file found in a tile/symbol directory, `name` is none for files which are not tile/symbol images
+#[derive(Debug, Clone)]
+pub struct DirFile {
+    pub path: PathBuf,
+    pub file_name: String,
+    pub name: Option<ImageFileName>,
+}
+
+pub fn scan_dir<P: AsRef<Path>>(dir_path: P, content: DirContent) -> Result<Vec<DirFile>, ScanDirError> {
+    let mut files = vec![];
+    for path in dir_files_iter(&dir_path).map_err(|error| ScanDirError::new(&dir_path, error))? {
+        let path = path.map_err(|error| ScanDirError::new(&dir_path, error))?;
+        let file_name = match path.file_name() {
+            Some(file_name) => file_name.to_string_lossy().to_string(),
+            None => return Err(ScanDirError::new(&dir_path, IOError::new(ErrorKind::InvalidData, format!("no file name in {}", path.to_string_lossy())))),
+        };
+        let name = ImageFileName::parse(&path, content);
+        files.push(DirFile { path, file_name, name });
+    }
+    files.sort_by(|left, right| left.path.cmp(&right.path));
+    Ok(files)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DirIssue {
+    // index not zero padded to 3 digits which breaks the sorting in other tools
+    UnpaddedName { path: PathBuf, normalized_name: String },
+    // file which is not a tile/symbol image e.g. .DS_Store or Thumbs.db
+    ForeignFile { path: PathBuf },
+    // PNG image not stored as 8 bit RGBA e.g. indexed color without transparency
+    NotRgbaPng { path: PathBuf, color_type: ColorType },
+    WrongDimensions { path: PathBuf, dimensions: ImageDimensions, expected: ImageDimensions },
+    DuplicateIndex { index: usize, paths: Vec<PathBuf> },
+    Unreadable { path: PathBuf, error: String },
+}
+
+impl Display for DirIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use DirIssue::*;
+        match self {
+            UnpaddedName { path, normalized_name } => write!(f, "{}: index is not zero padded, should be named {normalized_name}", path.to_string_lossy()),
+            ForeignFile { path } => write!(f, "{}: not a tile/symbol image", path.to_string_lossy()),
+            NotRgbaPng { path, color_type } => write!(f, "{}: PNG image stored as {color_type:?} instead of RGBA", path.to_string_lossy()),
+            WrongDimensions { path, dimensions, expected } => write!(f, "{}: image is {dimensions} instead of {expected}", path.to_string_lossy()),
+            DuplicateIndex { index, paths } => write!(f, "several files for index {index}: {}", paths.iter().map(|path| path.to_string_lossy()).collect::<Vec<_>>().join(", ")),
+            Unreadable { path, error } => write!(f, "{}: {error}", path.to_string_lossy()),
+        }
+    }
+}
+
+pub fn is_png<P: AsRef<Path>>(path: P) -> bool {
+    matches!(path.as_ref().extension().and_then(|extension| extension.to_str()), Some(extension) if extension.eq_ignore_ascii_case("png"))
+}
+
+// issues of a single image file, the decoded image is returned so that it does not need to be read again
+pub fn check_image_file(file: &DirFile, tile_kind: TileKind) -> (Vec<DirIssue>, Option<DynamicImage>) {
+    let name = match &file.name {
+        Some(name) => name,
+        None => return (vec![DirIssue::ForeignFile { path: file.path.clone() }], None),
+    };
+
+    let mut issues = vec![];
+    if name.normalized() != file.file_name {
+        issues.push(DirIssue::UnpaddedName { path: file.path.clone(), normalized_name: name.normalized() });
+    }
+
+    let image = match read_image_file(&file.path) {
+        Ok(image) => image,
+        Err(error) => {
+            issues.push(DirIssue::Unreadable { path: file.path.clone(), error: error.to_string() });
+            return (issues, None)
+        },
+    };
+
+    if is_png(&file.path) && image.color() != ColorType::Rgba8 {
+        issues.push(DirIssue::NotRgbaPng { path: file.path.clone(), color_type: image.color() });
+    }
+
+    let dimensions = ImageDimensions::from(image.dimensions());
+    let expected = name.expected_dimensions(tile_kind);
+    if dimensions != expected {
+        issues.push(DirIssue::WrongDimensions { path: file.path.clone(), dimensions, expected });
+    }
+
+    (issues, Some(image))
+}
+
+// files sharing the same start index, whatever their padding or extension
+pub fn duplicate_indices(files: &[DirFile]) -> Vec<DirIssue> {
+    let mut files_by_index: BTreeMap<usize, Vec<PathBuf>> = BTreeMap::new();
+    for file in files {
+        if let Some(name) = &file.name {
+            files_by_index.entry(name.start_index).or_default().push(file.path.clone());
+        }
+    }
+    files_by_index.into_iter()
+        .filter(|(_, paths)| paths.len() > 1)
+        .map(|(index, paths)| DirIssue::DuplicateIndex { index, paths })
+        .collect()
+}
+
+pub fn check_dir<P: AsRef<Path>>(dir_path: P, content: DirContent, tile_kind: TileKind) -> Result<Vec<DirIssue>, ScanDirError> {
+    let files = scan_dir(dir_path, content)?;
+    let mut issues = duplicate_indices(&files);
+    for file in &files {
+        issues.extend(check_image_file(file, tile_kind).0);
+    }
+    Ok(issues)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::osd::tile::Kind as TileKind;
+
+    use super::{DirContent, ImageFileName, ImageDimensions};
+
+    #[test]
+    fn parse_file_names() {
+        let name = ImageFileName::parse("tiles/7.png", DirContent::Tiles).unwrap();
+        assert_eq!(name.normalized(), "007.png");
+        let name = ImageFileName::parse("symbols/30-32.bmp", DirContent::Symbols).unwrap();
+        assert_eq!(name.normalized(), "030-032.bmp");
+        assert_eq!(name.expected_dimensions(TileKind::SD), ImageDimensions::new(3 * 36, 54));
+        let name = ImageFileName::parse("symbols/1_SYM_RSSI.png", DirContent::Symbols).unwrap();
+        assert_eq!(name.normalized(), "001_SYM_RSSI.png");
+        assert!(ImageFileName::parse("tiles/1_SYM_RSSI.png", DirContent::Tiles).is_none());
+        assert!(ImageFileName::parse("tiles/30-32.png", DirContent::Tiles).is_none());
+        assert!(ImageFileName::parse("tiles/.DS_Store", DirContent::Tiles).is_none());
+        assert!(ImageFileName::parse("tiles/Thumbs.db", DirContent::Tiles).is_none());
+    }
+}
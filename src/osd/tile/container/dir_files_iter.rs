@@ -0,0 +1,30 @@
+use std::path::{Path, PathBuf};
+use std::io::Error as IOError;
+use std::vec::IntoIter;
+
+use crate::osd::font_metadata::is_metadata_file;
+
+
+// the files are listed in path order so that they are processed in the same order whatever the file system, the
+// font.toml metadata file is not part of the tiles
+pub(crate) struct DirFilesIterator(IntoIter<PathBuf>);
+
+impl Iterator for DirFilesIterator {
+    type Item = Result<PathBuf, IOError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(Ok)
+    }
+}
+
+pub(crate) fn dir_files_iter<P: AsRef<Path>>(path: P) -> Result<DirFilesIterator, IOError> {
+    let mut file_paths = vec![];
+    for entry in std::fs::read_dir(path)? {
+        let path = entry?.path();
+        if path.is_file() && !is_metadata_file(&path) {
+            file_paths.push(path);
+        }
+    }
+    file_paths.sort();
+    Ok(DirFilesIterator(file_paths.into_iter()))
+}
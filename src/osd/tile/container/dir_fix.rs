@@ -0,0 +1,259 @@
+
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::io::Error as IOError;
+use std::path::{Path, PathBuf};
+
+use image::DynamicImage;
+use thiserror::Error;
+
+use crate::create_path::{create_path, CreatePathError};
+use crate::image::{WriteImageFile, WriteError as ImageWriteError};
+use crate::osd::tile::{Image, Kind as TileKind, fit::Fit};
+use super::dir_check::{self, DirContent, DirFile, DirIssue, ScanDirError};
+
+
+#[derive(Debug, Clone, Default)]
+pub struct FixOptions {
+    // rename the files whose index is not zero padded to 3 digits
+    pub renumber: bool,
+    // remove the files which are not tile/symbol images
+    pub strip_foreign: bool,
+    // rewrite the PNG images which are not stored as 8 bit RGBA
+    pub normalize_png: bool,
+    // adjust the images which do not have the expected dimensions
+    pub fit: Option<Fit>,
+}
+
+#[derive(Debug)]
+pub enum FileAction {
+    Remove,
+    // `image` is none when the file content is kept as is
+    Write { target_name: String, image: Option<Image> },
+}
+
+#[derive(Debug)]
+pub struct PlannedFile {
+    pub source: PathBuf,
+    pub action: FileAction,
+    // human readable description of the fixes applied to the file, empty when the file is left untouched
+    pub changes: Vec<String>,
+}
+
+impl PlannedFile {
+    pub fn is_touched(&self) -> bool {
+        !self.changes.is_empty()
+    }
+}
+
+#[derive(Debug)]
+pub struct FixPlan {
+    pub dir: PathBuf,
+    pub files: Vec<PlannedFile>,
+    // issues which are not fixed with the selected fixes
+    pub unfixed: Vec<DirIssue>,
+}
+
+#[derive(Debug)]
+pub struct AmbiguousFixes(pub Vec<String>);
+
+impl Display for AmbiguousFixes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0.join("; "))
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum PlanFixesError {
+    #[error(transparent)]
+    ScanDirError(#[from] ScanDirError),
+    #[error("nothing was modified as some fixes are ambiguous: {0}")]
+    Ambiguous(AmbiguousFixes),
+}
+
+#[derive(Debug, Error)]
+pub enum ApplyFixesError {
+    #[error(transparent)]
+    CreatePathError(#[from] CreatePathError),
+    #[error(transparent)]
+    ImageWriteError(#[from] ImageWriteError),
+    #[error("failed to {operation} {path}: {error}")]
+    FileError { operation: &'static str, path: PathBuf, error: IOError },
+}
+
+impl ApplyFixesError {
+    fn file_error<P: AsRef<Path>>(operation: &'static str, path: P, error: IOError) -> Self {
+        Self::FileError { operation, path: path.as_ref().to_path_buf(), error }
+    }
+}
+
+fn plan_file(file: &DirFile, issues: Vec<DirIssue>, image: Option<DynamicImage>, options: &FixOptions,
+        ambiguities: &mut Vec<String>, unfixed: &mut Vec<DirIssue>) -> PlannedFile {
+    let mut target_name = file.file_name.clone();
+    let mut changes = vec![];
+    let mut rewritten: Option<Image> = None;
+    let mut remove = false;
+
+    for issue in issues {
+        match &issue {
+            DirIssue::ForeignFile { .. } if options.strip_foreign => {
+                remove = true;
+                changes.push(format!("removed {}", file.path.to_string_lossy()));
+            },
+            DirIssue::UnpaddedName { normalized_name, .. } if options.renumber => {
+                changes.push(format!("renamed {} to {normalized_name}", file.path.to_string_lossy()));
+                target_name = normalized_name.clone();
+            },
+            DirIssue::NotRgbaPng { color_type, .. } if options.normalize_png => {
+                changes.push(format!("converted {} from {color_type:?} to RGBA", file.path.to_string_lossy()));
+                if rewritten.is_none() {
+                    rewritten = image.as_ref().map(|image| image.to_rgba8());
+                }
+            },
+            DirIssue::WrongDimensions { dimensions, expected, .. } if options.fit.is_some() => {
+                let fit = options.fit.unwrap();
+                let source = rewritten.take().or_else(|| image.as_ref().map(|image| image.to_rgba8())).unwrap();
+                match fit.apply(&source, *expected) {
+                    Some(fitted) => {
+                        changes.push(format!("adjusted {} from {dimensions} to {expected} ({fit})", file.path.to_string_lossy()));
+                        rewritten = Some(fitted);
+                    },
+                    None => {
+                        ambiguities.push(format!("{} is {dimensions} and can not be adjusted to {expected} with {fit}", file.path.to_string_lossy()));
+                        rewritten = Some(source);
+                    },
+                }
+            },
+            _ => unfixed.push(issue),
+        }
+    }
+
+    let action = match remove {
+        true => FileAction::Remove,
+        false => FileAction::Write { target_name, image: rewritten },
+    };
+    PlannedFile { source: file.path.clone(), action, changes }
+}
+
+// Nothing is modified while planning, the plan is only returned when none of the fixes is ambiguous
+pub fn plan_dir_fixes<P: AsRef<Path>>(dir: P, content: DirContent, tile_kind: TileKind, options: &FixOptions) -> Result<FixPlan, PlanFixesError> {
+    let files = dir_check::scan_dir(&dir, content)?;
+    let mut ambiguities = vec![];
+    let mut unfixed = vec![];
+
+    // renumbering is still possible, only a name clash after renaming is ambiguous
+    unfixed.extend(dir_check::duplicate_indices(&files));
+
+    let planned_files: Vec<PlannedFile> = files.iter().map(|file| {
+        let (issues, image) = dir_check::check_image_file(file, tile_kind);
+        plan_file(file, issues, image, options, &mut ambiguities, &mut unfixed)
+    }).collect();
+
+    // two files written with the same name
+    let mut sources_by_target: BTreeMap<&str, Vec<&Path>> = BTreeMap::new();
+    for planned_file in &planned_files {
+        if let FileAction::Write { target_name, .. } = &planned_file.action {
+            sources_by_target.entry(target_name).or_default().push(&planned_file.source);
+        }
+    }
+    for (target_name, sources) in sources_by_target {
+        if sources.len() > 1 {
+            ambiguities.push(format!("several files would be named {target_name}: {}", sources.iter().map(|path| path.to_string_lossy()).collect::<Vec<_>>().join(", ")));
+        }
+    }
+
+    if !ambiguities.is_empty() {
+        return Err(PlanFixesError::Ambiguous(AmbiguousFixes(ambiguities)));
+    }
+
+    Ok(FixPlan { dir: dir.as_ref().to_path_buf(), files: planned_files, unfixed })
+}
+
+impl FixPlan {
+
+    pub fn changes(&self) -> impl Iterator<Item = &String> {
+        self.files.iter().flat_map(|file| file.changes.iter())
+    }
+
+    // fixes the directory in place when `out_dir` is none, otherwise the directory is left untouched and
+    // every file which is kept is written to `out_dir`
+    pub fn apply(&self, out_dir: Option<&Path>) -> Result<(), ApplyFixesError> {
+        let target_dir = out_dir.unwrap_or(&self.dir);
+        create_path(target_dir)?;
+
+        // removing first as a removed file name can be the name of a renamed file
+        if out_dir.is_none() {
+            for file in &self.files {
+                if let FileAction::Remove = file.action {
+                    std::fs::remove_file(&file.source).map_err(|error| ApplyFixesError::file_error("remove", &file.source, error))?;
+                }
+            }
+        }
+
+        for file in &self.files {
+            if let FileAction::Write { target_name, image } = &file.action {
+                let target_path = target_dir.join(target_name);
+                match (image, out_dir) {
+                    (Some(image), _) => {
+                        image.write_image_file(&target_path)?;
+                        if out_dir.is_none() && target_path != file.source {
+                            std::fs::remove_file(&file.source).map_err(|error| ApplyFixesError::file_error("remove", &file.source, error))?;
+                        }
+                    },
+                    (None, None) => if target_path != file.source {
+                        std::fs::rename(&file.source, &target_path).map_err(|error| ApplyFixesError::file_error("rename", &file.source, error))?;
+                    },
+                    (None, Some(_)) => {
+                        std::fs::copy(&file.source, &target_path).map_err(|error| ApplyFixesError::file_error("copy", &file.source, error))?;
+                    },
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use image::GenericImageView;
+    use temp_dir::TempDir;
+
+    use crate::osd::tile::{Image, Kind as TileKind, fit::Fit};
+    use crate::osd::tile::container::dir_check::DirContent;
+
+    use super::{plan_dir_fixes, FixOptions, PlanFixesError};
+
+    fn options() -> FixOptions {
+        FixOptions { renumber: true, strip_foreign: true, normalize_png: true, fit: Some(Fit::Pad) }
+    }
+
+    #[test]
+    fn fix_in_place() {
+        let dir = TempDir::new().unwrap();
+        Image::new(36, 54).save(dir.child("1.png")).unwrap();
+        Image::new(36, 53).save(dir.child("012.png")).unwrap();
+        std::fs::write(dir.child(".DS_Store"), b"").unwrap();
+
+        let plan = plan_dir_fixes(dir.path(), DirContent::Tiles, TileKind::SD, &options()).unwrap();
+        assert_eq!(plan.changes().count(), 3);
+        plan.apply(None).unwrap();
+
+        assert!(dir.child("001.png").is_file());
+        assert!(!dir.child("1.png").exists());
+        assert!(!dir.child(".DS_Store").exists());
+        assert_eq!(image::open(dir.child("012.png")).unwrap().dimensions(), (36, 54));
+    }
+
+    #[test]
+    fn ambiguous_renumbering() {
+        let dir = TempDir::new().unwrap();
+        Image::new(36, 54).save(dir.child("5.png")).unwrap();
+        Image::new(36, 54).save(dir.child("005.png")).unwrap();
+
+        let result = plan_dir_fixes(dir.path(), DirContent::Tiles, TileKind::SD, &options());
+        assert!(matches!(result, Err(PlanFixesError::Ambiguous(_))));
+        assert!(dir.child("5.png").is_file());
+    }
+}
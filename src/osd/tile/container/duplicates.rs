@@ -0,0 +1,147 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde::Serialize;
+
+use crate::osd::tile::{regression::displayed_luminance, Kind as TileKind, Tile};
+use super::stats::is_blank;
+
+// Groups of identical or near identical tiles across one or more fonts, to collapse redundant glyphs and spot the
+// tiles pasted at the wrong index. The exact mode compares the pixels, the perceptual mode compares a 64 bit
+// difference hash of the tiles as seen over the video which tolerates slightly different anti-aliasing or a glyph
+// moved by a pixel. Only the tiles of the same kind are grouped and the blank tiles are left out.
+
+// size of the luminance grid the difference hash is computed from, each bit compares two horizontal neighbors
+const HASH_GRID_WIDTH: u32 = 9;
+const HASH_GRID_HEIGHT: u32 = 8;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DuplicateMatch {
+    Exact,
+    // tiles whose hashes differ by at most this many bits
+    Perceptual { max_distance: u32 },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct TileLocation {
+    // position of the font in the list given
+    pub font: usize,
+    pub tile_kind: String,
+    pub index: usize,
+}
+
+// mean displayed luminance of each cell of a HASH_GRID_WIDTH x HASH_GRID_HEIGHT grid over the tile
+fn luminance_grid(tile: &Tile) -> Vec<f64> {
+    let (width, height) = tile.dimensions();
+    let bounds = |cell: u32, cell_count: u32, size: u32| {
+        let start = cell * size / cell_count;
+        (start, ((cell + 1) * size / cell_count).max(start + 1))
+    };
+    itertools::iproduct!(0..HASH_GRID_HEIGHT, 0..HASH_GRID_WIDTH).map(|(cell_y, cell_x)| {
+        let (x_start, x_end) = bounds(cell_x, HASH_GRID_WIDTH, width);
+        let (y_start, y_end) = bounds(cell_y, HASH_GRID_HEIGHT, height);
+        let cell_pixels = itertools::iproduct!(y_start..y_end, x_start..x_end);
+        let pixel_count = ((x_end - x_start) * (y_end - y_start)) as f64;
+        cell_pixels.map(|(y, x)| displayed_luminance(tile.get_pixel(x, y))).sum::<f64>() / pixel_count
+    }).collect()
+}
+
+// difference hash: a bit per cell of the luminance grid set when the cell is darker than its right neighbor
+pub fn perceptual_hash(tile: &Tile) -> u64 {
+    let grid = luminance_grid(tile);
+    let width = HASH_GRID_WIDTH as usize;
+    grid.chunks(width)
+        .flat_map(|row| row.windows(2).map(|pair| pair[0] < pair[1]))
+        .fold(0, |hash, bit| hash << 1 | bit as u64)
+}
+
+pub fn hamming_distance(hash: u64, other_hash: u64) -> u32 {
+    (hash ^ other_hash).count_ones()
+}
+
+fn find_root(parents: &mut [usize], index: usize) -> usize {
+    let mut root = index;
+    while parents[root] != root {
+        root = parents[root];
+    }
+    parents[index] = root;
+    root
+}
+
+// `fonts` holds the tiles of each font as returned by `TileSet::iter`, the groups are sorted by location
+pub fn duplicate_groups(fonts: &[Vec<(TileKind, usize, &Tile)>], matching: DuplicateMatch) -> Vec<Vec<TileLocation>> {
+    let tiles: Vec<(TileLocation, &Tile)> = fonts.iter().enumerate()
+        .flat_map(|(font, tiles)| tiles.iter()
+            .filter(|(_, _, tile)| !is_blank(tile))
+            .map(move |(tile_kind, index, tile)| (TileLocation { font, tile_kind: tile_kind.to_string(), index: *index }, *tile)))
+        .collect();
+
+    let mut groups: BTreeMap<usize, Vec<TileLocation>> = BTreeMap::new();
+    match matching {
+        DuplicateMatch::Exact => {
+            let mut first_by_content: HashMap<&[u8], usize> = HashMap::new();
+            for (position, (_, tile)) in tiles.iter().enumerate() {
+                let first = *first_by_content.entry(tile.as_raw().as_slice()).or_insert(position);
+                groups.entry(first).or_default().push(tiles[position].0.clone());
+            }
+        },
+        DuplicateMatch::Perceptual { max_distance } => {
+            let hashes: Vec<u64> = tiles.iter().map(|(_, tile)| perceptual_hash(tile)).collect();
+            let mut parents: Vec<usize> = (0..tiles.len()).collect();
+            for (position, other_position) in itertools::iproduct!(0..tiles.len(), 0..tiles.len()).filter(|(position, other)| position < other) {
+                if tiles[position].1.kind() == tiles[other_position].1.kind() && hamming_distance(hashes[position], hashes[other_position]) <= max_distance {
+                    let (root, other_root) = (find_root(&mut parents, position), find_root(&mut parents, other_position));
+                    parents[root.max(other_root)] = root.min(other_root);
+                }
+            }
+            for (position, (location, _)) in tiles.iter().enumerate() {
+                let root = find_root(&mut parents, position);
+                groups.entry(root).or_default().push(location.clone());
+            }
+        },
+    }
+    let mut groups: Vec<Vec<TileLocation>> = groups.into_values().filter(|group| group.len() > 1).collect();
+    for group in &mut groups {
+        group.sort();
+    }
+    groups.sort();
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+
+    use crate::osd::tile::{Tile, Kind as TileKind};
+
+    use super::{duplicate_groups, DuplicateMatch};
+
+    fn bar(x: u32) -> Tile {
+        let mut tile = Tile::new(TileKind::SD);
+        for y in 3..15 {
+            tile.put_pixel(x, y, Rgba([255, 255, 255, 255]));
+            tile.put_pixel(x + 1, y, Rgba([255, 255, 255, 255]));
+        }
+        tile
+    }
+
+    fn located(tiles: &[Tile]) -> Vec<(TileKind, usize, &Tile)> {
+        tiles.iter().enumerate().map(|(index, tile)| (tile.kind(), index, tile)).collect()
+    }
+
+    #[test]
+    fn groups() {
+        let mut antialiased = bar(3);
+        antialiased.put_pixel(5, 8, Rgba([255, 255, 255, 64]));
+        let font = vec![bar(3), Tile::new(TileKind::SD), bar(8), bar(3), antialiased, Tile::new(TileKind::SD)];
+        let other_font = vec![bar(8)];
+        let fonts = [located(&font), located(&other_font)];
+        let locations = |matching| duplicate_groups(&fonts, matching).iter()
+            .map(|group| group.iter().map(|location| (location.font, location.index)).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+
+        // the blank tiles are not duplicates
+        assert_eq!(locations(DuplicateMatch::Exact), vec![vec![(0, 0), (0, 3)], vec![(0, 2), (1, 0)]]);
+        assert_eq!(locations(DuplicateMatch::Perceptual { max_distance: 2 }), vec![vec![(0, 0), (0, 3), (0, 4)], vec![(0, 2), (1, 0)]]);
+    }
+
+}
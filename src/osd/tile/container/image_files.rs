@@ -0,0 +1,40 @@
+
+use std::io::Error as IOError;
+use std::path::{Path, PathBuf};
+
+use image::RgbaImage;
+
+use crate::image::{read_rgba_image_file, ReadError as ImageReadError};
+use crate::image_format::ImageDecoding;
+use super::dir_files_iter::dir_files_iter;
+
+
+// Where the image files of tile/symbol directories are read from e.g. a directory of the file system or a directory
+// of an archive read in memory
+pub(crate) trait ImageFiles: Sync {
+    // path of the directory, used in the messages
+    fn dir_path(&self) -> &Path;
+
+    // paths of the files of the directory in path order
+    fn file_paths(&self) -> Result<Vec<PathBuf>, IOError>;
+
+    fn read_rgba_image(&self, path: &Path, decoding: ImageDecoding) -> Result<RgbaImage, ImageReadError>;
+}
+
+pub(crate) struct DirImageFiles<'a>(pub &'a Path);
+
+impl<'a> ImageFiles for DirImageFiles<'a> {
+
+    fn dir_path(&self) -> &Path {
+        self.0
+    }
+
+    fn file_paths(&self) -> Result<Vec<PathBuf>, IOError> {
+        dir_files_iter(self.0)?.collect()
+    }
+
+    fn read_rgba_image(&self, path: &Path, decoding: ImageDecoding) -> Result<RgbaImage, ImageReadError> {
+        read_rgba_image_file(path, decoding)
+    }
+
+}
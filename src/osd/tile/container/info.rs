@@ -0,0 +1,111 @@
+use serde::Serialize;
+
+use crate::osd::{bin_file, font_metadata::FontMetadata, tile::{Dimensions as TileDimensions, Kind as TileKind, Tile}};
+use super::{stats::is_blank, uniq_tile_kind::{TileKindError, UniqTileKind}};
+
+// Overview of a collection of tiles of a single kind as printed by the `info` command
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct TileCollectionInfo {
+    // absent for an empty collection of which the kind is not known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tile_kind: Option<TileKind>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tile_dimensions: Option<TileDimensions>,
+    pub tile_count: usize,
+    // number of bin file pages needed to hold the tiles
+    pub page_count: usize,
+    pub transparent_tile_count: usize,
+}
+
+impl TileCollectionInfo {
+
+    fn new(tile_kind: Option<TileKind>, tiles: &[Tile]) -> Self {
+        Self {
+            tile_kind,
+            tile_dimensions: tile_kind.map(|tile_kind| tile_kind.dimensions()),
+            tile_count: tiles.len(),
+            page_count: if tiles.is_empty() { 0 } else { bin_file::page_count(tiles.len()) },
+            transparent_tile_count: tiles.iter().filter(|tile| is_blank(tile)).count(),
+        }
+    }
+
+    pub fn compute(tiles: &[Tile]) -> Result<Self, TileKindError> {
+        match tiles.tile_kind() {
+            Ok(tile_kind) => Ok(Self::new(Some(tile_kind), tiles)),
+            Err(TileKindError::EmptyContainer) => Ok(Self::new(None, tiles)),
+            Err(error) => Err(error),
+        }
+    }
+
+    // same as `compute` for a collection of which the kind is known even when empty e.g. the tiles of a set
+    pub fn compute_of_kind(tile_kind: TileKind, tiles: &[Tile]) -> Result<Self, TileKindError> {
+        if let Some(loaded) = Self::compute(tiles)?.tile_kind {
+            if loaded != tile_kind {
+                return Err(TileKindError::LoadedDoesNotMatchRequested { requested: tile_kind, loaded })
+            }
+        }
+        Ok(Self::new(Some(tile_kind), tiles))
+    }
+
+    // same as `compute` without decoding the tiles of the file
+    #[cfg(feature = "fs")]
+    pub fn compute_bin_file(reader: &bin_file::LazyReader) -> Self {
+        let tile_kind = reader.tile_kind();
+        Self {
+            tile_kind: Some(tile_kind),
+            tile_dimensions: Some(tile_kind.dimensions()),
+            tile_count: reader.tile_count(),
+            page_count: bin_file::page_count(reader.tile_count()),
+            transparent_tile_count: (0..reader.tile_count()).filter(|&index| reader.is_blank(index) == Some(true)).count(),
+        }
+    }
+
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct FontInfo {
+    // format of the collection specification e.g. djibin or tilesetdir
+    pub format: String,
+    // details about the files detected while loading them e.g. gzip compression, absent when there is nothing to add
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub variant: Option<String>,
+    // one per tile kind, SD first for sets
+    pub collections: Vec<TileCollectionInfo>,
+    // font.toml of the tile and symbol directories, absent when there is none
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<FontMetadata>,
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+
+    use crate::osd::tile::{Tile, Kind as TileKind};
+
+    use super::TileCollectionInfo;
+
+    #[test]
+    fn compute() {
+        let mut tiles = vec![Tile::new(TileKind::HD); 300];
+        tiles[3].put_pixel(1, 1, Rgba([255, 255, 255, 255]));
+        let info = TileCollectionInfo::compute(&tiles).unwrap();
+        assert_eq!(info.tile_kind, Some(TileKind::HD));
+        assert_eq!(info.tile_dimensions, Some(TileKind::HD.dimensions()));
+        assert_eq!(info.page_count, 2);
+        assert_eq!(info.transparent_tile_count, 299);
+        assert_eq!(serde_json::to_value(&info).unwrap()["tile_dimensions"], "24x36");
+    }
+
+    #[test]
+    fn compute_empty() {
+        let info = TileCollectionInfo::compute(&[]).unwrap();
+        assert_eq!(info.tile_kind, None);
+        assert_eq!((info.tile_count, info.page_count, info.transparent_tile_count), (0, 0, 0));
+
+        let info = TileCollectionInfo::compute_of_kind(TileKind::SD, &[]).unwrap();
+        assert_eq!(info.tile_kind, Some(TileKind::SD));
+        assert_eq!(info.tile_count, 0);
+        assert!(TileCollectionInfo::compute_of_kind(TileKind::SD, &[Tile::new(TileKind::HD)]).is_err());
+    }
+}
@@ -1,6 +1,5 @@
 
 use std::collections::BTreeMap;
-use std::fs::ReadDir;
 use std::path::{Path, PathBuf};
 use std::io::Error as IOError;
 
@@ -8,39 +7,22 @@ use lazy_static::lazy_static;
 use regex::Regex;
 use thiserror::Error;
 
-use crate::osd::tile::container::symbol::{LoadError as SymbolLoadError, Symbol};
+use crate::image_format::{ImageDecoding, is_readable_image_path};
+use crate::osd::tile::{
+    container::symbol::{LoadError as SymbolLoadError, Symbol},
+    fit::Fit,
+};
+use super::image_files::{DirImageFiles, ImageFiles};
 
 
-struct DirFilesIterator(ReadDir);
-
-impl Iterator for DirFilesIterator {
-    type Item = Result<PathBuf, IOError>;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        match self.0.next()? {
-            Ok(entry) => {
-                let path = entry.path();
-                if path.is_file() {
-                    Some(Ok(path))
-                } else {
-                    None
-                }
-            },
-            Err(error) => Some(Err(error))
-        }
-    }
-}
-
-fn dir_files_iter<P: AsRef<Path>>(path: P) -> Result<DirFilesIterator, IOError> {
-    Ok(DirFilesIterator(std::fs::read_dir(path)?))
-}
-
 #[derive(Debug, Error)]
 pub enum LoadSymbolsFromDirError {
     #[error("failed to list files from directory {dir_path}: {error}")]
     DirListFiles { dir_path: PathBuf, error: IOError },
     #[error(transparent)]
     LoadError(#[from] SymbolLoadError),
+    #[error("duplicate symbol index {index}: {first} and {second}")]
+    DuplicateIndex { index: usize, first: PathBuf, second: PathBuf },
     #[error("overlapping symbol files: {0} and {1}")]
     OverlappingSymbolFiles(PathBuf, PathBuf),
     #[error("symbol span {real_span} does not match span from file name {file_name}")]
@@ -74,7 +56,8 @@ enum SymbolDirFileType {
     },
     Symbol {
         start_index: usize,
-        end_index: usize
+        end_index: usize,
+        rows: usize,
     }
 }
 
@@ -89,22 +72,52 @@ impl SymbolDirFileType {
     fn span(&self) -> usize {
         match self {
             SymbolDirFileType::Tile { .. } => 1,
-            SymbolDirFileType::Symbol { start_index, end_index } => end_index - start_index + 1,
+            SymbolDirFileType::Symbol { start_index, end_index, .. } => end_index - start_index + 1,
+        }
+    }
+
+    fn rows(&self) -> usize {
+        match self {
+            SymbolDirFileType::Tile { .. } => 1,
+            SymbolDirFileType::Symbol { rows, .. } => *rows,
         }
     }
 }
 
+// the symbol name suffix of named directories e.g. 001_SYM_RSSI.png is ignored, the symbols spanning several rows
+// of tiles have a <columns>x<rows> suffix e.g. 256-259_2x2.png
 fn identify_file_name<P: AsRef<Path>>(path: P) -> Option<SymbolDirFileType> {
     lazy_static! {
-        static ref FILE_NAME_RE: Regex = Regex::new(r"\A(?P<start_index>\d{3})(?:-(?P<end_index>\d{3}))?\.").unwrap();
+        static ref FILE_NAME_RE: Regex = Regex::new(r"\A(?P<start_index>\d{3})(?:-(?P<end_index>\d{3})(?:_(?P<columns>\d+)x(?P<rows>\d+))?)?(?:_\w+)?\.[^.]+\z").unwrap();
+    }
+
+    if !is_readable_image_path(&path) {
+        return None
     }
 
-    if let Some(captures) = FILE_NAME_RE.captures(path.as_ref().file_name().unwrap().to_string_lossy().to_string().as_str()) {
-        let start_index = captures.name("start_index").unwrap().as_str().parse().expect("failed to parse start index");
+    // the paths of the directory entries always have a file name
+    let file_name = path.as_ref().file_name()?.to_string_lossy().to_string();
+    if let Some(captures) = FILE_NAME_RE.captures(&file_name) {
+        let start_index = captures.name("start_index")?.as_str().parse().ok()?;
         match captures.name("end_index") {
             Some(end_index) => {
-                let end_index = end_index.as_str().parse().expect("failed to parse end index");
-                Some(SymbolDirFileType::Symbol { start_index, end_index })
+                let end_index = end_index.as_str().parse().ok()?;
+                if end_index <= start_index {
+                    log::warn!("ignoring {}: the end index of a symbol file name should be greater than its start index", path.as_ref().to_string_lossy());
+                    return None
+                }
+                let rows = match captures.name("columns").zip(captures.name("rows")) {
+                    Some((columns, rows)) => {
+                        let (columns, rows): (usize, usize) = (columns.as_str().parse().ok()?, rows.as_str().parse().ok()?);
+                        if columns * rows != end_index - start_index + 1 {
+                            log::warn!("ignoring {}: the columns and rows of a symbol file name should cover its tiles", path.as_ref().to_string_lossy());
+                            return None
+                        }
+                        rows
+                    },
+                    None => 1,
+                };
+                Some(SymbolDirFileType::Symbol { start_index, end_index, rows })
             },
             None => Some(SymbolDirFileType::Tile { index: start_index }),
         }
@@ -113,19 +126,28 @@ fn identify_file_name<P: AsRef<Path>>(path: P) -> Option<SymbolDirFileType> {
     }
 }
 
-pub fn load_symbols_from_dir<P: AsRef<Path>>(dir_path: P, max_symbols: usize) -> Result<Vec<Symbol>, LoadSymbolsFromDirError> {
+pub fn load_symbols_from_dir<P: AsRef<Path>>(dir_path: P, max_symbols: usize, fit: Option<Fit>, decoding: ImageDecoding) -> Result<Vec<Symbol>, LoadSymbolsFromDirError> {
+    load_symbols_from_files(&DirImageFiles(dir_path.as_ref()), max_symbols, fit, decoding)
+}
+
+pub(crate) fn load_symbols_from_files<F: ImageFiles>(files: &F, max_symbols: usize, fit: Option<Fit>, decoding: ImageDecoding) -> Result<Vec<Symbol>, LoadSymbolsFromDirError> {
+    let dir_path = files.dir_path();
 
     let mut symbol_files = BTreeMap::new();
-    let dir_files_iter = dir_files_iter(&dir_path).map_err(|error| LoadSymbolsFromDirError::dir_list_files(&dir_path, error))?;
-    for file_path in dir_files_iter {
-        let file_path = file_path.map_err(|error| LoadSymbolsFromDirError::dir_list_files(&dir_path, error))?;
+    let file_paths = files.file_paths().map_err(|error| LoadSymbolsFromDirError::dir_list_files(dir_path, error))?;
+    for file_path in file_paths {
 
         if let Some(file_type) = identify_file_name(&file_path) {
             use std::collections::btree_map;
             match symbol_files.entry(file_type.start_index()) {
                 btree_map::Entry::Vacant(entry) => { entry.insert((file_path, file_type)); },
                 btree_map::Entry::Occupied(entry) => {
-                    let (existing_path, _) = entry.get();
+                    let (existing_path, existing_file_type) = entry.get();
+                    // same name with a different extension
+                    if existing_file_type.span() == file_type.span() && existing_path.file_stem() == file_path.file_stem() {
+                        let (first, second) = if *existing_path < file_path { (existing_path.clone(), file_path) } else { (file_path, existing_path.clone()) };
+                        return Err(LoadSymbolsFromDirError::DuplicateIndex { index: file_type.start_index(), first, second });
+                    }
                     return Err(LoadSymbolsFromDirError::OverlappingSymbolFiles(file_path, existing_path.clone()));
                 },
             }
@@ -147,7 +169,10 @@ pub fn load_symbols_from_dir<P: AsRef<Path>>(dir_path: P, max_symbols: usize) ->
 
                 previous_symbol_file_path = Some(file_path);
 
-                match Symbol::load_image_file(file_path) {
+                let loaded_symbol = files.read_rgba_image(file_path, decoding)
+                    .map_err(SymbolLoadError::from)
+                    .and_then(|image| Symbol::from_image_rows(file_path, image, fit, file_type.rows()));
+                match loaded_symbol {
                     Ok(loaded_symbol) => {
 
                         if loaded_symbol.span() != file_type.span() {
@@ -179,14 +204,13 @@ pub fn load_symbols_from_dir<P: AsRef<Path>>(dir_path: P, max_symbols: usize) ->
 
             // first loaded tile: record the kind of tile
             (Some(symbol), None) => {
-                log::info!("detected {} kind of tiles in {}", symbol.tile_kind(), dir_path.as_ref().to_string_lossy());
+                log::debug!("detected {} kind of tiles in {}", symbol.tile_kind(), dir_path.to_string_lossy());
                 tile_kind = Some(symbol.tile_kind());
             },
 
             // we have already loaded a tile before, check that the new tile kind is matching what had recorded
-            (Some(symbol), Some(tile_kind)) => if symbol.tile_kind() != *tile_kind {
-                return Err(LoadSymbolsFromDirError::kind_mismatch(&dir_path))
-            },
+            (Some(symbol), Some(tile_kind)) if symbol.tile_kind() != *tile_kind =>
+                return Err(LoadSymbolsFromDirError::kind_mismatch(dir_path)),
 
             _ => {}
 
@@ -206,8 +230,27 @@ pub fn load_symbols_from_dir<P: AsRef<Path>>(dir_path: P, max_symbols: usize) ->
             let last_some_index = symbols.iter().rposition(Option::is_some).unwrap();
             symbols[0..=last_some_index].iter().map(|symbol| symbol.clone().unwrap_or_else(|| Symbol::new(tile_kind))).collect()
         }
-        None => return Err(LoadSymbolsFromDirError::no_symbol_found(&dir_path)),
+        None => return Err(LoadSymbolsFromDirError::no_symbol_found(dir_path)),
     };
 
     Ok(symbols)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use temp_dir::TempDir;
+
+    use crate::image_format::ImageDecoding;
+
+    use super::{load_symbols_from_dir, LoadSymbolsFromDirError};
+
+    #[test]
+    fn duplicate_index() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.child("001_SYM_RSSI.png"), []).unwrap();
+        std::fs::write(temp_dir.child("001_SYM_RSSI.tga"), []).unwrap();
+        let error = load_symbols_from_dir(temp_dir.path(), 512, None, ImageDecoding::default()).err().unwrap();
+        assert!(matches!(error, LoadSymbolsFromDirError::DuplicateIndex { index: 1, .. }));
+    }
+
+}
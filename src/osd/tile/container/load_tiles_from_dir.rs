@@ -1,23 +1,41 @@
 
+use std::collections::BTreeMap;
+use std::io::Error as IOError;
 use std::path::{Path, PathBuf};
 
+use clap::ValueEnum;
+use image::Rgba;
+use strum::Display;
 use thiserror::Error;
 
-use crate::osd::tile::{LoadError as TileLoadError, Tile};
-use crate::image::ReadError as ImageReadError;
+use crate::image_format::{ImageDecoding, is_readable_image_path};
+use crate::parallel;
+use crate::osd::tile::{Kind as TileKind, LoadError as TileLoadError, Tile, fit::Fit};
+use super::image_files::{DirImageFiles, ImageFiles};
+use super::split_symbols::parse_split_tile_file_stem;
 
 
 #[derive(Debug, Error)]
 pub enum LoadTilesFromDirError {
+    #[error("failed to list files from directory {dir_path}: {error}")]
+    DirListFiles { dir_path: PathBuf, error: IOError },
     #[error("error loading tile: {0}")]
     TileLoadError(TileLoadError),
+    #[error("duplicate tile index {index}: {first} and {second}")]
+    DuplicateIndex { index: usize, first: PathBuf, second: PathBuf },
     #[error("no tile found in directory: {0}")]
     NoTileFound(PathBuf),
     #[error("directory should contain a single kind of tile: {0}")]
-    KindMismatch(PathBuf)
+    KindMismatch(PathBuf),
+    #[error("missing tiles in directory {dir_path}: {}", format_indexes(indexes))]
+    MissingTiles { dir_path: PathBuf, indexes: Vec<usize> },
 }
 
 impl LoadTilesFromDirError {
+    pub fn dir_list_files<P: AsRef<Path>>(dir_path: P, error: IOError) -> Self {
+        Self::DirListFiles { dir_path: dir_path.as_ref().to_path_buf(), error }
+    }
+
     pub fn kind_mismatch<P: AsRef<Path>>(dir_path: P) -> Self {
         Self::KindMismatch(dir_path.as_ref().to_path_buf())
     }
@@ -33,51 +51,225 @@ impl From<TileLoadError> for LoadTilesFromDirError {
     }
 }
 
-pub fn load_tiles_from_dir<P: AsRef<Path>>(path: P, max_tiles: usize) -> Result<Vec<Tile>, LoadTilesFromDirError> {
-    let mut tiles = vec![];
-    let mut tile_kind = None;
+// How the gaps in the tile indexes of a directory are handled, the missing indexes are logged in every case
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Display, ValueEnum)]
+#[strum(serialize_all = "lowercase")]
+pub enum MissingTiles {
+    // a gap is an error
+    Error,
+    // the missing tiles are transparent
+    #[default]
+    Transparent,
+    // the missing tiles are drawn as a crossed box so that they stand out in previews and on the goggles
+    Placeholder,
+}
 
-    for index in 0..max_tiles {
-        let tile_path: PathBuf = [path.as_ref(), Path::new(&format!("{:03}.png", index))].iter().collect();
-        let tile = match Tile::load_image_file(tile_path) {
-            Ok(loaded_tile) => Some(loaded_tile),
-            Err(error) => match &error {
-                TileLoadError::ImageReadError(ImageReadError::OpenError { file_path: _, error: open_error }) =>
-                    match open_error.kind() {
-                        std::io::ErrorKind::NotFound => None,
-                        _ => return Err(error.into()),
-                    },
-                _ => return Err(error.into())
-            },
+impl MissingTiles {
+    fn tile(&self, tile_kind: TileKind) -> Tile {
+        match self {
+            MissingTiles::Error | MissingTiles::Transparent => Tile::new(tile_kind),
+            MissingTiles::Placeholder => placeholder_tile(tile_kind),
+        }
+    }
+}
+
+fn placeholder_tile(tile_kind: TileKind) -> Tile {
+    let mut tile = Tile::new(tile_kind);
+    let (width, height) = tile.dimensions();
+    let color = Rgba([255, 255, 255, 255]);
+    for y in 0..height {
+        for x in 0..width {
+            let on_border = x == 0 || y == 0 || x == width - 1 || y == height - 1;
+            // both diagonals, following the longer side so that they stay continuous
+            let on_diagonal = x * (height - 1) / (width - 1) == y || (width - 1 - x) * (height - 1) / (width - 1) == y;
+            if on_border || on_diagonal {
+                tile.put_pixel(x, y, color);
+            }
+        }
+    }
+    tile
+}
+
+// ranges of consecutive indexes are shortened e.g. 3, 7-9
+fn format_indexes(indexes: &[usize]) -> String {
+    let mut ranges: Vec<(usize, usize)> = vec![];
+    for &index in indexes {
+        match ranges.last_mut() {
+            Some((_, end)) if *end + 1 == index => *end = index,
+            _ => ranges.push((index, index)),
+        }
+    }
+    ranges.into_iter().map(|(start, end)| if start == end { start.to_string() } else { format!("{start}-{end}") })
+        .collect::<Vec<_>>().join(", ")
+}
+
+// tile image files are named from the tile index, any readable image extension is accepted e.g. 011.png or 011.bmp
+fn tile_file_index<P: AsRef<Path>>(path: P) -> Option<usize> {
+    if !is_readable_image_path(&path) {
+        return None
+    }
+    let file_stem = path.as_ref().file_stem()?.to_str()?;
+    // tiles of split symbol directories e.g. 031_SYM_SAT_1
+    if let Some((index, _, _)) = parse_split_tile_file_stem(file_stem) {
+        return Some(index)
+    }
+    if file_stem.is_empty() || !file_stem.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None
+    }
+    file_stem.parse().ok()
+}
+
+fn tile_files<F: ImageFiles>(files: &F, max_tiles: usize) -> Result<BTreeMap<usize, PathBuf>, LoadTilesFromDirError> {
+    let mut tile_files: BTreeMap<usize, PathBuf> = BTreeMap::new();
+    let file_paths = files.file_paths().map_err(|error| LoadTilesFromDirError::dir_list_files(files.dir_path(), error))?;
+    for file_path in file_paths {
+        let index = match tile_file_index(&file_path) {
+            Some(index) => index,
+            None => continue,
         };
+        if index >= max_tiles {
+            log::warn!("ignoring tile file beyond the maximum number of tiles ({max_tiles}): {}", file_path.to_string_lossy());
+            continue;
+        }
+        if let Some(existing_path) = tile_files.get(&index) {
+            let (first, second) = if *existing_path < file_path { (existing_path.clone(), file_path) } else { (file_path, existing_path.clone()) };
+            return Err(LoadTilesFromDirError::DuplicateIndex { index, first, second });
+        }
+        tile_files.insert(index, file_path);
+    }
+    Ok(tile_files)
+}
+
+pub fn load_tiles_from_dir<P: AsRef<Path>>(path: P, max_tiles: usize, fit: Option<Fit>, decoding: ImageDecoding) -> Result<Vec<Tile>, LoadTilesFromDirError> {
+    load_tiles_from_dir_missing(path, max_tiles, fit, decoding, MissingTiles::default())
+}
+
+pub fn load_tiles_from_dir_missing<P: AsRef<Path>>(path: P, max_tiles: usize, fit: Option<Fit>, decoding: ImageDecoding, missing_tiles: MissingTiles) -> Result<Vec<Tile>, LoadTilesFromDirError> {
+    load_tiles_from_files(&DirImageFiles(path.as_ref()), max_tiles, fit, decoding, missing_tiles)
+}
+
+pub(crate) fn load_tiles_from_files<F: ImageFiles>(files: &F, max_tiles: usize, fit: Option<Fit>, decoding: ImageDecoding, missing_tiles: MissingTiles) -> Result<Vec<Tile>, LoadTilesFromDirError> {
+    let path = files.dir_path();
+    let mut tiles: Vec<Option<Tile>> = vec![];
+    let mut tile_kind = None;
 
-        match (&tile, &tile_kind) {
+    let tile_files: Vec<(usize, PathBuf)> = tile_files(files, max_tiles)?.into_iter().collect();
+
+    // checked before loading the images so that an error is reported early
+    let present_indexes = tile_files.iter().map(|(index, _)| *index).collect::<Vec<_>>();
+    let missing_indexes = match present_indexes.last() {
+        Some(last_index) => (0..*last_index).filter(|index| present_indexes.binary_search(index).is_err()).collect::<Vec<_>>(),
+        None => vec![],
+    };
+    if !missing_indexes.is_empty() {
+        if missing_tiles == MissingTiles::Error {
+            return Err(LoadTilesFromDirError::MissingTiles { dir_path: path.to_path_buf(), indexes: missing_indexes })
+        }
+        log::warn!("missing tiles in {} replaced with {} tiles: {}", path.to_string_lossy(), missing_tiles, format_indexes(&missing_indexes));
+    }
+
+    for (index, tile) in load_tile_files(files, tile_files, fit, decoding)? {
+        tile_kind = Some(tile.kind());
+        // missing tiles before this one are filled according to the policy
+        tiles.resize(index, None);
+        tiles.push(Some(tile));
+    }
+
+    match tile_kind {
+        Some(tile_kind) => Ok(tiles.into_iter().map(|tile| tile.unwrap_or_else(|| missing_tiles.tile(tile_kind))).collect()),
+        None => Err(LoadTilesFromDirError::no_tile_found(path)),
+    }
+}
+
+// loads the tiles of the files present without filling the gaps, e.g. for the patches
+pub(crate) fn load_indexed_tiles_from_files<F: ImageFiles>(files: &F, max_tiles: usize, fit: Option<Fit>, decoding: ImageDecoding) -> Result<Vec<(usize, Tile)>, LoadTilesFromDirError> {
+    let tile_files = tile_files(files, max_tiles)?.into_iter().collect();
+    load_tile_files(files, tile_files, fit, decoding)
+}
+
+fn load_tile_files<F: ImageFiles>(files: &F, tile_files: Vec<(usize, PathBuf)>, fit: Option<Fit>, decoding: ImageDecoding) -> Result<Vec<(usize, Tile)>, LoadTilesFromDirError> {
+    let path = files.dir_path();
+    let mut tile_kind = None;
+
+    let loaded_tiles = parallel::try_map(&tile_files, |_, (_, tile_path)| {
+        files.read_rgba_image(tile_path, decoding)
+            .map_err(TileLoadError::from)
+            .and_then(|image| Tile::from_image_fit(tile_path, image, fit))
+    })?;
+
+    for tile in &loaded_tiles {
+
+        match &tile_kind {
 
             // first loaded tile: record the kind of tile
-            (Some(tile), None) => {
-                log::info!("detected {} kind of tiles in {}", tile.kind(), path.as_ref().to_string_lossy());
+            None => {
+                log::debug!("detected {} kind of tiles in {}", tile.kind(), path.to_string_lossy());
                 tile_kind = Some(tile.kind());
             },
 
             // we have already loaded a tile before, check that the new tile kind is matching what had recorded
-            (Some(tile), Some(tile_kind)) => if tile.kind() != *tile_kind {
-                return Err(LoadTilesFromDirError::kind_mismatch(&path))
+            Some(tile_kind) => if tile.kind() != *tile_kind {
+                return Err(LoadTilesFromDirError::kind_mismatch(path))
             },
 
-            _ => {}
-
         }
 
-        tiles.push(tile);
     }
 
-    let tiles = match tile_kind {
-        Some(tile_kind) => {
-            let last_some_index = tiles.iter().rposition(Option::is_some).unwrap();
-            tiles[0..=last_some_index].iter().map(|tile| tile.clone().unwrap_or_else(|| Tile::new(tile_kind))).collect()
-        }
-        None => return Err(LoadTilesFromDirError::no_tile_found(&path)),
-    };
+    Ok(tile_files.into_iter().map(|(index, _)| index).zip(loaded_tiles).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{Rgb, RgbImage, Rgba, RgbaImage};
+    use temp_dir::TempDir;
+
+    use crate::image_format::{ColorKey, ImageDecoding};
+
+    use super::{format_indexes, load_tiles_from_dir, tile_file_index, LoadTilesFromDirError};
+
+    #[test]
+    fn tile_file_names() {
+        assert_eq!(tile_file_index("tiles/011.png"), Some(11));
+        assert_eq!(tile_file_index("tiles/011.BMP"), Some(11));
+        assert_eq!(tile_file_index("tiles/256.gif"), Some(256));
+        assert_eq!(tile_file_index("tiles/011.txt"), None);
+        assert_eq!(tile_file_index("tiles/011-013.png"), None);
+        assert_eq!(tile_file_index("tiles/031_SYM_SAT_1.png"), Some(31));
+        assert_eq!(tile_file_index("tiles/.png"), None);
+    }
+
+    #[test]
+    fn color_key() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut image = RgbImage::from_pixel(24, 36, Rgb([255, 0, 255]));
+        image.put_pixel(1, 1, Rgb([255, 255, 255]));
+        image.save(temp_dir.child("000.bmp")).unwrap();
+        // the key only applies to the images without an alpha channel
+        RgbaImage::from_pixel(24, 36, Rgba([255, 0, 255, 255])).save(temp_dir.child("001.png")).unwrap();
+
+        let decoding = ImageDecoding { color_key: Some(ColorKey::Magenta), alpha: None };
+        let tiles = load_tiles_from_dir(temp_dir.path(), 512, None, decoding).unwrap();
+        assert_eq!(*tiles[0].get_pixel(0, 0), Rgba([0, 0, 0, 0]));
+        assert_eq!(*tiles[0].get_pixel(1, 1), Rgba([255, 255, 255, 255]));
+        assert_eq!(*tiles[1].get_pixel(0, 0), Rgba([255, 0, 255, 255]));
+
+        let tiles = load_tiles_from_dir(temp_dir.path(), 512, None, ImageDecoding::default()).unwrap();
+        assert_eq!(*tiles[0].get_pixel(0, 0), Rgba([255, 0, 255, 255]));
+    }
+
+    #[test]
+    fn duplicate_index() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.child("011.png"), []).unwrap();
+        std::fs::write(temp_dir.child("011.bmp"), []).unwrap();
+        let error = load_tiles_from_dir(temp_dir.path(), 512, None, ImageDecoding::default()).unwrap_err();
+        assert!(matches!(error, LoadTilesFromDirError::DuplicateIndex { index: 11, .. }));
+    }
 
-    Ok(tiles)
+    #[test]
+    fn missing_indexes() {
+        assert_eq!(format_indexes(&[3, 7, 8, 9, 12]), "3, 7-9, 12");
+        assert_eq!(format_indexes(&[0]), "0");
+    }
 }
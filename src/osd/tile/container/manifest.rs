@@ -0,0 +1,162 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Display;
+use std::io::Error as IOError;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use crate::atomic_file;
+use super::content_hash::to_hex;
+
+// Checksums of a font written by `hash --manifest` and checked by `verify`. The content hash and the tile hashes do not
+// depend on the format so that the published bin files can be checked against the tile directories they were reviewed
+// as, the SHA-256 of the files the tiles were loaded from catch any other change to the files themselves.
+
+#[derive(Debug, Error)]
+pub enum LoadManifestError {
+    #[error(transparent)]
+    Read(#[from] IOError),
+    #[error("invalid manifest file {}: {error}", .path.to_string_lossy())]
+    Parse { path: PathBuf, error: serde_json::Error },
+}
+
+#[derive(Debug, Error)]
+pub enum SaveManifestError {
+    #[error(transparent)]
+    Write(#[from] IOError),
+    #[error("failed to serialize the manifest: {0}")]
+    Serialize(#[from] serde_json::Error),
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub content_hash: String,
+    // by tile kind, up to the last non-blank tile
+    pub tile_hashes: BTreeMap<String, Vec<String>>,
+    // by path as given on the command line, the files of the directories included
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub files: BTreeMap<PathBuf, String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ManifestMismatch {
+    ContentHash { expected: String, actual: String },
+    TileCount { tile_kind: String, expected: usize, actual: usize },
+    Tile { tile_kind: String, index: usize },
+    File { path: PathBuf },
+}
+
+impl Display for ManifestMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use ManifestMismatch::*;
+        match self {
+            ContentHash { expected, actual } => write!(f, "content hash is {actual} instead of {expected}"),
+            TileCount { tile_kind, expected, actual } => write!(f, "{actual} {tile_kind} tiles instead of {expected}"),
+            Tile { tile_kind, index } => write!(f, "{tile_kind} tile {index:03} differs"),
+            File { path } => write!(f, "{}: checksum differs", path.to_string_lossy()),
+        }
+    }
+}
+
+pub fn file_hash<P: AsRef<Path>>(path: P) -> Result<String, IOError> {
+    let mut file = fs_err::File::open(path.as_ref())?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher)?;
+    Ok(to_hex(&hasher.finalize()))
+}
+
+fn add_file_hashes(path: &Path, hashes: &mut BTreeMap<PathBuf, String>) -> Result<(), IOError> {
+    if !path.is_dir() {
+        hashes.insert(path.to_path_buf(), file_hash(path)?);
+        return Ok(());
+    }
+    for entry in fs_err::read_dir(path)? {
+        add_file_hashes(&entry?.path(), hashes)?;
+    }
+    Ok(())
+}
+
+// SHA-256 of the files, the files under the directories included
+pub fn file_hashes<P: AsRef<Path>>(paths: &[P]) -> Result<BTreeMap<PathBuf, String>, IOError> {
+    let mut hashes = BTreeMap::new();
+    for path in paths {
+        add_file_hashes(path.as_ref(), &mut hashes)?;
+    }
+    Ok(hashes)
+}
+
+impl Manifest {
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, LoadManifestError> {
+        let content = fs_err::read_to_string(path.as_ref())?;
+        serde_json::from_str(&content).map_err(|error| LoadManifestError::Parse { path: path.as_ref().to_path_buf(), error })
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), SaveManifestError> {
+        let content = serde_json::to_string_pretty(self)? + "\n";
+        atomic_file::write(path, content)?;
+        Ok(())
+    }
+
+    // differences of `actual`, the manifest of the collection being verified, the files are only compared when listed
+    // in both so that a manifest of the reviewed tile directories can verify the bin files converted from them
+    pub fn compare(&self, actual: &Manifest) -> Vec<ManifestMismatch> {
+        let mut mismatches = vec![];
+        if self.content_hash != actual.content_hash {
+            mismatches.push(ManifestMismatch::ContentHash { expected: self.content_hash.clone(), actual: actual.content_hash.clone() });
+        }
+        let tile_kinds: BTreeSet<&String> = self.tile_hashes.keys().chain(actual.tile_hashes.keys()).collect();
+        for tile_kind in tile_kinds {
+            let expected_hashes = self.tile_hashes.get(tile_kind).map(Vec::as_slice).unwrap_or_default();
+            let actual_hashes = actual.tile_hashes.get(tile_kind).map(Vec::as_slice).unwrap_or_default();
+            if expected_hashes.len() != actual_hashes.len() {
+                mismatches.push(ManifestMismatch::TileCount { tile_kind: tile_kind.clone(), expected: expected_hashes.len(), actual: actual_hashes.len() });
+            }
+            mismatches.extend(expected_hashes.iter().zip(actual_hashes).enumerate()
+                .filter(|(_, (expected, actual))| expected != actual)
+                .map(|(index, _)| ManifestMismatch::Tile { tile_kind: tile_kind.clone(), index }));
+        }
+        mismatches.extend(self.files.iter()
+            .filter(|(path, hash)| actual.files.get(*path).is_some_and(|actual_hash| actual_hash != *hash))
+            .map(|(path, _)| ManifestMismatch::File { path: path.clone() }));
+        mismatches
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::path::PathBuf;
+
+    use super::{Manifest, ManifestMismatch};
+
+    #[test]
+    fn compare() {
+        let manifest = Manifest {
+            content_hash: "c1".to_owned(),
+            tile_hashes: BTreeMap::from([("SD".to_owned(), vec!["a".to_owned(), "b".to_owned()])]),
+            files: BTreeMap::from([(PathBuf::from("tiles/SD/000.png"), "f1".to_owned())]),
+        };
+        assert!(manifest.compare(&manifest).is_empty());
+
+        // the bin files converted from the tiles are not listed in the manifest
+        let converted = Manifest { files: BTreeMap::from([(PathBuf::from("font.bin"), "f2".to_owned())]), ..manifest.clone() };
+        assert!(manifest.compare(&converted).is_empty());
+
+        let modified = Manifest {
+            content_hash: "c2".to_owned(),
+            tile_hashes: BTreeMap::from([("SD".to_owned(), vec!["a".to_owned(), "x".to_owned(), "y".to_owned()])]),
+            files: BTreeMap::from([(PathBuf::from("tiles/SD/000.png"), "f3".to_owned())]),
+        };
+        assert_eq!(manifest.compare(&modified), vec![
+            ManifestMismatch::ContentHash { expected: "c1".to_owned(), actual: "c2".to_owned() },
+            ManifestMismatch::TileCount { tile_kind: "SD".to_owned(), expected: 2, actual: 3 },
+            ManifestMismatch::Tile { tile_kind: "SD".to_owned(), index: 1 },
+            ManifestMismatch::File { path: PathBuf::from("tiles/SD/000.png") },
+        ]);
+    }
+
+}
@@ -0,0 +1,265 @@
+use std::collections::BTreeMap;
+use std::io::ErrorKind as IOErrorKind;
+use std::path::Path;
+use std::str::FromStr;
+
+use derive_more::{Deref, Display, Error, From};
+use strum::IntoEnumIterator;
+use thiserror::Error as ThisError;
+
+use crate::create_path::{create_path, CreatePathError};
+use crate::image::{WriteImageFile, WriteError as ImageWriteError};
+use crate::image_format::{ImageDecoding, ImageFormat};
+use crate::osd::tile::{Kind as TileKind, Tile, fit::Fit};
+#[cfg(feature = "fs")]
+use crate::image::encode_image;
+use super::image_files::{DirImageFiles, ImageFiles};
+use super::load_tiles_from_dir::{load_indexed_tiles_from_files, LoadTilesFromDirError};
+use super::tile_set::TileSet;
+use super::uniq_tile_kind::TileKindError;
+#[cfg(feature = "fs")]
+use super::archive::{Archive, ArchiveError, ArchiveFormat, SaveToArchiveError};
+
+// A patch only holds some tiles of a font with their index, it is overlaid on a base font to replace these tiles.
+// It is stored like a tile set directory (SD/ and HD/ directories with the tile files named from their index) but
+// only with the files of the patched tiles, in a directory or a .zip/.tar.gz archive.
+
+
+#[derive(Debug, ThisError)]
+pub enum ParseIndexListError {
+    #[error("invalid index {0:?}: expected a number")]
+    InvalidIndex(String),
+    #[error("invalid index range {0:?}: the start should not be greater than the end")]
+    InvalidRange(String),
+}
+
+// list of tile indexes given as comma separated indexes and inclusive ranges e.g. 1,5-9,32
+#[derive(Debug, Clone, PartialEq, Eq, Deref)]
+pub struct IndexList(Vec<usize>);
+
+impl FromStr for IndexList {
+    type Err = ParseIndexListError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse_index = |index: &str| index.trim().parse::<usize>().map_err(|_| ParseIndexListError::InvalidIndex(index.to_owned()));
+        let mut indexes = vec![];
+        for item in s.split(',') {
+            match item.split_once('-') {
+                Some((start, end)) => {
+                    let (start, end) = (parse_index(start)?, parse_index(end)?);
+                    if start > end {
+                        return Err(ParseIndexListError::InvalidRange(item.to_owned()))
+                    }
+                    indexes.extend(start..=end);
+                },
+                None => indexes.push(parse_index(item)?),
+            }
+        }
+        indexes.sort_unstable();
+        indexes.dedup();
+        Ok(Self(indexes))
+    }
+}
+
+#[derive(Debug, Error, Display, From)]
+pub enum LoadPatchError {
+    LoadTilesFromDirError(LoadTilesFromDirError),
+    TileKindError(TileKindError),
+    #[cfg(feature = "fs")]
+    ArchiveError(ArchiveError),
+}
+
+#[derive(Debug, Error, Display, From)]
+pub enum SavePatchError {
+    CreatePathError(CreatePathError),
+    ImageWriteError(ImageWriteError),
+    #[cfg(feature = "fs")]
+    SaveToArchiveError(SaveToArchiveError),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Patch {
+    sd_tiles: BTreeMap<usize, Tile>,
+    hd_tiles: BTreeMap<usize, Tile>,
+}
+
+impl Patch {
+
+    fn tiles(&self, tile_kind: TileKind) -> &BTreeMap<usize, Tile> {
+        match tile_kind {
+            TileKind::SD => &self.sd_tiles,
+            TileKind::HD => &self.hd_tiles,
+        }
+    }
+
+    fn tiles_mut(&mut self, tile_kind: TileKind) -> &mut BTreeMap<usize, Tile> {
+        match tile_kind {
+            TileKind::SD => &mut self.sd_tiles,
+            TileKind::HD => &mut self.hd_tiles,
+        }
+    }
+
+    // the tiles of both kinds at the given indexes, the indexes beyond the tiles of the set are ignored
+    pub fn from_tile_set_indexes(tile_set: &TileSet, indexes: &[usize]) -> Self {
+        let mut patch = Self::default();
+        for tile_kind in TileKind::iter() {
+            let tiles = tile_set.tiles(tile_kind);
+            for &index in indexes.iter().filter(|index| **index < tiles.len()) {
+                patch.tiles_mut(tile_kind).insert(index, tiles[index].clone());
+            }
+        }
+        patch
+    }
+
+    // the tiles of the modified set which differ from the base set or are beyond its tiles
+    pub fn from_tile_set_changes(base: &TileSet, modified: &TileSet) -> Self {
+        let mut patch = Self::default();
+        for tile_kind in TileKind::iter() {
+            let base_tiles = base.tiles(tile_kind);
+            for (index, tile) in modified.tiles(tile_kind).iter().enumerate() {
+                if base_tiles.get(index).map_or(true, |base_tile| base_tile.as_raw() != tile.as_raw()) {
+                    patch.tiles_mut(tile_kind).insert(index, tile.clone());
+                }
+            }
+        }
+        patch
+    }
+
+    pub fn len(&self) -> usize {
+        self.sd_tiles.len() + self.hd_tiles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // the indexes of the patched tiles of a kind in increasing order
+    pub fn indexes(&self, tile_kind: TileKind) -> Vec<usize> {
+        self.tiles(tile_kind).keys().copied().collect()
+    }
+
+    // replaces the tiles of the set at the indexes of the patch, the set is extended with transparent tiles when the
+    // patch goes beyond its tiles, returns the number of replaced tiles
+    pub fn apply(&self, tile_set: &mut TileSet) -> usize {
+        for tile_kind in TileKind::iter() {
+            let tiles = match tile_kind {
+                TileKind::SD => &mut tile_set.sd_tiles,
+                TileKind::HD => &mut tile_set.hd_tiles,
+            };
+            for (&index, tile) in self.tiles(tile_kind) {
+                if index >= tiles.len() {
+                    tiles.resize(index + 1, Tile::new(tile_kind));
+                }
+                tiles[index] = tile.clone();
+            }
+        }
+        self.len()
+    }
+
+    fn load_from_files<F: ImageFiles>(&mut self, tile_kind: TileKind, files: &F, max_tiles: usize, fit: Option<Fit>, decoding: ImageDecoding) -> Result<(), LoadPatchError> {
+        let tiles = match load_indexed_tiles_from_files(files, max_tiles, fit, decoding) {
+            Ok(tiles) => tiles,
+            // a patch can leave out a kind of tiles entirely
+            Err(LoadTilesFromDirError::DirListFiles { error, .. }) if error.kind() == IOErrorKind::NotFound => return Ok(()),
+            Err(error) => return Err(error.into()),
+        };
+        for (index, tile) in tiles {
+            if tile.kind() != tile_kind {
+                return Err(TileKindError::LoadedDoesNotMatchRequested { requested: tile_kind, loaded: tile.kind() }.into())
+            }
+            self.tiles_mut(tile_kind).insert(index, tile);
+        }
+        Ok(())
+    }
+
+    pub fn load_from_dir<P: AsRef<Path>>(path: P, max_tiles: usize, fit: Option<Fit>, decoding: ImageDecoding) -> Result<Self, LoadPatchError> {
+        let mut patch = Self::default();
+        for tile_kind in TileKind::iter() {
+            patch.load_from_files(tile_kind, &DirImageFiles(&tile_kind.set_dir_path(&path)), max_tiles, fit, decoding)?;
+        }
+        Ok(patch)
+    }
+
+    #[cfg(feature = "fs")]
+    pub fn load_from_archive<P: AsRef<Path>>(path: P, max_tiles: usize, fit: Option<Fit>, decoding: ImageDecoding) -> Result<Self, LoadPatchError> {
+        let archive = Archive::load_file(path)?;
+        let mut patch = Self::default();
+        for tile_kind in TileKind::iter() {
+            patch.load_from_files(tile_kind, &archive.dir_image_files(tile_kind.set_dir_name()), max_tiles, fit, decoding)?;
+        }
+        Ok(patch)
+    }
+
+    // archive when the path has a .zip, .tar.gz or .tgz extension, directory otherwise
+    #[cfg(feature = "fs")]
+    pub fn load<P: AsRef<Path>>(path: P, max_tiles: usize, fit: Option<Fit>, decoding: ImageDecoding) -> Result<Self, LoadPatchError> {
+        match ArchiveFormat::from_path(&path) {
+            Ok(_) => Self::load_from_archive(path, max_tiles, fit, decoding),
+            Err(_) => Self::load_from_dir(path, max_tiles, fit, decoding),
+        }
+    }
+
+    pub fn save_to_dir<P: AsRef<Path>>(&self, path: P, image_format: ImageFormat) -> Result<(), SavePatchError> {
+        for tile_kind in TileKind::iter().filter(|tile_kind| !self.tiles(*tile_kind).is_empty()) {
+            let dir = tile_kind.set_dir_path(&path);
+            create_path(&dir)?;
+            for (index, tile) in self.tiles(tile_kind) {
+                tile.image().write_image_file(image_format.file_path(&dir, &format!("{index:03}")))?;
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "fs")]
+    pub fn save_to_archive<P: AsRef<Path>>(&self, path: P, image_format: ImageFormat) -> Result<(), SavePatchError> {
+        let mut archive = Archive::new(&path);
+        for tile_kind in TileKind::iter() {
+            let dir = Path::new(tile_kind.set_dir_name());
+            for (index, tile) in self.tiles(tile_kind) {
+                let file_path = image_format.file_path(dir, &format!("{index:03}"));
+                let content = encode_image(tile.image(), archive.path().join(&file_path), image_format).map_err(SaveToArchiveError::from)?;
+                archive.insert(file_path, content);
+            }
+        }
+        archive.save_file().map_err(SaveToArchiveError::from)?;
+        Ok(())
+    }
+
+    #[cfg(feature = "fs")]
+    pub fn save<P: AsRef<Path>>(&self, path: P, image_format: ImageFormat) -> Result<(), SavePatchError> {
+        match ArchiveFormat::from_path(&path) {
+            Ok(_) => self.save_to_archive(path, image_format),
+            Err(_) => self.save_to_dir(path, image_format),
+        }
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::osd::tile::{Kind as TileKind, Tile};
+    use crate::osd::tile::container::tile_set::TileSet;
+
+    use super::{IndexList, Patch};
+
+    #[test]
+    fn index_list() {
+        assert_eq!(*"5-7,1,6".parse::<IndexList>().unwrap(), vec![1, 5, 6, 7]);
+        assert!("7-5".parse::<IndexList>().is_err());
+        assert!("a".parse::<IndexList>().is_err());
+    }
+
+    #[test]
+    fn changes_and_apply() {
+        let base = TileSet::try_from_tiles(vec![Tile::new(TileKind::SD); 4], vec![Tile::new(TileKind::HD); 4]).unwrap();
+        let mut modified = base.clone();
+        modified.sd_tiles[2].put_pixel(0, 0, image::Rgba([255, 255, 255, 255]));
+        let patch = Patch::from_tile_set_changes(&base, &modified);
+        assert_eq!(patch.indexes(TileKind::SD), vec![2]);
+        assert!(patch.indexes(TileKind::HD).is_empty());
+
+        let mut patched = base;
+        assert_eq!(patch.apply(&mut patched), 1);
+        assert_eq!(patched.tiles(TileKind::SD)[2].get_pixel(0, 0), &image::Rgba([255, 255, 255, 255]));
+    }
+}
@@ -1,11 +1,12 @@
 
 use derive_more::{Error, Display, From};
-use std::path::{Path, PathBuf};
+use std::path::Path;
 
-use super::symbol::Symbol;
+use super::symbol::{Image, Symbol, names::SymbolNames};
 
 use crate::create_path::{create_path, CreatePathError};
-use crate::image::{WriteImageFile, WriteError as ImageWriteError};
+use crate::image_format::ImageFormat;
+use crate::image::{upscale, WriteImageFile, WriteError as ImageWriteError};
 
 
 #[derive(Debug, Error, Display, From)]
@@ -15,25 +16,55 @@ pub enum SaveSymbolsToDirError {
 }
 
 pub trait SaveSymbolsToDir {
-    fn save_to_dir<P: AsRef<Path>>(&self, path: P) -> Result<(), SaveSymbolsToDirError>;
+    fn save_to_dir<P: AsRef<Path>>(&self, path: P, image_format: ImageFormat) -> Result<(), SaveSymbolsToDirError> {
+        self.save_to_dir_scaled(path, image_format, 1)
+    }
+
+    // the images are upscaled by an integer factor for presentation, such directories cannot be loaded back
+    fn save_to_dir_scaled<P: AsRef<Path>>(&self, path: P, image_format: ImageFormat, scale: u32) -> Result<(), SaveSymbolsToDirError> {
+        self.save_to_dir_named(path, image_format, scale, None)
+    }
+
+    // the name of the symbols found in the names table is appended to the file names e.g. 001_SYM_RSSI.png,
+    // such directories can still be loaded
+    fn save_to_dir_named<P: AsRef<Path>>(&self, path: P, image_format: ImageFormat, scale: u32, names: Option<SymbolNames>) -> Result<(), SaveSymbolsToDirError>;
+}
+
+// e.g. 001, 030-032, 256-259_2x2 for the symbols spanning several rows of tiles (<columns>x<rows>) or 001_SYM_RSSI
+pub(crate) fn symbol_file_stem(tile_index: usize, symbol: &Symbol, names: Option<SymbolNames>) -> String {
+    let span = symbol.span();
+    let file_stem = match (span, symbol.rows()) {
+        (1, _) => format!("{tile_index:03}"),
+        (span, 1) => format!("{tile_index:03}-{:03}", tile_index + span - 1),
+        (span, rows) => format!("{tile_index:03}-{:03}_{}x{rows}", tile_index + span - 1, symbol.columns()),
+    };
+    match names.and_then(|names| names.name(tile_index, span)) {
+        Some(name) => format!("{file_stem}_{name}"),
+        None => file_stem,
+    }
+}
+
+fn save_symbol_images<'a, P, I, F>(symbols: I, path: P, image_format: ImageFormat, names: Option<SymbolNames>, symbol_image: F) -> Result<(), SaveSymbolsToDirError>
+where
+    P: AsRef<Path>,
+    I: IntoIterator<Item = &'a Symbol>,
+    F: Fn(&Symbol) -> Image,
+{
+    create_path(&path)?;
+    let mut tile_index = 0;
+    for symbol in symbols {
+        let file_stem = symbol_file_stem(tile_index, symbol, names);
+        symbol_image(symbol).write_image_file(image_format.file_path(&path, &file_stem))?;
+        tile_index += symbol.span();
+    }
+    Ok(())
 }
 
 impl<T> SaveSymbolsToDir for T
 where
     for<'any> &'any T: IntoIterator<Item = &'any Symbol>,
 {
-    fn save_to_dir<P: AsRef<Path>>(&self, path: P) -> Result<(), SaveSymbolsToDirError> {
-        create_path(&path)?;
-        let mut tile_index = 0;
-        for symbol in self {
-            let file_name = match symbol.span() {
-                1 => format!("{tile_index:03}.png"),
-                span => format!("{tile_index:03}-{:03}.png", tile_index + span - 1)
-            };
-            let file_path: PathBuf = [path.as_ref(), Path::new(&file_name)].iter().collect();
-            symbol.generate_image().write_image_file(file_path)?;
-            tile_index += symbol.span();
-        }
-        Ok(())
+    fn save_to_dir_named<P: AsRef<Path>>(&self, path: P, image_format: ImageFormat, scale: u32, names: Option<SymbolNames>) -> Result<(), SaveSymbolsToDirError> {
+        save_symbol_images(self, path, image_format, names, |symbol| upscale(&symbol.generate_image(), scale))
     }
 }
@@ -1,36 +1,170 @@
-
 use derive_more::{Error, Display, From};
-use image::ImageError;
+use std::collections::HashMap;
+use std::io::Error as IOError;
 use std::path::{Path, PathBuf};
 
+use clap::ValueEnum;
+
 use crate::{
-    osd::tile::Tile,
-    create_path::{create_path, CreatePathError}
+    image_format::ImageFormat,
+    osd::tile::{Image, Tile},
+    create_path::{create_path, CreatePathError},
+    image::{upscale, WriteImageFile, WriteError as ImageWriteError},
+    parallel,
 };
 
 
+#[derive(Debug, thiserror::Error)]
+#[error("failed to write the file of duplicate tile {file_path}: {error}")]
+pub struct DuplicateTileFileError {
+    file_path: PathBuf,
+    error: IOError,
+}
+
 #[derive(Debug, Error, Display, From)]
 pub enum SaveTilesToDirError {
     CreatePathError(CreatePathError),
-    ImageError(ImageError),
+    ImageWriteError(ImageWriteError),
+    DuplicateTileFileError(DuplicateTileFileError),
+}
+
+// How the files of the tiles identical to a previous tile are written when deduplicating, the first of the identical
+// tiles is written as usual
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum, strum::Display)]
+#[strum(serialize_all = "kebab-case")]
+pub enum DedupMode {
+    HardLink,
+    // the links are relative to the directory so that it can be moved
+    Symlink,
+    // only saves encoding the image again
+    Copy,
+}
+
+#[cfg(unix)]
+fn symlink(target: &Path, link: &Path) -> Result<(), IOError> {
+    std::os::unix::fs::symlink(target, link)
+}
+
+#[cfg(windows)]
+fn symlink(target: &Path, link: &Path) -> Result<(), IOError> {
+    std::os::windows::fs::symlink_file(target, link)
+}
+
+#[cfg(not(any(unix, windows)))]
+fn symlink(_target: &Path, _link: &Path) -> Result<(), IOError> {
+    Err(IOError::new(std::io::ErrorKind::Unsupported, "symbolic links are not supported on this platform"))
+}
+
+impl DedupMode {
+
+    fn write_duplicate(&self, original_path: &Path, path: &Path) -> Result<(), IOError> {
+        match self {
+            DedupMode::HardLink => std::fs::hard_link(original_path, path),
+            DedupMode::Symlink => symlink(Path::new(original_path.file_name().unwrap()), path),
+            DedupMode::Copy => std::fs::copy(original_path, path).map(|_| ()),
+        }
+    }
+
+}
+
+// index of the first tile with the same pixels for each tile, its own index for the first of the identical tiles
+fn original_tile_indices(tiles: &[&Tile]) -> Vec<usize> {
+    let mut first_indices: HashMap<&[u8], usize> = HashMap::new();
+    tiles.iter().enumerate()
+        .map(|(index, tile)| *first_indices.entry(tile.as_raw().as_slice()).or_insert(index))
+        .collect()
 }
 
 pub trait SaveTilesToDir {
-    fn save_tiles_to_dir<P: AsRef<Path>>(&self, path: P) -> Result<(), SaveTilesToDirError>;
+    fn save_tiles_to_dir<P: AsRef<Path>>(&self, path: P, image_format: ImageFormat) -> Result<(), SaveTilesToDirError> {
+        self.save_tiles_to_dir_scaled(path, image_format, 1)
+    }
+
+    // the images are upscaled by an integer factor for presentation, such directories cannot be loaded back
+    fn save_tiles_to_dir_scaled<P: AsRef<Path>>(&self, path: P, image_format: ImageFormat, scale: u32) -> Result<(), SaveTilesToDirError> {
+        self.save_tiles_to_dir_deduplicated(path, image_format, scale, None)
+    }
+
+    // the files of the tiles identical to a previous tile are written according to the dedup mode e.g. fonts with many
+    // blank tiles, every tile has its own file when not set
+    fn save_tiles_to_dir_deduplicated<P: AsRef<Path>>(&self, path: P, image_format: ImageFormat, scale: u32, dedup: Option<DedupMode>) -> Result<(), SaveTilesToDirError>;
+}
+
+fn save_tile_images<P, F>(tiles: &[&Tile], path: P, image_format: ImageFormat, dedup: Option<DedupMode>, tile_image: F) -> Result<(), SaveTilesToDirError>
+where
+    P: AsRef<Path>,
+    F: Fn(&Tile) -> Image + Sync + Send,
+{
+    create_path(&path)?;
+    let path = path.as_ref();
+    let tile_paths: Vec<PathBuf> = (0..tiles.len()).map(|index| image_format.file_path(path, &format!("{:03}", index))).collect();
+    let original_indices = match dedup {
+        Some(_) => original_tile_indices(tiles),
+        None => (0..tiles.len()).collect(),
+    };
+    let duplicate_file_error = |file_path: &Path, error: IOError| DuplicateTileFileError { file_path: file_path.to_path_buf(), error };
+
+    // the files left by a previous save may be links to each other, writing through them would change the linked files
+    if dedup.is_some() {
+        for tile_path in tile_paths.iter().filter(|tile_path| tile_path.symlink_metadata().is_ok()) {
+            std::fs::remove_file(tile_path).map_err(|error| duplicate_file_error(tile_path, error))?;
+        }
+    }
+
+    parallel::try_map(tiles, |index, tile| {
+        match original_indices[index] == index {
+            true => tile_image(tile).write_image_file(&tile_paths[index]),
+            false => Ok(()),
+        }
+    })?;
+
+    if let Some(dedup) = dedup {
+        let duplicates: Vec<(usize, usize)> = original_indices.into_iter().enumerate()
+            .filter(|(index, original_index)| index != original_index)
+            .collect();
+        for (index, original_index) in &duplicates {
+            dedup.write_duplicate(&tile_paths[*original_index], &tile_paths[*index])
+                .map_err(|error| duplicate_file_error(&tile_paths[*index], error))?;
+        }
+        log::debug!("{} duplicate tiles written as {dedup} in {}", duplicates.len(), path.to_string_lossy());
+    }
+    Ok(())
 }
 
 impl<T> SaveTilesToDir for T
 where
     for<'any> &'any T: IntoIterator<Item = &'any Tile>,
 {
-    fn save_tiles_to_dir<P: AsRef<Path>>(&self, path: P) -> Result<(), SaveTilesToDirError> {
-        create_path(&path)?;
+    fn save_tiles_to_dir_deduplicated<P: AsRef<Path>>(&self, path: P, image_format: ImageFormat, scale: u32, dedup: Option<DedupMode>) -> Result<(), SaveTilesToDirError> {
+        let tiles: Vec<&Tile> = self.into_iter().collect();
+        save_tile_images(&tiles, path, image_format, dedup, |tile| upscale(tile.image(), scale))
+    }
+}
 
-        for (index, tile) in self.into_iter().enumerate() {
-            let path: PathBuf = [path.as_ref(), Path::new(&format!("{:03}.png", index))].iter().collect();
-            tile.save(path)?;
-        }
+#[cfg(test)]
+mod tests {
+
+    use image::Rgba;
+    use temp_dir::TempDir;
 
-        Ok(())
+    use crate::image_format::ImageFormat;
+    use crate::osd::tile::{Tile, Kind as TileKind};
+
+    use super::{DedupMode, SaveTilesToDir};
+
+    #[test]
+    fn deduplicated() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut tiles = vec![Tile::new(TileKind::SD); 3];
+        tiles[1].put_pixel(0, 0, Rgba([255, 255, 255, 255]));
+        for dedup in [DedupMode::HardLink, DedupMode::Copy] {
+            let dir = temp_dir.child(dedup.to_string());
+            tiles.save_tiles_to_dir_deduplicated(&dir, ImageFormat::Png, 1, Some(dedup)).unwrap();
+            tiles.save_tiles_to_dir_deduplicated(&dir, ImageFormat::Png, 1, Some(dedup)).unwrap();
+            let content = |index: usize| std::fs::read(dir.join(format!("{index:03}.png"))).unwrap();
+            assert_eq!(content(0), content(2));
+            assert_ne!(content(0), content(1));
+        }
     }
-}
\ No newline at end of file
+
+}
@@ -5,8 +5,9 @@ use std::{
 };
 
 use derive_more::{Error, Display, From};
-use crate::{osd::{tile::{Tile, grid::Grid as TileGrid}, bin_file::{self, BinFileWriter}}, prelude::bin_file::FontPart, create_path::{CreatePathError, create_path}};
+use crate::{osd::{tile::{Tile, Kind as TileKind, grid::Grid as TileGrid}, bin_file::{self, BinFileWriter}}, prelude::bin_file::FontPart, create_path::{CreatePathError, create_path}};
 use super::uniq_tile_kind::{TileKindError, UniqTileKind};
+use super::CapacityError;
 
 
 #[derive(Debug, Error, Display, From)]
@@ -15,7 +16,40 @@ pub enum SaveTilesToBinFileError {
     CreateError(IOError),
     TileKindError(TileKindError),
     TileWriteError(bin_file::TileWriteError),
-    FillRemainingSpaceError(bin_file::FillRemainingSpaceError)
+    FillRemainingSpaceError(bin_file::FillRemainingSpaceError),
+    CapacityError(CapacityError),
+    TileCountMismatchError(TileCountMismatchError),
+}
+
+// strict padding: the tiles have to fill the pages exactly
+#[derive(Debug, Error, Display)]
+#[display("source has {tile_count} tiles but the target holds exactly {capacity} tiles")]
+pub struct TileCountMismatchError {
+    pub tile_count: usize,
+    pub capacity: usize,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Padding {
+    // the pages not filled by the tiles are completed with transparent tiles
+    #[default]
+    Pad,
+    // the tiles have to fill the pages exactly
+    Strict,
+}
+
+// writes a page of at most bin_file::TILE_COUNT tiles, the remaining space is filled with transparent tiles
+pub(crate) fn save_page_to_bin_file<P: AsRef<Path>>(tiles: &[Tile], tile_kind: TileKind, path: P) -> Result<(), SaveTilesToBinFileError> {
+    let mut writer = BinFileWriter::create(path)?;
+    for tile in tiles {
+        writer.write_tile(tile)?;
+    }
+    if tiles.is_empty() {
+        writer.write_tile(&Tile::new(tile_kind))?;
+    }
+    writer.fill_remaining_space()?;
+    writer.finish()?;
+    Ok(())
 }
 
 pub trait SaveToBinFile {
@@ -25,16 +59,9 @@ pub trait SaveToBinFile {
 
 impl SaveToBinFile for &[Tile] {
     fn save_to_bin_file<P: AsRef<Path>>(&self, path: P) -> Result<(), SaveTilesToBinFileError> {
-        self.tile_kind()?;
-        let mut writer = BinFileWriter::create(path)?;
-
-        for tile in self.iter() {
-            writer.write_tile(tile)?;
-        }
-
-        writer.fill_remaining_space()?;
-        writer.finish()?;
-        Ok(())
+        let tile_kind = self.tile_kind()?;
+        CapacityError::check(self.len(), bin_file::TILE_COUNT)?;
+        save_page_to_bin_file(self, tile_kind, path)
     }
 
     fn save_to_bin_file_norm<P: AsRef<Path>>(&self, dir: P, ident: &Option<&str>, part: FontPart) -> Result<(), SaveTilesToBinFileError> {
@@ -64,28 +91,64 @@ impl SaveTilesToBinFile for TileGrid {
 }
 
 pub trait SaveToBinFiles {
-    fn save_to_bin_files<P: AsRef<Path>>(&self, path1: P, path2: P) -> Result<(), SaveTilesToBinFileError>;
+    fn save_to_bin_files<P: AsRef<Path>>(&self, paths: &[P]) -> Result<(), SaveTilesToBinFileError>;
+    fn save_to_bin_files_padding<P: AsRef<Path>>(&self, paths: &[P], padding: Padding) -> Result<(), SaveTilesToBinFileError>;
     fn save_to_bin_files_norm<P: AsRef<Path>>(&self, dir: P, ident: &Option<&str>) -> Result<(), SaveTilesToBinFileError>;
+    fn save_to_bin_files_norm_padding<P: AsRef<Path>>(&self, dir: P, ident: &Option<&str>, page_count: usize, padding: Padding) -> Result<(), SaveTilesToBinFileError>;
 }
 
 impl SaveToBinFiles for &[Tile] {
-    fn save_to_bin_files<P: AsRef<Path>>(&self, path1: P, path2: P) -> Result<(), SaveTilesToBinFileError> {
-        (&self[0..bin_file::TILE_COUNT]).save_to_bin_file(path1)?;
-        (&self[bin_file::TILE_COUNT..2 * bin_file::TILE_COUNT]).save_to_bin_file(path2)
+    fn save_to_bin_files<P: AsRef<Path>>(&self, paths: &[P]) -> Result<(), SaveTilesToBinFileError> {
+        self.save_to_bin_files_padding(paths, Padding::default())
+    }
+
+    // each path is a page, pages not containing any tile are filled with transparent tiles
+    fn save_to_bin_files_padding<P: AsRef<Path>>(&self, paths: &[P], padding: Padding) -> Result<(), SaveTilesToBinFileError> {
+        let tile_kind = self.tile_kind()?;
+        let capacity = paths.len() * bin_file::TILE_COUNT;
+        CapacityError::check(self.len(), capacity)?;
+        if self.len() < capacity {
+            match padding {
+                Padding::Pad => log::debug!("padding the {} {tile_kind} tiles with {} transparent tiles to fill {} bin file pages", self.len(), capacity - self.len(), paths.len()),
+                Padding::Strict => return Err(TileCountMismatchError { tile_count: self.len(), capacity }.into()),
+            }
+        }
+        let mut pages = self.chunks(bin_file::TILE_COUNT);
+        for path in paths {
+            save_page_to_bin_file(pages.next().unwrap_or_default(), tile_kind, path)?;
+        }
+        Ok(())
     }
 
     fn save_to_bin_files_norm<P: AsRef<Path>>(&self, dir: P, ident: &Option<&str>) -> Result<(), SaveTilesToBinFileError> {
-        (&self[0..bin_file::TILE_COUNT]).save_to_bin_file_norm(&dir, ident, FontPart::Base)?;
-        (&self[bin_file::TILE_COUNT..2 * bin_file::TILE_COUNT]).save_to_bin_file_norm(&dir, ident, FontPart::Ext)
+        self.save_to_bin_files_norm_padding(dir, ident, bin_file::page_count(self.len()), Padding::default())
+    }
+
+    // `page_count` pages are written whatever the number of tiles, the tiles not fitting are an error
+    fn save_to_bin_files_norm_padding<P: AsRef<Path>>(&self, dir: P, ident: &Option<&str>, page_count: usize, padding: Padding) -> Result<(), SaveTilesToBinFileError> {
+        let tile_kind = self.tile_kind()?;
+        create_path(&dir)?;
+        let paths = (0..page_count)
+            .map(|page_index| bin_file::normalized_page_file_path(&dir, tile_kind, ident, page_index))
+            .collect::<Vec<_>>();
+        self.save_to_bin_files_padding(&paths, padding)
     }
 }
 
 impl SaveToBinFiles for Vec<Tile> {
-    fn save_to_bin_files<P: AsRef<Path>>(&self, path1: P, path2: P) -> Result<(), SaveTilesToBinFileError> {
-        self.as_slice().save_to_bin_files(path1, path2)
+    fn save_to_bin_files<P: AsRef<Path>>(&self, paths: &[P]) -> Result<(), SaveTilesToBinFileError> {
+        self.as_slice().save_to_bin_files(paths)
+    }
+
+    fn save_to_bin_files_padding<P: AsRef<Path>>(&self, paths: &[P], padding: Padding) -> Result<(), SaveTilesToBinFileError> {
+        self.as_slice().save_to_bin_files_padding(paths, padding)
     }
 
     fn save_to_bin_files_norm<P: AsRef<Path>>(&self, dir: P, ident: &Option<&str>) -> Result<(), SaveTilesToBinFileError> {
         self.as_slice().save_to_bin_files_norm(dir, ident)
     }
+
+    fn save_to_bin_files_norm_padding<P: AsRef<Path>>(&self, dir: P, ident: &Option<&str>, page_count: usize, padding: Padding) -> Result<(), SaveTilesToBinFileError> {
+        self.as_slice().save_to_bin_files_norm_padding(dir, ident, page_count, padding)
+    }
 }
@@ -1,32 +1,42 @@
 
 use std::path::Path;
 
-use crate::{osd::tile::Tile, prelude::IntoTileGrid};
-use crate::osd::tile::grid::SaveImageError as SaveGridImageError;
+use crate::{image_format::ImageFormat, osd::tile::Tile, prelude::IntoTileGrid};
+use crate::osd::tile::grid::{Layout as GridLayout, SaveImageError as SaveGridImageError};
+use crate::osd::tile::grid_annotation::Annotation as GridAnnotation;
 
 
 pub trait SaveToGridImage {
-    fn save_to_grid_image<P: AsRef<Path>>(&self, path: P) -> Result<(), SaveGridImageError>;
-    fn save_to_grid_image_norm<P: AsRef<Path>>(&self, dir: P, ident: &Option<&str>) -> Result<(), SaveGridImageError>;
+    fn save_to_grid_image<P: AsRef<Path>>(&self, path: P) -> Result<(), SaveGridImageError> {
+        self.save_to_grid_image_annotated(path, GridLayout::default(), None)
+    }
+
+    fn save_to_grid_image_norm<P: AsRef<Path>>(&self, dir: P, ident: &Option<&str>, image_format: ImageFormat) -> Result<(), SaveGridImageError> {
+        self.save_to_grid_image_norm_annotated(dir, ident, image_format, GridLayout::default(), None)
+    }
+
+    // annotated grids are for presentation only and cannot be loaded back
+    fn save_to_grid_image_annotated<P: AsRef<Path>>(&self, path: P, layout: GridLayout, annotation: Option<GridAnnotation>) -> Result<(), SaveGridImageError>;
+    fn save_to_grid_image_norm_annotated<P: AsRef<Path>>(&self, dir: P, ident: &Option<&str>, image_format: ImageFormat, layout: GridLayout, annotation: Option<GridAnnotation>) -> Result<(), SaveGridImageError>;
 }
 
 impl SaveToGridImage for Vec<Tile> {
-    fn save_to_grid_image<P: AsRef<Path>>(&self, path: P) -> Result<(), SaveGridImageError> {
-        self.into_tile_grid().save_image(path)?;
+    fn save_to_grid_image_annotated<P: AsRef<Path>>(&self, path: P, layout: GridLayout, annotation: Option<GridAnnotation>) -> Result<(), SaveGridImageError> {
+        self.into_tile_grid().save_image_annotated(path, layout, annotation)?;
         Ok(())
     }
 
-    fn save_to_grid_image_norm<P: AsRef<Path>>(&self, dir: P, ident: &Option<&str>) -> Result<(), SaveGridImageError> {
-        self.into_tile_grid().save_image_norm(dir, ident)
+    fn save_to_grid_image_norm_annotated<P: AsRef<Path>>(&self, dir: P, ident: &Option<&str>, image_format: ImageFormat, layout: GridLayout, annotation: Option<GridAnnotation>) -> Result<(), SaveGridImageError> {
+        self.into_tile_grid().save_image_norm_annotated(dir, ident, image_format, layout, annotation)
     }
 }
 
 impl SaveToGridImage for &[Tile] {
-    fn save_to_grid_image<P: AsRef<Path>>(&self, path: P) -> Result<(), SaveGridImageError> {
-        self.to_vec().save_to_grid_image(path)
+    fn save_to_grid_image_annotated<P: AsRef<Path>>(&self, path: P, layout: GridLayout, annotation: Option<GridAnnotation>) -> Result<(), SaveGridImageError> {
+        self.to_vec().save_to_grid_image_annotated(path, layout, annotation)
     }
 
-    fn save_to_grid_image_norm<P: AsRef<Path>>(&self, dir: P, ident: &Option<&str>) -> Result<(), SaveGridImageError> {
-        self.to_vec().save_to_grid_image_norm(dir, ident)
+    fn save_to_grid_image_norm_annotated<P: AsRef<Path>>(&self, dir: P, ident: &Option<&str>, image_format: ImageFormat, layout: GridLayout, annotation: Option<GridAnnotation>) -> Result<(), SaveGridImageError> {
+        self.to_vec().save_to_grid_image_norm_annotated(dir, ident, image_format, layout, annotation)
     }
 }
@@ -0,0 +1,147 @@
+use std::collections::BTreeMap;
+use std::io::Error as IOError;
+use std::path::Path;
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::create_path::create_path;
+use crate::image::WriteImageFile;
+use crate::image_format::{ImageFormat, is_readable_image_path};
+use super::dir_files_iter::dir_files_iter;
+use super::save_symbols_to_dir::SaveSymbolsToDirError;
+use super::symbol::{Symbol, names::SymbolNames, spec::{Spec, Specs}};
+
+// A split symbol directory is a tile directory where the tiles of the symbols are named after their symbol:
+// NNN_<symbol name>_<K>.png with K the position of the tile in the symbol e.g. 030_SYM_SAT_0.png and 031_SYM_SAT_1.png,
+// the symbols spanning several rows get a <columns>x<rows> suffix to their name e.g. 256_logo_2x2_0.png. It loads as
+// any tile directory and the symbol specs can be rebuilt from the file names.
+
+
+// e.g. 030_SYM_SAT_1
+pub(crate) fn split_tile_file_stem(tile_index: usize, symbol_name: &str, part: usize) -> String {
+    format!("{tile_index:03}_{symbol_name}_{part}")
+}
+
+// tile index, symbol name and position in the symbol of a split tile file stem
+pub(crate) fn parse_split_tile_file_stem(file_stem: &str) -> Option<(usize, &str, usize)> {
+    lazy_static! {
+        static ref FILE_STEM_RE: Regex = Regex::new(r"\A(?P<tile_index>\d+)_(?P<symbol_name>\w+)_(?P<part>\d+)\z").unwrap();
+    }
+    let captures = FILE_STEM_RE.captures(file_stem)?;
+    let tile_index = captures.name("tile_index")?.as_str().parse().ok()?;
+    let part = captures.name("part")?.as_str().parse().ok()?;
+    Some((tile_index, captures.name("symbol_name")?.as_str(), part))
+}
+
+// symbol name without the <columns>x<rows> suffix and the rows of the symbol
+fn parse_symbol_name_rows(symbol_name: &str) -> (&str, usize) {
+    lazy_static! {
+        static ref SHAPE_RE: Regex = Regex::new(r"\A(?P<name>\w+?)_(?P<columns>\d+)x(?P<rows>\d+)\z").unwrap();
+    }
+    match SHAPE_RE.captures(symbol_name) {
+        Some(captures) => match captures["rows"].parse() {
+            Ok(rows) if rows > 0 => (captures.name("name").unwrap().as_str(), rows),
+            _ => (symbol_name, 1),
+        },
+        None => (symbol_name, 1),
+    }
+}
+
+// name of the symbol in the file names: the spec name, else the name from the names table, else symbol_NNN for the
+// symbols spanning several tiles, the unnamed single tile symbols are written as plain NNN tile files
+fn split_symbol_name(tile_index: usize, symbol: &Symbol, specs: Option<&Specs>, names: Option<SymbolNames>) -> Option<String> {
+    let name = specs.and_then(|specs| specs.find_start_index(tile_index))
+        .filter(|spec| spec.span() == symbol.span())
+        .map(|spec| spec.name().clone())
+        .or_else(|| names.and_then(|names| names.name(tile_index, symbol.span())).map(str::to_owned))
+        .or_else(|| (symbol.span() > 1).then(|| format!("symbol_{tile_index:03}")))?;
+    Some(match symbol.rows() {
+        1 => name,
+        rows => format!("{name}_{}x{rows}", symbol.columns()),
+    })
+}
+
+pub trait SplitSymbolsToDir {
+    // writes the tiles of the symbols to a tile directory with file names keeping the symbol of each tile
+    fn split_to_dir<P: AsRef<Path>>(&self, path: P, image_format: ImageFormat, specs: Option<&Specs>, names: Option<SymbolNames>) -> Result<(), SaveSymbolsToDirError>;
+}
+
+impl<T> SplitSymbolsToDir for T
+where
+    for<'any> &'any T: IntoIterator<Item = &'any Symbol>,
+{
+    fn split_to_dir<P: AsRef<Path>>(&self, path: P, image_format: ImageFormat, specs: Option<&Specs>, names: Option<SymbolNames>) -> Result<(), SaveSymbolsToDirError> {
+        create_path(&path)?;
+        let mut tile_index = 0;
+        for symbol in self {
+            let symbol_name = split_symbol_name(tile_index, symbol, specs, names);
+            for part in 0..symbol.span() {
+                let file_stem = match &symbol_name {
+                    Some(symbol_name) => split_tile_file_stem(tile_index + part, symbol_name, part),
+                    None => format!("{:03}", tile_index + part),
+                };
+                symbol[part].image().write_image_file(image_format.file_path(&path, &file_stem))?;
+            }
+            tile_index += symbol.span();
+        }
+        Ok(())
+    }
+}
+
+// specs of the symbols spanning several tiles from the split tile file names, the tiles of a symbol have consecutive
+// indexes, the same symbol name and positions starting from 0
+pub fn specs_from_split_file_stems<'a, I: IntoIterator<Item = &'a str>>(file_stems: I) -> Specs {
+    let parts: BTreeMap<usize, (&str, usize)> = file_stems.into_iter()
+        .filter_map(parse_split_tile_file_stem)
+        .map(|(tile_index, symbol_name, part)| (tile_index, (symbol_name, part)))
+        .collect();
+    let mut specs = vec![];
+    let mut parts = parts.into_iter().peekable();
+    while let Some((start_tile_index, (symbol_name, part))) = parts.next() {
+        if part != 0 {
+            continue;
+        }
+        let mut span = 1;
+        while parts.peek() == Some(&(start_tile_index + span, (symbol_name, span))) {
+            parts.next();
+            span += 1;
+        }
+        let (name, rows) = parse_symbol_name_rows(symbol_name);
+        if span > 1 {
+            let rows = if span % rows == 0 { rows } else { 1 };
+            specs.push(Spec::new_rows(name, start_tile_index, span, rows));
+        }
+    }
+    specs.into()
+}
+
+pub fn load_split_symbol_specs<P: AsRef<Path>>(dir_path: P) -> Result<Specs, IOError> {
+    let mut file_stems = vec![];
+    for file_path in dir_files_iter(dir_path)? {
+        let file_path = file_path?;
+        if !is_readable_image_path(&file_path) {
+            continue;
+        }
+        if let Some(file_stem) = file_path.file_stem().and_then(|file_stem| file_stem.to_str()) {
+            file_stems.push(file_stem.to_owned());
+        }
+    }
+    Ok(specs_from_split_file_stems(file_stems.iter().map(String::as_str)))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{parse_split_tile_file_stem, specs_from_split_file_stems};
+
+    #[test]
+    fn split_file_stems() {
+        assert_eq!(parse_split_tile_file_stem("031_SYM_SAT_1"), Some((31, "SYM_SAT", 1)));
+        assert_eq!(parse_split_tile_file_stem("031"), None);
+        let specs = specs_from_split_file_stems(["000", "030_SYM_SAT_0", "031_SYM_SAT_1", "032_SYM_SAT_0", "256_logo_2x2_0", "257_logo_2x2_1", "258_logo_2x2_2", "259_logo_2x2_3"]);
+        let specs: Vec<(&str, usize, usize, usize)> = specs.iter().map(|spec| (spec.name().as_str(), spec.start_tile_index(), spec.span(), spec.rows())).collect();
+        assert_eq!(specs, vec![("SYM_SAT", 30, 2, 1), ("logo", 256, 4, 2)]);
+    }
+
+}
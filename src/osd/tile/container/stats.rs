@@ -0,0 +1,211 @@
+
+use std::collections::{BTreeMap, HashMap};
+
+use getset::{CopyGetters, Getters};
+use image::{Pixel, Rgba};
+use serde::Serialize;
+
+use crate::osd::{bin_file, tile::Tile};
+
+
+#[derive(Debug, Clone, Serialize, CopyGetters)]
+#[getset(get_copy = "pub")]
+pub struct PageStats {
+    index: usize,
+    tile_count: usize,
+    non_blank_tile_count: usize,
+}
+
+// width of the border of the tiles where visible pixels risk visually colliding with the neighbor tiles
+pub const DEFAULT_SAFE_MARGIN: u32 = 2;
+
+// Tiles with visible pixels within `margin` pixels of their border, grouped by how deep the pixels intrude into the
+// margin: 1 is only the innermost line of the margin, `margin` is touching the tile edge. The horizontal intrusions
+// (left/right edges) are reported separately from the vertical ones since the tiles are packed tighter horizontally.
+#[derive(Debug, Clone, Serialize, Getters, CopyGetters)]
+pub struct MarginReport {
+    #[getset(get_copy = "pub")]
+    margin: u32,
+
+    // intrusion depth -> tile indices
+    #[getset(get = "pub")]
+    horizontal: BTreeMap<u32, Vec<usize>>,
+
+    #[getset(get = "pub")]
+    vertical: BTreeMap<u32, Vec<usize>>,
+}
+
+impl MarginReport {
+
+    pub fn compute(tiles: &[Tile], margin: u32) -> Self {
+        let mut horizontal: BTreeMap<u32, Vec<usize>> = BTreeMap::new();
+        let mut vertical: BTreeMap<u32, Vec<usize>> = BTreeMap::new();
+        for (index, tile) in tiles.iter().enumerate() {
+            let bounds = match tile.content_bounds() {
+                Some(bounds) => bounds,
+                None => continue,
+            };
+            let tile_dimensions = tile.kind().dimensions();
+            let (left, right) = bounds.horizontal_margins(tile_dimensions);
+            let (top, bottom) = bounds.vertical_margins(tile_dimensions);
+            for (intrusions, content_margin) in [(&mut horizontal, left.min(right)), (&mut vertical, top.min(bottom))] {
+                if content_margin < margin {
+                    intrusions.entry(margin - content_margin).or_default().push(index);
+                }
+            }
+        }
+        Self { margin, horizontal, vertical }
+    }
+
+}
+
+#[derive(Debug, Clone, Serialize, Getters, CopyGetters)]
+pub struct FontStats {
+    #[getset(get_copy = "pub")]
+    tile_count: usize,
+
+    #[getset(get = "pub")]
+    pages: Vec<PageStats>,
+
+    // groups of indices of non-blank tiles having the exact same pixel content
+    #[getset(get = "pub")]
+    duplicate_groups: Vec<Vec<usize>>,
+
+    // indices of tiles with visible pixels on their border, the glyph is likely clipped
+    #[getset(get = "pub")]
+    edge_touching_tiles: Vec<usize>,
+
+    #[getset(get = "pub")]
+    margin_intrusions: MarginReport,
+
+    #[getset(get_copy = "pub")]
+    palette_violation_pixel_count: usize,
+
+    #[getset(get = "pub")]
+    palette_violation_tiles: Vec<usize>,
+}
+
+// OSD fonts are expected to only contain fully transparent pixels or fully opaque gray shades
+pub(crate) fn is_palette_violation(pixel: &Rgba<u8>) -> bool {
+    let [red, green, blue, alpha] = pixel.0;
+    match alpha {
+        0 => false,
+        255 => !(red == green && green == blue),
+        _ => true,
+    }
+}
+
+pub fn palette_violation_count(tile: &Tile) -> usize {
+    tile.pixels().filter(|pixel| is_palette_violation(pixel)).count()
+}
+
+pub(crate) fn is_blank(tile: &Tile) -> bool {
+    tile.pixels().all(|pixel| pixel.channels()[3] == 0)
+}
+
+fn touches_edge(tile: &Tile) -> bool {
+    let (width, height) = tile.dimensions();
+    tile.enumerate_pixels().any(|(x, y, pixel)|
+        pixel.channels()[3] != 0 && (x == 0 || y == 0 || x == width - 1 || y == height - 1)
+    )
+}
+
+impl FontStats {
+
+    pub fn compute(tiles: &[Tile]) -> Self {
+        Self::compute_with_margin(tiles, DEFAULT_SAFE_MARGIN)
+    }
+
+    pub fn compute_with_margin(tiles: &[Tile], safe_margin: u32) -> Self {
+        let pages = tiles.chunks(bin_file::TILE_COUNT).enumerate().map(|(index, page_tiles)|
+            PageStats {
+                index,
+                tile_count: page_tiles.len(),
+                non_blank_tile_count: page_tiles.iter().filter(|tile| !is_blank(tile)).count(),
+            }
+        ).collect();
+
+        let mut tiles_by_content: HashMap<&[u8], Vec<usize>> = HashMap::new();
+        for (index, tile) in tiles.iter().enumerate() {
+            if !is_blank(tile) {
+                tiles_by_content.entry(tile.as_raw().as_slice()).or_default().push(index);
+            }
+        }
+        let mut duplicate_groups: Vec<Vec<usize>> = tiles_by_content.into_values().filter(|indices| indices.len() > 1).collect();
+        duplicate_groups.sort();
+
+        let edge_touching_tiles = tiles.iter().enumerate()
+            .filter(|(_, tile)| touches_edge(tile))
+            .map(|(index, _)| index)
+            .collect();
+
+        let mut palette_violation_pixel_count = 0;
+        let mut palette_violation_tiles = vec![];
+        for (index, tile) in tiles.iter().enumerate() {
+            let violations = tile.pixels().filter(|pixel| is_palette_violation(pixel)).count();
+            if violations > 0 {
+                palette_violation_pixel_count += violations;
+                palette_violation_tiles.push(index);
+            }
+        }
+
+        Self {
+            tile_count: tiles.len(),
+            pages,
+            duplicate_groups,
+            edge_touching_tiles,
+            margin_intrusions: MarginReport::compute(tiles, safe_margin),
+            palette_violation_pixel_count,
+            palette_violation_tiles,
+        }
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+
+    use crate::osd::tile::{Tile, Kind};
+
+    use super::{FontStats, MarginReport};
+
+    #[test]
+    fn duplicates_and_blanks() {
+        let blank = Tile::new(Kind::SD);
+        let mut glyph = Tile::new(Kind::SD);
+        glyph.put_pixel(10, 10, Rgba([255, 255, 255, 255]));
+        let mut edge_glyph = Tile::new(Kind::SD);
+        edge_glyph.put_pixel(0, 10, Rgba([255, 0, 0, 255]));
+
+        let tiles = vec![blank.clone(), glyph.clone(), blank, glyph, edge_glyph];
+        let stats = FontStats::compute(&tiles);
+
+        assert_eq!(stats.tile_count(), 5);
+        assert_eq!(stats.pages().len(), 1);
+        assert_eq!(stats.pages()[0].non_blank_tile_count(), 3);
+        assert_eq!(stats.duplicate_groups(), &vec![vec![1, 3]]);
+        assert_eq!(stats.edge_touching_tiles(), &vec![4]);
+        assert_eq!(stats.palette_violation_pixel_count(), 1);
+        assert_eq!(stats.palette_violation_tiles(), &vec![4]);
+    }
+
+    #[test]
+    fn margin_intrusions() {
+        let mut touching_left = Tile::new(Kind::SD);
+        touching_left.put_pixel(0, 20, Rgba([255, 255, 255, 255]));
+        touching_left.put_pixel(10, 20, Rgba([255, 255, 255, 255]));
+        let mut near_bottom = Tile::new(Kind::HD);
+        near_bottom.put_pixel(12, 34, Rgba([255, 255, 255, 255]));
+        let mut centered = Tile::new(Kind::SD);
+        centered.put_pixel(18, 27, Rgba([255, 255, 255, 255]));
+
+        let bounds = touching_left.content_bounds().unwrap();
+        assert_eq!((bounds.left(), bounds.top(), bounds.right(), bounds.bottom()), (0, 20, 10, 20));
+        assert_eq!(Tile::new(Kind::SD).content_bounds(), None);
+
+        let report = MarginReport::compute(&[touching_left, near_bottom, centered, Tile::new(Kind::HD)], 3);
+        assert_eq!(report.horizontal().iter().collect::<Vec<_>>(), vec![(&3, &vec![0])]);
+        assert_eq!(report.vertical().iter().collect::<Vec<_>>(), vec![(&2, &vec![1])]);
+    }
+}
@@ -1,6 +1,7 @@
 
 pub mod spec;
 pub mod set;
+pub mod names;
 
 use std::fmt::Display;
 use std::path::Path;
@@ -12,13 +13,16 @@ use crate::dimensions;
 use crate::osd::tile::{
     Tile,
     Kind as TileKind,
+    AmbiguousScaleError,
     InvalidHeightError,
+    fit::{Fit, fit_image},
     container::{
         TileKindError,
         UniqTileKind
     }
 };
-use crate::image::{read_image_file, ReadError as ImageReadError};
+use crate::image::{read_rgba_image_file, ReadError as ImageReadError};
+use crate::image_format::ImageDecoding;
 
 
 #[derive(Debug, From, Error)]
@@ -29,6 +33,12 @@ pub enum LoadError {
         tile_kind: TileKind,
         image_width: u32,
     },
+    #[from(ignore)]
+    ScaledExportError {
+        tile_kind: TileKind,
+        scale: u32,
+    },
+    AmbiguousScaledExportError(AmbiguousScaleError),
 }
 
 impl Display for LoadError {
@@ -38,6 +48,10 @@ impl Display for LoadError {
             ImageReadError(image_error) => image_error.fmt(f),
             InvalidImageWidthError { tile_kind, image_width } => write!(f, "invalid tile image width for {tile_kind} tile kind: {image_width}"),
             InvalidImageHeightError(error) => error.fmt(f),
+            ScaledExportError { tile_kind, scale } =>
+                write!(f, "image looks like a {scale}x scaled export of {tile_kind} symbols which is for presentation only, export again without scaling to get loadable symbols"),
+            AmbiguousScaledExportError(error) =>
+                write!(f, "image {error} which are for presentation only, export again without scaling to get loadable symbols"),
         }
     }
 }
@@ -46,41 +60,103 @@ pub type ImageDimensions = dimensions::Dimensions<u32>;
 
 pub type Image = ImageBuffer<Rgba<u8>, Vec<u8>>;
 
+// The tiles of a symbol have consecutive indexes, they are arranged in rows of `columns` tiles in the symbol image
+// e.g. a 2x2 logo made of the tiles 256 to 259 with 256 and 257 on the first row
 #[derive(Clone, Index, CopyGetters)]
 pub struct Symbol {
     #[getset(get_copy = "pub")]
     tile_kind: TileKind,
+    #[getset(get_copy = "pub")]
+    columns: usize,
     #[index] tiles: Vec<Tile>,
 }
 
 impl Symbol {
 
     pub fn new(tile_kind: TileKind) -> Self {
-        Self { tile_kind, tiles: vec![Tile::new(tile_kind)] }
+        Self { tile_kind, columns: 1, tiles: vec![Tile::new(tile_kind)] }
+    }
+
+    fn image_tile_kind(image_width: u32, image_height: u32, rows: u32) -> Result<TileKind, LoadError> {
+        if image_height % rows != 0 {
+            return Err(InvalidHeightError(image_height).into())
+        }
+        let tile_kind = TileKind::for_height(image_height / rows)?;
+        if image_width % tile_kind.dimensions().width != 0 {
+            return Err(LoadError::InvalidImageWidthError { tile_kind, image_width })
+        }
+        Ok(tile_kind)
     }
 
     pub fn load_image_file<P: AsRef<Path>>(path: P) -> Result<Self, LoadError> {
-        let image = read_image_file(&path)?;
+        Self::load_image_file_fit(path, None)
+    }
+
+    pub fn load_image_file_fit<P: AsRef<Path>>(path: P, fit: Option<Fit>) -> Result<Self, LoadError> {
+        Self::load_image_file_decoded(path, fit, ImageDecoding::default())
+    }
+
+    pub fn load_image_file_decoded<P: AsRef<Path>>(path: P, fit: Option<Fit>, decoding: ImageDecoding) -> Result<Self, LoadError> {
+        let image = read_rgba_image_file(&path, decoding)?;
+        Self::from_image_fit(path, image, fit)
+    }
+
+    // the path is only used in the messages
+    pub(crate) fn from_image_fit<P: AsRef<Path>>(path: P, image: Image, fit: Option<Fit>) -> Result<Self, LoadError> {
+        Self::from_image_rows(path, image, fit, 1)
+    }
+
+    // the number of rows of tiles cannot be told from the dimensions alone since two rows of SD tiles are as high as
+    // a row of HD tiles, it comes from the file name
+    pub(crate) fn from_image_rows<P: AsRef<Path>>(path: P, image: Image, fit: Option<Fit>, rows: usize) -> Result<Self, LoadError> {
+        let rows = rows.max(1) as u32;
         let (image_width, image_height) = image.dimensions();
-        let tile_kind = TileKind::for_height(image_height)?;
+        let (tile_kind, image) = match (Self::image_tile_kind(image_width, image_height, rows), fit) {
+            (Ok(tile_kind), _) => (tile_kind, image),
+            (Err(error), Some(fit)) => {
+                let tile_kind = TileKind::closest_to_height(image_height / rows);
+                let tile_width = tile_kind.dimensions().width;
+                let columns = ((image_width + tile_width / 2) / tile_width).max(1);
+                let dimensions = ImageDimensions::new(columns * tile_width, rows * tile_kind.dimensions().height);
+                let image = fit_image(&path, &image, dimensions, fit).ok_or(error)?;
+                (tile_kind, image)
+            },
+            (Err(error), None) => return Err(match TileKind::for_scaled_dimensions(ImageDimensions::new(image_width, image_height / rows)) {
+                Ok(Some((tile_kind, scale))) => LoadError::ScaledExportError { tile_kind, scale },
+                Ok(None) => error,
+                Err(error) => LoadError::AmbiguousScaledExportError(error),
+            }),
+        };
         let tile_dimensions = tile_kind.dimensions();
-        if image_width % tile_dimensions.width != 0 {
-            return Err(LoadError::InvalidImageWidthError { tile_kind, image_width })
+        let columns = image.width() / tile_dimensions.width;
+        let mut tiles = Vec::with_capacity((columns * rows) as usize);
+        for row in 0..rows {
+            for column in 0..columns {
+                let (tile_x, tile_y) = (column * tile_dimensions.width, row * tile_dimensions.height);
+                let tile = Tile::try_from(image.view(tile_x, tile_y, tile_dimensions.width, tile_dimensions.height).to_image()).unwrap();
+                tiles.push(tile);
+            }
         }
-        let span = image_width / tile_dimensions.width;
-        let mut tiles = Vec::with_capacity(span as usize);
-        for tile_index in 0..span {
-            let tile_x = tile_index * tile_dimensions.width;
-            let tile = Tile::try_from(image.view(tile_x, 0, tile_dimensions.width, tile_dimensions.height).to_image()).unwrap();
-            tiles.push(tile);
+        Ok(Self { tile_kind, columns: columns as usize, tiles })
+    }
+
+    // the tiles are arranged in rows of `columns` tiles, the tile count has to be a multiple of the columns
+    pub fn try_from_tiles_columns(tiles: Vec<Tile>, columns: usize) -> Result<Self, TileKindError> {
+        let mut symbol = Self::try_from(tiles)?;
+        if columns > 0 && symbol.tiles.len() % columns == 0 {
+            symbol.columns = columns;
         }
-        Ok(Self { tile_kind, tiles })
+        Ok(symbol)
     }
 
     pub fn span(&self) -> usize {
         self.tiles.len()
     }
 
+    pub fn rows(&self) -> usize {
+        self.tiles.len() / self.columns
+    }
+
     pub fn tiles(&self) -> &Vec<Tile> {
         &self.tiles
     }
@@ -90,15 +166,18 @@ impl Symbol {
     }
 
     pub fn image_dimensions(&self) -> ImageDimensions {
-        ImageDimensions { width: self.span() as u32 * self.tile_kind.dimensions().width, height: self.tile_kind.dimensions().height }
+        let tile_dimensions = self.tile_kind.dimensions();
+        ImageDimensions { width: self.columns as u32 * tile_dimensions.width, height: self.rows() as u32 * tile_dimensions.height }
     }
 
     pub fn generate_image(&self) -> Image {
         let mut image = Image::new(self.image_dimensions().width, self.image_dimensions().height);
+        let tile_dimensions = self.tile_kind.dimensions();
 
         for (index, tile) in self.tiles.iter().enumerate() {
-            let x = index as u32 * self.tile_kind.dimensions().width;
-            image.copy_from(tile.image(), x, 0).unwrap();
+            let x = (index % self.columns) as u32 * tile_dimensions.width;
+            let y = (index / self.columns) as u32 * tile_dimensions.height;
+            image.copy_from(tile.image(), x, y).unwrap();
         }
 
         image
@@ -111,12 +190,12 @@ impl TryFrom<Vec<Tile>> for Symbol {
 
     fn try_from(tiles: Vec<Tile>) -> Result<Self, Self::Error> {
         let tile_kind = tiles.tile_kind()?;
-        Ok(Self { tile_kind, tiles })
+        Ok(Self { tile_kind, columns: tiles.len().max(1), tiles })
     }
 }
 
 impl From<Tile> for Symbol {
     fn from(tile: Tile) -> Self {
-        Self { tile_kind: tile.kind(), tiles: vec![tile] }
+        Self { tile_kind: tile.kind(), columns: 1, tiles: vec![tile] }
     }
 }
\ No newline at end of file
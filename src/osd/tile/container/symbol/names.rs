@@ -0,0 +1,210 @@
+
+use clap::ValueEnum;
+use strum::Display;
+
+
+// Built-in tables of the symbol names used in the OSD code of the flight controller firmwares, used to name the files
+// of symbol directories e.g. 001_SYM_RSSI.png
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Display, ValueEnum)]
+#[strum(serialize_all = "lowercase")]
+pub enum SymbolNames {
+    Betaflight,
+    Inav,
+    Ardupilot,
+}
+
+// (start tile index, span, name), the symbols spanning several tiles match the bundled specs files
+type NameTable = &'static [(usize, usize, &'static str)];
+
+const BETAFLIGHT: NameTable = &[
+    (0x01, 1, "SYM_RSSI"),
+    (0x04, 1, "SYM_THR"),
+    (0x05, 1, "SYM_OVER_HOME"),
+    (0x06, 1, "SYM_VOLT"),
+    (0x07, 1, "SYM_MAH"),
+    (0x08, 1, "SYM_STICK_OVERLAY_SPRITE_HIGH"),
+    (0x09, 1, "SYM_STICK_OVERLAY_SPRITE_MID"),
+    (0x0A, 1, "SYM_STICK_OVERLAY_SPRITE_LOW"),
+    (0x0B, 1, "SYM_STICK_OVERLAY_CENTER"),
+    (0x0C, 1, "SYM_M"),
+    (0x0D, 1, "SYM_F"),
+    (0x0E, 1, "SYM_C"),
+    (0x0F, 1, "SYM_FT"),
+    (0x10, 1, "SYM_BBLOG"),
+    (0x11, 1, "SYM_HOMEFLAG"),
+    (0x12, 1, "SYM_RPM"),
+    (0x13, 1, "SYM_AH_DECORATION"),
+    (0x14, 1, "SYM_ROLL"),
+    (0x15, 1, "SYM_PITCH"),
+    (0x16, 1, "SYM_STICK_OVERLAY_VERTICAL"),
+    (0x17, 1, "SYM_STICK_OVERLAY_HORIZONTAL"),
+    (0x18, 1, "SYM_HEADING_N"),
+    (0x19, 1, "SYM_HEADING_S"),
+    (0x1A, 1, "SYM_HEADING_E"),
+    (0x1B, 1, "SYM_HEADING_W"),
+    (0x1C, 1, "SYM_HEADING_DIVIDED_LINE"),
+    (0x1D, 1, "SYM_HEADING_LINE"),
+    (0x1E, 1, "SYM_SAT_L"),
+    (0x1F, 1, "SYM_SAT_R"),
+    (0x1E, 2, "SYM_SAT"),
+    (0x57, 1, "SYM_WATT"),
+    (0x60, 1, "SYM_ARROW_SOUTH"),
+    (0x61, 1, "SYM_ARROW_2"),
+    (0x62, 1, "SYM_ARROW_3"),
+    (0x63, 1, "SYM_ARROW_4"),
+    (0x64, 1, "SYM_ARROW_EAST"),
+    (0x65, 1, "SYM_ARROW_6"),
+    (0x66, 1, "SYM_ARROW_7"),
+    (0x67, 1, "SYM_ARROW_8"),
+    (0x68, 1, "SYM_ARROW_NORTH"),
+    (0x69, 1, "SYM_ARROW_10"),
+    (0x6A, 1, "SYM_ARROW_11"),
+    (0x6B, 1, "SYM_ARROW_12"),
+    (0x6C, 1, "SYM_ARROW_WEST"),
+    (0x6D, 1, "SYM_ARROW_14"),
+    (0x6E, 1, "SYM_ARROW_15"),
+    (0x6F, 1, "SYM_ARROW_16"),
+    (0x70, 1, "SYM_SPEED"),
+    (0x71, 1, "SYM_TOTAL_DISTANCE"),
+    (0x72, 1, "SYM_AH_CENTER_LINE"),
+    (0x73, 1, "SYM_AH_CENTER"),
+    (0x74, 1, "SYM_AH_CENTER_LINE_RIGHT"),
+    (0x7A, 1, "SYM_TEMPERATURE"),
+    (0x7B, 1, "SYM_LINK_QUALITY"),
+    (0x7D, 1, "SYM_KM"),
+    (0x7E, 1, "SYM_MILES"),
+    (0x7F, 1, "SYM_ALTITUDE"),
+    (0x80, 1, "SYM_AH_BAR9_0"),
+    (0x81, 1, "SYM_AH_BAR9_1"),
+    (0x82, 1, "SYM_AH_BAR9_2"),
+    (0x83, 1, "SYM_AH_BAR9_3"),
+    (0x84, 1, "SYM_AH_BAR9_4"),
+    (0x85, 1, "SYM_AH_BAR9_5"),
+    (0x86, 1, "SYM_AH_BAR9_6"),
+    (0x87, 1, "SYM_AH_BAR9_7"),
+    (0x88, 1, "SYM_AH_BAR9_8"),
+    (0x89, 1, "SYM_LAT"),
+    (0x8A, 1, "SYM_PB_START"),
+    (0x8B, 1, "SYM_PB_FULL"),
+    (0x8C, 1, "SYM_PB_HALF"),
+    (0x8D, 1, "SYM_PB_EMPTY"),
+    (0x8E, 1, "SYM_PB_END"),
+    (0x8F, 1, "SYM_PB_CLOSE"),
+    (0x90, 1, "SYM_BATT_FULL"),
+    (0x91, 1, "SYM_BATT_5"),
+    (0x92, 1, "SYM_BATT_4"),
+    (0x93, 1, "SYM_BATT_3"),
+    (0x94, 1, "SYM_BATT_2"),
+    (0x95, 1, "SYM_BATT_1"),
+    (0x96, 1, "SYM_BATT_EMPTY"),
+    (0x97, 1, "SYM_MAIN_BATT"),
+    (0x98, 1, "SYM_LON"),
+    (0x99, 1, "SYM_FTPS"),
+    (0x9A, 1, "SYM_AMP"),
+    (0x9B, 1, "SYM_ON_M"),
+    (0x9C, 1, "SYM_FLY_M"),
+    (0x9D, 1, "SYM_MPH"),
+    (0x9E, 1, "SYM_KPH"),
+    (0x9F, 1, "SYM_MPS"),
+];
+
+const INAV: NameTable = &[
+    (0x01, 1, "SYM_RSSI"),
+    (0x02, 1, "SYM_LQ"),
+    (0x03, 1, "SYM_LAT"),
+    (0x04, 1, "SYM_LON"),
+    (0x05, 1, "SYM_AZIMUTH"),
+    (0x06, 1, "SYM_TELEMETRY_0"),
+    (0x07, 1, "SYM_TELEMETRY_1"),
+    (0x08, 1, "SYM_SAT_L"),
+    (0x09, 1, "SYM_SAT_R"),
+    (0x08, 2, "SYM_SAT"),
+    (0x0A, 1, "SYM_HOME_NEAR"),
+    (0x0B, 1, "SYM_DEGREES"),
+    (0x0C, 1, "SYM_HEADING"),
+    (0x0D, 1, "SYM_SCALE"),
+    (0x0E, 1, "SYM_HDP_L"),
+    (0x0F, 1, "SYM_HDP_R"),
+    (0x0E, 2, "SYM_HDP"),
+    (0x10, 1, "SYM_HOME"),
+    (0x11, 1, "SYM_2RSS"),
+    (0x12, 1, "SYM_DB"),
+    (0x13, 1, "SYM_DBM"),
+    (0x14, 1, "SYM_SNR"),
+    (0x15, 1, "SYM_AH_DECORATION_UP"),
+    (0x16, 1, "SYM_AH_DECORATION_DOWN"),
+    (0x17, 1, "SYM_DIRECTION"),
+    (0x1F, 1, "SYM_VOLT"),
+    (0x60, 2, "SYM_MAH_NM"),
+    (0x6B, 2, "SYM_MAH_KM"),
+    (0x93, 2, "SYM_MAH_MI"),
+    (0xAB, 2, "SYM_AUTO_THR"),
+    (0x190, 3, "SYM_AH_CH_LEFT"),
+    (0x193, 3, "SYM_AH_CH_AIRCRAFT1"),
+    (0x196, 3, "SYM_AH_CH_AIRCRAFT2"),
+    (0x199, 3, "SYM_AH_CH_AIRCRAFT3"),
+    (0x19C, 3, "SYM_AH_CH_AIRCRAFT4"),
+    (0x19F, 3, "SYM_AH_CH_TYPE6"),
+    (0x1A2, 5, "SYM_AH_CH_TYPE7"),
+];
+
+const ARDUPILOT: NameTable = &[
+    (0x01, 1, "SYM_RSSI"),
+    (0x06, 1, "SYM_VOLT"),
+    (0x07, 1, "SYM_MAH"),
+    (0x1E, 1, "SYM_SAT_L"),
+    (0x1F, 1, "SYM_SAT_R"),
+    (0x1E, 2, "SYM_SAT"),
+    (0x60, 1, "SYM_ARROW_START"),
+    (0x80, 1, "SYM_AH_H_START"),
+    (0x90, 1, "SYM_BATT_FULL"),
+    (0x9A, 1, "SYM_AMP"),
+    (0x9D, 2, "SYM_MAH_KM"),
+    (0x9F, 1, "SYM_MS"),
+    (0xA1, 1, "SYM_KMH"),
+    (0xA8, 1, "SYM_DEGR"),
+    (0xAC, 2, "SYM_WH_KM"),
+    (0xB1, 1, "SYM_ALT_M"),
+    (0xB3, 1, "SYM_ALT_FT"),
+    (0xB9, 1, "SYM_M"),
+    (0xBA, 1, "SYM_KM"),
+    (0xBB, 1, "SYM_MI"),
+    (0xBD, 1, "SYM_HDOP_L"),
+    (0xBE, 1, "SYM_HDOP_R"),
+    (0xBF, 1, "SYM_HOME"),
+];
+
+impl SymbolNames {
+
+    fn table(&self) -> NameTable {
+        match self {
+            SymbolNames::Betaflight => BETAFLIGHT,
+            SymbolNames::Inav => INAV,
+            SymbolNames::Ardupilot => ARDUPILOT,
+        }
+    }
+
+    // name of the symbol starting at `start_tile_index` and spanning `span` tiles, none for the symbols without a name
+    pub fn name(&self, start_tile_index: usize, span: usize) -> Option<&'static str> {
+        self.table().iter()
+            .find(|(entry_start_tile_index, entry_span, _)| *entry_start_tile_index == start_tile_index && *entry_span == span)
+            .map(|(_, _, name)| *name)
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::SymbolNames;
+
+    #[test]
+    fn name() {
+        assert_eq!(SymbolNames::Betaflight.name(0x01, 1), Some("SYM_RSSI"));
+        assert_eq!(SymbolNames::Betaflight.name(0x68, 1), Some("SYM_ARROW_NORTH"));
+        assert_eq!(SymbolNames::Inav.name(0x08, 2), Some("SYM_SAT"));
+        assert_eq!(SymbolNames::Inav.name(0x08, 1), Some("SYM_SAT_L"));
+        assert_eq!(SymbolNames::Ardupilot.name(0x41, 1), None);
+    }
+
+}
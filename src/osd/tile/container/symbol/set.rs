@@ -6,18 +6,30 @@ use derive_more::{From, Display, Error};
 use getset::Getters;
 use strum::IntoEnumIterator;
 
-use crate::osd::tile::Kind as TileKind;
+use crate::image_format::{ImageDecoding, ImageFormat};
+use crate::osd::tile::{Kind as TileKind, fit::Fit};
+#[cfg(feature = "fs")]
+use crate::image::encode_image;
+#[cfg(feature = "fs")]
+use crate::osd::tile::container::archive::{Archive, ArchiveError, SaveToArchiveError};
 use crate::osd::tile::container::load_symbols_from_dir::{load_symbols_from_dir, LoadSymbolsFromDirError};
+#[cfg(feature = "fs")]
+use crate::osd::tile::container::load_symbols_from_dir::load_symbols_from_files;
 use crate::osd::tile::container::save_symbols_to_dir::SaveSymbolsToDirError;
+use crate::osd::tile::container::split_symbols::SplitSymbolsToDir;
+#[cfg(feature = "fs")]
+use crate::osd::tile::container::save_symbols_to_dir::symbol_file_stem;
 use crate::osd::tile::container::uniq_tile_kind::{UniqTileKind, TileKindError};
 use crate::prelude::SaveSymbolsToDir;
-use super::Symbol;
+use super::{Symbol, names::SymbolNames, spec::Specs};
 
 
 #[derive(Debug, Error, Display, From)]
 pub enum LoadFromDirError {
     LoadSymbolsFromDirError(LoadSymbolsFromDirError),
     TileKindError(TileKindError),
+    #[cfg(feature = "fs")]
+    ArchiveError(ArchiveError),
 }
 
 #[derive(Getters)]
@@ -43,19 +55,60 @@ impl Set {
         Ok(Self { sd_symbols, hd_symbols })
     }
 
-    pub fn save_to_dir<P: AsRef<Path>>(&self, dir: P) -> Result<(), SaveSymbolsToDirError> {
+    pub fn save_to_dir<P: AsRef<Path>>(&self, dir: P, image_format: ImageFormat) -> Result<(), SaveSymbolsToDirError> {
+        self.save_to_dir_scaled(dir, image_format, 1)
+    }
+
+    pub fn save_to_dir_scaled<P: AsRef<Path>>(&self, dir: P, image_format: ImageFormat, scale: u32) -> Result<(), SaveSymbolsToDirError> {
+        self.save_to_dir_named(dir, image_format, scale, None)
+    }
+
+    pub fn save_to_dir_named<P: AsRef<Path>>(&self, dir: P, image_format: ImageFormat, scale: u32, names: Option<SymbolNames>) -> Result<(), SaveSymbolsToDirError> {
+        for tile_kind in TileKind::iter() {
+            self[tile_kind].save_to_dir_named(tile_kind.set_dir_path(&dir), image_format, scale, names)?;
+        }
+        Ok(())
+    }
+
+    // written as a tile set directory, see `SplitSymbolsToDir`
+    pub fn split_to_dir<P: AsRef<Path>>(&self, dir: P, image_format: ImageFormat, specs: Option<&Specs>, names: Option<SymbolNames>) -> Result<(), SaveSymbolsToDirError> {
         for tile_kind in TileKind::iter() {
-            self[tile_kind].save_to_dir(tile_kind.set_dir_path(&dir))?;
+            self[tile_kind].split_to_dir(tile_kind.set_dir_path(&dir), image_format, specs, names)?;
         }
         Ok(())
     }
 
-    pub fn load_from_dir<P: AsRef<Path>>(dir_path: P, max_symbols: usize) -> Result<Self, LoadFromDirError> {
-        let sd_symbols = load_symbols_from_dir(TileKind::SD.set_dir_path(&dir_path), max_symbols)?;
-        let hd_symbols = load_symbols_from_dir(TileKind::HD.set_dir_path(&dir_path), max_symbols)?;
+    pub fn load_from_dir<P: AsRef<Path>>(dir_path: P, max_symbols: usize, fit: Option<Fit>, decoding: ImageDecoding) -> Result<Self, LoadFromDirError> {
+        let sd_symbols = load_symbols_from_dir(TileKind::SD.set_dir_path(&dir_path), max_symbols, fit, decoding)?;
+        let hd_symbols = load_symbols_from_dir(TileKind::HD.set_dir_path(&dir_path), max_symbols, fit, decoding)?;
+        Ok(Self::try_from_symbols(sd_symbols, hd_symbols)?)
+    }
+
+    // zip or tar.gz archive with the structure of a symbol set directory, the images are decoded from memory
+    #[cfg(feature = "fs")]
+    pub fn load_from_archive<P: AsRef<Path>>(path: P, max_symbols: usize, fit: Option<Fit>, decoding: ImageDecoding) -> Result<Self, LoadFromDirError> {
+        let archive = Archive::load_file(path)?;
+        let sd_symbols = load_symbols_from_files(&archive.dir_image_files(TileKind::SD.set_dir_name()), max_symbols, fit, decoding)?;
+        let hd_symbols = load_symbols_from_files(&archive.dir_image_files(TileKind::HD.set_dir_name()), max_symbols, fit, decoding)?;
         Ok(Self::try_from_symbols(sd_symbols, hd_symbols)?)
     }
 
+    #[cfg(feature = "fs")]
+    pub fn save_to_archive<P: AsRef<Path>>(&self, path: P, image_format: ImageFormat, names: Option<SymbolNames>) -> Result<(), SaveToArchiveError> {
+        let mut archive = Archive::new(&path);
+        for tile_kind in TileKind::iter() {
+            let dir = Path::new(tile_kind.set_dir_name());
+            let mut tile_index = 0;
+            for symbol in &self[tile_kind] {
+                let file_path = image_format.file_path(dir, &symbol_file_stem(tile_index, symbol, names));
+                let content = encode_image(&symbol.generate_image(), archive.path().join(&file_path), image_format)?;
+                archive.insert(file_path, content);
+                tile_index += symbol.span();
+            }
+        }
+        Ok(archive.save_file()?)
+    }
+
 }
 
 impl Index<TileKind> for Set {
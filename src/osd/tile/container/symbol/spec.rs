@@ -1,6 +1,7 @@
 
 use std::{
     collections::HashMap,
+    fmt::Display,
     io::Error as IOError,
     ops::Range,
     path::{
@@ -10,25 +11,43 @@ use std::{
 };
 
 use derive_more::{From, Deref};
-use getset::CopyGetters;
+use getset::{CopyGetters, Getters};
 use parse_int::parse;
 use regex::Regex;
 use lazy_static::lazy_static;
+use serde::{de::DeserializeOwned, ser::SerializeMap, Deserialize, Serialize, Serializer};
 use thiserror::Error;
-use fs_err::File;
 
+use crate::atomic_file;
+use crate::osd::tile::{Tile, Kind as TileKind, container::stats::is_blank};
+use super::Symbol;
 
-#[derive(Debug, CopyGetters)]
-#[getset(get_copy = "pub")]
+
+#[derive(Debug, Getters, CopyGetters)]
 pub struct Spec {
+    #[getset(get = "pub")]
+    name: String,
+    #[getset(get_copy = "pub")]
     start_tile_index: usize,
-    span: usize
+    #[getset(get_copy = "pub")]
+    span: usize,
+    // rows of tiles of the symbol image, the span is a multiple of it
+    #[getset(get_copy = "pub")]
+    rows: usize,
 }
 
 impl Spec {
 
-    pub fn new(start_tile_index: usize, span: usize) -> Self {
-        Self { start_tile_index, span }
+    pub fn new(name: &str, start_tile_index: usize, span: usize) -> Self {
+        Self::new_rows(name, start_tile_index, span, 1)
+    }
+
+    pub fn new_rows(name: &str, start_tile_index: usize, span: usize, rows: usize) -> Self {
+        Self { name: name.to_owned(), start_tile_index, span, rows }
+    }
+
+    pub fn columns(&self) -> usize {
+        self.span / self.rows
     }
 
     pub fn end_tile_index(&self) -> usize {
@@ -41,35 +60,308 @@ impl Spec {
 
 }
 
+// Specs files map the symbol names to `<start tile index>:<span>` strings, the start tile index can be hexadecimal
+// e.g. `GPS: '0x1E:2'`, or to tables with `start` and `span` keys e.g. `GPS = { start = 30, span = 2 }` in TOML.
+// Symbols spanning several rows of tiles are given as `<start tile index>:<columns>x<rows>` e.g. `logo: '0x100:2x2'`
+// or with a `rows` key in tables, the tiles still have consecutive indexes.
+// The `include` key lists other specs files to load first.
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum TableIndex {
+    Number(usize),
+    Text(String),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum FileEntry {
+    Spec(String),
+    Table { start: TableIndex, span: usize, rows: Option<usize> },
+    Include(Vec<PathBuf>),
+}
+
+const INCLUDE_KEY: &str = "include";
+
+// format of a specs file detected from its extension, any other extension is YAML
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FileFormat {
+    Yaml,
+    Json,
+    Toml,
+}
+
+impl FileFormat {
+
+    pub fn for_path<P: AsRef<Path>>(path: P) -> Self {
+        let extension = path.as_ref().extension().and_then(|extension| extension.to_str()).map(str::to_ascii_lowercase);
+        match extension.as_deref() {
+            Some("json") => Self::Json,
+            Some("toml") => Self::Toml,
+            _ => Self::Yaml,
+        }
+    }
+
+    fn parse<T: DeserializeOwned>(&self, content: &str) -> Result<T, String> {
+        match self {
+            Self::Yaml => serde_yaml::from_str(content).map_err(|error| error.to_string()),
+            Self::Json => serde_json::from_str(content).map_err(|error| error.to_string()),
+            Self::Toml => toml::from_str(content).map_err(|error| error.to_string()),
+        }
+    }
+
+    fn serialize<T: Serialize>(&self, value: &T) -> Result<String, String> {
+        match self {
+            Self::Yaml => serde_yaml::to_string(value).map_err(|error| error.to_string()),
+            Self::Json => serde_json::to_string_pretty(value).map(|content| content + "\n").map_err(|error| error.to_string()),
+            Self::Toml => toml::to_string(value).map_err(|error| error.to_string()),
+        }
+    }
+
+}
+
+// the specs are written in tile order as `<hexadecimal start tile index>:<span>` strings
+// or `<hexadecimal start tile index>:<columns>x<rows>` for the symbols spanning several rows
+struct FileContent<'a>(&'a [Spec]);
+
+impl<'a> Serialize for FileContent<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for spec in self.0 {
+            let span = match spec.rows {
+                1 => spec.span.to_string(),
+                rows => format!("{}x{rows}", spec.columns()),
+            };
+            map.serialize_entry(&spec.name, &format!("0x{:02X}:{span}", spec.start_tile_index))?;
+        }
+        map.end()
+    }
+}
+
+// spec with the file it was loaded from so that the conflicts can name both files
+#[derive(Debug)]
+struct SourcedSpec {
+    spec: Spec,
+    file_path: PathBuf,
+}
+
+impl SourcedSpec {
+    fn overlaps(&self, other: &Self) -> bool {
+        self.spec.start_tile_index < other.spec.end_tile_index() && other.spec.start_tile_index < self.spec.end_tile_index()
+    }
+}
+
+// `including` holds the files being loaded down the include chain to detect include cycles
+fn load_sourced_specs(path: &Path, including: &mut Vec<PathBuf>) -> Result<Vec<SourcedSpec>, LoadSpecsFileError> {
+    let canonical_path = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    if including.contains(&canonical_path) {
+        return Err(LoadSpecsFileError::IncludeCycle { file_path: path.to_path_buf() });
+    }
+    let file_content: HashMap<String, FileEntry> = FileFormat::for_path(path).parse(&fs_err::read_to_string(path)?)
+        .map_err(|error| LoadSpecsFileError::file_structure(path, error))?;
+    lazy_static! {
+        static ref SPEC_RE: Regex = Regex::new(r"\A(?P<start_tile_index>0x[\da-zA-Z]+|\d+):(?P<span>\d+)(?:x(?P<rows>\d+))?\z").unwrap();
+    }
+    let mut includes = vec![];
+    let mut own_specs = Vec::with_capacity(file_content.len());
+    for (symbol_name, entry) in file_content {
+        let (spec, start_tile_index, span, rows) = match entry {
+            FileEntry::Include(include_paths) if symbol_name == INCLUDE_KEY => {
+                includes = include_paths;
+                continue
+            },
+            FileEntry::Include(paths) => {
+                let paths = paths.iter().map(|path| path.to_string_lossy()).collect::<Vec<_>>().join(", ");
+                return Err(LoadSpecsFileError::invalid_symbol_spec(path, &symbol_name, &format!("[{paths}]")))
+            },
+            FileEntry::Spec(spec) => {
+                let captures = SPEC_RE.captures(&spec);
+                let start_tile_index = captures.as_ref().and_then(|captures| parse::<usize>(&captures["start_tile_index"]).ok());
+                let span = captures.as_ref().and_then(|captures| parse::<usize>(&captures["span"]).ok());
+                // the span of the <columns>x<rows> form is the number of columns
+                let rows = match captures.as_ref().and_then(|captures| captures.name("rows")) {
+                    Some(rows) => rows.as_str().parse::<usize>().ok(),
+                    None => Some(1),
+                };
+                let span = span.zip(rows).and_then(|(columns, rows)| columns.checked_mul(rows));
+                (spec, start_tile_index, span, rows)
+            },
+            FileEntry::Table { start, span, rows } => {
+                let (start_text, start_tile_index) = match start {
+                    TableIndex::Number(index) => (index.to_string(), Some(index)),
+                    TableIndex::Text(text) => (text.clone(), parse::<usize>(&text).ok()),
+                };
+                (format!("{start_text}:{span}"), start_tile_index, Some(span), Some(rows.unwrap_or(1)))
+            },
+        };
+        let invalid_symbol_spec = || LoadSpecsFileError::invalid_symbol_spec(path, &symbol_name, &spec);
+        let ((start_tile_index, span), rows) = start_tile_index.zip(span).zip(rows).ok_or_else(invalid_symbol_spec)?;
+        // an empty span would never move past the start tile when assembling the symbols
+        if span == 0 || rows == 0 || span % rows != 0 || start_tile_index.checked_add(span).is_none() {
+            return Err(invalid_symbol_spec());
+        }
+        own_specs.push(SourcedSpec { spec: Spec::new_rows(&symbol_name, start_tile_index, span, rows), file_path: path.to_path_buf() });
+    }
+
+    let mut merged = vec![];
+    if !includes.is_empty() {
+        including.push(canonical_path);
+        let base_dir = path.parent().unwrap_or_else(|| Path::new(""));
+        for include_path in includes {
+            merged = merge_sourced_specs(merged, load_sourced_specs(&base_dir.join(include_path), including)?)?;
+        }
+        including.pop();
+    }
+    merge_sourced_specs(merged, own_specs)
+}
+
+// the specs of `layer` override the previous ones covering exactly the same tiles
+fn merge_sourced_specs(previous: Vec<SourcedSpec>, layer: Vec<SourcedSpec>) -> Result<Vec<SourcedSpec>, LoadSpecsFileError> {
+    let mut merged = Vec::with_capacity(previous.len() + layer.len());
+    for previous_spec in previous {
+        let mut overriding_spec = None;
+        for layer_spec in layer.iter().filter(|layer_spec| layer_spec.overlaps(&previous_spec)) {
+            if layer_spec.spec.tile_index_range() != previous_spec.spec.tile_index_range() {
+                return Err(LoadSpecsFileError::conflicting_symbol_specs(&previous_spec, layer_spec));
+            }
+            overriding_spec = Some(layer_spec);
+        }
+        match overriding_spec {
+            Some(overriding_spec) => log::warn!("symbol {} of {} overrides symbol {} of {}",
+                overriding_spec.spec.name, overriding_spec.file_path.to_string_lossy(),
+                previous_spec.spec.name, previous_spec.file_path.to_string_lossy()),
+            None => merged.push(previous_spec),
+        }
+    }
+    merged.extend(layer);
+    Ok(merged)
+}
+
 #[derive(Debug, Deref)]
 pub struct Specs(Vec<Spec>);
 
 impl Specs {
 
+    // an `include` list of paths relative to the file loads other specs files first, the file's own specs
+    // are then merged over them the same way as with `load_files`
     pub fn load_file<P: AsRef<Path>>(path: P) -> Result<Self, LoadSpecsFileError> {
-        let file_content: HashMap<String, String> = serde_yaml::from_reader(File::open(&path)?)
-            .map_err(|error| LoadSpecsFileError::file_structure(&path, error))?;
-        lazy_static! {
-            static ref SPEC_RE: Regex = Regex::new(r"\A(?P<start_tile_index>0x[\da-zA-Z]+|\d+):(?P<span>\d+)\z").unwrap();
-        }
-        let mut spec_vec = Vec::with_capacity(file_content.len());
-        for (symbol_name, spec) in file_content {
-            match SPEC_RE.captures(&spec) {
-                Some(captures) => {
-                    let (start_tile_index, span) = (captures.name("start_tile_index").unwrap(), captures.name("span").unwrap());
-                    let spec = Spec::new(parse(start_tile_index.as_str()).unwrap(), parse(span.as_str()).unwrap());
-                    spec_vec.push(spec);
-                },
-                None => return Err(LoadSpecsFileError::invalid_symbol_spec(&path, &symbol_name, &spec)),
+        Self::load_file_tile_count(path, None)
+    }
+
+    // the specs going beyond the tile count are also conflicts
+    pub fn load_file_tile_count<P: AsRef<Path>>(path: P, tile_count: Option<usize>) -> Result<Self, LoadSpecsFileError> {
+        Self::checked(Self::from_sourced(load_sourced_specs(path.as_ref(), &mut vec![])?), tile_count)
+    }
+
+    // later files override the specs of earlier ones covering exactly the same tiles,
+    // specs partially overlapping the ones of an earlier file are an error
+    pub fn load_files<P: AsRef<Path>>(paths: &[P]) -> Result<Self, LoadSpecsFileError> {
+        Self::load_files_tile_count(paths, None)
+    }
+
+    pub fn load_files_tile_count<P: AsRef<Path>>(paths: &[P], tile_count: Option<usize>) -> Result<Self, LoadSpecsFileError> {
+        let mut merged = vec![];
+        for path in paths {
+            merged = merge_sourced_specs(merged, load_sourced_specs(path.as_ref(), &mut vec![])?)?;
+        }
+        Self::checked(Self::from_sourced(merged), tile_count)
+    }
+
+    fn checked(specs: Self, tile_count: Option<usize>) -> Result<Self, LoadSpecsFileError> {
+        ConflictingSpecsError::check(specs.conflicts(tile_count))?;
+        Ok(specs)
+    }
+
+    // every overlap of index ranges, symbol name used more than once and spec going beyond the tile count when given,
+    // the conflicts come in tile order
+    pub fn conflicts(&self, tile_count: Option<usize>) -> Vec<SpecConflict> {
+        let mut sorted_specs: Vec<&Spec> = self.iter().collect();
+        sorted_specs.sort_by_key(|spec| spec.start_tile_index);
+        let mut conflicts = vec![];
+        for (index, spec) in sorted_specs.iter().enumerate() {
+            for other in sorted_specs[index + 1..].iter().take_while(|other| other.start_tile_index < spec.end_tile_index()) {
+                conflicts.push(SpecConflict::Overlap { name: spec.name.clone(), other_name: other.name.clone() });
+            }
+            if let Some(other) = sorted_specs[..index].iter().find(|other| other.name == spec.name) {
+                conflicts.push(SpecConflict::DuplicateName {
+                    name: spec.name.clone(),
+                    start_tile_index: other.start_tile_index,
+                    other_start_tile_index: spec.start_tile_index,
+                });
+            }
+            if let Some(tile_count) = tile_count {
+                if spec.end_tile_index() > tile_count {
+                    conflicts.push(SpecConflict::BeyondTileCount { name: spec.name.clone(), end_tile_index: spec.end_tile_index(), tile_count });
+                }
+            }
+        }
+        conflicts
+    }
+
+    fn from_sourced(sourced_specs: Vec<SourcedSpec>) -> Self {
+        let mut spec_vec: Vec<Spec> = sourced_specs.into_iter().map(|sourced_spec| sourced_spec.spec).collect();
+        spec_vec.sort_by_key(Spec::start_tile_index);
+        spec_vec.into()
+    }
+
+    // specs of the symbols spanning several tiles, named from their start tile index e.g. symbol_030 since the symbol
+    // directories do not keep the names
+    pub fn from_symbols(symbols: &[Symbol]) -> Self {
+        let mut specs = vec![];
+        let mut tile_index = 0;
+        for symbol in symbols {
+            if symbol.span() > 1 {
+                specs.push(Spec::new_rows(&format!("symbol_{tile_index:03}"), tile_index, symbol.span(), symbol.rows()));
             }
+            tile_index += symbol.span();
         }
-        Ok(spec_vec.into())
+        Self(specs)
+    }
+
+    // the format follows the extension of the file as when loading
+    pub fn save_file<P: AsRef<Path>>(&self, path: P) -> Result<(), SaveSpecsFileError> {
+        let path = path.as_ref();
+        let content = FileFormat::for_path(path).serialize(&FileContent(&self.0))
+            .map_err(|error| SaveSpecsFileError::Serialize { file_path: path.to_path_buf(), error })?;
+        atomic_file::write(path, content)?;
+        Ok(())
     }
 
     pub fn find_start_index(&self, start_tile_index: usize) -> Option<&Spec> {
         self.iter().find(|sym_spec| sym_spec.start_tile_index() == start_tile_index)
     }
 
+    // checks every spec against the tiles the symbols are assembled from
+    pub fn check_tiles(&self, tiles: &[Tile], tile_kind: TileKind) -> Vec<SpecDiagnostic> {
+        self.iter().filter_map(|spec| {
+            let problem = if spec.end_tile_index() > tiles.len() {
+                SpecProblem::OutOfRange { tile_count: tiles.len() }
+            } else if tiles[spec.tile_index_range()].iter().all(is_blank) {
+                SpecProblem::AllBlank
+            } else {
+                return None
+            };
+            Some(SpecDiagnostic::new(spec, tile_kind, problem))
+        }).collect()
+    }
+
+    // checks the symbols loaded back from a symbol directory against the specs
+    pub fn check_symbols(&self, symbols: &[Symbol]) -> Vec<SpecDiagnostic> {
+        let mut diagnostics = vec![];
+        let mut tile_index = 0;
+        for symbol in symbols {
+            if let Some(spec) = self.find_start_index(tile_index) {
+                if symbol.span() != spec.span() {
+                    diagnostics.push(SpecDiagnostic::new(spec, symbol.tile_kind(), SpecProblem::WidthMismatch { image_span: symbol.span() }));
+                } else if symbol.rows() != spec.rows() {
+                    diagnostics.push(SpecDiagnostic::new(spec, symbol.tile_kind(), SpecProblem::RowsMismatch { image_rows: symbol.rows() }));
+                }
+            }
+            tile_index += symbol.span();
+        }
+        diagnostics
+    }
+
 }
 
 impl From<Vec<Spec>> for Specs {
@@ -78,22 +370,302 @@ impl From<Vec<Spec>> for Specs {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpecProblem {
+    // the span runs past the end of the tiles
+    OutOfRange { tile_count: usize },
+    // every tile of the span is blank
+    AllBlank,
+    // the symbol image loaded back does not span the number of tiles of the spec
+    WidthMismatch { image_span: usize },
+    // the symbol image loaded back spans the tiles of the spec on another number of rows
+    RowsMismatch { image_rows: usize },
+}
+
+impl Display for SpecProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SpecProblem::OutOfRange { tile_count } => write!(f, "out of range of the {tile_count} tiles"),
+            SpecProblem::AllBlank => f.write_str("all the tiles are blank"),
+            SpecProblem::WidthMismatch { image_span } => write!(f, "symbol image spans {image_span} tiles"),
+            SpecProblem::RowsMismatch { image_rows } => write!(f, "symbol image has {image_rows} rows of tiles"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Getters, CopyGetters)]
+pub struct SpecDiagnostic {
+    #[getset(get = "pub")]
+    symbol_name: String,
+    #[getset(get_copy = "pub")]
+    tile_kind: TileKind,
+    #[getset(get_copy = "pub")]
+    start_tile_index: usize,
+    #[getset(get_copy = "pub")]
+    span: usize,
+    #[getset(get = "pub")]
+    problem: SpecProblem,
+}
+
+impl SpecDiagnostic {
+    fn new(spec: &Spec, tile_kind: TileKind, problem: SpecProblem) -> Self {
+        Self { symbol_name: spec.name.clone(), tile_kind, start_tile_index: spec.start_tile_index, span: spec.span, problem }
+    }
+}
+
+impl Display for SpecDiagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} symbol {} starting at tile {} spanning {} tiles: {}", self.tile_kind, self.symbol_name, self.start_tile_index, self.span, self.problem)
+    }
+}
+
+// every problem found while checking the specs, not only the first one
+#[derive(Debug, Error)]
+pub struct InvalidSpecsError(Vec<SpecDiagnostic>);
+
+impl InvalidSpecsError {
+
+    pub fn check(diagnostics: Vec<SpecDiagnostic>) -> Result<(), Self> {
+        match diagnostics.is_empty() {
+            true => Ok(()),
+            false => Err(Self(diagnostics)),
+        }
+    }
+
+    pub fn diagnostics(&self) -> &[SpecDiagnostic] {
+        &self.0
+    }
+
+    pub fn into_diagnostics(self) -> Vec<SpecDiagnostic> {
+        self.0
+    }
+
+    pub fn merge(mut self, other: Self) -> Self {
+        self.0.extend(other.0);
+        self
+    }
+
+}
+
+impl Display for InvalidSpecsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid symbol specs, {} problems:", self.0.len())?;
+        for diagnostic in &self.0 {
+            write!(f, "\n  {diagnostic}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpecConflict {
+    // the index ranges of the two symbols share tiles
+    Overlap { name: String, other_name: String },
+    DuplicateName { name: String, start_tile_index: usize, other_start_tile_index: usize },
+    BeyondTileCount { name: String, end_tile_index: usize, tile_count: usize },
+}
+
+impl Display for SpecConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SpecConflict::Overlap { name, other_name } => write!(f, "symbols {name} and {other_name} overlap"),
+            SpecConflict::DuplicateName { name, start_tile_index, other_start_tile_index } =>
+                write!(f, "symbol name {name} used at tiles {start_tile_index} and {other_start_tile_index}"),
+            SpecConflict::BeyondTileCount { name, end_tile_index, tile_count } =>
+                write!(f, "symbol {name} ends at tile {} beyond the {tile_count} tiles", end_tile_index - 1),
+        }
+    }
+}
+
+// every conflict found between the specs, not only the first one
+#[derive(Debug, Error)]
+pub struct ConflictingSpecsError(Vec<SpecConflict>);
+
+impl ConflictingSpecsError {
+
+    pub fn check(conflicts: Vec<SpecConflict>) -> Result<(), Self> {
+        match conflicts.is_empty() {
+            true => Ok(()),
+            false => Err(Self(conflicts)),
+        }
+    }
+
+    pub fn conflicts(&self) -> &[SpecConflict] {
+        &self.0
+    }
+
+}
+
+impl Display for ConflictingSpecsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "conflicting symbol specs, {} conflicts:", self.0.len())?;
+        for conflict in &self.0 {
+            write!(f, "\n  {conflict}")?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, From, Error)]
 pub enum LoadSpecsFileError {
     #[error("failed to open symbol specs file: {0}")]
     OpenError(IOError),
     #[error("failed to parse symbol specs file {file_path}: {error}")]
-    FileStructureError { file_path: PathBuf, error: serde_yaml::Error },
+    FileStructureError { file_path: PathBuf, error: String },
     #[error("invalid spec for symbol {symbol_name} in file {file_path}: {spec}")]
     InvalidSymbolSpec { file_path: PathBuf, symbol_name: String, spec: String },
+    #[error("symbol {symbol_name} of {file_path} partially overlaps symbol {other_symbol_name} of {other_file_path}")]
+    ConflictingSymbolSpecs { file_path: PathBuf, symbol_name: String, other_file_path: PathBuf, other_symbol_name: String },
+    #[error("symbol specs file {file_path} includes itself")]
+    IncludeCycle { file_path: PathBuf },
+    #[error(transparent)]
+    Conflicts(ConflictingSpecsError),
 }
 
 impl LoadSpecsFileError {
-    pub fn file_structure<P: AsRef<Path>>(file_path: P, error: serde_yaml::Error) -> Self {
-        Self::FileStructureError { file_path: file_path.as_ref().to_path_buf(), error }
+    pub fn file_structure<P: AsRef<Path>, E: Display>(file_path: P, error: E) -> Self {
+        Self::FileStructureError { file_path: file_path.as_ref().to_path_buf(), error: error.to_string() }
     }
 
     pub fn invalid_symbol_spec<P: AsRef<Path>>(file_path: P, symbol_name: &str, spec: &str) -> Self {
         Self::InvalidSymbolSpec { file_path: file_path.as_ref().to_path_buf(), symbol_name: symbol_name.to_owned(), spec: spec.to_owned() }
     }
-}
\ No newline at end of file
+
+    fn conflicting_symbol_specs(previous_spec: &SourcedSpec, spec: &SourcedSpec) -> Self {
+        Self::ConflictingSymbolSpecs {
+            file_path: spec.file_path.clone(),
+            symbol_name: spec.spec.name.clone(),
+            other_file_path: previous_spec.file_path.clone(),
+            other_symbol_name: previous_spec.spec.name.clone(),
+        }
+    }
+}
+
+#[derive(Debug, From, Error)]
+pub enum SaveSpecsFileError {
+    #[error("failed to write symbol specs file: {0}")]
+    FileError(IOError),
+    #[from(ignore)]
+    #[error("failed to serialize symbol specs file {file_path}: {error}")]
+    Serialize { file_path: PathBuf, error: String },
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+    use temp_dir::TempDir;
+
+    use crate::osd::tile::{Tile, Kind as TileKind};
+
+    use super::{FileFormat, LoadSpecsFileError, Spec, SpecConflict, Specs, SpecProblem};
+
+    #[test]
+    fn check_tiles_reports_every_problem() {
+        let mut glyph = Tile::new(TileKind::SD);
+        glyph.put_pixel(10, 10, Rgba([255, 255, 255, 255]));
+        let tiles = vec![glyph.clone(), glyph, Tile::new(TileKind::SD), Tile::new(TileKind::SD)];
+        let specs = Specs::from(vec![Spec::new("ok", 0, 2), Spec::new("blank", 2, 2), Spec::new("too_wide", 3, 4)]);
+
+        let diagnostics = specs.check_tiles(&tiles, TileKind::SD);
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].symbol_name(), "blank");
+        assert_eq!(diagnostics[0].problem(), &SpecProblem::AllBlank);
+        assert_eq!(diagnostics[1].symbol_name(), "too_wide");
+        assert_eq!(diagnostics[1].problem(), &SpecProblem::OutOfRange { tile_count: 4 });
+    }
+
+    #[test]
+    fn merge_specs_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let write = |file_name: &str, content: &str| {
+            let path = temp_dir.child(file_name);
+            std::fs::write(&path, content).unwrap();
+            path
+        };
+        let base = write("base.yaml", "GPS: '0x1E:2'\nhome: '0x05:1'\n");
+        let overlay = write("overlay.yaml", "my_gps: '0x1E:2'\nlogo: '0x100:4'\n");
+        let conflicting = write("conflicting.yaml", "wide_home: '0x04:2'\n");
+        let including = write("including.yaml", "include: [base.yaml]\nmy_gps: '0x1E:2'\n");
+        let cycle = write("cycle.yaml", "include: [cycle.yaml]\n");
+
+        let names = |specs: &Specs| specs.iter().map(|spec| spec.name().clone()).collect::<Vec<_>>();
+        let specs = Specs::load_files(&[&base, &overlay]).unwrap();
+        assert_eq!(names(&specs), vec!["home", "my_gps", "logo"]);
+        assert_eq!(names(&Specs::load_file(&including).unwrap()), vec!["home", "my_gps"]);
+
+        match Specs::load_files(&[&base, &conflicting]) {
+            Err(LoadSpecsFileError::ConflictingSymbolSpecs { file_path, other_file_path, .. }) => {
+                assert_eq!((file_path, other_file_path), (conflicting, base));
+            },
+            result => panic!("unexpected result: {result:?}"),
+        }
+        assert!(matches!(Specs::load_file(&cycle), Err(LoadSpecsFileError::IncludeCycle { .. })));
+    }
+
+    #[test]
+    fn specs_file_formats() {
+        let temp_dir = TempDir::new().unwrap();
+        let toml_path = temp_dir.child("specs.toml");
+        std::fs::write(&toml_path, "GPS = '0x1E:2'\nhome = { start = 5, span = 1 }\nlogo = { start = '0x100', span = 4 }\n").unwrap();
+        let specs = Specs::load_file(&toml_path).unwrap();
+        let summary = |specs: &Specs| specs.iter().map(|spec| (spec.name().clone(), spec.start_tile_index(), spec.span())).collect::<Vec<_>>();
+        let expected = vec![("home".to_owned(), 5, 1), ("GPS".to_owned(), 0x1E, 2), ("logo".to_owned(), 0x100, 4)];
+        assert_eq!(summary(&specs), expected);
+
+        for file_name in ["specs.json", "specs.yaml", "specs.toml"] {
+            let path = temp_dir.child(file_name);
+            specs.save_file(&path).unwrap();
+            assert_eq!(summary(&Specs::load_file(&path).unwrap()), expected);
+        }
+        assert!(std::fs::read_to_string(temp_dir.child("specs.json")).unwrap().contains("\"GPS\": \"0x1E:2\""));
+        assert_eq!(FileFormat::for_path("sym_specs.yml"), FileFormat::Yaml);
+
+        std::fs::write(&toml_path, "home = { start = 5, span = 0 }\n").unwrap();
+        assert!(matches!(Specs::load_file(&toml_path), Err(LoadSpecsFileError::InvalidSymbolSpec { .. })));
+    }
+
+    #[test]
+    fn conflicting_specs() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.child("specs.yaml");
+        std::fs::write(&path, "a: '0x01:3'\nb: '0x02:1'\nc: '0x10:4'\n").unwrap();
+        let conflicts = match Specs::load_file_tile_count(&path, Some(0x12)) {
+            Err(LoadSpecsFileError::Conflicts(error)) => error.conflicts().to_vec(),
+            result => panic!("unexpected result: {result:?}"),
+        };
+        assert_eq!(conflicts, vec![
+            SpecConflict::Overlap { name: "a".to_owned(), other_name: "b".to_owned() },
+            SpecConflict::BeyondTileCount { name: "c".to_owned(), end_tile_index: 0x14, tile_count: 0x12 },
+        ]);
+
+        let specs = Specs::from(vec![Spec::new("a", 0, 1), Spec::new("a", 4, 2)]);
+        assert_eq!(specs.conflicts(None), vec![SpecConflict::DuplicateName { name: "a".to_owned(), start_tile_index: 0, other_start_tile_index: 4 }]);
+    }
+
+    #[test]
+    fn multi_row_symbols() {
+        use crate::osd::tile::container::ToSymbols;
+
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.child("specs.yaml");
+        std::fs::write(&path, "logo: '0x01:2x2'\nbar: { start: 5, span: 6, rows: 3 }\n").unwrap();
+        let specs = Specs::load_file(&path).unwrap();
+        let shapes = specs.iter().map(|spec| (spec.start_tile_index(), spec.span(), spec.columns(), spec.rows())).collect::<Vec<_>>();
+        assert_eq!(shapes, vec![(1, 4, 2, 2), (5, 6, 2, 3)]);
+        specs.save_file(&path).unwrap();
+        assert!(std::fs::read_to_string(&path).unwrap().contains("0x01:2x2"));
+
+        let tiles = (0..4).map(|index| {
+            let mut tile = Tile::new(TileKind::SD);
+            tile.put_pixel(index, 0, Rgba([255, 255, 255, 255]));
+            tile
+        }).collect::<Vec<_>>();
+        let specs = Specs::from(vec![Spec::new_rows("logo", 0, 4, 2)]);
+        let symbols = tiles.to_symbols(&specs).unwrap();
+        let image = symbols[0].generate_image();
+        assert_eq!(image.dimensions(), (72, 108));
+        // the third tile starts the second row
+        assert_eq!(image.get_pixel(2, 54), &Rgba([255, 255, 255, 255]));
+        assert_eq!(Specs::from_symbols(&symbols)[0].rows(), 2);
+    }
+}
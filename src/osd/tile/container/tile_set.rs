@@ -1,29 +1,47 @@
 
 use std::ops::Index;
+use std::slice::SliceIndex;
 use std::path::Path;
 
 use derive_more::{Display, Error, From};
 use getset::Getters;
 use strum::IntoEnumIterator;
 
+#[cfg(feature = "fs")]
+use crate::image::encode_image;
+use crate::image_format::{ImageDecoding, ImageFormat};
+#[cfg(feature = "fs")]
+use crate::parallel;
 use crate::osd::tile::container::UniqTileKind;
-use crate::osd::tile::{Kind as TileKind, Tile};
+use crate::osd::tile::{Kind as TileKind, Tile, fit::Fit};
 use crate::osd::tile::grid::{Grid as TileGrid, LoadError as GridLoadError};
-use super::save_to_bin_file::{SaveToBinFiles, SaveTilesToBinFileError};
+use crate::osd::bin_file;
+use crate::osd::name_template::NameTemplate;
+use crate::create_path::create_path;
+use super::save_to_bin_file::{Padding, SaveToBinFiles, SaveTilesToBinFileError};
 use super::save_to_grid::SaveToGridImage;
 use super::uniq_tile_kind::TileKindError;
-use super::{IntoTilesVec, ToSymbols};
-use super::load_tiles_from_dir::{load_tiles_from_dir, LoadTilesFromDirError};
-use super::save_tiles_to_dir::{SaveTilesToDir, SaveTilesToDirError};
+use super::{IntoTilesVec, ToSymbols, ToSymbolsError};
+use super::load_tiles_from_dir::{load_tiles_from_dir_missing, LoadTilesFromDirError, MissingTiles};
+#[cfg(feature = "fs")]
+use super::load_tiles_from_dir::load_tiles_from_files;
+#[cfg(feature = "fs")]
+use super::archive::{Archive, ArchiveError, SaveToArchiveError};
+use super::save_tiles_to_dir::{DedupMode, SaveTilesToDir, SaveTilesToDirError};
+use super::stats::is_blank;
+use super::char_map::{CharMap, RemapError};
 use super::symbol::set::Set as SymbolSet;
 use super::symbol::spec::Specs as SymbolSpecs;
-use crate::osd::tile::grid::SaveImageError as SaveGridImageError;
+use crate::osd::tile::grid::{Layout as GridLayout, SaveImageError as SaveGridImageError};
+use crate::osd::tile::grid_annotation::Annotation as GridAnnotation;
 
 
 #[derive(Debug, Display, Error, From)]
 pub enum LoadTileSetTilesFromDirError {
     LoadTilesFromDirError(LoadTilesFromDirError),
     TileKindError(TileKindError),
+    #[cfg(feature = "fs")]
+    ArchiveError(ArchiveError),
 }
 
 #[derive(Debug, Display, Error, From)]
@@ -55,45 +73,147 @@ impl TileSet {
         Ok(Self { sd_tiles, hd_tiles })
     }
 
-    pub fn load_from_dir<P: AsRef<Path>>(path: P, max_tiles: usize) -> Result<Self, LoadTileSetTilesFromDirError> {
-        let sd_tiles = load_tiles_from_dir(TileKind::SD.set_dir_path(&path), max_tiles)?;
-        let hd_tiles = load_tiles_from_dir(TileKind::HD.set_dir_path(&path), max_tiles)?;
+    pub fn load_from_dir<P: AsRef<Path>>(path: P, max_tiles: usize, fit: Option<Fit>, decoding: ImageDecoding) -> Result<Self, LoadTileSetTilesFromDirError> {
+        Self::load_from_dir_missing(path, max_tiles, fit, decoding, MissingTiles::default())
+    }
+
+    pub fn load_from_dir_missing<P: AsRef<Path>>(path: P, max_tiles: usize, fit: Option<Fit>, decoding: ImageDecoding, missing_tiles: MissingTiles) -> Result<Self, LoadTileSetTilesFromDirError> {
+        let sd_tiles = load_tiles_from_dir_missing(TileKind::SD.set_dir_path(&path), max_tiles, fit, decoding, missing_tiles)?;
+        let hd_tiles = load_tiles_from_dir_missing(TileKind::HD.set_dir_path(&path), max_tiles, fit, decoding, missing_tiles)?;
         Ok(Self::try_from_tiles(sd_tiles, hd_tiles)?)
     }
 
-    pub fn load_from_tile_grids<P: AsRef<Path>>(sd_grid_path: P, hd_grid_path: P) -> Result<Self, LoadFromTileGridsError> {
-        let sd_tiles = TileGrid::load_from_image(sd_grid_path)?.to_vec();
-        let hd_tiles = TileGrid::load_from_image(hd_grid_path)?.to_vec();
+    // zip or tar.gz archive with the structure of a tile set directory, the images are decoded from memory
+    #[cfg(feature = "fs")]
+    pub fn load_from_archive<P: AsRef<Path>>(path: P, max_tiles: usize, fit: Option<Fit>, decoding: ImageDecoding) -> Result<Self, LoadTileSetTilesFromDirError> {
+        Self::load_from_archive_missing(path, max_tiles, fit, decoding, MissingTiles::default())
+    }
+
+    #[cfg(feature = "fs")]
+    pub fn load_from_archive_missing<P: AsRef<Path>>(path: P, max_tiles: usize, fit: Option<Fit>, decoding: ImageDecoding, missing_tiles: MissingTiles) -> Result<Self, LoadTileSetTilesFromDirError> {
+        let archive = Archive::load_file(path)?;
+        let sd_tiles = load_tiles_from_files(&archive.dir_image_files(TileKind::SD.set_dir_name()), max_tiles, fit, decoding, missing_tiles)?;
+        let hd_tiles = load_tiles_from_files(&archive.dir_image_files(TileKind::HD.set_dir_name()), max_tiles, fit, decoding, missing_tiles)?;
         Ok(Self::try_from_tiles(sd_tiles, hd_tiles)?)
     }
 
-    pub fn into_symbol_set(self, specs: &SymbolSpecs) -> Result<SymbolSet, TileKindError> {
-        Ok(SymbolSet {
-            sd_symbols: self.sd_tiles.to_symbols(specs)?,
-            hd_symbols: self.hd_tiles.to_symbols(specs)?
-        })
+    #[cfg(feature = "fs")]
+    pub fn save_to_archive<P: AsRef<Path>>(&self, path: P, image_format: ImageFormat) -> Result<(), SaveToArchiveError> {
+        let mut archive = Archive::new(&path);
+        for tile_kind in TileKind::iter() {
+            let dir = Path::new(tile_kind.set_dir_name());
+            let files = parallel::try_map(&self[tile_kind], |index, tile| {
+                let file_path = image_format.file_path(dir, &format!("{index:03}"));
+                encode_image(tile.image(), archive.path().join(&file_path), image_format).map(|content| (file_path, content))
+            })?;
+            for (file_path, content) in files {
+                archive.insert(file_path, content);
+            }
+        }
+        Ok(archive.save_file()?)
     }
 
-    pub fn save_to_bin_files<P: AsRef<Path>>(&self, sd_path: P, sd_2_path: P, hd_path: P, hd_2_path: P) -> Result<(), SaveTilesToBinFileError> {
-        self.sd_tiles.save_to_bin_files(sd_path, sd_2_path)?;
-        self.hd_tiles.save_to_bin_files(hd_path, hd_2_path)
+    pub fn load_from_tile_grids<P: AsRef<Path>>(sd_grid_path: P, hd_grid_path: P, fit: Option<Fit>) -> Result<Self, LoadFromTileGridsError> {
+        let sd_tiles = TileGrid::load_from_image_fit(sd_grid_path, fit)?.to_vec();
+        let hd_tiles = TileGrid::load_from_image_fit(hd_grid_path, fit)?.to_vec();
+        Ok(Self::try_from_tiles(sd_tiles, hd_tiles)?)
+    }
+
+    // the problems of the SD and HD specs are reported together
+    pub fn into_symbol_set(self, specs: &SymbolSpecs) -> Result<SymbolSet, ToSymbolsError> {
+        match (self.sd_tiles.to_symbols(specs), self.hd_tiles.to_symbols(specs)) {
+            (Ok(sd_symbols), Ok(hd_symbols)) => Ok(SymbolSet { sd_symbols, hd_symbols }),
+            (Err(ToSymbolsError::InvalidSpecs(sd_error)), Err(ToSymbolsError::InvalidSpecs(hd_error))) =>
+                Err(sd_error.merge(hd_error).into()),
+            (Err(error), _) | (_, Err(error)) => Err(error),
+        }
+    }
+
+    pub fn save_to_bin_files<P: AsRef<Path>>(&self, sd_paths: &[P], hd_paths: &[P]) -> Result<(), SaveTilesToBinFileError> {
+        self.save_to_bin_files_padding(sd_paths, hd_paths, Padding::default())
+    }
+
+    pub fn save_to_bin_files_padding<P: AsRef<Path>>(&self, sd_paths: &[P], hd_paths: &[P], padding: Padding) -> Result<(), SaveTilesToBinFileError> {
+        self.sd_tiles.save_to_bin_files_padding(sd_paths, padding)?;
+        self.hd_tiles.save_to_bin_files_padding(hd_paths, padding)
     }
 
     pub fn save_to_bin_files_norm<P: AsRef<Path>>(&self, dir: P, ident: &Option<&str>) -> Result<(), SaveTilesToBinFileError> {
-        self.sd_tiles.save_to_bin_files_norm(&dir, ident)?;
-        self.hd_tiles.save_to_bin_files_norm(&dir, ident)
+        self.save_to_bin_files_norm_padding(dir, ident, Padding::default())
+    }
+
+    // the SD and HD tiles are written to the same number of pages so that the set is complete
+    pub fn save_to_bin_files_norm_padding<P: AsRef<Path>>(&self, dir: P, ident: &Option<&str>, padding: Padding) -> Result<(), SaveTilesToBinFileError> {
+        let page_count = bin_file::page_count(self.sd_tiles.len().max(self.hd_tiles.len()));
+        self.sd_tiles.save_to_bin_files_norm_padding(&dir, ident, page_count, padding)?;
+        self.hd_tiles.save_to_bin_files_norm_padding(&dir, ident, page_count, padding)
+    }
+
+    // same as save_to_bin_files_norm_padding with the file names given by the template
+    pub fn save_to_bin_files_templated_padding<P: AsRef<Path>>(&self, dir: P, ident: &Option<&str>, template: &NameTemplate, padding: Padding) -> Result<(), SaveTilesToBinFileError> {
+        create_path(&dir)?;
+        let page_count = bin_file::page_count(self.sd_tiles.len().max(self.hd_tiles.len()));
+        for tile_kind in TileKind::iter() {
+            let paths: Vec<_> = (0..page_count).map(|page_index| template.file_path(&dir, tile_kind, ident, page_index)).collect();
+            self[tile_kind].save_to_bin_files_padding(&paths, padding)?;
+        }
+        Ok(())
+    }
+
+    // the non-blank tiles of the layer replace the tiles at the same index, the set is extended with blank tiles when
+    // the layer has non-blank tiles past its end, returns the number of replaced tiles
+    pub fn overlay(&mut self, layer: &TileSet) -> usize {
+        overlay_tiles(&mut self.sd_tiles, &layer.sd_tiles) + overlay_tiles(&mut self.hd_tiles, &layer.hd_tiles)
+    }
+
+    pub fn tiles(&self, tile_kind: TileKind) -> &[Tile] {
+        &self[tile_kind]
+    }
+
+    // the SD tiles then the HD tiles with their kind and index
+    pub fn iter(&self) -> impl Iterator<Item = (TileKind, usize, &Tile)> {
+        TileKind::iter().flat_map(move |tile_kind| self[tile_kind].iter().enumerate().map(move |(index, tile)| (tile_kind, index, tile)))
+    }
+
+    // only the tiles with at least one non-transparent pixel
+    pub fn iter_non_empty(&self) -> impl Iterator<Item = (TileKind, usize, &Tile)> {
+        self.iter().filter(|(_, _, tile)| !is_blank(tile))
+    }
+
+    pub fn remap(&self, char_map: &CharMap) -> Result<Self, RemapError> {
+        Ok(Self { sd_tiles: char_map.remap(&self.sd_tiles)?, hd_tiles: char_map.remap(&self.hd_tiles)? })
     }
 
     pub fn save_to_grids<P: AsRef<Path>>(&self, sd_path: P, hd_path: P) -> Result<(), SaveGridImageError> {
-        self.sd_tiles.save_to_grid_image(sd_path)?;
-        self.hd_tiles.save_to_grid_image(hd_path)
+        self.save_to_grids_annotated(sd_path, hd_path, GridLayout::default(), None)
+    }
+
+    pub fn save_to_grids_annotated<P: AsRef<Path>>(&self, sd_path: P, hd_path: P, layout: GridLayout, annotation: Option<GridAnnotation>) -> Result<(), SaveGridImageError> {
+        self.sd_tiles.save_to_grid_image_annotated(sd_path, layout, annotation)?;
+        self.hd_tiles.save_to_grid_image_annotated(hd_path, layout, annotation)
     }
 
-    pub fn save_to_grids_norm<P: AsRef<Path>>(&self, dir: P, ident: &Option<&str>) -> Result<(), SaveGridImageError> {
-        self.sd_tiles.save_to_grid_image_norm(&dir, ident)?;
-        self.hd_tiles.save_to_grid_image_norm(&dir, ident)
+    pub fn save_to_grids_norm<P: AsRef<Path>>(&self, dir: P, ident: &Option<&str>, image_format: ImageFormat) -> Result<(), SaveGridImageError> {
+        self.save_to_grids_norm_annotated(dir, ident, image_format, GridLayout::default(), None)
     }
 
+    pub fn save_to_grids_norm_annotated<P: AsRef<Path>>(&self, dir: P, ident: &Option<&str>, image_format: ImageFormat, layout: GridLayout, annotation: Option<GridAnnotation>) -> Result<(), SaveGridImageError> {
+        self.sd_tiles.save_to_grid_image_norm_annotated(&dir, ident, image_format, layout, annotation)?;
+        self.hd_tiles.save_to_grid_image_norm_annotated(&dir, ident, image_format, layout, annotation)
+    }
+
+}
+
+fn overlay_tiles(tiles: &mut Vec<Tile>, layer: &[Tile]) -> usize {
+    let mut overlaid_count = 0;
+    for (index, tile) in layer.iter().enumerate().filter(|(_, tile)| !is_blank(tile)) {
+        if index >= tiles.len() {
+            tiles.resize(index + 1, Tile::new(tile.kind()));
+        }
+        tiles[index] = tile.clone();
+        overlaid_count += 1;
+    }
+    overlaid_count
 }
 
 impl Index<TileKind> for TileSet {
@@ -107,10 +227,33 @@ impl Index<TileKind> for TileSet {
     }
 }
 
+// a bare index would be ambiguous since the set holds the SD and HD tiles, the tiles are indexed with their kind
+// e.g. `tile_set[(TileKind::HD, 65)]` or `tile_set[(TileKind::SD, 0x30..0x3a)]`
+impl<I: SliceIndex<[Tile]>> Index<(TileKind, I)> for TileSet {
+    type Output = I::Output;
+
+    fn index(&self, (tile_kind, index): (TileKind, I)) -> &Self::Output {
+        &self[tile_kind][index]
+    }
+}
+
+// `&TileSet` cannot implement `IntoIterator` without conflicting with the blanket implementations over the
+// collections of tiles, `iter()` is used for borrowing iteration
+impl IntoIterator for TileSet {
+    type Item = (TileKind, usize, Tile);
+    type IntoIter = std::vec::IntoIter<Self::Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let sd_tiles = self.sd_tiles.into_iter().enumerate().map(|(index, tile)| (TileKind::SD, index, tile));
+        let hd_tiles = self.hd_tiles.into_iter().enumerate().map(|(index, tile)| (TileKind::HD, index, tile));
+        sd_tiles.chain(hd_tiles).collect::<Vec<_>>().into_iter()
+    }
+}
+
 impl SaveTilesToDir for TileSet {
-    fn save_tiles_to_dir<P: AsRef<Path>>(&self, path: P) -> Result<(), SaveTilesToDirError> {
+    fn save_tiles_to_dir_deduplicated<P: AsRef<Path>>(&self, path: P, image_format: ImageFormat, scale: u32, dedup: Option<DedupMode>) -> Result<(), SaveTilesToDirError> {
         for tile_kind in TileKind::iter() {
-            self[tile_kind].save_tiles_to_dir(tile_kind.set_dir_path(&path))?;
+            self[tile_kind].save_tiles_to_dir_deduplicated(tile_kind.set_dir_path(&path), image_format, scale, dedup)?;
         }
         Ok(())
     }
@@ -124,3 +267,49 @@ impl From<SymbolSet> for TileSet {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+
+    use crate::osd::tile::{Tile, Kind as TileKind};
+
+    use super::TileSet;
+
+    #[test]
+    fn overlay() {
+        let glyph = |tile_kind, shade| {
+            let mut tile = Tile::new(tile_kind);
+            tile.put_pixel(1, 1, Rgba([shade, shade, shade, 255]));
+            tile
+        };
+        let mut base = TileSet::try_from_tiles(vec![glyph(TileKind::SD, 10); 4], vec![glyph(TileKind::HD, 10); 4]).unwrap();
+        let mut sd_layer = vec![Tile::new(TileKind::SD); 6];
+        sd_layer[1] = glyph(TileKind::SD, 200);
+        sd_layer[5] = glyph(TileKind::SD, 200);
+        let layer = TileSet::try_from_tiles(sd_layer, vec![Tile::new(TileKind::HD); 2]).unwrap();
+
+        assert_eq!(base.overlay(&layer), 2);
+        assert_eq!(base.sd_tiles().len(), 6);
+        assert_eq!(base.sd_tiles()[0].get_pixel(1, 1).0[0], 10);
+        assert_eq!(base.sd_tiles()[1].get_pixel(1, 1).0[0], 200);
+        assert_eq!(base.sd_tiles()[4].get_pixel(1, 1).0[3], 0);
+        assert_eq!(base.hd_tiles().len(), 4);
+    }
+
+    #[test]
+    fn iteration_and_indexing() {
+        let mut sd_tiles = vec![Tile::new(TileKind::SD); 3];
+        sd_tiles[2].put_pixel(0, 0, Rgba([255, 255, 255, 255]));
+        let mut hd_tiles = vec![Tile::new(TileKind::HD); 2];
+        hd_tiles[1].put_pixel(0, 0, Rgba([255, 255, 255, 255]));
+        let tile_set = TileSet::try_from_tiles(sd_tiles, hd_tiles).unwrap();
+
+        assert_eq!(tile_set[(TileKind::SD, 2)].get_pixel(0, 0).0[3], 255);
+        assert_eq!(tile_set[(TileKind::HD, 1..)].len(), 1);
+        assert_eq!(tile_set.iter().count(), 5);
+        let non_empty: Vec<(TileKind, usize)> = tile_set.iter_non_empty().map(|(tile_kind, index, _)| (tile_kind, index)).collect();
+        assert_eq!(non_empty, [(TileKind::SD, 2), (TileKind::HD, 1)]);
+        assert_eq!(tile_set.into_iter().last().map(|(tile_kind, index, _)| (tile_kind, index)), Some((TileKind::HD, 1)));
+    }
+}
@@ -1,7 +1,7 @@
 
 use thiserror::Error;
 
-use crate::osd::tile::{Kind as TileKind, Tile};
+use crate::osd::tile::{Dimensions as TileDimensions, Kind as TileKind, Tile};
 use super::{symbol::Symbol, IntoSymbolsTilesIter};
 
 
@@ -15,7 +15,21 @@ pub enum TileKindError {
     LoadedDoesNotMatchRequested {
         requested: TileKind,
         loaded: TileKind,
+    },
+    // e.g. a tile rotated by 90°, its image would not fit in the space of a tile of its kind
+    #[error("{tile_kind} tile with dimensions {dimensions} which do not match its kind")]
+    InvalidTileDimensions {
+        tile_kind: TileKind,
+        dimensions: TileDimensions,
+    },
+}
+
+fn check_tile_dimensions(tile: &Tile) -> Result<(), TileKindError> {
+    let dimensions = TileDimensions::from(tile.dimensions());
+    if dimensions != tile.kind().dimensions() {
+        return Err(TileKindError::InvalidTileDimensions { tile_kind: tile.kind(), dimensions })
     }
+    Ok(())
 }
 
 pub trait TilesIterUniqTileKind {
@@ -27,9 +41,14 @@ where
     T: Iterator<Item = &'a Tile>
 {
     fn tile_kind(&mut self) -> Result<TileKind, TileKindError> {
-        let first_tile_kind = self.next().ok_or(TileKindError::EmptyContainer)?.kind();
-        if ! self.all(|tile| tile.kind() == first_tile_kind) {
-            return Err(TileKindError::MultipleTileKinds)
+        let first_tile = self.next().ok_or(TileKindError::EmptyContainer)?;
+        check_tile_dimensions(first_tile)?;
+        let first_tile_kind = first_tile.kind();
+        for tile in self {
+            if tile.kind() != first_tile_kind {
+                return Err(TileKindError::MultipleTileKinds)
+            }
+            check_tile_dimensions(tile)?;
         }
         Ok(first_tile_kind)
     }
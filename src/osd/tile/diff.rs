@@ -0,0 +1,172 @@
+
+use std::path::Path;
+
+use derive_more::{Display, Error, From};
+use getset::CopyGetters;
+use image::{GenericImage, Rgba};
+use serde::Serialize;
+
+use super::{
+    Tile,
+    Kind as TileKind,
+    Image,
+    grid::{Grid, Image as GridImage},
+    container::uniq_tile_kind::{TileKindError, TilesIterUniqTileKind},
+};
+use crate::{
+    create_path::{create_path, CreatePathError},
+    image::{WriteImageFile, WriteError as ImageWriteError},
+};
+
+
+const SEPARATOR_THICKNESS: u32 = 2;
+const CHANGED_PIXEL_COLOR: Rgba<u8> = Rgba([255, 0, 0, 255]);
+const PLACEHOLDER_COLOR: Rgba<u8> = Rgba([128, 128, 128, 255]);
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, strum::Display)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum ChangeKind {
+    Changed,
+    // only present in the new tiles
+    Added,
+    // only present in the old tiles
+    Removed,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, CopyGetters)]
+#[getset(get_copy = "pub")]
+pub struct TileChange {
+    index: usize,
+    kind: ChangeKind,
+}
+
+#[derive(Debug, Error, Display, From)]
+pub enum SaveVisualDiffError {
+    CreatePathError(CreatePathError),
+    ImageWriteError(ImageWriteError),
+    TileKindError(TileKindError),
+}
+
+fn tiles_are_identical(old: &Tile, new: &Tile) -> bool {
+    old.kind() == new.kind() && old.as_raw() == new.as_raw()
+}
+
+fn diff_by<T, F: Fn(&T, &T) -> bool>(old: &[T], new: &[T], identical: F) -> Vec<TileChange> {
+    (0..old.len().max(new.len())).filter_map(|index| {
+        let kind = match (old.get(index), new.get(index)) {
+            (Some(old), Some(new)) if identical(old, new) => return None,
+            (Some(_), Some(_)) => ChangeKind::Changed,
+            (None, Some(_)) => ChangeKind::Added,
+            (Some(_), None) => ChangeKind::Removed,
+            (None, None) => unreachable!(),
+        };
+        Some(TileChange { index, kind })
+    }).collect()
+}
+
+pub fn diff_tiles(old: &[Tile], new: &[Tile]) -> Vec<TileChange> {
+    diff_by(old, new, tiles_are_identical)
+}
+
+// compares the raw RGBA bytes of tiles of the same kind without decoding them e.g. from `bin_file::LazyReader`
+pub fn diff_raw_tiles(old: &[&[u8]], new: &[&[u8]]) -> Vec<TileChange> {
+    diff_by(old, new, |old, new| old == new)
+}
+
+// stands for a tile missing from one of the collections, diagonal stripes so that it cannot be mistaken for a glyph
+pub fn hatched_placeholder(kind: TileKind) -> Tile {
+    let mut tile = Tile::new(kind);
+    for (x, y, pixel) in tile.enumerate_pixels_mut() {
+        if (x + y) % 6 < 2 {
+            *pixel = PLACEHOLDER_COLOR;
+        }
+    }
+    tile
+}
+
+// old tile | new tile | mask of the changed pixels in red, separated the same way as the tiles of a grid
+pub fn comparison_image(old: &Tile, new: &Tile) -> Image {
+    let width = old.width().max(new.width());
+    let height = old.height().max(new.height());
+    let mut image = Image::from_pixel(3 * width + 2 * SEPARATOR_THICKNESS, height, Rgba([0, 0, 0, 255]));
+    let mut mask = Image::new(width, height);
+    for (x, y, pixel) in mask.enumerate_pixels_mut() {
+        let old_pixel = old.get_pixel_checked(x, y);
+        let new_pixel = new.get_pixel_checked(x, y);
+        *pixel = if old_pixel == new_pixel { Rgba([0, 0, 0, 0]) } else { CHANGED_PIXEL_COLOR };
+    }
+    let panel_x = |panel: u32| panel * (width + SEPARATOR_THICKNESS);
+    image.copy_from(old.image(), panel_x(0), 0).unwrap();
+    image.copy_from(new.image(), panel_x(1), 0).unwrap();
+    image.copy_from(&mask, panel_x(2), 0).unwrap();
+    image
+}
+
+fn tint(tile: &Tile) -> Tile {
+    let mut tile = tile.clone();
+    for pixel in tile.pixels_mut() {
+        let [red, green, blue, alpha] = pixel.0;
+        *pixel = Rgba([((red as u16 + 255) / 2) as u8, green / 2, blue / 2, alpha.max(128)]);
+    }
+    tile
+}
+
+// grid of the new tiles (old ones when removed) with the changed cells tinted in red
+pub fn overview_image(old: &[Tile], new: &[Tile]) -> Result<GridImage, TileKindError> {
+    // the tiles have to be of a single kind to be laid out in a grid
+    new.iter().chain(old.iter()).tile_kind()?;
+    let changes = diff_tiles(old, new);
+    let mut tiles: Vec<Tile> = (0..old.len().max(new.len()))
+        .map(|index| new.get(index).or_else(|| old.get(index)).unwrap().clone())
+        .collect();
+    for change in changes {
+        tiles[change.index] = tint(&tiles[change.index]);
+    }
+    Grid::from(tiles).generate_image()
+}
+
+// writes a comparison image named from the index of each differing tile and overview.png
+pub fn save_visual_diff<P: AsRef<Path>>(old: &[Tile], new: &[Tile], dir: P) -> Result<(), SaveVisualDiffError> {
+    create_path(&dir)?;
+    for change in diff_tiles(old, new) {
+        let (old_tile, new_tile) = match (old.get(change.index), new.get(change.index)) {
+            (Some(old_tile), Some(new_tile)) => (old_tile.clone(), new_tile.clone()),
+            (None, Some(new_tile)) => (hatched_placeholder(new_tile.kind()), new_tile.clone()),
+            (Some(old_tile), None) => (old_tile.clone(), hatched_placeholder(old_tile.kind())),
+            (None, None) => unreachable!(),
+        };
+        let path = dir.as_ref().join(format!("{:03}.png", change.index));
+        comparison_image(&old_tile, &new_tile).write_image_file(path)?;
+    }
+    overview_image(old, new)?.write_image_file(dir.as_ref().join("overview.png"))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+
+    use crate::osd::tile::{Tile, Kind as TileKind};
+
+    use super::{comparison_image, diff_tiles, hatched_placeholder, ChangeKind, CHANGED_PIXEL_COLOR, SEPARATOR_THICKNESS};
+
+    #[test]
+    fn comparison_mask() {
+        let old = Tile::new(TileKind::SD);
+        let mut new = Tile::new(TileKind::SD);
+        new.put_pixel(3, 4, Rgba([255, 255, 255, 255]));
+
+        let image = comparison_image(&old, &new);
+        let width = TileKind::SD.dimensions().width;
+        assert_eq!(image.width(), 3 * width + 2 * SEPARATOR_THICKNESS);
+        let mask_x = 2 * (width + SEPARATOR_THICKNESS);
+        assert_eq!(*image.get_pixel(mask_x + 3, 4), CHANGED_PIXEL_COLOR);
+        assert_eq!(image.get_pixel(mask_x, 0).0[3], 0);
+
+        let changes = diff_tiles(&[old.clone(), old.clone()], &[new, old.clone(), old]);
+        let kinds: Vec<_> = changes.iter().map(|change| (change.index(), change.kind())).collect();
+        assert_eq!(kinds, vec![(0, ChangeKind::Changed), (2, ChangeKind::Added)]);
+        assert!(hatched_placeholder(TileKind::HD).pixels().any(|pixel| pixel.0[3] != 0));
+    }
+}
@@ -0,0 +1,78 @@
+
+use std::path::Path;
+
+use clap::ValueEnum;
+use image::imageops::{self, FilterType};
+use strum::Display;
+
+use crate::dimensions::Dimensions;
+
+use super::Image;
+
+
+// How to adjust images which do not have the exact expected dimensions when loading tiles
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Display, ValueEnum)]
+#[strum(serialize_all = "lowercase")]
+pub enum Fit {
+    // center the image and trim what is outside, only for images larger than expected
+    Crop,
+    // center the image on a transparent canvas, only for images smaller than expected
+    Pad,
+    // resample the image to the expected dimensions
+    Scale,
+}
+
+impl Fit {
+
+    pub fn apply(&self, image: &Image, dimensions: Dimensions<u32>) -> Option<Image> {
+        let (width, height) = image.dimensions();
+        match self {
+            Fit::Crop if width < dimensions.width || height < dimensions.height => None,
+            Fit::Pad if width > dimensions.width || height > dimensions.height => None,
+            Fit::Crop | Fit::Pad => {
+                let mut fitted = Image::new(dimensions.width, dimensions.height);
+                let x = (dimensions.width as i64 - width as i64) / 2;
+                let y = (dimensions.height as i64 - height as i64) / 2;
+                imageops::replace(&mut fitted, image, x, y);
+                Some(fitted)
+            },
+            Fit::Scale => Some(imageops::resize(image, dimensions.width, dimensions.height, FilterType::Triangle)),
+        }
+    }
+
+}
+
+// Every tile source goes through here when an image needs adjusting so that they all behave the same
+pub(crate) fn fit_image<P: AsRef<Path>>(path: P, image: &Image, dimensions: Dimensions<u32>, fit: Fit) -> Option<Image> {
+    let original_dimensions = Dimensions::from(image.dimensions());
+    let fitted = fit.apply(image, dimensions)?;
+    log::warn!("{}: adjusted image from {original_dimensions} to {dimensions} ({fit})", path.as_ref().to_string_lossy());
+    Some(fitted)
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+
+    use crate::osd::tile::{Image, SD_DIMENSIONS};
+
+    use super::Fit;
+
+    #[test]
+    fn crop_and_pad() {
+        let mut oversize = Image::new(38, 56);
+        oversize.put_pixel(1, 1, Rgba([255, 255, 255, 255]));
+        let cropped = Fit::Crop.apply(&oversize, SD_DIMENSIONS).unwrap();
+        assert_eq!(cropped.dimensions(), (36, 54));
+        assert_eq!(cropped.get_pixel(0, 0), &Rgba([255, 255, 255, 255]));
+        assert!(Fit::Pad.apply(&oversize, SD_DIMENSIONS).is_none());
+
+        let mut undersize = Image::new(34, 52);
+        undersize.put_pixel(0, 0, Rgba([255, 255, 255, 255]));
+        let padded = Fit::Pad.apply(&undersize, SD_DIMENSIONS).unwrap();
+        assert_eq!(padded.dimensions(), (36, 54));
+        assert_eq!(padded.get_pixel(1, 1), &Rgba([255, 255, 255, 255]));
+        assert_eq!(padded.get_pixel(0, 0)[3], 0);
+        assert!(Fit::Crop.apply(&undersize, SD_DIMENSIONS).is_none());
+    }
+}
@@ -0,0 +1,233 @@
+
+use std::{
+    collections::BTreeMap,
+    io::Error as IOError,
+    path::{Path, PathBuf},
+};
+
+use ab_glyph::{Font, FontVec, PxScale, ScaleFont, point};
+use derive_more::From;
+use fs_err::File;
+use image::Rgba;
+use parse_int::parse;
+use serde::Deserialize;
+use strum::IntoEnumIterator;
+use thiserror::Error;
+
+use super::{
+    Tile,
+    Kind as TileKind,
+    SD_DIMENSIONS,
+    container::tile_set::TileSet,
+};
+
+use crate::osd::bin_file;
+
+// Rasterizes the glyphs of a TrueType/OpenType font into tiles. The glyphs are rendered white with the coverage as
+// alpha, scaled so that the line height of the font fills the tile height minus the vertical margins and centered
+// horizontally on their advance width. Outlines can then be added with the tile transforms.
+//
+// The mapping file is a YAML map of characters to tile indices, the characters are given either as themselves or as
+// U+XXXX codepoints and the indices as numbers or strings in decimal or hexadecimal e.g.
+//   "A": 0x41
+//   "U+00B0": 8
+
+// margin between the line of text and the top/bottom edges of the SD tiles, scaled for the other tile kinds
+pub const DEFAULT_MARGIN: u32 = 2;
+
+// base and extended bin file pages, the characters mapped to higher indices are an error
+pub const MAX_TILE_COUNT: usize = 2 * bin_file::TILE_COUNT;
+
+const GLYPH_COLOR: [u8; 3] = [255, 255, 255];
+
+#[derive(Debug, From, Error)]
+pub enum LoadFontError {
+    #[error(transparent)]
+    FileError(IOError),
+    #[from(ignore)]
+    #[error("file {0} is not a valid TrueType/OpenType font")]
+    InvalidFont(PathBuf),
+}
+
+#[derive(Debug, From, Error)]
+pub enum LoadMappingError {
+    #[error("failed to open character mapping file: {0}")]
+    OpenError(IOError),
+    #[from(ignore)]
+    #[error("failed to parse character mapping file {file_path}: {error}")]
+    FileStructureError { file_path: PathBuf, error: serde_yaml::Error },
+    #[from(ignore)]
+    #[error("invalid character mapping in file {file_path}: {character} -> {index}")]
+    InvalidMapping { file_path: PathBuf, character: String, index: String },
+}
+
+#[derive(Debug, From, Error)]
+pub enum RenderError {
+    #[error(transparent)]
+    LoadFontError(LoadFontError),
+    #[error(transparent)]
+    LoadMappingError(LoadMappingError),
+    #[from(ignore)]
+    #[error("character {character:?} is mapped to tile {index} which is past the {MAX_TILE_COUNT} tiles of the bin file pages")]
+    IndexOutOfRange { character: char, index: usize },
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum MappingIndex {
+    Number(usize),
+    Text(String),
+}
+
+// character -> tile index
+pub type Mapping = BTreeMap<char, usize>;
+
+// printable ASCII characters at the index of their code as in the Betaflight/INAV fonts
+pub fn ascii_mapping() -> Mapping {
+    (0x20..0x7f_u8).map(|code| (code as char, code as usize)).collect()
+}
+
+fn parse_character(text: &str) -> Option<char> {
+    let mut chars = text.chars();
+    match (chars.next(), chars.next()) {
+        (Some(char), None) => Some(char),
+        _ => text.strip_prefix("U+").and_then(|code| u32::from_str_radix(code, 16).ok()).and_then(char::from_u32),
+    }
+}
+
+pub fn load_mapping_file<P: AsRef<Path>>(path: P) -> Result<Mapping, LoadMappingError> {
+    let path = path.as_ref();
+    let file_content: BTreeMap<String, MappingIndex> = serde_yaml::from_reader(File::open(path)?)
+        .map_err(|error| LoadMappingError::FileStructureError { file_path: path.to_path_buf(), error })?;
+    file_content.into_iter().map(|(character, index)| {
+        let parsed_index = match &index {
+            MappingIndex::Number(index) => Some(*index),
+            MappingIndex::Text(text) => parse(text).ok(),
+        };
+        match (parse_character(&character), parsed_index) {
+            (Some(char), Some(index)) => Ok((char, index)),
+            _ => {
+                let index = match index {
+                    MappingIndex::Number(index) => index.to_string(),
+                    MappingIndex::Text(text) => text,
+                };
+                Err(LoadMappingError::InvalidMapping { file_path: path.to_path_buf(), character, index })
+            },
+        }
+    }).collect()
+}
+
+pub fn load_font_file<P: AsRef<Path>>(path: P) -> Result<FontVec, LoadFontError> {
+    let data = fs_err::read(&path)?;
+    FontVec::try_from_vec(data).map_err(|_| LoadFontError::InvalidFont(path.as_ref().to_path_buf()))
+}
+
+fn scaled_margin(tile_kind: TileKind, margin: u32) -> u32 {
+    (margin * tile_kind.dimensions().height + SD_DIMENSIONS.height / 2) / SD_DIMENSIONS.height
+}
+
+pub fn render_glyph<F: Font>(font: &F, char: char, tile_kind: TileKind, margin: u32) -> Tile {
+    let mut tile = Tile::new(tile_kind);
+    let dimensions = tile_kind.dimensions();
+    let margin = scaled_margin(tile_kind, margin);
+    let scale = PxScale::from(dimensions.height.saturating_sub(2 * margin).max(1) as f32);
+    let scaled_font = font.as_scaled(scale);
+
+    let glyph_id = scaled_font.glyph_id(char);
+    if glyph_id.0 == 0 {
+        log::warn!("the font has no glyph for {char:?}, its tile is left blank");
+        return tile;
+    }
+    let advance = scaled_font.h_advance(glyph_id);
+    if advance > dimensions.width as f32 {
+        log::warn!("the glyph for {char:?} is wider than the {tile_kind} tiles and gets clipped");
+    }
+    let position = point(((dimensions.width as f32 - advance) / 2.0).round(), margin as f32 + scaled_font.ascent());
+    let outlined_glyph = match font.outline_glyph(glyph_id.with_scale_and_position(scale, position)) {
+        Some(outlined_glyph) => outlined_glyph,
+        // e.g. space
+        None => return tile,
+    };
+
+    let bounds = outlined_glyph.px_bounds();
+    outlined_glyph.draw(|x, y, coverage| {
+        let (x, y) = (bounds.min.x as i64 + x as i64, bounds.min.y as i64 + y as i64);
+        let alpha = (coverage.clamp(0.0, 1.0) * 255.0).round() as u8;
+        if x >= 0 && y >= 0 && (x as u32) < dimensions.width && (y as u32) < dimensions.height && alpha > 0 {
+            let [red, green, blue] = GLYPH_COLOR;
+            tile.put_pixel(x as u32, y as u32, Rgba([red, green, blue, alpha]));
+        }
+    });
+    tile
+}
+
+// the tiles which are not mapped are blank, the set is padded to whole bin file pages
+pub fn render_tile_set<F: Font>(font: &F, mapping: &Mapping, margin: u32) -> Result<TileSet, RenderError> {
+    if let Some((&character, &index)) = mapping.iter().find(|(_, &index)| index >= MAX_TILE_COUNT) {
+        return Err(RenderError::IndexOutOfRange { character, index })
+    }
+    let tile_count = bin_file::page_count(mapping.values().max().map_or(0, |index| index + 1)) * bin_file::TILE_COUNT;
+    let render_tiles = |tile_kind| {
+        let mut tiles = vec![Tile::new(tile_kind); tile_count];
+        for (char, index) in mapping {
+            tiles[*index] = render_glyph(font, *char, tile_kind, margin);
+        }
+        tiles
+    };
+    let mut tiles = TileKind::iter().map(render_tiles);
+    Ok(TileSet::try_from_tiles(tiles.next().unwrap(), tiles.next().unwrap()).expect("rendered tiles should have the requested kind"))
+}
+
+pub fn render_font_file<P: AsRef<Path>>(font_path: P, mapping: &Mapping, margin: u32) -> Result<TileSet, RenderError> {
+    let font = load_font_file(font_path)?;
+    render_tile_set(&font, mapping, margin)
+}
+
+#[cfg(test)]
+mod tests {
+    use temp_dir::TempDir;
+
+    use crate::osd::tile::{container::stats::is_blank, Kind as TileKind};
+    use super::{
+        ascii_mapping, load_font_file, load_mapping_file, parse_character, render_tile_set, LoadMappingError, Mapping,
+        RenderError, DEFAULT_MARGIN, MAX_TILE_COUNT,
+    };
+
+    #[test]
+    fn mapping_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.child("mapping.yaml");
+        std::fs::write(&path, "A: 0x41\n\"U+00B0\": 8\n\"1\": \"49\"\n").unwrap();
+        let mapping = load_mapping_file(&path).unwrap();
+        assert_eq!(mapping.len(), 3);
+        assert_eq!(mapping[&'A'], 0x41);
+        assert_eq!(mapping[&'°'], 8);
+        assert_eq!(mapping[&'1'], 49);
+
+        std::fs::write(&path, "AB: 3\n").unwrap();
+        assert!(matches!(load_mapping_file(&path), Err(LoadMappingError::InvalidMapping { .. })));
+
+        assert_eq!(parse_character("U+zz"), None);
+        assert_eq!(ascii_mapping()[&'~'], 0x7e);
+    }
+
+    // the test font only has a glyph for 'A', a 400x700 units rectangle
+    #[test]
+    fn render() {
+        let font = load_font_file("test_files/square_glyph.ttf").unwrap();
+        let mapping = Mapping::from([('A', 0x41), ('B', 0x42), ('C', 300)]);
+        let tile_set = render_tile_set(&font, &mapping, DEFAULT_MARGIN).unwrap();
+        for tile_kind in [TileKind::SD, TileKind::HD] {
+            let tiles = &tile_set[tile_kind];
+            assert_eq!(tiles.len(), MAX_TILE_COUNT);
+            assert!(!is_blank(&tiles[0x41]));
+            assert!(tiles[0x41].pixels().filter(|pixel| pixel.0[3] > 0).all(|pixel| pixel.0[..3] == [255, 255, 255]));
+            assert!(is_blank(&tiles[0x42]));
+            assert!(tiles.iter().enumerate().filter(|(index, _)| *index != 0x41).all(|(_, tile)| is_blank(tile)));
+        }
+
+        let mapping = Mapping::from([('A', MAX_TILE_COUNT)]);
+        assert!(matches!(render_tile_set(&font, &mapping, DEFAULT_MARGIN),
+            Err(RenderError::IndexOutOfRange { character: 'A', index: MAX_TILE_COUNT })));
+    }
+}
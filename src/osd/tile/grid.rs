@@ -1,17 +1,21 @@
 
 use std::ops::Index;
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
 use derive_more::{Deref, Display, From, IntoIterator};
 use thiserror::Error;
 use getset::Getters;
-use image::{ImageBuffer, Rgba, GenericImage, GenericImageView};
+use image::{DynamicImage, ImageBuffer, Rgba, GenericImage, GenericImageView};
 use strum::IntoEnumIterator;
 
 use super::{
     Tile,
     Kind as TileKind,
+    fit::{Fit, fit_image},
+    grid_annotation::{annotate, Annotation},
     container::{
+        CapacityError,
         tile_set::TileSet,
         uniq_tile_kind::{UniqTileKind, TileKindError},
     },
@@ -20,9 +24,12 @@ use super::{
 use crate::{
     create_path::{create_path, CreatePathError},
     dimensions,
+    image_format::{AmbiguousImageFileError, ImageDecoding, ImageFormat},
     osd::tile,
+    parallel,
     image::{
-        read_image_file,
+        encode_image,
+        read_rgba_image_file,
         WriteImageFile,
         ReadError as ImageLoadError,
         WriteError as ImageWriteError,
@@ -30,12 +37,26 @@ use crate::{
 };
 
 
-#[derive(Debug, Error)]
-#[error("image dimensions {0} does not match valid dimensions for any of the recognized tile kinds")]
-pub struct InvalidImageDimensionsError(ImageDimensions);
+#[derive(Debug, Clone, Copy, Error)]
+#[error("image dimensions {image_dimensions} do not match a {columns} columns grid of any of the recognized tile kinds, expected {}",
+    expected_dimensions_description(*columns))]
+pub struct InvalidImageDimensionsError {
+    pub image_dimensions: ImageDimensions,
+    pub columns: usize,
+}
+
+// e.g. "SD tiles: 190px wide and 18px high plus 20px per additional row, HD tiles: ..."
+fn expected_dimensions_description(columns: usize) -> String {
+    tile::Kind::iter().map(|tile_kind| {
+        let row_dimensions = Grid::image_dimensions_columns(&tile_kind, columns, 1);
+        let row_height = tile_kind.dimensions().height + SEPARATOR_THICKNESS;
+        format!("{tile_kind} tiles: {}px wide and {}px high plus {row_height}px per additional row", row_dimensions.width, row_dimensions.height)
+    }).collect::<Vec<_>>().join(", ")
+}
 
 #[derive(Debug, From, Error, Display)]
 pub enum LoadError {
+    AmbiguousImageFile(AmbiguousImageFileError),
     ImageLoadError(ImageLoadError),
     InvalidImageDimensions(InvalidImageDimensionsError),
     TileKindError(TileKindError),
@@ -46,12 +67,85 @@ pub enum SaveImageError {
     CreatePathError(CreatePathError),
     ImageWriteError(ImageWriteError),
     TileKindError(TileKindError),
+    CapacityError(CapacityError),
 }
 
+#[derive(Debug, Error)]
+#[error("invalid grid layout `{0}`: expected <columns> or <columns>x<rows> with non-zero numbers e.g. 16 or 32x8")]
+pub struct InvalidLayoutError(String);
+
 pub type ImageDimensions = dimensions::Dimensions<u32>;
 
 const WIDTH: usize = 16;
-const SEPARATOR_THICKNESS: u32 = 2;
+
+// stands for the path in the messages and errors about the images not read from a file
+const IN_MEMORY_IMAGE_NAME: &str = "<grid image>";
+pub(crate) const SEPARATOR_THICKNESS: u32 = 2;
+// largest right/bottom margin accepted around the grid in lenient mode e.g. a border added by an editor or a canvas
+// rounded up to a multiple of 8
+const LENIENT_MAX_MARGIN: u32 = 8;
+
+// How the dimensions of grid images are checked when loading them
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum Validation {
+    // the dimensions must exactly match a grid
+    #[default]
+    Strict,
+    // images slightly larger than a grid are accepted, the pixels on the right and at the bottom of the grid are ignored
+    Lenient,
+}
+
+// Arrangement of the tiles in a grid image, the tiles are laid out row by row. The number of rows follows the number
+// of tiles unless it is fixed in which case the grid is completed with transparent tiles when saving. The number of
+// rows of a loaded grid always follows the image height.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Layout {
+    pub columns: usize,
+    pub rows: Option<usize>,
+}
+
+impl Layout {
+
+    pub fn new(columns: usize, rows: Option<usize>) -> Self {
+        Self { columns, rows }
+    }
+
+    fn rows_for_tile_count(&self, tile_count: usize) -> usize {
+        match self.rows {
+            Some(rows) => rows,
+            None => tile_count.div_ceil(self.columns),
+        }
+    }
+
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Self { columns: WIDTH, rows: None }
+    }
+}
+
+impl std::fmt::Display for Layout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.rows {
+            Some(rows) => write!(f, "{}x{rows}", self.columns),
+            None => write!(f, "{}", self.columns),
+        }
+    }
+}
+
+impl FromStr for Layout {
+    type Err = InvalidLayoutError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse_count = |count: &str| count.parse::<usize>().ok().filter(|count| *count > 0);
+        let layout = match s.split_once('x') {
+            Some((columns, rows)) => parse_count(columns).zip(parse_count(rows)).map(|(columns, rows)| Self::new(columns, Some(rows))),
+            None => parse_count(s).map(|columns| Self::new(columns, None)),
+        };
+        layout.ok_or_else(|| InvalidLayoutError(s.to_owned()))
+    }
+}
 
 pub type Image = ImageBuffer<Rgba<u8>, Vec<u8>>;
 
@@ -61,7 +155,11 @@ pub struct Grid(Vec<Tile>);
 impl Grid {
 
     pub fn index_to_grid_coordinates(index: usize) -> (usize, usize) {
-        (index % WIDTH, index / WIDTH)
+        Self::index_to_grid_coordinates_columns(index, WIDTH)
+    }
+
+    pub(crate) fn index_to_grid_coordinates_columns(index: usize, columns: usize) -> (usize, usize) {
+        (index % columns, index / columns)
     }
 
     fn grid_coordinates_to_index(x: usize, y: usize) -> usize {
@@ -69,7 +167,7 @@ impl Grid {
         x + y * WIDTH
     }
 
-    fn image_tile_position(tile_kind: &tile::Kind, x: u32, y: u32) -> (u32, u32) {
+    pub(crate) fn image_tile_position(tile_kind: &tile::Kind, x: u32, y: u32) -> (u32, u32) {
         let tile_dimensions = tile_kind.dimensions();
         (
             x * (SEPARATOR_THICKNESS + tile_dimensions.width()),
@@ -78,47 +176,140 @@ impl Grid {
     }
 
     pub fn image_tile_kind_and_grid_height(image_dimensions: ImageDimensions) -> Result<(tile::Kind, usize), InvalidImageDimensionsError> {
+        Self::image_tile_kind_and_grid_height_columns(image_dimensions, WIDTH)
+    }
+
+    // tile count of a default layout grid image from its dimensions, without decoding the image
+    pub fn image_tile_kind_and_count(image_dimensions: ImageDimensions) -> Result<(tile::Kind, usize), InvalidImageDimensionsError> {
+        Self::image_tile_kind_and_grid_height(image_dimensions).map(|(tile_kind, grid_height)| (tile_kind, grid_height * WIDTH))
+    }
+
+    pub fn image_tile_kind_and_grid_height_columns(image_dimensions: ImageDimensions, columns: usize) -> Result<(tile::Kind, usize), InvalidImageDimensionsError> {
+        let error = InvalidImageDimensionsError { image_dimensions, columns };
         for tile_kind in tile::Kind::iter() {
-            let expected_width = (WIDTH as u32 - 1) * SEPARATOR_THICKNESS + WIDTH as u32 * tile_kind.dimensions().width;
+            let expected_width = Self::image_dimensions_columns(&tile_kind, columns, 1).width;
             if image_dimensions.width == expected_width {
-                if (image_dimensions.height - tile_kind.dimensions().height) % (tile_kind.dimensions().height + SEPARATOR_THICKNESS) == 0 {
-                    let grid_height = (image_dimensions.height - tile_kind.dimensions().height) / (tile_kind.dimensions().height + SEPARATOR_THICKNESS) + 1;
+                let row_height = tile_kind.dimensions().height + SEPARATOR_THICKNESS;
+                // images shorter than a single row of tiles
+                let extra_rows_height = image_dimensions.height.checked_sub(tile_kind.dimensions().height)
+                    .ok_or(error)?;
+                if extra_rows_height % row_height == 0 {
+                    let grid_height = extra_rows_height / row_height + 1;
                     return Ok((tile_kind, grid_height as usize));
                 } else {
-                    return Err(InvalidImageDimensionsError(image_dimensions))
+                    return Err(error)
                 }
             }
         }
-        Err(InvalidImageDimensionsError(image_dimensions))
+        Err(error)
     }
 
-    pub fn load_from_image<P: AsRef<Path>>(path: P) -> Result<Self, LoadError> {
-        let image = read_image_file(&path)?;
-        let (img_dim_width, img_dim_height) = image.dimensions();
-        let (tile_kind, grid_height) = Self::image_tile_kind_and_grid_height(ImageDimensions { width: img_dim_width, height: img_dim_height })?;
-        log::info!("detected {tile_kind} kind of tiles in a {WIDTH}x{grid_height} grid in {}", path.as_ref().to_string_lossy());
-        let tile_dimensions = tile_kind.dimensions();
-        let mut tiles_container = Vec::with_capacity(WIDTH * grid_height);
+    fn closest_tile_kind_and_grid_height(image_dimensions: ImageDimensions, columns: usize) -> (tile::Kind, usize) {
+        let tile_kind = tile::Kind::iter().min_by_key(|tile_kind|
+            (Self::image_dimensions_columns(tile_kind, columns, 1).width as i64 - image_dimensions.width as i64).abs()
+        ).unwrap();
+        let row_height = tile_kind.dimensions().height + SEPARATOR_THICKNESS;
+        let grid_height = ((image_dimensions.height + SEPARATOR_THICKNESS + row_height / 2) / row_height).max(1);
+        (tile_kind, grid_height as usize)
+    }
 
-        for y in 0..grid_height {
-            for x in 0..WIDTH {
-                let (tile_pos_x, tile_pos_y) = Self::image_tile_position(&tile_kind, x as u32, y as u32);
-                let tile_view = image.view(tile_pos_x, tile_pos_y, tile_dimensions.width, tile_dimensions.height).to_image();
-                tiles_container.push(Tile::try_from(tile_view.clone()).unwrap());
-            }
+    // images smaller than the grid are never accepted
+    fn lenient_tile_kind_and_grid_height(image_dimensions: ImageDimensions, columns: usize) -> Option<(tile::Kind, usize)> {
+        let tile_kind = tile::Kind::iter().find(|tile_kind| {
+            let width = Self::image_dimensions_columns(tile_kind, columns, 1).width;
+            image_dimensions.width >= width && image_dimensions.width - width <= LENIENT_MAX_MARGIN
+        })?;
+        let row_height = tile_kind.dimensions().height + SEPARATOR_THICKNESS;
+        let grid_height = (image_dimensions.height + SEPARATOR_THICKNESS) / row_height;
+        if grid_height == 0 || image_dimensions.height - Self::image_dimensions_columns(&tile_kind, columns, grid_height as usize).height > LENIENT_MAX_MARGIN {
+            return None
         }
+        Some((tile_kind, grid_height as usize))
+    }
+
+    pub fn load_from_image<P: AsRef<Path>>(path: P) -> Result<Self, LoadError> {
+        Self::load_from_image_fit(path, None)
+    }
+
+    pub fn load_from_image_fit<P: AsRef<Path>>(path: P, fit: Option<Fit>) -> Result<Self, LoadError> {
+        Self::load_from_image_decoded(path, fit, ImageDecoding::default(), Validation::Strict, Layout::default())
+    }
+
+    // only the number of columns of the layout is used, the number of rows follows the image height
+    pub fn load_from_image_decoded<P: AsRef<Path>>(path: P, fit: Option<Fit>, decoding: ImageDecoding, validation: Validation, layout: Layout) -> Result<Self, LoadError> {
+        let image = read_rgba_image_file(&path, decoding)?;
+        Self::from_rgba_image(path, image, fit, validation, layout)
+    }
 
-        Ok(Self(tiles_container))
+    // in-memory counterpart of `load_from_image` e.g. for an image received by a web service
+    pub fn from_image(image: DynamicImage) -> Result<Self, LoadError> {
+        Self::from_image_fit(image, None, Validation::Strict, Layout::default())
     }
 
-    pub fn load_from_image_norm<P: AsRef<Path>>(dir: P, tile_kind: TileKind, ident: &Option<&str>) -> Result<Self, LoadError> {
-        Self::load_from_image(normalized_image_file_path(dir, tile_kind, ident))
+    pub fn from_image_fit(image: DynamicImage, fit: Option<Fit>, validation: Validation, layout: Layout) -> Result<Self, LoadError> {
+        Self::from_rgba_image(IN_MEMORY_IMAGE_NAME, image.into_rgba8(), fit, validation, layout)
     }
 
-    fn image_dimensions(tile_kind: &tile::Kind, height: usize) -> ImageDimensions {
+    fn from_rgba_image<P: AsRef<Path>>(path: P, image: Image, fit: Option<Fit>, validation: Validation, layout: Layout) -> Result<Self, LoadError> {
+        let columns = layout.columns;
+        let image_dimensions = ImageDimensions::from(image.dimensions());
+        let lenient_kind_and_height = match validation {
+            Validation::Strict => None,
+            Validation::Lenient => Self::lenient_tile_kind_and_grid_height(image_dimensions, columns),
+        };
+        let ((tile_kind, grid_height), image) = match (Self::image_tile_kind_and_grid_height_columns(image_dimensions, columns), lenient_kind_and_height, fit) {
+            (Ok(kind_and_height), _, _) => (kind_and_height, image),
+            (Err(_), Some((tile_kind, grid_height)), _) => {
+                let grid_dimensions = Self::image_dimensions_columns(&tile_kind, columns, grid_height);
+                log::warn!("{}: grid image is {image_dimensions}, ignoring {}px on the right and {}px at the bottom of the {grid_dimensions} grid",
+                    path.as_ref().to_string_lossy(),
+                    image_dimensions.width - grid_dimensions.width,
+                    image_dimensions.height - grid_dimensions.height,
+                );
+                ((tile_kind, grid_height), image)
+            },
+            (Err(error), None, Some(fit)) => {
+                let (tile_kind, grid_height) = Self::closest_tile_kind_and_grid_height(image_dimensions, columns);
+                let image = fit_image(&path, &image, Self::image_dimensions_columns(&tile_kind, columns, grid_height), fit).ok_or(error)?;
+                ((tile_kind, grid_height), image)
+            },
+            (Err(error), None, None) => return Err(error.into()),
+        };
+        log::debug!("detected {tile_kind} kind of tiles in a {columns}x{grid_height} grid in {}", path.as_ref().to_string_lossy());
+        Ok(Self::from_image_columns(&image, tile_kind, columns, grid_height))
+    }
+
+    pub(crate) fn split_image(image: &Image, tile_kind: TileKind, grid_height: usize) -> Self {
+        Self::from_image_columns(image, tile_kind, WIDTH, grid_height)
+    }
+
+    // the image has to be at least as large as the grid, anything beyond the grid is ignored
+    fn from_image_columns(image: &Image, tile_kind: TileKind, columns: usize, grid_height: usize) -> Self {
+        let tile_dimensions = tile_kind.dimensions();
+        let tile_indices: Vec<usize> = (0..columns * grid_height).collect();
+        let tiles = parallel::map(&tile_indices, |_, index| {
+            let (x, y) = Self::index_to_grid_coordinates_columns(*index, columns);
+            let (tile_pos_x, tile_pos_y) = Self::image_tile_position(&tile_kind, x as u32, y as u32);
+            let tile_view = image.view(tile_pos_x, tile_pos_y, tile_dimensions.width, tile_dimensions.height).to_image();
+            Tile::try_from(tile_view).unwrap()
+        });
+
+        Self(tiles)
+    }
+
+    pub fn load_from_image_norm<P: AsRef<Path>>(dir: P, tile_kind: TileKind, ident: &Option<&str>, fit: Option<Fit>, decoding: ImageDecoding, validation: Validation, layout: Layout) -> Result<Self, LoadError> {
+        let path = ImageFormat::find_file_path(dir, &normalized_image_file_stem(tile_kind, ident))?;
+        Self::load_from_image_decoded(path, fit, decoding, validation, layout)
+    }
+
+    pub(crate) fn image_dimensions(tile_kind: &tile::Kind, height: usize) -> ImageDimensions {
+        Self::image_dimensions_columns(tile_kind, WIDTH, height)
+    }
+
+    fn image_dimensions_columns(tile_kind: &tile::Kind, columns: usize, height: usize) -> ImageDimensions {
         let tile_dimensions = tile_kind.dimensions();
         ImageDimensions {
-            width: WIDTH as u32 * tile_dimensions.width() + (WIDTH as u32 - 1) * SEPARATOR_THICKNESS,
+            width: columns as u32 * tile_dimensions.width() + (columns as u32 - 1) * SEPARATOR_THICKNESS,
             height: height as u32 * tile_dimensions.height() + (height as u32 - 1) * SEPARATOR_THICKNESS
         }
     }
@@ -133,12 +324,35 @@ impl Grid {
     }
 
     pub fn generate_image(&self) -> Result<Image, TileKindError> {
+        self.generate_image_columns(WIDTH, self.height())
+    }
+
+    // the annotation is for presentation only, annotated images cannot be loaded back
+    pub fn generate_annotated_image(&self, layout: Layout, annotation: Option<Annotation>) -> Result<Image, SaveImageError> {
+        let rows = layout.rows_for_tile_count(self.0.len());
+        CapacityError::check(self.0.len(), layout.columns * rows)?;
+        let mut image = match layout.rows {
+            Some(_) => {
+                let tile_kind = self.tile_kind()?;
+                let mut tiles = self.0.clone();
+                tiles.resize(layout.columns * rows, Tile::new(tile_kind));
+                Self(tiles).generate_image_columns(layout.columns, rows)?
+            },
+            None => self.generate_image_columns(layout.columns, rows)?,
+        };
+        if let Some(annotation) = annotation {
+            annotate(&mut image, self.tile_kind()?, self.0.len(), layout.columns, annotation);
+        }
+        Ok(image)
+    }
+
+    fn generate_image_columns(&self, columns: usize, rows: usize) -> Result<Image, TileKindError> {
         let tile_kind = self.tile_kind()?;
-        let img_dim = Self::image_dimensions(&tile_kind, self.height());
+        let img_dim = Self::image_dimensions_columns(&tile_kind, columns, rows);
         let mut image = Image::from_pixel(img_dim.width(), img_dim.height(), Rgba::from([0, 0, 0, 255]));
 
         for (index, tile) in self.0.iter().enumerate() {
-            let (x, y) = Self::index_to_grid_coordinates(index);
+            let (x, y) = Self::index_to_grid_coordinates_columns(index, columns);
             let (tile_x_position, tile_y_position) = Self::image_tile_position(&tile_kind, x as u32, y as u32);
             image.copy_from(tile.image(), tile_x_position, tile_y_position).unwrap();
         }
@@ -146,22 +360,36 @@ impl Grid {
         Ok(image)
     }
 
-    pub fn normalized_image_file_name(&self, ident: &Option<&str>) -> Result<PathBuf, TileKindError> {
-        Ok(normalized_image_file_name(self.tile_kind()?, ident))
+    // in-memory counterpart of `save_image_annotated` without annotation, the image file content in the requested format
+    pub fn encode_image(&self, image_format: ImageFormat, layout: Layout) -> Result<Vec<u8>, SaveImageError> {
+        let image = self.generate_annotated_image(layout, None)?;
+        Ok(encode_image(&image, IN_MEMORY_IMAGE_NAME, image_format)?)
     }
 
-    pub fn normalized_image_file_path<P: AsRef<Path>>(&self, dir: P, ident: &Option<&str>) -> Result<PathBuf, TileKindError> {
-        Ok(normalized_image_file_path(dir, self.tile_kind()?, ident))
+    pub fn normalized_image_file_name(&self, ident: &Option<&str>, image_format: ImageFormat) -> Result<PathBuf, TileKindError> {
+        Ok(normalized_image_file_name(self.tile_kind()?, ident, image_format))
+    }
+
+    pub fn normalized_image_file_path<P: AsRef<Path>>(&self, dir: P, ident: &Option<&str>, image_format: ImageFormat) -> Result<PathBuf, TileKindError> {
+        Ok(normalized_image_file_path(dir, self.tile_kind()?, ident, image_format))
     }
 
     pub fn save_image<P: AsRef<Path>>(&self, path: P) -> Result<(), SaveImageError> {
-        self.generate_image()?.write_image_file(path)?;
+        self.save_image_annotated(path, Layout::default(), None)
+    }
+
+    pub fn save_image_annotated<P: AsRef<Path>>(&self, path: P, layout: Layout, annotation: Option<Annotation>) -> Result<(), SaveImageError> {
+        self.generate_annotated_image(layout, annotation)?.write_image_file(path)?;
         Ok(())
     }
 
-    pub fn save_image_norm<P: AsRef<Path>>(&self, dir: P, ident: &Option<&str>) -> Result<(), SaveImageError> {
+    pub fn save_image_norm<P: AsRef<Path>>(&self, dir: P, ident: &Option<&str>, image_format: ImageFormat) -> Result<(), SaveImageError> {
+        self.save_image_norm_annotated(dir, ident, image_format, Layout::default(), None)
+    }
+
+    pub fn save_image_norm_annotated<P: AsRef<Path>>(&self, dir: P, ident: &Option<&str>, image_format: ImageFormat, layout: Layout, annotation: Option<Annotation>) -> Result<(), SaveImageError> {
         create_path(&dir)?;
-        self.save_image(self.normalized_image_file_path(&dir, ident)?)
+        self.save_image_annotated(self.normalized_image_file_path(&dir, ident, image_format)?, layout, annotation)
     }
 
 }
@@ -186,7 +414,7 @@ impl From<&[Tile]> for Grid {
     }
 }
 
-pub fn normalized_image_file_name(tile_kind: TileKind, ident: &Option<&str>) -> PathBuf {
+pub fn normalized_image_file_stem(tile_kind: TileKind, ident: &Option<&str>) -> String {
     let tile_kind_str = match tile_kind {
         TileKind::SD => "_sd",
         TileKind::HD => "_hd",
@@ -195,11 +423,15 @@ pub fn normalized_image_file_name(tile_kind: TileKind, ident: &Option<&str>) ->
         Some(ident) => format!("_{ident}"),
         None => "".to_owned(),
     };
-    PathBuf::from(format!("grid{ident}{tile_kind_str}.png"))
+    format!("grid{ident}{tile_kind_str}")
 }
 
-pub fn normalized_image_file_path<P: AsRef<Path>>(dir: P, tile_kind: TileKind, ident: &Option<&str>) -> PathBuf {
-    [dir.as_ref().to_path_buf(), normalized_image_file_name(tile_kind, ident)].into_iter().collect()
+pub fn normalized_image_file_name(tile_kind: TileKind, ident: &Option<&str>, image_format: ImageFormat) -> PathBuf {
+    PathBuf::from(format!("{}.{}", normalized_image_file_stem(tile_kind, ident), image_format.extension()))
+}
+
+pub fn normalized_image_file_path<P: AsRef<Path>>(dir: P, tile_kind: TileKind, ident: &Option<&str>, image_format: ImageFormat) -> PathBuf {
+    image_format.file_path(dir, &normalized_image_file_stem(tile_kind, ident))
 }
 
 #[derive(Getters)]
@@ -219,18 +451,18 @@ impl Set {
         Ok(())
     }
 
-    pub fn load_from_images<P: AsRef<Path>>(sd_grid_image_path: P, hd_grid_image_path: P) -> Result<Self, LoadError> {
-        let sd_grid = Grid::load_from_image(sd_grid_image_path)?;
+    pub fn load_from_images<P: AsRef<Path>>(sd_grid_image_path: P, hd_grid_image_path: P, fit: Option<Fit>, decoding: ImageDecoding, validation: Validation, layout: Layout) -> Result<Self, LoadError> {
+        let sd_grid = Grid::load_from_image_decoded(sd_grid_image_path, fit, decoding, validation, layout)?;
         Self::check_grid_kind(&sd_grid, TileKind::SD)?;
-        let hd_grid = Grid::load_from_image(hd_grid_image_path)?;
+        let hd_grid = Grid::load_from_image_decoded(hd_grid_image_path, fit, decoding, validation, layout)?;
         Self::check_grid_kind(&hd_grid, TileKind::HD)?;
         Ok(Self { sd_grid, hd_grid })
     }
 
-    pub fn load_from_images_norm<P: AsRef<Path>>(dir: P, ident: &Option<&str>) -> Result<Self, LoadError> {
-        let sd_grid = Grid::load_from_image_norm(&dir, TileKind::SD, ident)?;
+    pub fn load_from_images_norm<P: AsRef<Path>>(dir: P, ident: &Option<&str>, fit: Option<Fit>, decoding: ImageDecoding, validation: Validation, layout: Layout) -> Result<Self, LoadError> {
+        let sd_grid = Grid::load_from_image_norm(&dir, TileKind::SD, ident, fit, decoding, validation, layout)?;
         Self::check_grid_kind(&sd_grid, TileKind::SD)?;
-        let hd_grid = Grid::load_from_image_norm(&dir, TileKind::HD, ident)?;
+        let hd_grid = Grid::load_from_image_norm(&dir, TileKind::HD, ident, fit, decoding, validation, layout)?;
         Self::check_grid_kind(&hd_grid, TileKind::HD)?;
         Ok(Self { sd_grid, hd_grid })
     }
@@ -240,13 +472,114 @@ impl Set {
         self.hd_grid.save_image(hd_grid_path)
     }
 
-    pub fn save_images_norm<P: AsRef<Path>>(&self, dir: P, ident: &Option<&str>) -> Result<(), SaveImageError> {
-        self.sd_grid.save_image_norm(&dir, ident)?;
-        self.hd_grid.save_image_norm(&dir, ident)
+    pub fn save_images_norm<P: AsRef<Path>>(&self, dir: P, ident: &Option<&str>, image_format: ImageFormat) -> Result<(), SaveImageError> {
+        self.sd_grid.save_image_norm(&dir, ident, image_format)?;
+        self.hd_grid.save_image_norm(&dir, ident, image_format)
     }
 
     pub fn into_tile_set(self) -> TileSet {
         TileSet { sd_tiles: self.sd_grid.0, hd_tiles: self.hd_grid.0 }
     }
 
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+    use temp_dir::TempDir;
+
+    use crate::image::WriteImageFile;
+    use crate::image_format::{ImageDecoding, ImageFormat};
+    use crate::osd::tile::{Tile, Kind as TileKind};
+
+    use super::{Grid, Image, ImageDimensions, Layout, SaveImageError, Validation};
+
+    fn write_grid_image(temp_dir: &TempDir, name: &str, width: u32, height: u32) -> std::path::PathBuf {
+        let path = temp_dir.child(name);
+        Image::from_pixel(width, height, Rgba([0, 0, 0, 255])).write_image_file(&path).unwrap();
+        path
+    }
+
+    #[test]
+    fn in_memory() {
+        let mut tiles = vec![Tile::new(TileKind::HD); 20];
+        tiles[17].put_pixel(3, 4, Rgba([255, 0, 0, 255]));
+        let grid = Grid::from(tiles);
+        let bytes = grid.encode_image(ImageFormat::Png, Layout::default()).unwrap();
+        let loaded = Grid::from_image(image::load_from_memory(&bytes).unwrap()).unwrap();
+        assert_eq!(loaded.len(), 32);
+        assert_eq!(loaded[(1, 1)].as_raw(), grid[(1, 1)].as_raw());
+        assert!(Grid::from_image(image::DynamicImage::new_rgba8(10, 10)).is_err());
+    }
+
+    #[test]
+    fn lenient_dimensions() {
+        let temp_dir = TempDir::new().unwrap();
+        let grid_dimensions = Grid::image_dimensions(&TileKind::SD, 2);
+
+        let bordered = write_grid_image(&temp_dir, "bordered.png", grid_dimensions.width + 2, grid_dimensions.height + 2);
+        assert!(Grid::load_from_image_decoded(&bordered, None, ImageDecoding::default(), Validation::Strict, Layout::default()).is_err());
+        let grid = Grid::load_from_image_decoded(&bordered, None, ImageDecoding::default(), Validation::Lenient, Layout::default()).unwrap();
+        assert_eq!(grid.len(), 32);
+
+        let right_border = write_grid_image(&temp_dir, "right_border.png", grid_dimensions.width + 8, grid_dimensions.height);
+        assert!(Grid::load_from_image_decoded(&right_border, None, ImageDecoding::default(), Validation::Strict, Layout::default()).is_err());
+        assert_eq!(Grid::load_from_image_decoded(&right_border, None, ImageDecoding::default(), Validation::Lenient, Layout::default()).unwrap().len(), 32);
+
+        let bottom_border = write_grid_image(&temp_dir, "bottom_border.png", grid_dimensions.width, grid_dimensions.height + 5);
+        assert!(Grid::load_from_image_decoded(&bottom_border, None, ImageDecoding::default(), Validation::Strict, Layout::default()).is_err());
+        assert_eq!(Grid::load_from_image_decoded(&bottom_border, None, ImageDecoding::default(), Validation::Lenient, Layout::default()).unwrap().len(), 32);
+
+        let wide_border = write_grid_image(&temp_dir, "wide_border.png", grid_dimensions.width + 9, grid_dimensions.height);
+        assert!(Grid::load_from_image_decoded(&wide_border, None, ImageDecoding::default(), Validation::Lenient, Layout::default()).is_err());
+
+        let short = write_grid_image(&temp_dir, "short.png", grid_dimensions.width, grid_dimensions.height - 10);
+        assert!(Grid::load_from_image_decoded(&short, None, ImageDecoding::default(), Validation::Strict, Layout::default()).is_err());
+        assert!(Grid::load_from_image_decoded(&short, None, ImageDecoding::default(), Validation::Lenient, Layout::default()).is_err());
+
+        // a grid short by a whole row is a valid grid of one row less, the lenient mode does not make up the row
+        let row_short = Grid::image_dimensions(&TileKind::SD, 1);
+        let row_short = write_grid_image(&temp_dir, "row_short.png", row_short.width, row_short.height);
+        assert_eq!(Grid::load_from_image_decoded(&row_short, None, ImageDecoding::default(), Validation::Strict, Layout::default()).unwrap().len(), 16);
+        assert_eq!(Grid::load_from_image_decoded(&row_short, None, ImageDecoding::default(), Validation::Lenient, Layout::default()).unwrap().len(), 16);
+    }
+
+    #[test]
+    fn invalid_dimensions_error() {
+        let error = Grid::image_tile_kind_and_grid_height(ImageDimensions { width: 24, height: 9216 }).unwrap_err();
+        let message = error.to_string();
+        assert!(message.contains("24x9216"));
+        assert!(message.contains(&format!("SD tiles: {}px wide", Grid::image_dimensions(&TileKind::SD, 1).width)));
+        assert!(message.contains(&format!("HD tiles: {}px wide", Grid::image_dimensions(&TileKind::HD, 1).width)));
+    }
+
+    #[test]
+    fn layouts() {
+        assert_eq!("32x8".parse::<Layout>().unwrap(), Layout::new(32, Some(8)));
+        assert_eq!("1".parse::<Layout>().unwrap(), Layout::new(1, None));
+        assert!("0x16".parse::<Layout>().is_err());
+        assert!("16x".parse::<Layout>().is_err());
+        assert_eq!(Layout::default().to_string(), "16");
+
+        let temp_dir = TempDir::new().unwrap();
+        let mut tiles = vec![Tile::new(TileKind::HD); 20];
+        tiles[19].put_pixel(2, 2, Rgba([255, 255, 255, 255]));
+        let grid = Grid::from(tiles.clone());
+
+        let column_path = temp_dir.child("column.png");
+        grid.save_image_annotated(&column_path, Layout::new(1, None), None).unwrap();
+        assert_eq!(image::image_dimensions(&column_path).unwrap(), (24, 20 * 36 + 19 * 2));
+        let loaded = Grid::load_from_image_decoded(&column_path, None, ImageDecoding::default(), Validation::Strict, Layout::new(1, None)).unwrap().to_vec();
+        assert_eq!(loaded.len(), 20);
+        assert_eq!(loaded[19].image(), tiles[19].image());
+
+        let fixed_path = temp_dir.child("fixed.png");
+        grid.save_image_annotated(&fixed_path, Layout::new(8, Some(4)), None).unwrap();
+        let loaded = Grid::load_from_image_decoded(&fixed_path, None, ImageDecoding::default(), Validation::Strict, Layout::new(8, None)).unwrap().to_vec();
+        assert_eq!(loaded.len(), 32);
+        assert_eq!(loaded[19].image(), tiles[19].image());
+        assert!(loaded[31].pixels().all(|pixel| pixel.0[3] == 0));
+
+        assert!(matches!(grid.save_image_annotated(&fixed_path, Layout::new(8, Some(2)), None), Err(SaveImageError::CapacityError(_))));
+    }
+}
@@ -0,0 +1,111 @@
+use clap::ValueEnum;
+use image::Rgba;
+
+use super::{
+    Kind as TileKind,
+    grid::{Grid, Image, SEPARATOR_THICKNESS},
+    preview::{digits_width, draw_digits},
+};
+use crate::osd::bin_file;
+
+// Overlays for the grid images used to document the layout of a font e.g. on wiki pages: the index of each tile in
+// its top left corner and optionally the boundaries of the bin file pages drawn in the separators between the tiles.
+// Annotated grids are for presentation only and cannot be loaded back.
+
+const LABEL_COLOR: Rgba<u8> = Rgba([255, 255, 0, 255]);
+const PAGE_SEPARATOR_COLOR: Rgba<u8> = Rgba([255, 0, 0, 255]);
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum, strum::Display)]
+#[strum(serialize_all = "kebab-case")]
+pub enum Annotation {
+    // tile indexes
+    Indexes,
+    // tile indexes and bin file page separators
+    Pages,
+}
+
+impl Annotation {
+
+    fn page_separators(&self) -> bool {
+        matches!(self, Annotation::Pages)
+    }
+
+}
+
+// the labels are 3x5 pixel digits scaled to fit 3 digits in the width of a tile
+fn label_pixel_size(tile_kind: TileKind) -> u32 {
+    (tile_kind.dimensions().width / 12).max(1)
+}
+
+// darkens the pixels under the label so that it stands out on light tiles, transparent pixels become partly opaque
+fn draw_label_background(image: &mut Image, x: u32, y: u32, width: u32, height: u32) {
+    for pixel_y in y..(y + height).min(image.height()) {
+        for pixel_x in x..(x + width).min(image.width()) {
+            let Rgba([red, green, blue, alpha]) = *image.get_pixel(pixel_x, pixel_y);
+            image.put_pixel(pixel_x, pixel_y, Rgba([red / 3, green / 3, blue / 3, alpha.max(160)]));
+        }
+    }
+}
+
+fn fill_rect(image: &mut Image, x: u32, y: u32, width: u32, height: u32, color: Rgba<u8>) {
+    for pixel_y in y..(y + height).min(image.height()) {
+        for pixel_x in x..(x + width).min(image.width()) {
+            image.put_pixel(pixel_x, pixel_y, color);
+        }
+    }
+}
+
+// a tile starts a new page along its top edge when the tile above it is in a previous page and along its left edge
+// when it is the first tile of a page, which holds for any number of columns
+fn draw_page_separators(image: &mut Image, tile_kind: TileKind, tile_count: usize, columns: usize) {
+    let tile_dimensions = tile_kind.dimensions();
+    let page = |index: usize| index / bin_file::TILE_COUNT;
+    for index in bin_file::TILE_COUNT..tile_count {
+        let (x, y) = Grid::index_to_grid_coordinates_columns(index, columns);
+        let (tile_x, tile_y) = Grid::image_tile_position(&tile_kind, x as u32, y as u32);
+        if y > 0 && page(index - columns) < page(index) {
+            let separator_x = tile_x.saturating_sub(SEPARATOR_THICKNESS);
+            fill_rect(image, separator_x, tile_y - SEPARATOR_THICKNESS, tile_x - separator_x + tile_dimensions.width, SEPARATOR_THICKNESS, PAGE_SEPARATOR_COLOR);
+        }
+        if x > 0 && index % bin_file::TILE_COUNT == 0 {
+            fill_rect(image, tile_x - SEPARATOR_THICKNESS, tile_y, SEPARATOR_THICKNESS, tile_dimensions.height, PAGE_SEPARATOR_COLOR);
+        }
+    }
+}
+
+// `tile_count` is the number of actual tiles of the grid, the tiles completing a fixed number of rows are not labeled
+pub(crate) fn annotate(image: &mut Image, tile_kind: TileKind, tile_count: usize, columns: usize, annotation: Annotation) {
+    let pixel_size = label_pixel_size(tile_kind);
+    for index in 0..tile_count {
+        let (x, y) = Grid::index_to_grid_coordinates_columns(index, columns);
+        let (tile_x, tile_y) = Grid::image_tile_position(&tile_kind, x as u32, y as u32);
+        let label = index.to_string();
+        draw_label_background(image, tile_x, tile_y, digits_width(&label, pixel_size) + 2 * pixel_size, 7 * pixel_size);
+        draw_digits(image, &label, tile_x + pixel_size, tile_y + pixel_size, pixel_size, LABEL_COLOR);
+    }
+    if annotation.page_separators() {
+        draw_page_separators(image, tile_kind, tile_count, columns);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use image::Rgba;
+
+    use crate::osd::tile::{Kind as TileKind, Tile, grid::{Grid, Layout}};
+    use super::Annotation;
+
+    #[test]
+    fn page_separators() {
+        let grid = Grid::from(vec![Tile::new(TileKind::SD); 512]);
+        let image = grid.generate_annotated_image(Layout::default(), Some(Annotation::Pages)).unwrap();
+        // first pixel of the separator between the rows 15 and 16 which are the last row of the first page and the
+        // first row of the second page
+        let (_, tile_y) = Grid::image_tile_position(&TileKind::SD, 0, 16);
+        assert_eq!(image.get_pixel(0, tile_y - 1), &Rgba([255, 0, 0, 255]));
+        let image = grid.generate_annotated_image(Layout::default(), Some(Annotation::Indexes)).unwrap();
+        assert_ne!(image.get_pixel(0, tile_y - 1), &Rgba([255, 0, 0, 255]));
+    }
+
+}
@@ -0,0 +1,131 @@
+
+use std::path::Path;
+
+use image::{GenericImage, GenericImageView};
+use thiserror::Error;
+
+use super::{Tile, Kind as TileKind, Image};
+
+use crate::{
+    dimensions,
+    image_format::ImageDecoding,
+    osd::tile::container::{CapacityError, uniq_tile_kind::{UniqTileKind, TileKindError}},
+    image::{
+        read_rgba_image_file,
+        WriteImageFile,
+        ReadError as ImageLoadError,
+        WriteError as ImageWriteError,
+    },
+};
+
+pub type ImageDimensions = dimensions::Dimensions<u32>;
+
+// Font bitmap of the HDZero goggles: a single sheet of 16 columns by 32 rows of HD tiles without separators,
+// the tile at index N is at column N % 16 and row N / 16
+pub const WIDTH: u32 = 16;
+pub const HEIGHT: u32 = 32;
+pub const TILE_COUNT: usize = (WIDTH * HEIGHT) as usize;
+
+#[derive(Debug, Error)]
+pub enum LoadError {
+    #[error(transparent)]
+    ImageLoadError(#[from] ImageLoadError),
+    #[error("HDZero font image dimensions {0} do not match the {expected} sheet of 16x32 HD tiles", expected = image_dimensions())]
+    InvalidImageDimensions(ImageDimensions),
+}
+
+#[derive(Debug, Error)]
+pub enum SaveImageError {
+    #[error(transparent)]
+    ImageWriteError(#[from] ImageWriteError),
+    #[error(transparent)]
+    TileKindError(#[from] TileKindError),
+    #[error("HDZero fonts only contain HD tiles, got {0} tiles")]
+    NotHDTiles(TileKind),
+    #[error(transparent)]
+    CapacityError(#[from] CapacityError),
+}
+
+pub fn image_dimensions() -> ImageDimensions {
+    let tile_dimensions = TileKind::HD.dimensions();
+    ImageDimensions::new(WIDTH * tile_dimensions.width, HEIGHT * tile_dimensions.height)
+}
+
+fn tile_position(index: usize) -> (u32, u32) {
+    let tile_dimensions = TileKind::HD.dimensions();
+    let index = index as u32;
+    ((index % WIDTH) * tile_dimensions.width, (index / WIDTH) * tile_dimensions.height)
+}
+
+pub fn load_image<P: AsRef<Path>>(path: P) -> Result<Vec<Tile>, LoadError> {
+    load_image_decoded(path, ImageDecoding::default())
+}
+
+pub fn load_image_decoded<P: AsRef<Path>>(path: P, decoding: ImageDecoding) -> Result<Vec<Tile>, LoadError> {
+    let image = read_rgba_image_file(&path, decoding)?;
+    let dimensions = ImageDimensions::from(image.dimensions());
+    if dimensions != image_dimensions() {
+        return Err(LoadError::InvalidImageDimensions(dimensions));
+    }
+
+    let tile_dimensions = TileKind::HD.dimensions();
+    let tiles = (0..TILE_COUNT).map(|index| {
+        let (x, y) = tile_position(index);
+        Tile::try_from(image.view(x, y, tile_dimensions.width, tile_dimensions.height).to_image()).unwrap()
+    }).collect();
+
+    Ok(tiles)
+}
+
+// the tiles missing to fill the sheet are left transparent
+pub fn generate_image(tiles: &[Tile]) -> Result<Image, SaveImageError> {
+    let tile_kind = tiles.tile_kind()?;
+    if tile_kind != TileKind::HD {
+        return Err(SaveImageError::NotHDTiles(tile_kind));
+    }
+    CapacityError::check(tiles.len(), TILE_COUNT)?;
+
+    let dimensions = image_dimensions();
+    let mut image = Image::new(dimensions.width, dimensions.height);
+    for (index, tile) in tiles.iter().enumerate() {
+        let (x, y) = tile_position(index);
+        image.copy_from(tile.image(), x, y).unwrap();
+    }
+    Ok(image)
+}
+
+pub fn save_image<P: AsRef<Path>>(tiles: &[Tile], path: P) -> Result<(), SaveImageError> {
+    generate_image(tiles)?.write_image_file(path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+    use temp_dir::TempDir;
+
+    use crate::osd::tile::{Tile, Kind};
+
+    use super::{load_image, save_image, LoadError, SaveImageError, TILE_COUNT};
+
+    #[test]
+    fn round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.child("BTFL_000.png");
+        let mut tiles = vec![Tile::new(Kind::HD); 300];
+        tiles[17].put_pixel(3, 5, Rgba([255, 255, 255, 255]));
+        save_image(&tiles, &path).unwrap();
+
+        let image = image::open(&path).unwrap().into_rgba8();
+        assert_eq!(image.dimensions(), (16 * 24, 32 * 36));
+        assert_eq!(*image.get_pixel(24 + 3, 36 + 5), Rgba([255, 255, 255, 255]));
+
+        let loaded = load_image(&path).unwrap();
+        assert_eq!(loaded.len(), TILE_COUNT);
+        assert_eq!(loaded[17].image(), tiles[17].image());
+
+        assert!(matches!(save_image(&[Tile::new(Kind::SD)], &path), Err(SaveImageError::NotHDTiles(Kind::SD))));
+        image::imageops::crop_imm(&image, 0, 0, 384, 576).to_image().save(&path).unwrap();
+        assert!(matches!(load_image(&path), Err(LoadError::InvalidImageDimensions(_))));
+    }
+}
@@ -0,0 +1,148 @@
+use std::path::Path;
+
+use derive_more::From;
+use image::{imageops, Rgba, RgbaImage};
+use strum::IntoEnumIterator;
+use thiserror::Error;
+
+use super::{
+    Tile,
+    Kind as TileKind,
+    container::{
+        tile_set::TileSet,
+        uniq_tile_kind::{TileKindError, TilesIterUniqTileKind},
+    },
+};
+use crate::{
+    create_path::{create_path, CreatePathError},
+    image::{WriteImageFile, WriteError as ImageWriteError},
+    osd::bin_file,
+};
+
+// Mock of a Betaflight OSD screen rendered with a font to judge its readability before flashing it: RSSI, battery,
+// current, consumption, speed, flight time, altitude, crosshair and a warning laid out on the character grid of the
+// screen over a sky/ground background. The symbols are taken at their Betaflight indices and the text at the index of
+// the ASCII code of the characters.
+
+const SYM_RSSI: usize = 0x01;
+const SYM_VOLT: usize = 0x06;
+const SYM_MAH: usize = 0x07;
+const SYM_M: usize = 0x0c;
+const SYM_SPEED: usize = 0x70;
+const SYM_AH_CENTER_LINE: usize = 0x72;
+const SYM_AH_CENTER: usize = 0x73;
+const SYM_AH_CENTER_LINE_RIGHT: usize = 0x74;
+const SYM_ALTITUDE: usize = 0x7f;
+const SYM_BATT_FULL: usize = 0x90;
+const SYM_AMP: usize = 0x9a;
+const SYM_FLY_M: usize = 0x9c;
+const SYM_KPH: usize = 0x9e;
+
+const SKY_COLOR: Rgba<u8> = Rgba([110, 140, 170, 255]);
+const GROUND_COLOR: Rgba<u8> = Rgba([90, 80, 60, 255]);
+
+#[derive(Debug, Error, From)]
+pub enum RenderOsdMockError {
+    #[error(transparent)]
+    TileKindError(TileKindError),
+    #[from(ignore)]
+    #[error("the OSD mock needs a full page of {page_tile_count} tiles, the font only contains {0}", page_tile_count = bin_file::TILE_COUNT)]
+    NotEnoughTiles(usize),
+}
+
+#[derive(Debug, Error, From)]
+pub enum SaveOsdMocksError {
+    #[error(transparent)]
+    RenderError(RenderOsdMockError),
+    #[error(transparent)]
+    CreatePathError(CreatePathError),
+    #[error(transparent)]
+    ImageWriteError(ImageWriteError),
+}
+
+// columns and rows of the character grid: PAL analog screen for SD, Betaflight HD canvas for HD
+pub fn screen_grid(tile_kind: TileKind) -> (u32, u32) {
+    match tile_kind {
+        TileKind::SD => (30, 16),
+        TileKind::HD => (53, 20),
+    }
+}
+
+fn text(text: &str) -> impl Iterator<Item = usize> + '_ {
+    text.bytes().map(usize::from)
+}
+
+// elements as column, row and tile indices
+fn layout(columns: u32, rows: u32) -> Vec<(u32, u32, Vec<usize>)> {
+    let (center_column, center_row) = (columns / 2, rows / 2);
+    let warning = "LOW BATTERY";
+    vec![
+        (1, 1, [SYM_RSSI].into_iter().chain(text("99")).collect()),
+        (columns - 7, 1, [SYM_BATT_FULL].into_iter().chain(text("16.4")).chain([SYM_VOLT]).collect()),
+        (columns - 7, 2, text("12.50").chain([SYM_AMP]).collect()),
+        (1, center_row, [SYM_SPEED].into_iter().chain(text("45")).chain([SYM_KPH]).collect()),
+        (center_column - 1, center_row, vec![SYM_AH_CENTER_LINE, SYM_AH_CENTER, SYM_AH_CENTER_LINE_RIGHT]),
+        (center_column - warning.len() as u32 / 2, rows * 2 / 3, text(warning).collect()),
+        (1, rows - 2, [SYM_FLY_M].into_iter().chain(text("02:15")).collect()),
+        (center_column - 2, rows - 2, text("850").chain([SYM_MAH]).collect()),
+        (columns - 7, rows - 2, [SYM_ALTITUDE].into_iter().chain(text("12.3")).chain([SYM_M]).collect()),
+    ]
+}
+
+// the background is split in sky and ground at the horizon when no color is given
+pub fn render_osd_mock(tiles: &[Tile], background: Option<Rgba<u8>>) -> Result<RgbaImage, RenderOsdMockError> {
+    let tile_kind = tiles.iter().tile_kind()?;
+    if tiles.len() < bin_file::TILE_COUNT {
+        return Err(RenderOsdMockError::NotEnoughTiles(tiles.len()));
+    }
+    let tile_dimensions = tile_kind.dimensions();
+    let (columns, rows) = screen_grid(tile_kind);
+    let (width, height) = (columns * tile_dimensions.width, rows * tile_dimensions.height);
+    let mut image = RgbaImage::from_fn(width, height, |_, y| match background {
+        Some(color) => color,
+        None if y < height / 2 => SKY_COLOR,
+        None => GROUND_COLOR,
+    });
+    for (column, row, indices) in layout(columns, rows) {
+        for (offset, index) in indices.into_iter().enumerate() {
+            let x = (column + offset as u32) * tile_dimensions.width;
+            imageops::overlay(&mut image, tiles[index].image(), x as i64, (row * tile_dimensions.height) as i64);
+        }
+    }
+    Ok(image)
+}
+
+// writes osd_sd.png and osd_hd.png in `dir`
+pub fn save_osd_mocks<P: AsRef<Path>>(tile_set: &TileSet, dir: P, background: Option<Rgba<u8>>) -> Result<(), SaveOsdMocksError> {
+    create_path(&dir)?;
+    for tile_kind in TileKind::iter() {
+        let path = dir.as_ref().join(format!("osd_{}.png", tile_kind.to_string().to_lowercase()));
+        render_osd_mock(tile_set.tiles(tile_kind), background)?.write_image_file(path)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+
+    use crate::osd::tile::{Tile, Kind};
+
+    use super::{render_osd_mock, screen_grid, RenderOsdMockError, SYM_AH_CENTER, SKY_COLOR};
+
+    #[test]
+    fn osd_mock() {
+        let mut tiles = vec![Tile::new(Kind::HD); 256];
+        for pixel in tiles[SYM_AH_CENTER].pixels_mut() {
+            *pixel = Rgba([255, 255, 255, 255]);
+        }
+        let image = render_osd_mock(&tiles, None).unwrap();
+        let (columns, rows) = screen_grid(Kind::HD);
+        let dimensions = Kind::HD.dimensions();
+        assert_eq!(image.dimensions(), (columns * dimensions.width, rows * dimensions.height));
+        assert_eq!(image.get_pixel(0, 0), &SKY_COLOR);
+        assert_eq!(image.get_pixel(columns / 2 * dimensions.width, rows / 2 * dimensions.height), &Rgba([255, 255, 255, 255]));
+
+        assert!(matches!(render_osd_mock(&tiles[..100], None), Err(RenderOsdMockError::NotEnoughTiles(100))));
+    }
+}
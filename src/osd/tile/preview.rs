@@ -0,0 +1,164 @@
+use std::{io::Error as IOError, path::Path};
+
+use derive_more::From;
+use image::{imageops, Rgba, RgbaImage};
+use thiserror::Error;
+
+use super::{
+    Tile,
+    container::uniq_tile_kind::{TileKindError, TilesIterUniqTileKind},
+};
+use crate::{
+    atomic_file,
+    base64,
+    image::{encode_image, upscale, WriteImageFile, WriteError as ImageWriteError},
+    image_format::ImageFormat,
+    osd::bin_file,
+};
+
+// Contact sheets to review a whole font at a glance: the tiles are laid out 16 per row with their index under each
+// of them and the bin file pages separated by a wider gap. The sheet is either a PNG image or a standalone HTML page
+// embedding the tiles, chosen from the extension of the written file.
+
+const COLUMNS: usize = 16;
+const CELL_SPACING: u32 = 4;
+const PAGE_SPACING: u32 = 24;
+// size of the pixels of the index labels
+const LABEL_PIXEL_SIZE: u32 = 2;
+const BACKGROUND_COLOR: Rgba<u8> = Rgba([64, 64, 64, 255]);
+const LABEL_COLOR: Rgba<u8> = Rgba([255, 255, 255, 255]);
+
+// 3x5 pixels digits, one row per byte with the leftmost pixel in the third bit
+pub(crate) const DIGITS: [[u8; 5]; 10] = [
+    [7, 5, 5, 5, 7], [2, 6, 2, 2, 7], [7, 1, 7, 4, 7], [7, 1, 7, 1, 7], [5, 5, 7, 1, 1],
+    [7, 4, 7, 1, 7], [7, 4, 7, 5, 7], [7, 1, 1, 1, 1], [7, 5, 7, 5, 7], [7, 5, 7, 1, 7],
+];
+
+#[derive(Debug, Error, From)]
+pub enum SaveContactSheetError {
+    #[error(transparent)]
+    TileKindError(TileKindError),
+    #[error(transparent)]
+    ImageWriteError(ImageWriteError),
+    #[error("failed to write contact sheet: {0}")]
+    FileError(IOError),
+}
+
+fn label_width(label: &str) -> u32 {
+    digits_width(label, LABEL_PIXEL_SIZE)
+}
+
+pub(crate) fn digits_width(digits: &str, pixel_size: u32) -> u32 {
+    (digits.len() as u32 * 4).saturating_sub(1) * pixel_size
+}
+
+// the pixels falling outside of the image are left out
+pub(crate) fn draw_digits(image: &mut RgbaImage, digits: &str, x: u32, y: u32, pixel_size: u32, color: Rgba<u8>) {
+    for (digit_index, digit) in digits.bytes().enumerate() {
+        let rows = DIGITS[(digit - b'0') as usize];
+        for (row_index, row) in rows.iter().enumerate() {
+            for column in (0..3).filter(|column| row & (4 >> column) != 0) {
+                let pixel_x = x + (digit_index as u32 * 4 + column) * pixel_size;
+                let pixel_y = y + row_index as u32 * pixel_size;
+                for (dx, dy) in (0..pixel_size).flat_map(|dy| (0..pixel_size).map(move |dx| (dx, dy))) {
+                    if let Some(pixel) = image.get_pixel_mut_checked(pixel_x + dx, pixel_y + dy) {
+                        *pixel = color;
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn draw_label(image: &mut RgbaImage, label: &str, x: u32, y: u32) {
+    draw_digits(image, label, x, y, LABEL_PIXEL_SIZE, LABEL_COLOR)
+}
+
+// the tiles are upscaled by the integer factor `scale`, the labels keep their size
+pub fn contact_sheet_image(tiles: &[Tile], scale: u32) -> Result<RgbaImage, TileKindError> {
+    let tile_dimensions = tiles.iter().tile_kind()?.dimensions();
+    let (tile_width, tile_height) = (tile_dimensions.width * scale, tile_dimensions.height * scale);
+    let label_height = 5 * LABEL_PIXEL_SIZE;
+    let cell_width = tile_width.max(label_width(&(tiles.len() - 1).to_string())) + CELL_SPACING;
+    let cell_height = tile_height + CELL_SPACING + label_height + CELL_SPACING;
+    let rows_per_page = bin_file::TILE_COUNT / COLUMNS;
+    let row_count = tiles.len().div_ceil(COLUMNS);
+    let page_count = row_count.div_ceil(rows_per_page);
+
+    let width = CELL_SPACING + COLUMNS as u32 * cell_width;
+    let height = CELL_SPACING + row_count as u32 * cell_height + (page_count as u32 - 1) * PAGE_SPACING;
+    let mut image = RgbaImage::from_pixel(width, height, BACKGROUND_COLOR);
+    for (index, tile) in tiles.iter().enumerate() {
+        let row = index / COLUMNS;
+        let cell_x = CELL_SPACING + (index % COLUMNS) as u32 * cell_width;
+        let cell_y = CELL_SPACING + row as u32 * cell_height + (row / rows_per_page) as u32 * PAGE_SPACING;
+        let tile_x = cell_x + (cell_width - CELL_SPACING - tile_width) / 2;
+        imageops::overlay(&mut image, &upscale(tile.image(), scale), tile_x as i64, cell_y as i64);
+        let label = index.to_string();
+        let label_x = cell_x + (cell_width - CELL_SPACING - label_width(&label)) / 2;
+        draw_label(&mut image, &label, label_x, cell_y + tile_height + CELL_SPACING);
+    }
+    Ok(image)
+}
+
+// standalone page: the tiles are embedded as data URIs and displayed pixelated at `scale` times their size
+pub fn contact_sheet_html(tiles: &[Tile], scale: u32) -> Result<String, SaveContactSheetError> {
+    let tile_dimensions = tiles.iter().tile_kind()?.dimensions();
+    let mut html = String::from(concat!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Font preview</title>\n<style>\n",
+        "body { background: #404040; color: #fff; font-family: monospace; }\n",
+        ".page { display: grid; grid-template-columns: repeat(16, max-content); gap: 4px; margin-bottom: 24px; }\n",
+        "figure { margin: 0; text-align: center; }\n",
+        "img { image-rendering: pixelated; display: block; }\n",
+        "</style>\n</head>\n<body>\n",
+    ));
+    for (page_index, page) in tiles.chunks(bin_file::TILE_COUNT).enumerate() {
+        html.push_str(&format!("<h2>Page {}</h2>\n<div class=\"page\">\n", page_index + 1));
+        for (page_tile_index, tile) in page.iter().enumerate() {
+            let index = page_index * bin_file::TILE_COUNT + page_tile_index;
+            let png = encode_image(tile.image(), format!("{index}.png"), ImageFormat::Png)?;
+            html.push_str(&format!("<figure><img width=\"{}\" height=\"{}\" src=\"data:image/png;base64,{}\"><figcaption>{index}</figcaption></figure>\n",
+                tile_dimensions.width * scale, tile_dimensions.height * scale, base64::encode(&png)));
+        }
+        html.push_str("</div>\n");
+    }
+    html.push_str("</body>\n</html>\n");
+    Ok(html)
+}
+
+// HTML page for the .html and .htm extensions, image otherwise
+pub fn save_contact_sheet<P: AsRef<Path>>(tiles: &[Tile], path: P, scale: u32) -> Result<(), SaveContactSheetError> {
+    let is_html = path.as_ref().extension().and_then(|extension| extension.to_str())
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("html") || extension.eq_ignore_ascii_case("htm"));
+    match is_html {
+        true => atomic_file::write(path, contact_sheet_html(tiles, scale)?)?,
+        false => contact_sheet_image(tiles, scale)?.write_image_file(path)?,
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+
+    use crate::osd::tile::{Tile, Kind};
+
+    use super::{contact_sheet_html, contact_sheet_image, BACKGROUND_COLOR};
+
+    #[test]
+    fn contact_sheet() {
+        let mut tile = Tile::new(Kind::SD);
+        tile.put_pixel(0, 0, Rgba([255, 255, 255, 255]));
+        let tiles = vec![tile; 300];
+        let one_page = contact_sheet_image(&tiles[..256], 1).unwrap();
+        let two_pages = contact_sheet_image(&tiles, 1).unwrap();
+        assert_eq!(one_page.width(), two_pages.width());
+        assert!(two_pages.height() > one_page.height() * 300 / 256);
+        assert_eq!(one_page.get_pixel(0, 0), &BACKGROUND_COLOR);
+
+        let html = contact_sheet_html(&tiles, 2).unwrap();
+        assert_eq!(html.matches("<figure>").count(), 300);
+        assert!(html.contains("<h2>Page 2</h2>"));
+        assert!(contact_sheet_image(&[], 1).is_err());
+    }
+}
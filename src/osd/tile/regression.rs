@@ -0,0 +1,179 @@
+use std::path::Path;
+
+use derive_more::{Display, Error, From};
+use image::{GenericImage, Rgba};
+use serde::Serialize;
+
+use super::{
+    Tile,
+    Image,
+    combined_grid::{draw_label, LABEL_HEIGHT},
+    diff::{comparison_image, diff_tiles, ChangeKind},
+};
+use crate::image::{WriteImageFile, WriteError as ImageWriteError};
+
+// Comparison of a font against a reference font for the CI gates of font repositories: the number of changed pixels
+// and a perceptual difference per tile, the tiles exceeding the thresholds failing the comparison. The perceptual
+// difference compares the tiles as seen over the video, blurred so that a glyph moved by a pixel or a slightly
+// different anti-aliasing weighs less than a missing stroke.
+
+const BACKGROUND_COLOR: Rgba<u8> = Rgba([0, 0, 0, 255]);
+// luminance the tiles are composited over, a mid gray so that both the white glyphs and their black outline show
+const VIDEO_LUMINANCE: f64 = 0.5;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TileDifference {
+    pub tile_kind: String,
+    pub index: usize,
+    pub kind: ChangeKind,
+    pub changed_pixels: usize,
+    // percentage of the luminance range, see `perceptual_difference`
+    pub perceptual_difference: f64,
+}
+
+#[derive(Debug, Error, Display, From)]
+pub enum SaveMontageError {
+    ImageWriteError(ImageWriteError),
+}
+
+// differences above which a tile fails the comparison, any difference fails it when neither is set
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Thresholds {
+    pub max_changed_pixels: Option<usize>,
+    pub max_perceptual_difference: Option<f64>,
+}
+
+impl Thresholds {
+
+    pub fn exceeded_by(&self, difference: &TileDifference) -> bool {
+        match (self.max_changed_pixels, self.max_perceptual_difference) {
+            (None, None) => true,
+            (max_changed_pixels, max_perceptual_difference) =>
+                max_changed_pixels.is_some_and(|max| difference.changed_pixels > max)
+                    || max_perceptual_difference.is_some_and(|max| difference.perceptual_difference > max),
+        }
+    }
+
+}
+
+// luminance of the pixel composited over the video, 0 to 1
+pub(crate) fn displayed_luminance(pixel: &Rgba<u8>) -> f64 {
+    let [red, green, blue, alpha] = pixel.0;
+    let luminance = (0.2126 * red as f64 + 0.7152 * green as f64 + 0.0722 * blue as f64) / 255.0;
+    let alpha = alpha as f64 / 255.0;
+    luminance * alpha + VIDEO_LUMINANCE * (1.0 - alpha)
+}
+
+// mean displayed luminance of the 3x3 neighbourhood of each pixel, the pixels out of the tile being the video
+fn blurred_luminance(tile: &Tile) -> Vec<f64> {
+    let (width, height) = (tile.width() as i64, tile.height() as i64);
+    let luminance = |x: i64, y: i64| match (0..width).contains(&x) && (0..height).contains(&y) {
+        true => displayed_luminance(tile.get_pixel(x as u32, y as u32)),
+        false => VIDEO_LUMINANCE,
+    };
+    itertools::iproduct!(0..height, 0..width)
+        .map(|(y, x)| itertools::iproduct!(-1..=1, -1..=1).map(|(dy, dx)| luminance(x + dx, y + dy)).sum::<f64>() / 9.0)
+        .collect()
+}
+
+// root mean square of the differences of the blurred luminances in percent of the luminance range, a white tile
+// against a transparent one being 50
+pub fn perceptual_difference(reference: &Tile, tile: &Tile) -> f64 {
+    let (reference, tile) = (blurred_luminance(reference), blurred_luminance(tile));
+    let square_sum: f64 = reference.iter().zip(tile.iter()).map(|(reference, tile)| (reference - tile).powi(2)).sum();
+    100.0 * (square_sum / reference.len().max(1) as f64).sqrt()
+}
+
+pub fn changed_pixel_count(reference: &Tile, tile: &Tile) -> usize {
+    reference.pixels().zip(tile.pixels()).filter(|(reference, tile)| reference != tile).count()
+}
+
+// the tiles of a single kind missing from one of the collections are compared against a transparent tile
+pub fn tile_differences(reference: &[Tile], tiles: &[Tile]) -> Vec<TileDifference> {
+    diff_tiles(reference, tiles).into_iter().map(|change| {
+        let (reference_tile, tile) = compared_tiles(reference, tiles, change.index());
+        TileDifference {
+            tile_kind: tile.kind().to_string(),
+            index: change.index(),
+            kind: change.kind(),
+            changed_pixels: changed_pixel_count(&reference_tile, &tile),
+            perceptual_difference: perceptual_difference(&reference_tile, &tile),
+        }
+    }).collect()
+}
+
+fn compared_tiles(reference: &[Tile], tiles: &[Tile], index: usize) -> (Tile, Tile) {
+    match (reference.get(index), tiles.get(index)) {
+        (Some(reference_tile), Some(tile)) => (reference_tile.clone(), tile.clone()),
+        (None, Some(tile)) => (Tile::new(tile.kind()), tile.clone()),
+        (Some(reference_tile), None) => (reference_tile.clone(), Tile::new(reference_tile.kind())),
+        (None, None) => unreachable!(),
+    }
+}
+
+// label naming the tile e.g. "HD 123" and its comparison image (reference | tile | changed pixels in red)
+pub fn montage_rows(reference: &[Tile], tiles: &[Tile], differences: &[TileDifference]) -> Vec<(String, Image)> {
+    differences.iter().map(|difference| {
+        let (reference_tile, tile) = compared_tiles(reference, tiles, difference.index);
+        (format!("{} {:03}", difference.tile_kind, difference.index), comparison_image(&reference_tile, &tile))
+    }).collect()
+}
+
+// the rows stacked top to bottom, each comparison image below a label strip, None without rows
+pub fn montage_image(rows: &[(String, Image)]) -> Option<Image> {
+    let width = rows.iter().map(|(_, image)| image.width()).max()?;
+    let height = rows.iter().map(|(_, image)| LABEL_HEIGHT + image.height()).sum();
+    let mut montage = Image::from_pixel(width, height, BACKGROUND_COLOR);
+    let mut y = 0;
+    for (label, image) in rows {
+        draw_label(&mut montage, y, label);
+        montage.copy_from(image, 0, y + LABEL_HEIGHT).unwrap();
+        y += LABEL_HEIGHT + image.height();
+    }
+    Some(montage)
+}
+
+// nothing is written without rows
+pub fn save_montage<P: AsRef<Path>>(rows: &[(String, Image)], path: P) -> Result<(), SaveMontageError> {
+    if let Some(montage) = montage_image(rows) {
+        montage.write_image_file(path)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+
+    use crate::osd::tile::{Tile, Kind as TileKind};
+
+    use super::{montage_image, montage_rows, perceptual_difference, tile_differences, Thresholds, LABEL_HEIGHT};
+
+    #[test]
+    fn differences() {
+        let reference = Tile::new(TileKind::SD);
+        let mut shifted = Tile::new(TileKind::SD);
+        let mut moved = Tile::new(TileKind::SD);
+        for y in 4..14 {
+            shifted.put_pixel(5, y, Rgba([255, 255, 255, 255]));
+            moved.put_pixel(6, y, Rgba([255, 255, 255, 255]));
+        }
+        assert_eq!(perceptual_difference(&reference, &reference), 0.0);
+        // a stroke moved by a pixel is less different than a stroke added
+        assert!(perceptual_difference(&shifted, &moved) < perceptual_difference(&reference, &moved));
+
+        let tiles = [reference.clone(), moved.clone(), moved];
+        let differences = tile_differences(&[reference, shifted.clone()], &tiles);
+        let indexes: Vec<_> = differences.iter().map(|difference| (difference.index, difference.changed_pixels)).collect();
+        assert_eq!(indexes, vec![(1, 20), (2, 10)]);
+        assert!(Thresholds::default().exceeded_by(&differences[0]));
+        let thresholds = Thresholds { max_changed_pixels: Some(15), max_perceptual_difference: None };
+        assert_eq!(differences.iter().map(|difference| thresholds.exceeded_by(difference)).collect::<Vec<_>>(), vec![true, false]);
+
+        assert!(montage_image(&[]).is_none());
+        let rows = montage_rows(&[Tile::new(TileKind::SD), shifted], &tiles, &differences);
+        let montage = montage_image(&rows).unwrap();
+        assert_eq!(montage.height(), 2 * (LABEL_HEIGHT + TileKind::SD.dimensions().height));
+    }
+
+}
@@ -0,0 +1,90 @@
+
+use clap::ValueEnum;
+use image::{imageops::{self, FilterType}, Rgba};
+use strum::Display;
+
+use super::{Tile, Kind as TileKind};
+
+
+// Resampling filter used when the tiles have to be converted to another tile kind for the target format
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Display, ValueEnum)]
+#[strum(serialize_all = "kebab-case")]
+pub enum RescaleFilter {
+    Nearest,
+    #[value(alias = "bilinear")]
+    Triangle,
+    // sharp enough for the glyphs edges without the ringing of Lanczos
+    #[default]
+    CatmullRom,
+    Gaussian,
+    #[value(alias = "lanczos")]
+    Lanczos3,
+}
+
+impl RescaleFilter {
+    fn filter_type(&self) -> FilterType {
+        match self {
+            RescaleFilter::Nearest => FilterType::Nearest,
+            RescaleFilter::Triangle => FilterType::Triangle,
+            RescaleFilter::CatmullRom => FilterType::CatmullRom,
+            RescaleFilter::Gaussian => FilterType::Gaussian,
+            RescaleFilter::Lanczos3 => FilterType::Lanczos3,
+        }
+    }
+}
+
+// snaps the pixels back to the OSD font palette: fully transparent or opaque gray shades
+pub fn requantize(tile: &mut Tile) {
+    for pixel in tile.pixels_mut() {
+        let [red, green, blue, alpha] = pixel.0;
+        *pixel = if alpha < 128 {
+            Rgba([0, 0, 0, 0])
+        } else {
+            let shade = ((red as u16 + green as u16 + blue as u16) / 3) as u8;
+            Rgba([shade, shade, shade, 255])
+        };
+    }
+}
+
+pub fn rescale_tile(tile: &Tile, tile_kind: TileKind, filter: RescaleFilter) -> Tile {
+    if tile.kind() == tile_kind {
+        return tile.clone();
+    }
+    let dimensions = tile_kind.dimensions();
+    let image = imageops::resize(tile.image(), dimensions.width, dimensions.height, filter.filter_type());
+    Tile::try_from(image).expect("image resized to the tile kind dimensions")
+}
+
+pub fn rescale_tiles(tiles: &[Tile], tile_kind: TileKind, filter: RescaleFilter, requantize_tiles: bool) -> Vec<Tile> {
+    tiles.iter().map(|tile| {
+        let mut tile = rescale_tile(tile, tile_kind, filter);
+        if requantize_tiles {
+            requantize(&mut tile);
+        }
+        tile
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+
+    use crate::osd::tile::{Tile, Kind as TileKind};
+
+    use super::{rescale_tiles, RescaleFilter};
+
+    #[test]
+    fn sd_to_hd() {
+        let mut tile = Tile::new(TileKind::SD);
+        for y in 0..27 {
+            for x in 0..36 {
+                tile.put_pixel(x, y, Rgba([200, 200, 200, 255]));
+            }
+        }
+        let rescaled = rescale_tiles(&[tile], TileKind::HD, RescaleFilter::default(), true);
+        assert_eq!(rescaled[0].kind(), TileKind::HD);
+        assert_eq!(*rescaled[0].get_pixel(12, 5), Rgba([200, 200, 200, 255]));
+        assert_eq!(*rescaled[0].get_pixel(12, 30), Rgba([0, 0, 0, 0]));
+        assert!(rescaled[0].pixels().all(|pixel| pixel.0[3] == 0 || pixel.0[3] == 255));
+    }
+}
@@ -0,0 +1,82 @@
+use std::path::Path;
+
+use derive_more::From;
+use image::{imageops, Rgba, RgbaImage};
+use thiserror::Error;
+
+use super::{Tile, font_render::Mapping};
+use crate::image::{upscale, WriteImageFile, WriteError as ImageWriteError};
+
+// Renders a string with the tiles of a font the way the OSD would display it: one tile per character, the lines are
+// separated by '\n' and the shorter lines are padded with the background. Used for previews and to verify that the
+// characters landed at the expected indices.
+
+#[derive(Debug, Error)]
+pub enum RenderTextError {
+    #[error("no tiles to render the text with")]
+    NoTiles,
+    #[error("character {0:?} is not in the character mapping")]
+    UnmappedCharacter(char),
+    #[error("character {character:?} is mapped to tile {index} but the font only contains {tile_count} tiles")]
+    IndexOutOfRange { character: char, index: usize, tile_count: usize },
+}
+
+#[derive(Debug, Error, From)]
+pub enum SaveTextImageError {
+    #[error(transparent)]
+    RenderError(RenderTextError),
+    #[error(transparent)]
+    ImageWriteError(ImageWriteError),
+}
+
+pub fn render_text(tiles: &[Tile], mapping: &Mapping, text: &str, background: Rgba<u8>) -> Result<RgbaImage, RenderTextError> {
+    let tile_dimensions = tiles.first().ok_or(RenderTextError::NoTiles)?.kind().dimensions();
+    let lines: Vec<Vec<&Tile>> = text.lines().map(|line|
+        line.chars().map(|character| {
+            let index = *mapping.get(&character).ok_or(RenderTextError::UnmappedCharacter(character))?;
+            tiles.get(index).ok_or(RenderTextError::IndexOutOfRange { character, index, tile_count: tiles.len() })
+        }).collect()
+    ).collect::<Result<_, _>>()?;
+
+    let columns = lines.iter().map(Vec::len).max().unwrap_or(0) as u32;
+    let mut image = RgbaImage::from_pixel(columns * tile_dimensions.width, lines.len() as u32 * tile_dimensions.height, background);
+    for (row, line) in lines.iter().enumerate() {
+        for (column, tile) in line.iter().enumerate() {
+            let (x, y) = (column * tile_dimensions.width as usize, row * tile_dimensions.height as usize);
+            imageops::overlay(&mut image, tile.image(), x as i64, y as i64);
+        }
+    }
+    Ok(image)
+}
+
+// the image is upscaled by the integer factor `scale` so that the pixels stay crisp
+pub fn save_text_image<P: AsRef<Path>>(tiles: &[Tile], mapping: &Mapping, text: &str, background: Rgba<u8>, scale: u32, path: P) -> Result<(), SaveTextImageError> {
+    let image = render_text(tiles, mapping, text, background)?;
+    upscale(&image, scale).write_image_file(path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+
+    use crate::osd::tile::{Tile, Kind, font_render::ascii_mapping};
+
+    use super::{render_text, RenderTextError};
+
+    #[test]
+    fn lines() {
+        let mut tiles = vec![Tile::new(Kind::SD); 256];
+        tiles[b'A' as usize].put_pixel(0, 0, Rgba([255, 255, 255, 255]));
+        let background = Rgba([0, 0, 255, 255]);
+        let image = render_text(&tiles, &ascii_mapping(), "BA\nA", background).unwrap();
+        let dimensions = Kind::SD.dimensions();
+        assert_eq!(image.dimensions(), (2 * dimensions.width, 2 * dimensions.height));
+        assert_eq!(image.get_pixel(dimensions.width, 0), &Rgba([255, 255, 255, 255]));
+        assert_eq!(image.get_pixel(0, dimensions.height), &Rgba([255, 255, 255, 255]));
+        assert_eq!(image.get_pixel(dimensions.width, dimensions.height), &background);
+
+        assert!(matches!(render_text(&tiles, &ascii_mapping(), "é", background), Err(RenderTextError::UnmappedCharacter('é'))));
+        assert!(matches!(render_text(&tiles[..10], &ascii_mapping(), "A", background), Err(RenderTextError::IndexOutOfRange { .. })));
+    }
+}
@@ -0,0 +1,616 @@
+
+use std::str::FromStr;
+
+use image::{imageops, Rgba};
+use thiserror::Error;
+
+use super::{Tile, Kind as TileKind, Dimensions, SD_DIMENSIONS, container::tile_set::TileSet};
+use crate::color::{parse_color, ParseColorError};
+
+
+const TRANSPARENT: Rgba<u8> = Rgba([0, 0, 0, 0]);
+
+// ordered dithering thresholds
+const BAYER_MATRIX: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+// clockwise rotations
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Rotation {
+    Rotate90,
+    Rotate180,
+    Rotate270,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Flip {
+    // mirrors left and right
+    Horizontal,
+    // mirrors top and bottom
+    Vertical,
+}
+
+#[derive(Debug, Error)]
+#[error("tile {index} is {dimensions} after the transforms instead of {expected} for a {tile_kind} tile, rotations by 90° or 270° swap the width and height of the tiles")]
+pub struct TransformedDimensionsError {
+    index: usize,
+    tile_kind: TileKind,
+    dimensions: Dimensions,
+    expected: Dimensions,
+}
+
+#[derive(Debug, Error)]
+#[error("{tile_kind} tiles are not square, they can only be rotated by 90° or 270° in a chain of transforms restoring their dimensions")]
+pub struct NonSquareRotationError {
+    tile_kind: TileKind,
+}
+
+// thicknesses are specified in pixels for SD tiles and scaled by the tile height ratio for the other tile kinds
+fn scaled_thickness(tile_kind: TileKind, thickness: u32) -> u32 {
+    if thickness == 0 {
+        return 0;
+    }
+    let scaled = (thickness * tile_kind.dimensions().height + SD_DIMENSIONS.height / 2) / SD_DIMENSIONS.height;
+    scaled.max(1)
+}
+
+impl Tile {
+
+    fn is_transparent_at(&self, x: i64, y: i64) -> bool {
+        let (width, height) = self.dimensions();
+        if x < 0 || y < 0 || x >= width as i64 || y >= height as i64 {
+            return true;
+        }
+        self.get_pixel(x as u32, y as u32)[3] == 0
+    }
+
+    // applies `function` to the color channels of the visible pixels, transparent pixels are left untouched
+    fn map_visible_color_channels<F: Fn(u8) -> u8>(&mut self, function: F) {
+        for pixel in self.pixels_mut() {
+            if pixel[3] != 0 {
+                for channel in pixel.0[0..3].iter_mut() {
+                    *channel = function(*channel);
+                }
+            }
+        }
+    }
+
+    pub fn invert(&mut self) {
+        self.map_visible_color_channels(|value| 255 - value);
+    }
+
+    // the width and height of the image are swapped by 90° and 270° rotations while the tile kind is kept, only used
+    // in a chain of transforms which is checked to end up with the dimensions of the tile kind
+    fn rotate_image(&mut self, rotation: Rotation) {
+        let rotated = match rotation {
+            Rotation::Rotate90 => imageops::rotate90(self.image()),
+            Rotation::Rotate180 => imageops::rotate180(self.image()),
+            Rotation::Rotate270 => imageops::rotate270(self.image()),
+        };
+        self.set_image(rotated);
+    }
+
+    // rotations by 90° or 270° are rejected for the tile kinds which are not square since the rotated tile would not
+    // have the dimensions of its kind
+    pub fn rotate(&mut self, rotation: Rotation) -> Result<(), NonSquareRotationError> {
+        let Dimensions { width, height } = self.kind().dimensions();
+        if rotation != Rotation::Rotate180 && width != height {
+            return Err(NonSquareRotationError { tile_kind: self.kind() })
+        }
+        self.rotate_image(rotation);
+        Ok(())
+    }
+
+    pub fn flip(&mut self, flip: Flip) {
+        match flip {
+            Flip::Horizontal => imageops::flip_horizontal_in_place(&mut **self),
+            Flip::Vertical => imageops::flip_vertical_in_place(&mut **self),
+        }
+    }
+
+    pub fn brightness(&mut self, delta: i16) {
+        self.map_visible_color_channels(|value| (value as i16 + delta).clamp(0, 255) as u8);
+    }
+
+    // moves the shades away from (positive percentage) or towards (negative percentage) the middle gray
+    pub fn contrast(&mut self, percent: i16) {
+        let factor = (100 + percent as i32).max(0);
+        self.map_visible_color_channels(|value| ((value as i32 - 128) * factor / 100 + 128).clamp(0, 255) as u8);
+    }
+
+    // multiplies the shades by the color e.g. white glyphs become of the color and black outlines stay black
+    pub fn tint(&mut self, color: Rgba<u8>) {
+        for pixel in self.pixels_mut().filter(|pixel| pixel[3] != 0) {
+            for (channel, color_channel) in pixel.0[0..3].iter_mut().zip(color.0) {
+                *channel = (*channel as u16 * color_channel as u16 / 255) as u8;
+            }
+        }
+    }
+
+    // ITU-R BT.601 luma
+    pub fn grayscale(&mut self) {
+        for pixel in self.pixels_mut().filter(|pixel| pixel[3] != 0) {
+            let [red, green, blue, _] = pixel.0;
+            let luma = ((red as u32 * 299 + green as u32 * 587 + blue as u32 * 114 + 500) / 1000) as u8;
+            pixel.0[0..3].fill(luma);
+        }
+    }
+
+    // stretches the `black_point`..=`white_point` range of shades to the full 0..=255 range
+    pub fn levels(&mut self, black_point: u8, white_point: u8) {
+        let range = (white_point as i32 - black_point as i32).max(1);
+        self.map_visible_color_channels(|value|
+            ((value as i32 - black_point as i32) * 255 / range).clamp(0, 255) as u8
+        );
+    }
+
+    // dilates the visible pixels by `thickness` pixels filling the newly covered transparent pixels with `color`,
+    // the visible pixels are left untouched
+    pub fn add_outline(&mut self, color: Rgba<u8>, thickness: u32) {
+        let thickness = scaled_thickness(self.kind, thickness) as i64;
+        let original = self.clone();
+        for (x, y, pixel) in self.enumerate_pixels_mut() {
+            if pixel[3] != 0 {
+                continue;
+            }
+            let covered = (-thickness..=thickness).any(|dy|
+                (-thickness..=thickness).any(|dx|
+                    dx * dx + dy * dy <= thickness * thickness && !original.is_transparent_at(x as i64 + dx, y as i64 + dy)
+                )
+            );
+            if covered {
+                *pixel = color;
+            }
+        }
+    }
+
+    // fills the transparent pixels covered by the visible pixels once shifted by `offset` pixels right and down with
+    // `color`, its alpha sets the opacity of the shadow
+    pub fn add_drop_shadow(&mut self, color: Rgba<u8>, offset: u32) {
+        let offset = scaled_thickness(self.kind, offset) as i64;
+        let original = self.clone();
+        for (x, y, pixel) in self.enumerate_pixels_mut() {
+            if pixel[3] == 0 && !original.is_transparent_at(x as i64 - offset, y as i64 - offset) {
+                *pixel = color;
+            }
+        }
+    }
+
+    // restricts the pixels to the OSD palette: fully transparent or one of `levels` evenly spaced opaque gray shades
+    // from black to white e.g. 3 for black, gray and white, with ordered dithering the shades between two levels are
+    // approximated by a pattern of both
+    pub fn quantize(&mut self, levels: u8, dither: bool) {
+        let max_level = levels.max(2) as f32 - 1.0;
+        for (x, y, pixel) in self.enumerate_pixels_mut() {
+            let [red, green, blue, alpha] = pixel.0;
+            if alpha < 128 {
+                *pixel = TRANSPARENT;
+                continue;
+            }
+            let shade = (red as f32 + green as f32 + blue as f32) / 3.0 / 255.0;
+            let offset = match dither {
+                true => (BAYER_MATRIX[y as usize % 4][x as usize % 4] as f32 + 0.5) / 16.0 - 0.5,
+                false => 0.0,
+            };
+            let level = (shade * max_level + offset).round().clamp(0.0, max_level);
+            let shade = (level * 255.0 / max_level).round() as u8;
+            *pixel = Rgba([shade, shade, shade, 255]);
+        }
+    }
+
+    // the semi-transparent pixels with an alpha lower than `threshold` become transparent and the other ones opaque,
+    // returns the number of modified pixels
+    pub fn threshold_alpha(&mut self, threshold: u8) -> usize {
+        let mut modified_count = 0;
+        for pixel in self.pixels_mut().filter(|pixel| pixel[3] != 0 && pixel[3] != 255) {
+            if pixel[3] < threshold {
+                *pixel = TRANSPARENT;
+            } else {
+                pixel[3] = 255;
+            }
+            modified_count += 1;
+        }
+        modified_count
+    }
+
+    // strips `thickness` layers of `color` pixels adjacent to transparency
+    pub fn remove_outline(&mut self, color: Rgba<u8>, thickness: u32) {
+        for _ in 0..scaled_thickness(self.kind, thickness) {
+            let original = self.clone();
+            for (x, y, pixel) in self.enumerate_pixels_mut() {
+                if pixel[3] == 0 || pixel.0[0..3] != color.0[0..3] {
+                    continue;
+                }
+                let (x, y) = (x as i64, y as i64);
+                let on_edge = [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)].into_iter()
+                    .any(|(x, y)| original.is_transparent_at(x, y));
+                if on_edge {
+                    *pixel = TRANSPARENT;
+                }
+            }
+        }
+    }
+
+}
+
+impl TileSet {
+
+    fn tiles_mut(&mut self) -> impl Iterator<Item = &mut Tile> {
+        self.sd_tiles.iter_mut().chain(self.hd_tiles.iter_mut())
+    }
+
+    pub fn add_outline(&mut self, color: Rgba<u8>, thickness: u32) {
+        self.tiles_mut().for_each(|tile| tile.add_outline(color, thickness));
+    }
+
+    pub fn remove_outline(&mut self, color: Rgba<u8>, thickness: u32) {
+        self.tiles_mut().for_each(|tile| tile.remove_outline(color, thickness));
+    }
+
+    pub fn add_drop_shadow(&mut self, color: Rgba<u8>, offset: u32) {
+        self.tiles_mut().for_each(|tile| tile.add_drop_shadow(color, offset));
+    }
+
+    pub fn invert(&mut self) {
+        self.tiles_mut().for_each(Tile::invert);
+    }
+
+    pub fn brightness(&mut self, delta: i16) {
+        self.tiles_mut().for_each(|tile| tile.brightness(delta));
+    }
+
+    pub fn levels(&mut self, black_point: u8, white_point: u8) {
+        self.tiles_mut().for_each(|tile| tile.levels(black_point, white_point));
+    }
+
+    pub fn contrast(&mut self, percent: i16) {
+        self.tiles_mut().for_each(|tile| tile.contrast(percent));
+    }
+
+    pub fn tint(&mut self, color: Rgba<u8>) {
+        self.tiles_mut().for_each(|tile| tile.tint(color));
+    }
+
+    pub fn grayscale(&mut self) {
+        self.tiles_mut().for_each(Tile::grayscale);
+    }
+
+    pub fn quantize(&mut self, levels: u8, dither: bool) {
+        self.tiles_mut().for_each(|tile| tile.quantize(levels, dither));
+    }
+
+    pub fn threshold_alpha(&mut self, threshold: u8) -> usize {
+        self.tiles_mut().map(|tile| tile.threshold_alpha(threshold)).sum()
+    }
+
+    pub fn apply_transforms(&mut self, transforms: &[TileTransform]) -> Result<(), TransformedDimensionsError> {
+        apply_transforms(&mut self.sd_tiles, transforms)?;
+        apply_transforms(&mut self.hd_tiles, transforms)
+    }
+
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TileTransform {
+    Invert,
+    Brightness(i16),
+    Levels { black_point: u8, white_point: u8 },
+    Contrast(i16),
+    Tint(Rgba<u8>),
+    Grayscale,
+    Quantize { levels: u8, dither: bool },
+    AddOutline { color: Rgba<u8>, thickness: u32 },
+    RemoveOutline { color: Rgba<u8>, thickness: u32 },
+    DropShadow { color: Rgba<u8>, offset: u32 },
+    Rotate(Rotation),
+    Flip(Flip),
+}
+
+impl TileTransform {
+
+    // the tile may not have the dimensions of its kind after a rotation by 90° or 270°, see `transform_tile`
+    fn apply_unchecked(&self, tile: &mut Tile) {
+        match *self {
+            TileTransform::Invert => tile.invert(),
+            TileTransform::Brightness(delta) => tile.brightness(delta),
+            TileTransform::Levels { black_point, white_point } => tile.levels(black_point, white_point),
+            TileTransform::Contrast(percent) => tile.contrast(percent),
+            TileTransform::Tint(color) => tile.tint(color),
+            TileTransform::Grayscale => tile.grayscale(),
+            TileTransform::Quantize { levels, dither } => tile.quantize(levels, dither),
+            TileTransform::AddOutline { color, thickness } => tile.add_outline(color, thickness),
+            TileTransform::RemoveOutline { color, thickness } => tile.remove_outline(color, thickness),
+            TileTransform::DropShadow { color, offset } => tile.add_drop_shadow(color, offset),
+            TileTransform::Rotate(rotation) => tile.rotate_image(rotation),
+            TileTransform::Flip(flip) => tile.flip(flip),
+        }
+    }
+
+}
+
+pub fn threshold_alpha(tiles: &mut [Tile], threshold: u8) -> usize {
+    tiles.iter_mut().map(|tile| tile.threshold_alpha(threshold)).sum()
+}
+
+pub fn apply_transforms(tiles: &mut [Tile], transforms: &[TileTransform]) -> Result<(), TransformedDimensionsError> {
+    for (index, tile) in tiles.iter_mut().enumerate() {
+        transform_tile(index, tile, transforms)?;
+    }
+    Ok(())
+}
+
+// every format stores the tiles with the dimensions of their kind, the transforms have to preserve them as a whole,
+// the tile is left untouched otherwise
+fn transform_tile(index: usize, tile: &mut Tile, transforms: &[TileTransform]) -> Result<(), TransformedDimensionsError> {
+    let mut transformed = tile.clone();
+    for transform in transforms {
+        transform.apply_unchecked(&mut transformed);
+    }
+    let dimensions = Dimensions::from(transformed.dimensions());
+    let expected = transformed.kind().dimensions();
+    if dimensions != expected {
+        return Err(TransformedDimensionsError { index, tile_kind: transformed.kind(), dimensions, expected })
+    }
+    *tile = transformed;
+    Ok(())
+}
+
+#[derive(Debug, Error)]
+pub enum ParseTransformError {
+    #[error("unknown transform: {0}")]
+    UnknownTransform(String),
+    #[error("invalid argument for transform {transform}: {argument}")]
+    InvalidArgument { transform: String, argument: String },
+    #[error("transform {0} requires an argument")]
+    MissingArgument(String),
+    #[error(transparent)]
+    InvalidColor(#[from] ParseColorError),
+}
+
+impl ParseTransformError {
+    fn invalid_argument(transform: &str, argument: &str) -> Self {
+        Self::InvalidArgument { transform: transform.to_owned(), argument: argument.to_owned() }
+    }
+}
+
+// outline and drop shadow arguments are in the form `pixels[:color]`, the color defaulting to black
+fn parse_outline_argument(transform: &str, argument: &str) -> Result<(Rgba<u8>, u32), ParseTransformError> {
+    let (thickness, color) = match argument.split_once(':') {
+        Some((thickness, color)) => (thickness, parse_color(color)?),
+        None => (argument, Rgba([0, 0, 0, 255])),
+    };
+    let thickness = thickness.parse().map_err(|_| ParseTransformError::invalid_argument(transform, argument))?;
+    Ok((color, thickness))
+}
+
+impl FromStr for TileTransform {
+    type Err = ParseTransformError;
+
+    // transforms are specified as `name` or `name=argument` e.g. `invert`, `brightness=-20`, `levels=16:240`, `tint=#00FF00`,
+    // `add-outline=2:#000000`
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let (name, argument) = match input.split_once('=') {
+            Some((name, argument)) => (name, Some(argument)),
+            None => (input, None),
+        };
+        let require_argument = || argument.ok_or_else(|| ParseTransformError::MissingArgument(name.to_owned()));
+        let transform = match name {
+            "invert" => TileTransform::Invert,
+            "grayscale" => TileTransform::Grayscale,
+            "rotate90" => TileTransform::Rotate(Rotation::Rotate90),
+            "rotate180" => TileTransform::Rotate(Rotation::Rotate180),
+            "rotate270" => TileTransform::Rotate(Rotation::Rotate270),
+            "flip-h" => TileTransform::Flip(Flip::Horizontal),
+            "flip-v" => TileTransform::Flip(Flip::Vertical),
+            "brightness" => {
+                let argument = require_argument()?;
+                TileTransform::Brightness(argument.parse().map_err(|_| ParseTransformError::invalid_argument(name, argument))?)
+            },
+            "contrast" => {
+                let argument = require_argument()?;
+                let percent = argument.parse().ok().filter(|percent: &i16| *percent >= -100)
+                    .ok_or_else(|| ParseTransformError::invalid_argument(name, argument))?;
+                TileTransform::Contrast(percent)
+            },
+            "tint" => TileTransform::Tint(parse_color(require_argument()?)?),
+            "quantize" => {
+                let argument = argument.unwrap_or("2");
+                let invalid_argument = || ParseTransformError::invalid_argument(name, argument);
+                let (levels, dither) = match argument.split_once(':') {
+                    Some((levels, "dither")) => (levels, true),
+                    Some(_) => return Err(invalid_argument()),
+                    None => (argument, false),
+                };
+                let levels = levels.parse().ok().filter(|levels: &u8| *levels >= 2).ok_or_else(invalid_argument)?;
+                TileTransform::Quantize { levels, dither }
+            },
+            "levels" => {
+                let argument = require_argument()?;
+                let invalid_argument = || ParseTransformError::invalid_argument(name, argument);
+                let (black_point, white_point) = argument.split_once(':').ok_or_else(invalid_argument)?;
+                let black_point = black_point.parse().map_err(|_| invalid_argument())?;
+                let white_point = white_point.parse().map_err(|_| invalid_argument())?;
+                if black_point >= white_point {
+                    return Err(invalid_argument());
+                }
+                TileTransform::Levels { black_point, white_point }
+            },
+            "add-outline" => {
+                let (color, thickness) = parse_outline_argument(name, require_argument()?)?;
+                TileTransform::AddOutline { color, thickness }
+            },
+            "remove-outline" => {
+                let (color, thickness) = parse_outline_argument(name, require_argument()?)?;
+                TileTransform::RemoveOutline { color, thickness }
+            },
+            "drop-shadow" => {
+                let (color, offset) = parse_outline_argument(name, require_argument()?)?;
+                TileTransform::DropShadow { color, offset }
+            },
+            _ => return Err(ParseTransformError::UnknownTransform(name.to_owned())),
+        };
+        Ok(transform)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+
+    use crate::osd::tile::{Tile, Kind};
+
+    use super::{apply_transforms, Flip, Rotation, TileTransform};
+
+    #[test]
+    fn add_remove_outline() {
+        let white = Rgba([255, 255, 255, 255]);
+        let black = Rgba([0, 0, 0, 255]);
+        let mut tile = Tile::new(Kind::SD);
+        tile.put_pixel(10, 10, white);
+        let original = tile.clone();
+
+        tile.add_outline(black, 1);
+        assert_eq!(tile.get_pixel(10, 10), &white);
+        for (x, y) in [(9, 10), (11, 10), (10, 9), (10, 11)] {
+            assert_eq!(tile.get_pixel(x, y), &black);
+        }
+        assert_eq!(tile.get_pixel(9, 9)[3], 0);
+
+        tile.remove_outline(black, 1);
+        assert_eq!(tile.image(), original.image());
+    }
+
+    #[test]
+    fn drop_shadow() {
+        let shadow = Rgba([0, 0, 0, 128]);
+        let mut tile = Tile::new(Kind::SD);
+        tile.put_pixel(10, 10, Rgba([255, 255, 255, 255]));
+        tile.put_pixel(11, 10, Rgba([255, 255, 255, 255]));
+
+        tile.add_drop_shadow(shadow, 2);
+        assert_eq!(tile.get_pixel(12, 12), &shadow);
+        assert_eq!(tile.get_pixel(13, 12), &shadow);
+        assert_eq!(tile.get_pixel(11, 10), &Rgba([255, 255, 255, 255]));
+        assert_eq!(tile.get_pixel(11, 11)[3], 0);
+        assert_eq!("drop-shadow=2:#00000080".parse::<TileTransform>().unwrap(), TileTransform::DropShadow { color: shadow, offset: 2 });
+    }
+
+    #[test]
+    fn quantize() {
+        let mut tile = Tile::new(Kind::SD);
+        tile.put_pixel(1, 1, Rgba([200, 200, 200, 255]));
+        tile.put_pixel(2, 1, Rgba([100, 100, 100, 200]));
+        tile.put_pixel(3, 1, Rgba([255, 0, 0, 100]));
+        let mut quantized = tile.clone();
+        quantized.quantize(2, false);
+        assert_eq!(quantized.get_pixel(1, 1), &Rgba([255, 255, 255, 255]));
+        assert_eq!(quantized.get_pixel(2, 1), &Rgba([0, 0, 0, 255]));
+        assert_eq!(quantized.get_pixel(3, 1), &Rgba([0, 0, 0, 0]));
+
+        let mut gray = Tile::new(Kind::SD);
+        for (x, y) in (0..4).flat_map(|y| (0..4).map(move |x| (x, y))) {
+            gray.put_pixel(x, y, Rgba([128, 128, 128, 255]));
+        }
+        gray.quantize(2, true);
+        let white_count = (0..4).flat_map(|y| (0..4).map(move |x| (x, y))).filter(|(x, y)| gray.get_pixel(*x, *y)[0] == 255).count();
+        assert_eq!(white_count, 8);
+
+        assert_eq!("quantize=3:dither".parse::<TileTransform>().unwrap(), TileTransform::Quantize { levels: 3, dither: true });
+        assert_eq!("quantize".parse::<TileTransform>().unwrap(), TileTransform::Quantize { levels: 2, dither: false });
+        assert!("quantize=1".parse::<TileTransform>().is_err());
+    }
+
+    #[test]
+    fn alpha_threshold() {
+        let mut tile = Tile::new(Kind::SD);
+        tile.put_pixel(1, 1, Rgba([255, 255, 255, 100]));
+        tile.put_pixel(2, 1, Rgba([255, 255, 255, 200]));
+        tile.put_pixel(3, 1, Rgba([255, 255, 255, 255]));
+
+        assert_eq!(tile.threshold_alpha(128), 2);
+        assert_eq!(tile.get_pixel(1, 1), &Rgba([0, 0, 0, 0]));
+        assert_eq!(tile.get_pixel(2, 1), &Rgba([255, 255, 255, 255]));
+        assert_eq!(tile.threshold_alpha(128), 0);
+    }
+
+    #[test]
+    fn invert_twice_is_identity() {
+        let mut tile = Tile::new(Kind::HD);
+        tile.put_pixel(1, 1, Rgba([255, 255, 255, 255]));
+        tile.put_pixel(2, 1, Rgba([100, 100, 100, 255]));
+        tile.put_pixel(3, 1, Rgba([0, 0, 0, 128]));
+        let original = tile.clone();
+
+        tile.invert();
+        assert_eq!(tile.get_pixel(1, 1), &Rgba([0, 0, 0, 255]));
+        assert_eq!(tile.get_pixel(0, 0), original.get_pixel(0, 0));
+        tile.invert();
+        assert_eq!(tile.image(), original.image());
+    }
+
+    #[test]
+    fn rotate_and_flip() {
+        let mut tile = Tile::new(Kind::SD);
+        tile.put_pixel(1, 2, Rgba([255, 255, 255, 255]));
+        tile.put_pixel(30, 50, Rgba([100, 100, 100, 255]));
+        let original = tile.clone();
+
+        assert!(tile.rotate(Rotation::Rotate90).is_err());
+        assert_eq!(tile.image(), original.image());
+        let mut tiles = [tile.clone()];
+        assert!(apply_transforms(&mut tiles, &[TileTransform::Rotate(Rotation::Rotate90)]).is_err());
+        assert_eq!(tiles[0].image(), original.image());
+        let rotations = [TileTransform::Rotate(Rotation::Rotate90), TileTransform::Rotate(Rotation::Rotate270)];
+        apply_transforms(&mut tiles, &rotations).unwrap();
+        assert_eq!(tiles[0].image(), original.image());
+        let mirrored = [TileTransform::Rotate(Rotation::Rotate90), TileTransform::Flip(Flip::Vertical), TileTransform::Rotate(Rotation::Rotate270)];
+        apply_transforms(&mut tiles, &mirrored).unwrap();
+        assert_eq!(tiles[0].get_pixel(36 - 1 - 1, 2), &Rgba([255, 255, 255, 255]));
+
+        tile.flip(Flip::Horizontal);
+        assert_eq!(tile.get_pixel(36 - 1 - 1, 2), &Rgba([255, 255, 255, 255]));
+        tile.flip(Flip::Vertical);
+        tile.rotate(Rotation::Rotate180).unwrap();
+        assert_eq!(tile.image(), original.image());
+    }
+
+    #[test]
+    fn color_transforms() {
+        let mut tile = Tile::new(Kind::SD);
+        tile.put_pixel(1, 1, Rgba([255, 255, 255, 255]));
+        tile.put_pixel(2, 1, Rgba([0, 0, 0, 255]));
+        tile.put_pixel(3, 1, Rgba([200, 100, 50, 255]));
+
+        let mut tinted = tile.clone();
+        tinted.tint(Rgba([0, 255, 0, 255]));
+        assert_eq!(tinted.get_pixel(1, 1), &Rgba([0, 255, 0, 255]));
+        assert_eq!(tinted.get_pixel(2, 1), &Rgba([0, 0, 0, 255]));
+        assert_eq!(tinted.get_pixel(0, 0)[3], 0);
+
+        let mut gray = tile.clone();
+        gray.grayscale();
+        assert_eq!(gray.get_pixel(3, 1), &Rgba([124, 124, 124, 255]));
+
+        tile.contrast(-100);
+        assert_eq!(tile.get_pixel(1, 1), &Rgba([128, 128, 128, 255]));
+    }
+
+    #[test]
+    fn parse_transforms() {
+        assert_eq!("invert".parse::<TileTransform>().unwrap(), TileTransform::Invert);
+        assert_eq!("brightness=-20".parse::<TileTransform>().unwrap(), TileTransform::Brightness(-20));
+        assert_eq!("levels=16:240".parse::<TileTransform>().unwrap(), TileTransform::Levels { black_point: 16, white_point: 240 });
+        assert_eq!(
+            "add-outline=2:white".parse::<TileTransform>().unwrap(),
+            TileTransform::AddOutline { color: Rgba([255, 255, 255, 255]), thickness: 2 }
+        );
+        assert_eq!("rotate270".parse::<TileTransform>().unwrap(), TileTransform::Rotate(Rotation::Rotate270));
+        assert_eq!("flip-h".parse::<TileTransform>().unwrap(), TileTransform::Flip(Flip::Horizontal));
+        assert_eq!("tint=#FFB000".parse::<TileTransform>().unwrap(), TileTransform::Tint(Rgba([255, 176, 0, 255])));
+        assert_eq!("contrast=30".parse::<TileTransform>().unwrap(), TileTransform::Contrast(30));
+        assert_eq!("grayscale".parse::<TileTransform>().unwrap(), TileTransform::Grayscale);
+        assert!("contrast=-150".parse::<TileTransform>().is_err());
+        assert!("levels=240:16".parse::<TileTransform>().is_err());
+        assert!("brightness".parse::<TileTransform>().is_err());
+        assert!("blur".parse::<TileTransform>().is_err());
+    }
+}
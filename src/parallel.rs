@@ -0,0 +1,32 @@
+
+// Maps the items on the rayon thread pool when the `parallel` feature is enabled and in order on the current thread
+// otherwise, the results are in the order of the items either way
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+
+#[cfg(feature = "parallel")]
+pub fn map<T, R, F>(items: &[T], f: F) -> Vec<R>
+where T: Sync, R: Send, F: Fn(usize, &T) -> R + Sync + Send {
+    items.par_iter().enumerate().map(|(index, item)| f(index, item)).collect()
+}
+
+#[cfg(not(feature = "parallel"))]
+pub fn map<T, R, F>(items: &[T], f: F) -> Vec<R>
+where F: Fn(usize, &T) -> R {
+    items.iter().enumerate().map(|(index, item)| f(index, item)).collect()
+}
+
+// with the `parallel` feature the error returned when several items fail is not necessarily the one of the first item
+#[cfg(feature = "parallel")]
+pub fn try_map<T, R, E, F>(items: &[T], f: F) -> Result<Vec<R>, E>
+where T: Sync, R: Send, E: Send, F: Fn(usize, &T) -> Result<R, E> + Sync + Send {
+    items.par_iter().enumerate().map(|(index, item)| f(index, item)).collect()
+}
+
+#[cfg(not(feature = "parallel"))]
+pub fn try_map<T, R, E, F>(items: &[T], f: F) -> Result<Vec<R>, E>
+where F: Fn(usize, &T) -> Result<R, E> {
+    items.iter().enumerate().map(|(index, item)| f(index, item)).collect()
+}
@@ -1,21 +1,33 @@
 
+pub use crate::convert_options::{ConvertOptions, InvalidConvertOptionsError};
+pub use crate::image_format::ImageFormat;
+
 pub use crate::osd::{
     bin_file::{
         self,
         LoadError as BinFileLoadError,
     },
     avatar_file::load as load_avatar_file,
+    font_dir::FontDir,
     tile::{
         self,
         Tile,
         Dimensions as TileDimensions,
+        fit::Fit as TileFit,
+        rescale::RescaleFilter,
+        transform::{
+            TileTransform,
+            apply_transforms,
+        },
         container::{
             into_tile_grid::IntoTileGrid,
             load_symbols_from_dir::load_symbols_from_dir,
-            load_tiles_from_dir::load_tiles_from_dir,
+            load_tiles_from_dir::{load_tiles_from_dir, load_tiles_from_dir_missing, MissingTiles},
             save_symbols_to_dir::SaveSymbolsToDir,
             save_tiles_to_dir::SaveTilesToDir,
+            split_symbols::{SplitSymbolsToDir, load_split_symbol_specs},
             save_to_bin_file::{
+                Padding as BinFilePadding,
                 SaveTilesToBinFile,
                 SaveToBinFile,
             },
@@ -24,19 +36,30 @@ pub use crate::osd::{
                 SaveTilesToAvatarFile,
             },
             save_to_grid::SaveToGridImage,
+            stats::FontStats,
+            content_hash::ContentHash,
+            char_map::CharMap,
+            patch::{IndexList, Patch},
             symbol::{
                 set::Set as SymbolSet,
+                names::SymbolNames,
                 spec::Specs as SymbolSpecs,
             },
             tile_set::TileSet,
             ToSymbols,
             IntoTilesVec,
         },
+        grid_annotation::Annotation as TileGridAnnotation,
         grid::{
             Grid as TileGrid,
             Set as TileGridSet,
+            Validation as TileGridValidation,
+            Layout as TileGridLayout,
             LoadError as GridLoadError,
             SaveImageError as GridSaveImageError,
         },
     }
 };
+
+#[cfg(feature = "fs")]
+pub use crate::osd::tile::container::archive::Archive;
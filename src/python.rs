@@ -0,0 +1,212 @@
+// Python module for the scripts using the library e.g. the CI of font repositories, built with the `python` feature:
+//
+//   from hd_fpv_osd_font_tool import TileSet
+//   tile_set = TileSet.load_bin_files_norm("fonts", "inav")
+//   tile_set.save_grids_norm("grids", "inav")
+//
+// The tile kinds are given as "SD" or "HD", the image formats as their extension e.g. "png", the failures are raised as
+// RuntimeError with the message of the error.
+
+use std::fmt::Display;
+
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+
+use crate::convert_options::DEFAULT_MAX_TILE_COUNT;
+use crate::image_format::{ImageDecoding, ImageFormat};
+use crate::osd::tile::{
+    Kind as TileKind,
+    container::{
+        IntoTilesVec,
+        save_tiles_to_dir::SaveTilesToDir,
+        symbol::{set::Set as SymbolSet, spec::Specs as SymbolSpecs},
+        tile_set::TileSet,
+    },
+    grid::{Grid, Layout as GridLayout},
+};
+
+
+fn runtime_error<E: Display>(error: E) -> PyErr {
+    PyRuntimeError::new_err(error.to_string())
+}
+
+fn tile_kind(kind: &str) -> PyResult<TileKind> {
+    match kind.to_ascii_uppercase().as_str() {
+        "SD" => Ok(TileKind::SD),
+        "HD" => Ok(TileKind::HD),
+        _ => Err(PyValueError::new_err(format!("invalid tile kind `{kind}`: expected SD or HD"))),
+    }
+}
+
+fn image_format(format: &str) -> PyResult<ImageFormat> {
+    format.parse().map_err(|error: <ImageFormat as std::str::FromStr>::Err| PyValueError::new_err(error.to_string()))
+}
+
+#[pyclass(name = "TileSet")]
+pub struct PyTileSet(TileSet);
+
+#[pymethods]
+impl PyTileSet {
+
+    #[staticmethod]
+    #[pyo3(signature = (dir, ident = None))]
+    fn load_bin_files_norm(dir: &str, ident: Option<&str>) -> PyResult<Self> {
+        TileSet::load_bin_files_norm(dir, &ident).map(Self).map_err(runtime_error)
+    }
+
+    #[staticmethod]
+    fn load_bin_files(sd_paths: Vec<String>, hd_paths: Vec<String>) -> PyResult<Self> {
+        TileSet::load_bin_files(&sd_paths, &hd_paths).map(Self).map_err(runtime_error)
+    }
+
+    #[staticmethod]
+    fn from_bin_bytes(sd_pages: Vec<&[u8]>, hd_pages: Vec<&[u8]>) -> PyResult<Self> {
+        TileSet::from_bin_bytes(&sd_pages, &hd_pages).map(Self).map_err(runtime_error)
+    }
+
+    #[staticmethod]
+    fn load_dir(dir: &str) -> PyResult<Self> {
+        TileSet::load_from_dir(dir, DEFAULT_MAX_TILE_COUNT, None, ImageDecoding::default()).map(Self).map_err(runtime_error)
+    }
+
+    #[staticmethod]
+    fn load_grids(sd_path: &str, hd_path: &str) -> PyResult<Self> {
+        TileSet::load_from_tile_grids(sd_path, hd_path, None).map(Self).map_err(runtime_error)
+    }
+
+    #[staticmethod]
+    fn load_archive(path: &str) -> PyResult<Self> {
+        TileSet::load_from_archive(path, DEFAULT_MAX_TILE_COUNT, None, ImageDecoding::default()).map(Self).map_err(runtime_error)
+    }
+
+    fn tile_count(&self, kind: &str) -> PyResult<usize> {
+        Ok(self.0.tiles(tile_kind(kind)?).len())
+    }
+
+    // RGBA pixels row by row
+    fn tile_rgba<'py>(&self, py: Python<'py>, kind: &str, index: usize) -> PyResult<&'py PyBytes> {
+        let kind = tile_kind(kind)?;
+        let tile = self.0.tiles(kind).get(index)
+            .ok_or_else(|| pyo3::exceptions::PyIndexError::new_err(format!("no {kind} tile at index {index}")))?;
+        Ok(PyBytes::new(py, tile.as_raw()))
+    }
+
+    // indexes of the tiles with at least one non-transparent pixel
+    fn non_empty_tiles(&self) -> Vec<(String, usize)> {
+        self.0.iter_non_empty().map(|(kind, index, _)| (kind.to_string(), index)).collect()
+    }
+
+    // the SD and HD pages
+    fn to_bin_bytes<'py>(&self, py: Python<'py>) -> PyResult<(Vec<&'py PyBytes>, Vec<&'py PyBytes>)> {
+        let (sd_pages, hd_pages) = self.0.to_bin_bytes().map_err(runtime_error)?;
+        let py_pages = |pages: Vec<Vec<u8>>| -> Vec<&'py PyBytes> { pages.iter().map(|page| PyBytes::new(py, page)).collect() };
+        Ok((py_pages(sd_pages), py_pages(hd_pages)))
+    }
+
+    #[pyo3(signature = (dir, ident = None))]
+    fn save_bin_files_norm(&self, dir: &str, ident: Option<&str>) -> PyResult<()> {
+        self.0.save_to_bin_files_norm(dir, &ident).map_err(runtime_error)
+    }
+
+    #[pyo3(signature = (dir, ident = None, format = "png"))]
+    fn save_grids_norm(&self, dir: &str, ident: Option<&str>, format: &str) -> PyResult<()> {
+        self.0.save_to_grids_norm(dir, &ident, image_format(format)?).map_err(runtime_error)
+    }
+
+    #[pyo3(signature = (dir, format = "png"))]
+    fn save_dir(&self, dir: &str, format: &str) -> PyResult<()> {
+        self.0.save_tiles_to_dir(dir, image_format(format)?).map_err(runtime_error)
+    }
+
+    #[pyo3(signature = (path, format = "png"))]
+    fn save_archive(&self, path: &str, format: &str) -> PyResult<()> {
+        self.0.save_to_archive(path, image_format(format)?).map_err(runtime_error)
+    }
+
+    fn to_symbol_set(&self, specs_paths: Vec<String>) -> PyResult<PySymbolSet> {
+        let specs = SymbolSpecs::load_files(&specs_paths).map_err(runtime_error)?;
+        self.0.clone().into_symbol_set(&specs).map(PySymbolSet).map_err(runtime_error)
+    }
+
+}
+
+#[pyclass(name = "TileGrid")]
+pub struct PyTileGrid(Grid);
+
+#[pymethods]
+impl PyTileGrid {
+
+    #[staticmethod]
+    fn load_image(path: &str) -> PyResult<Self> {
+        Grid::load_from_image(path).map(Self).map_err(runtime_error)
+    }
+
+    #[staticmethod]
+    fn from_image_bytes(bytes: &[u8]) -> PyResult<Self> {
+        let image = image::load_from_memory(bytes).map_err(runtime_error)?;
+        Grid::from_image(image).map(Self).map_err(runtime_error)
+    }
+
+    fn __len__(&self) -> usize {
+        self.0.len()
+    }
+
+    // RGBA pixels row by row
+    fn tile_rgba<'py>(&self, py: Python<'py>, index: usize) -> PyResult<&'py PyBytes> {
+        let tile = self.0.get(index)
+            .ok_or_else(|| pyo3::exceptions::PyIndexError::new_err(format!("no tile at index {index}")))?;
+        Ok(PyBytes::new(py, tile.as_raw()))
+    }
+
+    fn save_image(&self, path: &str) -> PyResult<()> {
+        self.0.save_image(path).map_err(runtime_error)
+    }
+
+    #[pyo3(signature = (format = "png"))]
+    fn encode_image<'py>(&self, py: Python<'py>, format: &str) -> PyResult<&'py PyBytes> {
+        let bytes = self.0.encode_image(image_format(format)?, GridLayout::default()).map_err(runtime_error)?;
+        Ok(PyBytes::new(py, &bytes))
+    }
+
+}
+
+#[pyclass(name = "SymbolSet")]
+pub struct PySymbolSet(SymbolSet);
+
+#[pymethods]
+impl PySymbolSet {
+
+    #[staticmethod]
+    fn load_dir(dir: &str) -> PyResult<Self> {
+        SymbolSet::load_from_dir(dir, DEFAULT_MAX_TILE_COUNT, None, ImageDecoding::default()).map(Self).map_err(runtime_error)
+    }
+
+    fn symbol_count(&self, kind: &str) -> PyResult<usize> {
+        Ok(match tile_kind(kind)? {
+            TileKind::SD => self.0.sd_symbols().len(),
+            TileKind::HD => self.0.hd_symbols().len(),
+        })
+    }
+
+    #[pyo3(signature = (dir, format = "png"))]
+    fn save_dir(&self, dir: &str, format: &str) -> PyResult<()> {
+        self.0.save_to_dir(dir, image_format(format)?).map_err(runtime_error)
+    }
+
+    fn to_tile_set(&self) -> PyTileSet {
+        PyTileSet(TileSet {
+            sd_tiles: self.0.sd_symbols().clone().into_tiles_vec(),
+            hd_tiles: self.0.hd_symbols().clone().into_tiles_vec(),
+        })
+    }
+
+}
+
+#[pymodule]
+fn hd_fpv_osd_font_tool(_py: Python, module: &PyModule) -> PyResult<()> {
+    module.add_class::<PyTileSet>()?;
+    module.add_class::<PyTileGrid>()?;
+    module.add_class::<PySymbolSet>()?;
+    Ok(())
+}
@@ -0,0 +1,151 @@
+
+use std::{collections::BTreeMap, fmt::Display, path::{Path, PathBuf}};
+
+use serde::Serialize;
+
+use crate::osd::{ident_scan::IdentScan, tile::{Kind as TileKind, container::{duplicates::TileLocation, info::FontInfo, stats::FontStats}, diff::TileChange, regression::TileDifference}};
+
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct CollectionReport {
+    pub format: String,
+    pub paths: Vec<PathBuf>,
+}
+
+impl CollectionReport {
+    pub fn new<P: AsRef<Path>>(format: &str, paths: &[P]) -> Self {
+        Self { format: format.to_owned(), paths: paths.iter().map(|path| path.as_ref().to_path_buf()).collect() }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct ErrorReport {
+    // stable identifier of the class of error, the message is meant for humans and can change
+    pub code: String,
+    pub message: String,
+}
+
+// tiles resampled to another tile kind for the target format
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct RescaleReport {
+    pub tile_count: usize,
+    pub from: String,
+    pub to: String,
+}
+
+impl Display for RescaleReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "rescaled {} tiles {} → {}", self.tile_count, self.from, self.to)
+    }
+}
+
+// Machine-readable description of what a command did
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CommandReport {
+    pub command: String,
+    pub success: bool,
+    pub inputs: Vec<CollectionReport>,
+    pub outputs: Vec<CollectionReport>,
+    pub tile_counts: BTreeMap<String, usize>,
+    pub warnings: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rescaled: Option<RescaleReport>,
+    // pixels made transparent or opaque by `--alpha-threshold`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alpha_thresholded_pixels: Option<usize>,
+    // whether the conversion was skipped by `--incremental`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub up_to_date: Option<bool>,
+    // files touched by the command, e.g. by `fix`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub changes: Vec<String>,
+    // problems found by `check`, differences found by `verify`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub problems: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ErrorReport>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stats: Option<FontStats>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub info: Option<FontInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub idents: Option<IdentScan>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diff: Option<Vec<TileChange>>,
+    // groups of duplicate tiles found by `find-duplicates`, the font being the index of the input
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duplicate_groups: Option<Vec<Vec<TileLocation>>>,
+    // differing tiles found by `compare`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comparison: Option<Vec<TileDifference>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_hash: Option<String>,
+    // per tile content hashes by tile kind, e.g. from `hash --per-tile`
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub tile_hashes: BTreeMap<String, Vec<String>>,
+}
+
+impl CommandReport {
+
+    pub fn new(command: &str) -> Self {
+        Self { command: command.to_owned(), ..Default::default() }
+    }
+
+    pub fn add_tile_count(&mut self, tile_kind: TileKind, count: usize) {
+        *self.tile_counts.entry(tile_kind.to_string()).or_default() += count;
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::osd::tile::Kind as TileKind;
+
+    use super::{CommandReport, CollectionReport, ErrorReport};
+
+    #[test]
+    fn json_snapshot() {
+        let mut report = CommandReport::new("convert");
+        report.inputs.push(CollectionReport::new("djibin", &["font.bin"]));
+        report.outputs.push(CollectionReport::new("tiledir", &["tiles"]));
+        report.add_tile_count(TileKind::SD, 256);
+        report.warnings.push("something odd".to_owned());
+        report.error = Some(ErrorReport { code: "bin_file_save".to_owned(), message: "failed".to_owned() });
+
+        let expected = r#"{
+  "command": "convert",
+  "success": false,
+  "inputs": [
+    {
+      "format": "djibin",
+      "paths": [
+        "font.bin"
+      ]
+    }
+  ],
+  "outputs": [
+    {
+      "format": "tiledir",
+      "paths": [
+        "tiles"
+      ]
+    }
+  ],
+  "tile_counts": {
+    "SD": 256
+  },
+  "warnings": [
+    "something odd"
+  ],
+  "error": {
+    "code": "bin_file_save",
+    "message": "failed"
+  }
+}"#;
+        assert_eq!(report.to_json().unwrap(), expected);
+    }
+}
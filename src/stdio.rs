@@ -0,0 +1,19 @@
+
+use std::io::{Error as IOError, Read};
+use std::path::Path;
+
+// `-` in place of a bin file or image path reads from stdin or writes to stdout so that the tool can be used in pipes
+
+
+pub const STDIO_PATH: &str = "-";
+
+pub fn is_stdio_path<P: AsRef<Path>>(path: P) -> bool {
+    path.as_ref() == Path::new(STDIO_PATH)
+}
+
+// the formats need to seek or to detect their type from the first bytes so the whole input is read at once
+pub(crate) fn read_stdin() -> Result<Vec<u8>, IOError> {
+    let mut data = vec![];
+    std::io::stdin().lock().read_to_end(&mut data)?;
+    Ok(data)
+}
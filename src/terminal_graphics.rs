@@ -0,0 +1,164 @@
+use std::{collections::BTreeSet, fmt::Write};
+
+use clap::ValueEnum;
+use image::{Rgba, RgbaImage};
+use strum::Display;
+
+use crate::base64;
+
+// Escape sequences displaying images directly in terminals e.g. to inspect tiles over SSH. The pixels with an alpha
+// lower than 128 are left transparent, the others are drawn opaque.
+
+// bytes of base64 payload per escape sequence of the kitty graphics protocol
+const KITTY_CHUNK_SIZE: usize = 4096;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, ValueEnum)]
+#[strum(serialize_all = "lowercase")]
+pub enum Protocol {
+    // kitty graphics protocol, also supported by WezTerm, Konsole and Ghostty
+    Kitty,
+    Sixel,
+    // two pixels per character cell with the upper/lower half block characters, works in any 24-bit color terminal
+    Blocks,
+}
+
+impl Protocol {
+
+    // guessed from the environment since querying the terminal would need raw mode, defaults to blocks
+    pub fn detect() -> Self {
+        let env = |name| std::env::var(name).unwrap_or_default();
+        let term = env("TERM");
+        if term == "xterm-kitty" || !env("KITTY_WINDOW_ID").is_empty() || ["WezTerm", "ghostty"].contains(&env("TERM_PROGRAM").as_str()) {
+            return Protocol::Kitty
+        }
+        if term.contains("sixel") || term.starts_with("foot") || term.starts_with("mlterm") {
+            return Protocol::Sixel
+        }
+        Protocol::Blocks
+    }
+
+}
+
+fn is_opaque(pixel: &Rgba<u8>) -> bool {
+    pixel[3] >= 128
+}
+
+pub fn encode(image: &RgbaImage, protocol: Protocol) -> String {
+    match protocol {
+        Protocol::Kitty => encode_kitty(image),
+        Protocol::Sixel => encode_sixel(image),
+        Protocol::Blocks => encode_blocks(image),
+    }
+}
+
+pub fn encode_kitty(image: &RgbaImage) -> String {
+    let payload = base64::encode(image.as_raw());
+    let chunks: Vec<&[u8]> = payload.as_bytes().chunks(KITTY_CHUNK_SIZE).collect();
+    let mut output = String::new();
+    for (index, chunk) in chunks.iter().enumerate() {
+        let more = (index + 1 < chunks.len()) as u8;
+        let chunk = std::str::from_utf8(chunk).expect("base64 is ASCII");
+        let result = match index {
+            0 => write!(output, "\x1b_Ga=T,f=32,s={},v={},m={more};{chunk}\x1b\\", image.width(), image.height()),
+            _ => write!(output, "\x1b_Gm={more};{chunk}\x1b\\"),
+        };
+        result.unwrap();
+    }
+    output.push('\n');
+    output
+}
+
+// 6 levels per channel
+fn sixel_color_index(pixel: &Rgba<u8>) -> usize {
+    let level = |channel: u8| (channel as usize * 5 + 127) / 255;
+    level(pixel[0]) * 36 + level(pixel[1]) * 6 + level(pixel[2])
+}
+
+fn push_sixel_run(output: &mut String, sixel: char, count: usize) {
+    match count {
+        0 => {},
+        1..=3 => (0..count).for_each(|_| output.push(sixel)),
+        _ => write!(output, "!{count}{sixel}").unwrap(),
+    }
+}
+
+pub fn encode_sixel(image: &RgbaImage) -> String {
+    // the second parameter keeps the pixels which are not drawn transparent
+    let mut output = format!("\x1bP0;1;0q\"1;1;{};{}", image.width(), image.height());
+    let colors: BTreeSet<usize> = image.pixels().filter(|pixel| is_opaque(pixel)).map(sixel_color_index).collect();
+    for color in &colors {
+        let percent = |level: usize| level * 20;
+        write!(output, "#{color};2;{};{};{}", percent(color / 36), percent(color / 6 % 6), percent(color % 6)).unwrap();
+    }
+
+    for band_y in (0..image.height()).step_by(6) {
+        let band_height = (image.height() - band_y).min(6);
+        let sixel_bits = |x: u32, color: usize| (0..band_height).fold(0_u8, |bits, dy| {
+            let pixel = image.get_pixel(x, band_y + dy);
+            match is_opaque(pixel) && sixel_color_index(pixel) == color {
+                true => bits | 1 << dy,
+                false => bits,
+            }
+        });
+        for color in &colors {
+            let sixels: Vec<char> = (0..image.width()).map(|x| (63 + sixel_bits(x, *color)) as char).collect();
+            if sixels.iter().all(|sixel| *sixel == '?') {
+                continue;
+            }
+            write!(output, "#{color}").unwrap();
+            let (mut run_sixel, mut run_count) = (sixels[0], 0);
+            for sixel in sixels {
+                if sixel != run_sixel {
+                    push_sixel_run(&mut output, run_sixel, run_count);
+                    (run_sixel, run_count) = (sixel, 0);
+                }
+                run_count += 1;
+            }
+            push_sixel_run(&mut output, run_sixel, run_count);
+            output.push('$');
+        }
+        output.push('-');
+    }
+    output.push_str("\x1b\\\n");
+    output
+}
+
+pub fn encode_blocks(image: &RgbaImage) -> String {
+    let mut output = String::new();
+    let color = |pixel: &Rgba<u8>| format!("{};{};{}", pixel[0], pixel[1], pixel[2]);
+    for y in (0..image.height()).step_by(2) {
+        for x in 0..image.width() {
+            let top = Some(image.get_pixel(x, y)).filter(|pixel| is_opaque(pixel));
+            let bottom = image.get_pixel_checked(x, y + 1).filter(|pixel| is_opaque(pixel));
+            match (top, bottom) {
+                (None, None) => output.push_str("\x1b[0m "),
+                (Some(top), None) => write!(output, "\x1b[0m\x1b[38;2;{}m\u{2580}", color(top)).unwrap(),
+                (None, Some(bottom)) => write!(output, "\x1b[0m\x1b[38;2;{}m\u{2584}", color(bottom)).unwrap(),
+                (Some(top), Some(bottom)) => write!(output, "\x1b[38;2;{}m\x1b[48;2;{}m\u{2580}", color(top), color(bottom)).unwrap(),
+            }
+        }
+        output.push_str("\x1b[0m\n");
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{Rgba, RgbaImage};
+
+    use super::{encode_blocks, encode_kitty, encode_sixel};
+
+    #[test]
+    fn encodings() {
+        let mut image = RgbaImage::new(2, 7);
+        image.put_pixel(0, 0, Rgba([255, 255, 255, 255]));
+        image.put_pixel(0, 6, Rgba([255, 255, 255, 255]));
+
+        let sixel = encode_sixel(&image);
+        assert!(sixel.starts_with("\x1bP0;1;0q\"1;1;2;7#215;2;100;100;100"));
+        assert!(sixel.contains("#215@?$-#215@?$-"));
+
+        assert_eq!(encode_blocks(&image).lines().count(), 4);
+        assert!(encode_kitty(&image).starts_with("\x1b_Ga=T,f=32,s=2,v=7,m=0;"));
+    }
+}